@@ -1,6 +1,6 @@
 use std::{
     cell::RefCell,
-    collections::{HashMap, hash_map::Entry},
+    collections::{HashMap, VecDeque, hash_map::Entry},
     fmt::Debug,
 };
 
@@ -26,7 +26,7 @@ use crate::{
         Coordinate as RpcCoordinate, InputService, Key as RpcKeyKind, KeyState as RpcKeyState,
         MouseAction as RpcMouseKind,
     },
-    run::MS_PER_TICK_F32,
+    run::{MS_PER_TICK, MS_PER_TICK_F32},
 };
 
 /// Base mean in milliseconds to generate a pair from.
@@ -564,6 +564,22 @@ enum InputDelay {
     AlreadyTracked,
 }
 
+/// A key stroke postponed by input delay compensation.
+#[derive(Debug, Clone, Copy)]
+enum PendingKey {
+    Key(KeyKind),
+    KeyUp(KeyKind),
+    KeyDown(KeyKind, bool),
+}
+
+impl PendingKey {
+    fn kind(self) -> KeyKind {
+        match self {
+            PendingKey::Key(kind) | PendingKey::KeyUp(kind) | PendingKey::KeyDown(kind, _) => kind,
+        }
+    }
+}
+
 /// A trait for sending inputs.
 #[cfg_attr(test, automock)]
 pub trait Input: Debug {
@@ -573,6 +589,16 @@ pub trait Input: Debug {
     /// Overwrites the current input method with new `method`.
     fn set_method(&mut self, method: InputMethod);
 
+    /// Sets the milliseconds to delay all key strokes by to compensate for input lag.
+    ///
+    /// Delayed key strokes are sent in order by [`Self::update`].
+    fn set_delay_compensation_millis(&mut self, millis: u64);
+
+    /// Discards key strokes postponed by delay compensation.
+    ///
+    /// Postponed key releases are still sent immediately so that no key is left held.
+    fn clear_pending_keys(&self);
+
     /// Sends mouse `kind` to `(x, y)` relative to the client coordinate (e.g. capture area).
     ///
     /// `(0, 0)` is top-left and `(width, height)` is bottom-right.
@@ -608,6 +634,9 @@ pub struct DefaultInput {
     delay_rng: Rng,
     delay_mean_std_pair: (f32, f32),
     delay_map: RefCell<HashMap<KeyKind, (u32, bool)>>,
+    delay_compensation_ticks: u32,
+    /// Key strokes waiting for the delay compensation with their remaining ticks, oldest first.
+    pending_keys: RefCell<VecDeque<(u32, PendingKey)>>,
 }
 
 impl DefaultInput {
@@ -617,9 +646,50 @@ impl DefaultInput {
             delay_rng: rng,
             delay_mean_std_pair: (BASE_MEAN_MS_DELAY, BASE_STD_MS_DELAY),
             delay_map: RefCell::new(HashMap::new()),
+            delay_compensation_ticks: 0,
+            pending_keys: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    /// Postpones `key` if delay compensation is enabled.
+    ///
+    /// Returns `true` if `key` is postponed and should not be sent immediately.
+    fn postpone_key(&self, key: PendingKey) -> bool {
+        if self.delay_compensation_ticks == 0 {
+            return false;
+        }
+
+        self.pending_keys
+            .borrow_mut()
+            .push_back((self.delay_compensation_ticks, key));
+        true
+    }
+
+    /// Sends postponed key strokes whose delay compensation has elapsed.
+    fn update_pending_keys(&self) {
+        let mut pending = self.pending_keys.borrow_mut();
+        for (ticks, _) in pending.iter_mut() {
+            *ticks = ticks.saturating_sub(1);
+        }
+
+        while let Some((0, key)) = pending.front().copied() {
+            pending.pop_front();
+            let _ = match key {
+                PendingKey::Key(kind) => self.send_key_inner(kind),
+                PendingKey::KeyUp(kind) => self.send_key_up_inner(kind, false),
+                PendingKey::KeyDown(kind, repeatable) => self.send_key_down_inner(kind, repeatable),
+            };
         }
     }
 
+    #[inline]
+    fn has_pending_key(&self, kind: KeyKind) -> bool {
+        self.pending_keys
+            .borrow()
+            .iter()
+            .any(|(_, key)| key.kind() == kind)
+    }
+
     #[inline]
     fn key_state(&self, kind: KeyKind) -> Result<KeyState> {
         match &self.kind {
@@ -739,6 +809,8 @@ impl DefaultInput {
             )
         }
 
+        self.update_pending_keys();
+
         let mut map = self.delay_map.borrow_mut();
         if map.is_empty() {
             return;
@@ -776,6 +848,19 @@ impl Input for DefaultInput {
         self.kind = input_method_inner_from(method, self.delay_rng.rng_seed());
     }
 
+    fn set_delay_compensation_millis(&mut self, millis: u64) {
+        // Rounds up so that a delay shorter than a tick is not dropped
+        self.delay_compensation_ticks = millis.div_ceil(MS_PER_TICK) as u32;
+    }
+
+    fn clear_pending_keys(&self) {
+        for (_, key) in self.pending_keys.take() {
+            if let PendingKey::KeyUp(kind) = key {
+                let _ = self.send_key_up_inner(kind, false);
+            }
+        }
+    }
+
     fn send_mouse(&self, x: i32, y: i32, kind: MouseKind) {
         match &self.kind {
             InputMethodInner::Rpc(window, service) => {
@@ -810,24 +895,30 @@ impl Input for DefaultInput {
     }
 
     fn send_key(&self, kind: KeyKind) {
-        let _ = self.send_key_inner(kind);
+        if !self.postpone_key(PendingKey::Key(kind)) {
+            let _ = self.send_key_inner(kind);
+        }
     }
 
     fn send_key_up(&self, kind: KeyKind) {
-        let _ = self.send_key_up_inner(kind, false);
+        if !self.postpone_key(PendingKey::KeyUp(kind)) {
+            let _ = self.send_key_up_inner(kind, false);
+        }
     }
 
     fn send_key_down_with_options(&self, kind: KeyKind, options: InputKeyDownOptions) {
-        let _ = self.send_key_down_inner(kind, options.repeatable);
+        if !self.postpone_key(PendingKey::KeyDown(kind, options.repeatable)) {
+            let _ = self.send_key_down_inner(kind, options.repeatable);
+        }
     }
 
     fn is_key_cleared(&self, kind: KeyKind) -> bool {
-        !self.delay_map.borrow().contains_key(&kind)
+        !self.delay_map.borrow().contains_key(&kind) && !self.has_pending_key(kind)
     }
 
     #[inline]
     fn all_keys_cleared(&self) -> bool {
-        self.delay_map.borrow().is_empty()
+        self.delay_map.borrow().is_empty() && self.pending_keys.borrow().is_empty()
     }
 }
 
@@ -836,7 +927,10 @@ impl Input for DefaultInput {
 /// A bridge trait between platform-specific and database.
 #[cfg_attr(test, automock)]
 pub trait Capture: Debug + 'static {
-    fn grab(&mut self) -> Result<Frame, Error>;
+    /// Grabs a frame reusing `buffer` for the frame data.
+    ///
+    /// The `buffer` is moved into the returned [`Frame`] on success and left intact on failure.
+    fn grab(&mut self, buffer: &mut Vec<u8>) -> Result<Frame, Error>;
 
     fn window(&self) -> Window;
 
@@ -864,8 +958,8 @@ impl DefaultCapture {
 
 impl Capture for DefaultCapture {
     #[inline]
-    fn grab(&mut self) -> Result<Frame, Error> {
-        self.inner.grab()
+    fn grab(&mut self, buffer: &mut Vec<u8>) -> Result<Frame, Error> {
+        self.inner.grab_into(buffer)
     }
 
     #[inline]
@@ -971,6 +1065,49 @@ mod tests {
         assert!(!sender.has_input_delay(KeyKind::Ctrl));
     }
 
+    #[test]
+    fn send_key_delayed_by_compensation_ticks() {
+        let mut sender = test_key_sender();
+        sender.set_delay_compensation_millis(3 * MS_PER_TICK);
+
+        sender.send_key(KeyKind::Ctrl);
+        for _ in 0..2 {
+            sender.update(0);
+            assert!(sender.has_pending_key(KeyKind::Ctrl));
+            assert!(!sender.is_key_cleared(KeyKind::Ctrl));
+        }
+
+        sender.update(0);
+        assert!(!sender.has_pending_key(KeyKind::Ctrl));
+    }
+
+    #[test]
+    fn set_delay_compensation_millis_rounds_up_to_tick() {
+        let mut sender = test_key_sender();
+
+        sender.set_delay_compensation_millis(10);
+        assert_eq!(sender.delay_compensation_ticks, 1);
+
+        sender.set_delay_compensation_millis(MS_PER_TICK + 1);
+        assert_eq!(sender.delay_compensation_ticks, 2);
+
+        sender.set_delay_compensation_millis(0);
+        assert_eq!(sender.delay_compensation_ticks, 0);
+    }
+
+    #[test]
+    fn clear_pending_keys_discards_postponed_keys() {
+        let mut sender = test_key_sender();
+        sender.set_delay_compensation_millis(3 * MS_PER_TICK);
+
+        sender.send_key_down(KeyKind::Left);
+        sender.send_key(KeyKind::Ctrl);
+        sender.send_key_up(KeyKind::Left);
+        sender.clear_pending_keys();
+
+        assert!(sender.all_keys_cleared());
+    }
+
     #[test]
     fn update_input_delay_refresh_mean_std_pair_every_interval() {
         let mut sender = test_key_sender();
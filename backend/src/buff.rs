@@ -7,7 +7,7 @@ use anyhow::Result;
 use strum::EnumIter;
 
 use crate::{
-    Character, Settings,
+    Character, CharacterBuffKind, Settings,
     detect::BuffKind as DetectorBuffKind,
     ecs::Resources,
     player::Player,
@@ -77,35 +77,44 @@ impl BuffContext {
     }
 
     /// Updates the enabled states of each buff to only detect if enabled.
-    pub fn update_enabled_state(&mut self, character: &Character, settings: &Settings) {
-        self.enabled = match self.kind {
-            BuffKind::Rune => settings.enable_rune_solving,
-            BuffKind::Familiar => character.familiar_buff_key.enabled,
-            BuffKind::SayramElixir => character.sayram_elixir_key.enabled,
-            BuffKind::AureliaElixir => character.aurelia_elixir_key.enabled,
-            BuffKind::ExpCouponX2 | BuffKind::ExpCouponX3 | BuffKind::ExpCouponX4 => {
-                character.exp_x2_key.enabled
-                    || character.exp_x3_key.enabled
-                    || character.exp_x4_key.enabled
-            }
-            BuffKind::BonusExpCoupon => character.bonus_exp_key.enabled,
-            BuffKind::LegionWealth => character.legion_wealth_key.enabled,
-            BuffKind::LegionLuck => character.legion_luck_key.enabled,
-            BuffKind::WealthAcquisitionPotion | BuffKind::SmallWealthAcquisitionPotion => {
-                character.wealth_acquisition_potion_key.enabled
-                    || character.small_wealth_acquisition_potion_key.enabled
-            }
-            BuffKind::ExpAccumulationPotion | BuffKind::SmallExpAccumulationPotion => {
-                character.exp_accumulation_potion_key.enabled
-                    || character.small_exp_accumulation_potion_key.enabled
-            }
-            BuffKind::ForTheGuild => character.for_the_guild_key.enabled,
-            BuffKind::HardHitter => character.hard_hitter_key.enabled,
-            BuffKind::ExtremeRedPotion => character.extreme_red_potion_key.enabled,
-            BuffKind::ExtremeBluePotion => character.extreme_blue_potion_key.enabled,
-            BuffKind::ExtremeGreenPotion => character.extreme_green_potion_key.enabled,
-            BuffKind::ExtremeGoldPotion => character.extreme_gold_potion_key.enabled,
-        };
+    ///
+    /// Buffs in `waited` are always enabled so that waiting for them does not stall until timing
+    /// out.
+    pub fn update_enabled_state(
+        &mut self,
+        character: &Character,
+        settings: &Settings,
+        waited: &[BuffKind],
+    ) {
+        self.enabled = waited.contains(&self.kind)
+            || match self.kind {
+                BuffKind::Rune => settings.enable_rune_solving,
+                BuffKind::Familiar => character.familiar_buff_key.enabled,
+                BuffKind::SayramElixir => character.sayram_elixir_key.enabled,
+                BuffKind::AureliaElixir => character.aurelia_elixir_key.enabled,
+                BuffKind::ExpCouponX2 | BuffKind::ExpCouponX3 | BuffKind::ExpCouponX4 => {
+                    character.exp_x2_key.enabled
+                        || character.exp_x3_key.enabled
+                        || character.exp_x4_key.enabled
+                }
+                BuffKind::BonusExpCoupon => character.bonus_exp_key.enabled,
+                BuffKind::LegionWealth => character.legion_wealth_key.enabled,
+                BuffKind::LegionLuck => character.legion_luck_key.enabled,
+                BuffKind::WealthAcquisitionPotion | BuffKind::SmallWealthAcquisitionPotion => {
+                    character.wealth_acquisition_potion_key.enabled
+                        || character.small_wealth_acquisition_potion_key.enabled
+                }
+                BuffKind::ExpAccumulationPotion | BuffKind::SmallExpAccumulationPotion => {
+                    character.exp_accumulation_potion_key.enabled
+                        || character.small_exp_accumulation_potion_key.enabled
+                }
+                BuffKind::ForTheGuild => character.for_the_guild_key.enabled,
+                BuffKind::HardHitter => character.hard_hitter_key.enabled,
+                BuffKind::ExtremeRedPotion => character.extreme_red_potion_key.enabled,
+                BuffKind::ExtremeBluePotion => character.extreme_blue_potion_key.enabled,
+                BuffKind::ExtremeGreenPotion => character.extreme_green_potion_key.enabled,
+                BuffKind::ExtremeGoldPotion => character.extreme_gold_potion_key.enabled,
+            };
         if !self.enabled {
             self.fail_count = 0;
             self.task = None;
@@ -114,8 +123,7 @@ impl BuffContext {
 }
 
 // The kind of buff.
-#[derive(Clone, Copy, Debug, EnumIter)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Copy, PartialEq, Debug, EnumIter)]
 #[repr(usize)]
 pub enum BuffKind {
     // NOTE: Upon failing to solving rune, there is a cooldown
@@ -146,6 +154,34 @@ impl BuffKind {
     pub const COUNT: usize = mem::variant_count::<BuffKind>();
 }
 
+impl From<CharacterBuffKind> for BuffKind {
+    fn from(kind: CharacterBuffKind) -> Self {
+        match kind {
+            CharacterBuffKind::Familiar => BuffKind::Familiar,
+            CharacterBuffKind::SayramElixir => BuffKind::SayramElixir,
+            CharacterBuffKind::AureliaElixir => BuffKind::AureliaElixir,
+            CharacterBuffKind::ExpCouponX2 => BuffKind::ExpCouponX2,
+            CharacterBuffKind::ExpCouponX3 => BuffKind::ExpCouponX3,
+            CharacterBuffKind::ExpCouponX4 => BuffKind::ExpCouponX4,
+            CharacterBuffKind::BonusExpCoupon => BuffKind::BonusExpCoupon,
+            CharacterBuffKind::LegionWealth => BuffKind::LegionWealth,
+            CharacterBuffKind::LegionLuck => BuffKind::LegionLuck,
+            CharacterBuffKind::WealthAcquisitionPotion => BuffKind::WealthAcquisitionPotion,
+            CharacterBuffKind::ExpAccumulationPotion => BuffKind::ExpAccumulationPotion,
+            CharacterBuffKind::SmallWealthAcquisitionPotion => {
+                BuffKind::SmallWealthAcquisitionPotion
+            }
+            CharacterBuffKind::SmallExpAccumulationPotion => BuffKind::SmallExpAccumulationPotion,
+            CharacterBuffKind::ForTheGuild => BuffKind::ForTheGuild,
+            CharacterBuffKind::HardHitter => BuffKind::HardHitter,
+            CharacterBuffKind::ExtremeRedPotion => BuffKind::ExtremeRedPotion,
+            CharacterBuffKind::ExtremeBluePotion => BuffKind::ExtremeBluePotion,
+            CharacterBuffKind::ExtremeGreenPotion => BuffKind::ExtremeGreenPotion,
+            CharacterBuffKind::ExtremeGoldPotion => BuffKind::ExtremeGoldPotion,
+        }
+    }
+}
+
 impl Index<BuffKind> for BuffEntities {
     type Output = BuffEntity;
 
@@ -372,10 +408,24 @@ mod tests {
         let config = Character::default();
         settings.enable_rune_solving = false;
 
-        state.update_enabled_state(&config, &settings);
+        state.update_enabled_state(&config, &settings, &[]);
 
         assert!(!state.enabled);
         assert_eq!(state.fail_count, 0);
         assert!(state.task.is_none());
     }
+
+    #[test]
+    fn update_enabled_state_enables_waited_buff() {
+        let kind = BuffKind::SayramElixir;
+        let mut state = BuffContext::new(kind);
+        let settings = Settings::default();
+        let config = Character::default();
+
+        state.update_enabled_state(&config, &settings, &[]);
+        assert!(!state.enabled);
+
+        state.update_enabled_state(&config, &settings, &[kind]);
+        assert!(state.enabled);
+    }
 }
@@ -28,7 +28,8 @@ pub enum BotCommandKind {
     Start,
     Stop { go_to_town: bool },
     Suspend,
-    Status,
+    Status { instance: Option<usize> },
+    Where { instance: Option<usize> },
     Chat { content: String },
     Action { action: BotAction, count: u32 },
 }
@@ -46,6 +47,8 @@ enum BotCommandKindInner {
     Suspend,
     #[strum(to_string = "status", message = "See bot current status")]
     Status,
+    #[strum(to_string = "where", message = "See the current map name and minimap")]
+    Where,
     #[strum(
         to_string = "chat",
         message = "Send a message inside the game (256 characters max)"
@@ -155,6 +158,22 @@ impl EventHandler for DefaultEventHandler {
                         "go-to-town",
                         "Whether to go to town when stopping",
                     )),
+                    BotCommandKindInner::Status => command.add_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "instance",
+                            "The instance number to see status of (selected instance if empty)",
+                        )
+                        .min_int_value(1),
+                    ),
+                    BotCommandKindInner::Where => command.add_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "instance",
+                            "The instance number to see map of (selected instance if empty)",
+                        )
+                        .min_int_value(1),
+                    ),
 
                     BotCommandKindInner::Action => {
                         let kind = BotAction::iter().fold(
@@ -183,8 +202,7 @@ impl EventHandler for DefaultEventHandler {
                     BotCommandKindInner::StartStream
                     | BotCommandKindInner::StopStream
                     | BotCommandKindInner::Start
-                    | BotCommandKindInner::Suspend
-                    | BotCommandKindInner::Status => command,
+                    | BotCommandKindInner::Suspend => command,
                 }
             })
             .collect::<Vec<_>>();
@@ -248,11 +266,22 @@ impl EventHandler for DefaultEventHandler {
                     .await;
                 }
                 BotCommandKindInner::Status => {
+                    let instance = instance_option(&command);
                     single_command(
                         &self.command_sender,
                         &context,
                         &command,
-                        BotCommandKind::Status,
+                        BotCommandKind::Status { instance },
+                    )
+                    .await;
+                }
+                BotCommandKindInner::Where => {
+                    let instance = instance_option(&command);
+                    single_command(
+                        &self.command_sender,
+                        &context,
+                        &command,
+                        BotCommandKind::Where { instance },
                     )
                     .await;
                 }
@@ -294,6 +323,18 @@ impl EventHandler for DefaultEventHandler {
     }
 }
 
+/// Returns the zero-based instance index from the `instance` option of `command`.
+#[inline]
+fn instance_option(command: &CommandInteraction) -> Option<usize> {
+    // Instance number starts from 1 to match the UI
+    command
+        .data
+        .options
+        .first()
+        .and_then(|option| option.value.as_i64()?.to_usize())
+        .map(|instance| instance.saturating_sub(1))
+}
+
 async fn single_command(
     sender: &Sender<BotCommand>,
     context: &Context,
@@ -337,7 +378,13 @@ async fn start_stream_command(
         let start_time = Instant::now();
         let max_duration = Duration::from_mins(15);
         while start_time.elapsed() < max_duration {
-            single_command(&sender, &context, &command, BotCommandKind::Status).await;
+            single_command(
+                &sender,
+                &context,
+                &command,
+                BotCommandKind::Status { instance: None },
+            )
+            .await;
             sleep(Duration::from_millis(500)).await;
         }
         response_with(&context, &command, "Streaming finished.").await;
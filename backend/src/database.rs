@@ -2,6 +2,7 @@ use std::{
     collections::{HashMap, HashSet},
     env,
     sync::{LazyLock, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{Result, bail};
@@ -13,7 +14,12 @@ use strum::{Display, EnumIter, EnumString};
 use tokio::sync::broadcast::{Receiver, Sender, channel};
 
 use crate::pathing;
-use crate::{bridge::KeyKind, models::Localization};
+use crate::{
+    bridge::KeyKind,
+    detect::{PLAYER_DOT_COLOR_LOWER, PLAYER_DOT_COLOR_UPPER},
+    models::Localization,
+    player::{RUNE_COOLDOWN_TICKS, RUNE_PRESS_KEY_INTERVAL_TICKS},
+};
 
 const MAPS: &str = "maps";
 const NAVIGATION_PATHS: &str = "navigation_paths";
@@ -21,6 +27,7 @@ const CHARACTERS: &str = "characters";
 const SETTINGS: &str = "settings";
 const SEEDS: &str = "seeds";
 const LOCALIZATIONS: &str = "localizations";
+const ACTION_SNIPPETS: &str = "action_snippets";
 
 static CONNECTION: LazyLock<Mutex<Connection>> = LazyLock::new(|| {
     let path = env::current_exe()
@@ -57,6 +64,10 @@ static CONNECTION: LazyLock<Mutex<Connection>> = LazyLock::new(|| {
                 id INTEGER PRIMARY KEY,
                 data TEXT NOT NULL
             );
+            CREATE TABLE IF NOT EXISTS {ACTION_SNIPPETS} (
+                id INTEGER PRIMARY KEY,
+                data TEXT NOT NULL
+            );
             "#
         )
         .as_str(),
@@ -158,6 +169,11 @@ pub struct Familiars {
     pub swap_check_millis: u64,
     pub swappable_familiars: SwappableFamiliars,
     pub swappable_rarities: HashSet<FamiliarRarity>,
+    #[serde(default = "familiars_sort_by_level_default")]
+    pub sort_by_level: bool,
+    /// Maximum number of swaps within an hour, zero means no limit.
+    #[serde(default)]
+    pub max_swaps_per_hour: u32,
 }
 
 impl Default for Familiars {
@@ -167,6 +183,8 @@ impl Default for Familiars {
             swap_check_millis: familiars_swap_check_millis(),
             swappable_familiars: SwappableFamiliars::default(),
             swappable_rarities: HashSet::default(),
+            sort_by_level: familiars_sort_by_level_default(),
+            max_swaps_per_hour: 0,
         }
     }
 }
@@ -175,6 +193,10 @@ fn familiars_swap_check_millis() -> u64 {
     300000
 }
 
+fn familiars_sort_by_level_default() -> bool {
+    true
+}
+
 #[derive(
     Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
 )]
@@ -185,6 +207,29 @@ pub enum EliteBossBehavior {
     UseKey,
 }
 
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum OtherPlayerBehavior {
+    #[default]
+    None,
+    Halt,
+    CycleChannel,
+    GoToTown,
+}
+
 #[derive(
     Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
 )]
@@ -206,6 +251,49 @@ pub struct Notifications {
     pub notify_on_player_guildie_appear: bool,
     pub notify_on_player_stranger_appear: bool,
     pub notify_on_player_friend_appear: bool,
+    #[serde(default)]
+    pub notify_on_game_frozen: bool,
+}
+
+/// The inclusive HSV thresholds of a minimap color.
+///
+/// Hue is in the full `0..=255` range. Defaults to the thresholds of the player dot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColorThresholds {
+    pub lower: [u8; 3],
+    pub upper: [u8; 3],
+}
+
+impl Default for ColorThresholds {
+    fn default() -> Self {
+        Self {
+            lower: PLAYER_DOT_COLOR_LOWER,
+            upper: PLAYER_DOT_COLOR_UPPER,
+        }
+    }
+}
+
+/// A region of the screen in percentages of the screen size.
+///
+/// Detections restricted to a region skip matching outside of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetectionRegion {
+    pub x_percent: u32,
+    pub y_percent: u32,
+    pub width_percent: u32,
+    pub height_percent: u32,
+}
+
+impl Default for DetectionRegion {
+    /// The whole screen.
+    fn default() -> Self {
+        Self {
+            x_percent: 0,
+            y_percent: 0,
+            width_percent: 100,
+            height_percent: 100,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -213,18 +301,106 @@ pub struct Settings {
     #[serde(skip_serializing, default)]
     pub id: Option<i64>,
     pub capture_mode: CaptureMode,
+    /// Capture modes to fall back to in order when [`Self::capture_mode`] keeps failing.
+    #[serde(default)]
+    pub capture_mode_fallbacks: Vec<CaptureMode>,
+    /// A substring of the window name used to select the capture window.
+    ///
+    /// When empty or no window matches, the default game window class is used.
+    #[serde(default)]
+    pub capture_window_pattern: String,
+    /// Whether to run independent detections in parallel on a small thread pool.
+    #[serde(default)]
+    pub enable_parallel_detection: bool,
+    /// The percentage to downscale the buffs region to before detecting buffs.
+    #[serde(default = "buff_detection_scale_percent_default")]
+    pub buff_detection_scale_percent: u32,
+    /// The region of the screen to detect buffs in.
+    #[serde(default = "buffs_detection_region_default")]
+    pub buffs_detection_region: DetectionRegion,
+    /// The region of the screen to detect popups in.
+    #[serde(default)]
+    pub popups_detection_region: DetectionRegion,
+    /// The HSV thresholds of the player dot color used when the player template is not found.
+    #[serde(default)]
+    pub player_dot_color_thresholds: ColorThresholds,
+    /// Whether to detect on a worker thread so that slow detection does not stall inputs.
+    #[serde(default)]
+    pub enable_detection_pipeline: bool,
     #[serde(default = "enable_rune_solving_default")]
     pub enable_rune_solving: bool,
+    /// Number of consecutive lagging samples required to determine a spinning rune arrow.
+    #[serde(default = "rune_spin_lag_samples_default")]
+    pub rune_spin_lag_samples: u32,
+    /// The minimum rotation between two samples for a spinning rune arrow to be lagging.
+    #[serde(default = "rune_spin_lag_threshold_default")]
+    pub rune_spin_lag_threshold: u32,
+    /// Number of ticks to wait for rune cooldown before retrying to solve rune.
+    #[serde(default = "rune_cooldown_ticks_default")]
+    pub rune_cooldown_ticks: u32,
+    /// Number of ticks to wait between pressing each rune key.
+    #[serde(default = "rune_press_key_interval_ticks_default")]
+    pub rune_press_key_interval_ticks: u32,
+    /// Whether to defer solving rune while an elite boss appears or the health is decreasing.
+    #[serde(default)]
+    pub solve_rune_only_when_safe: bool,
+    /// Extra margin around each portal to avoid up jumping into.
+    #[serde(default)]
+    pub portal_avoidance_margin: u32,
+    /// Percentage to scale movement timeouts by for high-latency connections.
+    #[serde(default = "move_timeout_scale_percent_default")]
+    pub move_timeout_scale_percent: u32,
+    /// Number of ticks to wait for a direction change to register before using a key.
+    #[serde(default = "change_direction_timeout_ticks_default")]
+    pub change_direction_timeout_ticks: u32,
     pub enable_panic_mode: bool,
+    #[serde(default, deserialize_with = "deserialize_with_ok_or_default")]
+    pub stranger_player_behavior: OtherPlayerBehavior,
+    #[serde(default, deserialize_with = "deserialize_with_ok_or_default")]
+    pub guildie_player_behavior: OtherPlayerBehavior,
+    /// Names of known players that do not trigger [`Self::stranger_player_behavior`].
+    #[serde(default)]
+    pub player_name_whitelist: Vec<String>,
     pub stop_on_fail_or_change_map: bool,
+    /// Milliseconds capture must keep failing for before halting and notifying.
+    ///
+    /// Zero halts on the first failure.
+    #[serde(default)]
+    pub capture_failed_threshold_millis: u64,
+    /// Milliseconds the captured frame must stay unchanged for the game to be considered frozen.
+    ///
+    /// Zero disables the detection.
+    #[serde(default = "game_frozen_threshold_millis_default")]
+    pub game_frozen_threshold_millis: u64,
+    #[serde(default)]
+    pub stop_on_game_frozen: bool,
     #[serde(default, deserialize_with = "deserialize_with_ok_or_default")]
     pub cycle_run_stop: CycleRunStopMode,
     #[serde(default = "cycle_run_duration_millis_default")]
     pub cycle_run_duration_millis: u64,
     #[serde(default = "cycle_stop_duration_millis_default")]
     pub cycle_stop_duration_millis: u64,
+    /// Number of ticks to wait before rotating any action after starting to run.
+    #[serde(default)]
+    pub startup_delay_ticks: u32,
+    /// Whether to cast all enabled buffs once after starting to run.
+    #[serde(default)]
+    pub enable_buffs_burst_on_start: bool,
+    /// Minimum number of ticks to idle for between normal actions rotations.
+    #[serde(default)]
+    pub rotation_idle_min_ticks: u32,
+    /// Maximum number of ticks to idle for between normal actions rotations.
+    ///
+    /// Zero disables idling between rotations.
+    #[serde(default)]
+    pub rotation_idle_max_ticks: u32,
     pub input_method: InputMethod,
     pub input_method_rpc_server_url: String,
+    /// Milliseconds to delay all key sends by to compensate for a consistent input lag.
+    ///
+    /// Zero sends keys immediately.
+    #[serde(default)]
+    pub input_delay_compensation_millis: u64,
     #[serde(default)]
     pub discord_bot_access_token: String,
     pub notifications: Notifications,
@@ -237,6 +413,21 @@ pub struct Settings {
     pub platform_end_key: KeyBindingConfiguration,
     #[serde(default = "platform_add_key_default")]
     pub platform_add_key: KeyBindingConfiguration,
+    /// The minimum level of logs to output.
+    ///
+    /// Debug builds always output at least [`LogLevel::Debug`].
+    #[serde(default, deserialize_with = "deserialize_with_ok_or_default")]
+    pub log_level: LogLevel,
+    /// The file to output logs to in addition to stdout.
+    ///
+    /// If empty, `log.txt` next to the executable is used.
+    #[serde(default)]
+    pub log_file_path: String,
+    /// Whether to save solved runes for training.
+    ///
+    /// Only used in debug builds.
+    #[serde(default)]
+    pub auto_save_rune: bool,
 }
 
 impl Default for Settings {
@@ -244,14 +435,41 @@ impl Default for Settings {
         Self {
             id: None,
             capture_mode: CaptureMode::default(),
+            capture_mode_fallbacks: vec![],
+            capture_window_pattern: String::default(),
+            enable_parallel_detection: false,
+            buff_detection_scale_percent: buff_detection_scale_percent_default(),
+            buffs_detection_region: buffs_detection_region_default(),
+            popups_detection_region: DetectionRegion::default(),
+            player_dot_color_thresholds: ColorThresholds::default(),
+            enable_detection_pipeline: false,
             enable_rune_solving: enable_rune_solving_default(),
+            rune_spin_lag_samples: rune_spin_lag_samples_default(),
+            rune_spin_lag_threshold: rune_spin_lag_threshold_default(),
+            rune_cooldown_ticks: rune_cooldown_ticks_default(),
+            rune_press_key_interval_ticks: rune_press_key_interval_ticks_default(),
+            portal_avoidance_margin: 0,
+            move_timeout_scale_percent: move_timeout_scale_percent_default(),
+            change_direction_timeout_ticks: change_direction_timeout_ticks_default(),
+            solve_rune_only_when_safe: false,
             enable_panic_mode: false,
+            stranger_player_behavior: OtherPlayerBehavior::default(),
+            guildie_player_behavior: OtherPlayerBehavior::default(),
+            player_name_whitelist: vec![],
             input_method: InputMethod::default(),
             input_method_rpc_server_url: String::default(),
+            input_delay_compensation_millis: 0,
             stop_on_fail_or_change_map: false,
+            capture_failed_threshold_millis: 0,
+            game_frozen_threshold_millis: game_frozen_threshold_millis_default(),
+            stop_on_game_frozen: false,
             cycle_run_stop: CycleRunStopMode::default(),
             cycle_run_duration_millis: cycle_run_duration_millis_default(),
             cycle_stop_duration_millis: cycle_stop_duration_millis_default(),
+            startup_delay_ticks: 0,
+            enable_buffs_burst_on_start: false,
+            rotation_idle_min_ticks: 0,
+            rotation_idle_max_ticks: 0,
             discord_bot_access_token: String::default(),
             notifications: Notifications::default(),
             familiars: Familiars::default(),
@@ -259,6 +477,9 @@ impl Default for Settings {
             platform_start_key: platform_start_key_default(),
             platform_end_key: platform_end_key_default(),
             platform_add_key: platform_add_key_default(),
+            log_level: LogLevel::default(),
+            log_file_path: String::default(),
+            auto_save_rune: false,
         }
     }
 }
@@ -273,10 +494,52 @@ fn cycle_stop_duration_millis_default() -> u64 {
     3600000 // 1 hour
 }
 
+fn game_frozen_threshold_millis_default() -> u64 {
+    30000 // 30 seconds
+}
+
+fn buff_detection_scale_percent_default() -> u32 {
+    100
+}
+
+fn buffs_detection_region_default() -> DetectionRegion {
+    // Top right of the screen
+    DetectionRegion {
+        x_percent: 66,
+        y_percent: 0,
+        width_percent: 34,
+        height_percent: 25,
+    }
+}
+
 fn enable_rune_solving_default() -> bool {
     true
 }
 
+fn rune_spin_lag_samples_default() -> u32 {
+    1
+}
+
+fn rune_spin_lag_threshold_default() -> u32 {
+    30
+}
+
+fn rune_cooldown_ticks_default() -> u32 {
+    RUNE_COOLDOWN_TICKS
+}
+
+fn rune_press_key_interval_ticks_default() -> u32 {
+    RUNE_PRESS_KEY_INTERVAL_TICKS
+}
+
+fn move_timeout_scale_percent_default() -> u32 {
+    100
+}
+
+fn change_direction_timeout_ticks_default() -> u32 {
+    3
+}
+
 fn toggle_actions_key_default() -> KeyBindingConfiguration {
     KeyBindingConfiguration {
         key: KeyBinding::Comma,
@@ -336,8 +599,22 @@ pub struct Character {
     pub feed_pet_millis: u64,
     #[serde(default = "feed_pet_count_default", alias = "num_pets")]
     pub feed_pet_count: u32,
+    #[serde(default = "wait_millis_default")]
+    pub feed_pet_wait_before_millis: u64,
+    #[serde(default = "wait_millis_default")]
+    pub feed_pet_wait_after_millis: u64,
+    #[serde(default)]
+    pub feed_pet_wait_millis_random_range: u64,
     pub potion_key: KeyBindingConfiguration,
     pub potion_mode: PotionMode,
+    #[serde(default = "wait_millis_default")]
+    pub potion_wait_before_millis: u64,
+    #[serde(default = "wait_millis_default")]
+    pub potion_wait_after_millis: u64,
+    #[serde(default)]
+    pub potion_wait_millis_random_range: u64,
+    #[serde(default)]
+    pub extra_potions: Vec<PotionConfiguration>,
     pub health_update_millis: u64,
     pub familiar_buff_key: KeyBindingConfiguration,
     #[serde(default = "key_default")]
@@ -371,6 +648,15 @@ pub struct Character {
     pub class: Class,
     #[serde(default)]
     pub disable_double_jumping: bool,
+    /// Minimum x distance to double jump instead of walking.
+    #[serde(default = "double_jump_threshold_default")]
+    pub double_jump_threshold: u32,
+    /// Horizontal distance of each teleport hop with `0` to disable hopping.
+    #[serde(default)]
+    pub teleport_range: u32,
+    /// Number of ticks the player position must stay unchanged to be considered stationary.
+    #[serde(default = "stationary_ticks_default")]
+    pub stationary_ticks: u32,
     pub disable_adjusting: bool,
     #[serde(default)]
     pub disable_teleport_on_fall: bool,
@@ -378,17 +664,56 @@ pub struct Character {
     pub up_jump_is_flight: bool,
     #[serde(default)]
     pub up_jump_specific_key_should_jump: bool,
+    /// Number of ticks to wait before re-sending the jump key when up jumping.
+    #[serde(default = "up_jump_spam_delay_default")]
+    pub up_jump_spam_delay: u32,
+    /// Number of ticks between each up key tap when climbing up a rope with `0` to hold the key.
+    #[serde(default)]
+    pub climb_up_interval_ticks: u32,
+    /// Number of ticks between each down key tap when climbing down a rope with `0` to hold the
+    /// key.
+    #[serde(default)]
+    pub climb_down_interval_ticks: u32,
+    /// Whether double jumping taps the direction key after each jump key for flash jump.
+    #[serde(default)]
+    pub use_flash_jump: bool,
+    /// Whether to verify the player is facing the action key direction by the detected movement
+    /// direction before using the key.
+    #[serde(default)]
+    pub verify_direction_by_detection: bool,
     pub actions: Vec<ActionConfiguration>,
     #[serde(default, deserialize_with = "deserialize_with_ok_or_default")]
     pub elite_boss_behavior: EliteBossBehavior,
     #[serde(default)]
     pub elite_boss_behavior_key: KeyBinding,
+    /// Buffs to cast first, in order, when several buffs are missing at the same time.
+    #[serde(default)]
+    pub buffs_priority: Vec<CharacterBuffKind>,
+    /// Buffs to keep using even while halting.
+    #[serde(default)]
+    pub buffs_while_halting: Vec<CharacterBuffKind>,
 }
 
 fn feed_pet_count_default() -> u32 {
     3
 }
 
+fn wait_millis_default() -> u64 {
+    350
+}
+
+fn double_jump_threshold_default() -> u32 {
+    25
+}
+
+fn stationary_ticks_default() -> u32 {
+    6
+}
+
+fn up_jump_spam_delay_default() -> u32 {
+    7
+}
+
 fn jump_key_default() -> KeyBindingConfiguration {
     // Enabled is not neccessary but for semantic purpose
     KeyBindingConfiguration {
@@ -422,8 +747,15 @@ impl Default for Character {
             feed_pet_key: KeyBindingConfiguration::default(),
             feed_pet_millis: 320000,
             feed_pet_count: feed_pet_count_default(),
+            feed_pet_wait_before_millis: wait_millis_default(),
+            feed_pet_wait_after_millis: wait_millis_default(),
+            feed_pet_wait_millis_random_range: 0,
             potion_key: KeyBindingConfiguration::default(),
             potion_mode: PotionMode::EveryMillis(180000),
+            potion_wait_before_millis: wait_millis_default(),
+            potion_wait_after_millis: wait_millis_default(),
+            potion_wait_millis_random_range: 0,
+            extra_potions: vec![],
             health_update_millis: 1000,
             familiar_buff_key: KeyBindingConfiguration::default(),
             familiar_essence_key: key_default(),
@@ -448,17 +780,53 @@ impl Default for Character {
             vip_booster_key: KeyBindingConfiguration::default(),
             class: Class::default(),
             disable_double_jumping: false,
+            double_jump_threshold: double_jump_threshold_default(),
+            teleport_range: 0,
+            stationary_ticks: stationary_ticks_default(),
             disable_adjusting: false,
             disable_teleport_on_fall: false,
             up_jump_is_flight: false,
             up_jump_specific_key_should_jump: false,
+            up_jump_spam_delay: up_jump_spam_delay_default(),
+            climb_up_interval_ticks: 0,
+            climb_down_interval_ticks: 0,
+            use_flash_jump: false,
+            verify_direction_by_detection: false,
             actions: vec![],
             elite_boss_behavior_key: KeyBinding::default(),
             elite_boss_behavior: EliteBossBehavior::default(),
+            buffs_priority: vec![],
+            buffs_while_halting: vec![],
         }
     }
 }
 
+/// A buff that can be configured for a [`Character`].
+#[derive(
+    Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum CharacterBuffKind {
+    Familiar,
+    SayramElixir,
+    AureliaElixir,
+    ExpCouponX2,
+    ExpCouponX3,
+    ExpCouponX4,
+    BonusExpCoupon,
+    LegionWealth,
+    LegionLuck,
+    WealthAcquisitionPotion,
+    ExpAccumulationPotion,
+    SmallWealthAcquisitionPotion,
+    SmallExpAccumulationPotion,
+    ForTheGuild,
+    HardHitter,
+    ExtremeRedPotion,
+    ExtremeBluePotion,
+    ExtremeGreenPotion,
+    ExtremeGoldPotion,
+}
+
 #[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, EnumIter, Display, EnumString)]
 pub enum PotionMode {
     EveryMillis(u64),
@@ -471,6 +839,26 @@ impl Default for PotionMode {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PotionConfiguration {
+    pub key: KeyBindingConfiguration,
+    #[serde(default = "potion_mode_default")]
+    pub mode: PotionMode,
+}
+
+impl Default for PotionConfiguration {
+    fn default() -> Self {
+        Self {
+            key: KeyBindingConfiguration::default(),
+            mode: potion_mode_default(),
+        }
+    }
+}
+
+fn potion_mode_default() -> PotionMode {
+    PotionMode::EveryMillis(180000)
+}
+
 #[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, EnumIter, Display, EnumString)]
 pub enum ActionConfigurationCondition {
     EveryMillis(u64),
@@ -535,6 +923,7 @@ impl From<ActionConfiguration> for Action {
             wait_before_use_millis_random_range: value.wait_before_millis_random_range,
             wait_after_use_millis: value.wait_after_millis,
             wait_after_use_millis_random_range: value.wait_after_millis_random_range,
+            force: false,
         })
     }
 }
@@ -612,6 +1001,8 @@ pub enum RotationMode {
     StartToEndThenReverse,
     AutoMobbing,
     PingPong,
+    /// Ping pong that also alternates vertically using [`Minimap::rotation_ping_pong_bound`].
+    Patrol,
 }
 
 impl_identifiable!(Character);
@@ -627,8 +1018,20 @@ pub struct Minimap {
     pub rotation_mode: RotationMode,
     #[serde(default)]
     pub rotation_ping_pong_bound: Bound,
+    /// Number of pixels to inset [`Self::rotation_ping_pong_bound`] horizontal edges by.
+    ///
+    /// The player turns around once reaching the inset edge to avoid hugging walls.
+    #[serde(default)]
+    pub rotation_ping_pong_bound_inset: u32,
+    /// Weights for chaining one, two or three double jumps before turning around in ping pong.
+    #[serde(default)]
+    pub rotation_ping_pong_double_jump_weights: [u32; 3],
     #[serde(default)]
     pub rotation_auto_mob_bound: Bound,
+    /// Additional disjoint auto mobbing bounds to cycle through after
+    /// [`Self::rotation_auto_mob_bound`].
+    #[serde(default)]
+    pub rotation_auto_mob_extra_bounds: Vec<Bound>,
     #[serde(default)]
     pub rotation_mobbing_key: MobbingKey,
     pub platforms: Vec<Platform>,
@@ -641,8 +1044,43 @@ pub struct Minimap {
     pub auto_mob_use_key_when_pathing: bool,
     #[serde(default)]
     pub auto_mob_use_key_when_pathing_update_millis: u64,
+    /// Percentage of the auto mobbing bound half width the player can stray from the bound
+    /// center before moving back to the center.
+    ///
+    /// Zero disables re-centering.
+    #[serde(default)]
+    pub auto_mob_recenter_percent: u32,
+    /// Minimum milliseconds between each auto mobbing key use.
+    #[serde(default)]
+    pub auto_mob_attack_interval_millis: u64,
+    /// Whether auto mobbing prefers mobs on higher platforms when choosing a target.
+    #[serde(default)]
+    pub auto_mob_prefer_higher_platforms: bool,
+    /// Number of ticks for an ignored auto mobbing position to decay by half.
+    ///
+    /// Zero disables decaying.
+    #[serde(default)]
+    pub auto_mob_ignore_xs_half_life_ticks: u32,
     pub actions_any_reset_on_erda_condition: bool,
+    /// Whether to disable rune solving on this map even if it is enabled in [`Settings`].
+    #[serde(default)]
+    pub disable_rune_solving: bool,
+    /// Whether to keep up jumping even when the player is near a portal on this map.
+    #[serde(default)]
+    pub disable_portal_upjump_abort: bool,
+    /// Whether to stop horizontal movement at the current platform edge when the destination is
+    /// not on any of [`Self::platforms`].
+    #[serde(default)]
+    pub stop_at_platform_edge: bool,
+    /// Percentage of the baseline walking speed the player walks at on this map.
+    ///
+    /// This is calibrated automatically the first time the player walks on this map.
+    #[serde(default)]
+    pub movement_scale_percent: Option<u32>,
     pub actions: HashMap<String, Vec<Action>>,
+    /// Optional metadata of presets in [`Self::actions`] keyed by the same preset name.
+    #[serde(default)]
+    pub preset_metadata: HashMap<String, PresetMetadata>,
     // Not FK, loose coupling to another navigation paths and its index
     #[serde(default)]
     pub paths_id_index: Option<(i64, usize)>,
@@ -650,6 +1088,42 @@ pub struct Minimap {
 
 impl_identifiable!(Minimap);
 
+/// User annotations of an actions preset.
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PresetMetadata {
+    #[serde(default)]
+    pub description: String,
+    /// Seconds since Unix epoch the preset was created at.
+    #[serde(default)]
+    pub created_at: Option<u64>,
+}
+
+impl PresetMetadata {
+    /// Creates a new [`PresetMetadata`] with `description` created at the current time.
+    pub fn new(description: String) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|duration| duration.as_secs());
+
+        Self {
+            description,
+            created_at,
+        }
+    }
+}
+
+/// A named, reusable sequence of actions that can be inserted into any minimap preset.
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ActionSnippet {
+    #[serde(skip_serializing, default)]
+    pub id: Option<i64>,
+    pub name: String,
+    pub actions: Vec<Action>,
+}
+
+impl_identifiable!(ActionSnippet);
+
 #[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct NavigationPaths {
     #[serde(skip_serializing, default)]
@@ -684,8 +1158,12 @@ pub struct NavigationPoint {
     Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
 )]
 pub enum NavigationTransition {
+    /// Enters the portal using the up key.
     #[default]
     Portal,
+    /// Enters the portal using the interact key.
+    #[strum(to_string = "Portal (Interact)")]
+    PortalInteract,
 }
 
 fn deserialize_with_ok_or_default<'a, T, D>(deserializer: D) -> Result<T, D::Error>
@@ -724,6 +1202,11 @@ pub struct ActionMove {
     pub position: Position,
     pub condition: ActionCondition,
     pub wait_after_move_millis: u64,
+    /// Maximum distance in both axes from the destination to consider the move arrived.
+    ///
+    /// A value of `0` uses the default movement thresholds.
+    #[serde(default)]
+    pub arrival_tolerance: i32,
 }
 
 #[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
@@ -741,6 +1224,10 @@ pub struct ActionKey {
     pub wait_after_use_millis: u64,
     pub wait_after_use_millis_random_range: u64,
     pub queue_to_front: Option<bool>,
+    /// Whether to use the key immediately without satisfying [`Self::direction`] and
+    /// [`Self::with`].
+    #[serde(default)]
+    pub force: bool,
 }
 
 impl Default for ActionKey {
@@ -758,6 +1245,7 @@ impl Default for ActionKey {
             wait_after_use_millis: 0,
             wait_after_use_millis_random_range: 0,
             queue_to_front: None,
+            force: false,
         }
     }
 }
@@ -800,6 +1288,31 @@ fn count_default() -> u32 {
     1
 }
 
+/// An action that keeps the player stationary for a duration.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ActionHoldPosition {
+    pub hold_millis: u64,
+    /// The direction to face before holding.
+    pub direction: ActionKeyDirection,
+    pub condition: ActionCondition,
+}
+
+/// An action that stalls the rotation until a buff is present or timing out.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ActionWaitForBuff {
+    pub buff: CharacterBuffKind,
+    pub condition: ActionCondition,
+}
+
+impl Default for ActionWaitForBuff {
+    fn default() -> Self {
+        Self {
+            buff: CharacterBuffKind::Familiar,
+            condition: ActionCondition::default(),
+        }
+    }
+}
+
 #[derive(
     Clone, Copy, Display, Default, EnumString, EnumIter, PartialEq, Debug, Serialize, Deserialize,
 )]
@@ -815,6 +1328,8 @@ pub enum Class {
 pub enum Action {
     Move(ActionMove),
     Key(ActionKey),
+    HoldPosition(ActionHoldPosition),
+    WaitForBuff(ActionWaitForBuff),
 }
 
 impl Action {
@@ -822,6 +1337,8 @@ impl Action {
         match self {
             Action::Move(action) => action.condition,
             Action::Key(action) => action.condition,
+            Action::HoldPosition(action) => action.condition,
+            Action::WaitForBuff(action) => action.condition,
         }
     }
 
@@ -835,6 +1352,14 @@ impl Action {
                 condition,
                 ..*action
             }),
+            Action::HoldPosition(action) => Action::HoldPosition(ActionHoldPosition {
+                condition,
+                ..*action
+            }),
+            Action::WaitForBuff(action) => Action::WaitForBuff(ActionWaitForBuff {
+                condition,
+                ..*action
+            }),
         }
     }
 }
@@ -858,6 +1383,8 @@ pub enum ActionKeyWith {
     Any,
     Stationary,
     DoubleJump,
+    Falling,
+    OnGround,
 }
 
 #[derive(
@@ -1126,6 +1653,18 @@ pub fn delete_navigation_paths(paths: &NavigationPaths) -> Result<()> {
     })
 }
 
+pub fn query_action_snippets() -> Result<Vec<ActionSnippet>> {
+    query_from_table(ACTION_SNIPPETS)
+}
+
+pub fn upsert_action_snippet(snippet: &mut ActionSnippet) -> Result<()> {
+    upsert_to_table(ACTION_SNIPPETS, snippet)
+}
+
+pub fn delete_action_snippet(snippet: &ActionSnippet) -> Result<()> {
+    delete_from_table(ACTION_SNIPPETS, snippet)
+}
+
 fn map_data<T>(mut stmt: Statement<'_>, params: impl Params) -> Result<Vec<T>>
 where
     T: DeserializeOwned + Identifiable + Default,
@@ -1196,3 +1735,36 @@ fn delete_from_table<T: Identifiable>(table: &str, data: &T) -> Result<()> {
 
     inner(table, data.id())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimap_preset_metadata_round_trip() {
+        let mut minimap = Minimap::default();
+        minimap.actions.insert("preset".to_string(), vec![]);
+        minimap.preset_metadata.insert(
+            "preset".to_string(),
+            PresetMetadata {
+                description: "night farming".to_string(),
+                created_at: Some(1_700_000_000),
+            },
+        );
+
+        let json = serde_json::to_string(&minimap).unwrap();
+        let loaded = serde_json::from_str::<Minimap>(&json).unwrap();
+
+        assert_eq!(loaded.preset_metadata, minimap.preset_metadata);
+    }
+
+    #[test]
+    fn minimap_without_preset_metadata_loads_default() {
+        let mut json = serde_json::to_value(Minimap::default()).unwrap();
+        json.as_object_mut().unwrap().remove("preset_metadata");
+
+        let loaded = serde_json::from_value::<Minimap>(json).unwrap();
+
+        assert!(loaded.preset_metadata.is_empty());
+    }
+}
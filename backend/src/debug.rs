@@ -23,6 +23,7 @@ use opencv::{
     imgcodecs::imwrite_def,
 };
 use rand::distr::{Alphanumeric, SampleString};
+use serde::{Deserialize, Serialize};
 
 use crate::bridge::KeyKind;
 
@@ -230,6 +231,17 @@ pub fn save_image_for_training_to(
     imwrite_def(image.to_str().unwrap(), &mat).unwrap();
 }
 
+/// Saves already encoded `frames` in order to a new folder under the dataset directory.
+pub fn save_encoded_frames<'a>(frames: impl Iterator<Item = &'a [u8]>, extension: &str) {
+    let id = Alphanumeric.sample_string(&mut rand::rng(), 8);
+    let dir = DATASET_DIR.join("recent").join(id);
+    fs::create_dir_all(dir.clone()).unwrap();
+
+    for (i, frame) in frames.enumerate() {
+        fs::write(dir.join(format!("{i:04}{extension}")), frame).unwrap();
+    }
+}
+
 #[allow(unused)]
 pub fn debug_rune(mat: &Mat, preds: &Vec<&[f32]>, w_ratio: f32, h_ratio: f32) {
     let size = mat.size().unwrap();
@@ -276,10 +288,44 @@ pub fn save_rune_for_training<T: MatTraitConst + ToInputArray>(
 
     let dataset = &DATASET_RUNE_DIR;
     let label = dataset.join(format!("{name}.txt"));
+    let sidecar = dataset.join(format!("{name}.json"));
     let image = dataset.join(format!("{name}.png"));
 
     imwrite_def(image.to_str().unwrap(), mat).unwrap();
     fs::write(label, labels).unwrap();
+    fs::write(sidecar, rune_result_to_json(result)).unwrap();
+}
+
+/// A labeled arrow of a solved rune saved alongside the training image.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct RuneArrowLabel {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    key: String,
+}
+
+fn rune_result_to_json(result: [(Rect, KeyKind); 4]) -> String {
+    let labels = result
+        .into_iter()
+        .map(|(bbox, arrow)| RuneArrowLabel {
+            x: bbox.x,
+            y: bbox.y,
+            width: bbox.width,
+            height: bbox.height,
+            key: match arrow {
+                KeyKind::Up => "up",
+                KeyKind::Down => "down",
+                KeyKind::Left => "left",
+                KeyKind::Right => "right",
+                _ => unreachable!(),
+            }
+            .to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_string_pretty(&labels).unwrap()
 }
 
 #[allow(unused)]
@@ -352,3 +398,55 @@ fn to_grayscale(mat: &impl MatTraitConst) -> Mat {
     }
     mat
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rune_result_to_json_matches_result() {
+        let result = [
+            (Rect::new(1, 2, 3, 4), KeyKind::Up),
+            (Rect::new(5, 6, 7, 8), KeyKind::Down),
+            (Rect::new(9, 10, 11, 12), KeyKind::Left),
+            (Rect::new(13, 14, 15, 16), KeyKind::Right),
+        ];
+
+        let labels =
+            serde_json::from_str::<Vec<RuneArrowLabel>>(&rune_result_to_json(result)).unwrap();
+
+        assert_eq!(
+            labels,
+            vec![
+                RuneArrowLabel {
+                    x: 1,
+                    y: 2,
+                    width: 3,
+                    height: 4,
+                    key: "up".to_string(),
+                },
+                RuneArrowLabel {
+                    x: 5,
+                    y: 6,
+                    width: 7,
+                    height: 8,
+                    key: "down".to_string(),
+                },
+                RuneArrowLabel {
+                    x: 9,
+                    y: 10,
+                    width: 11,
+                    height: 12,
+                    key: "left".to_string(),
+                },
+                RuneArrowLabel {
+                    x: 13,
+                    y: 14,
+                    width: 15,
+                    height: 16,
+                    key: "right".to_string(),
+                },
+            ]
+        );
+    }
+}
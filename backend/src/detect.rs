@@ -1,9 +1,12 @@
 use core::slice::SlicePattern;
 use std::{
+    any::Any,
+    borrow::Cow,
     collections::HashMap,
     env,
     fmt::Debug,
     sync::{Arc, LazyLock, Mutex},
+    thread,
 };
 
 use anyhow::{Result, anyhow, bail};
@@ -18,8 +21,8 @@ use opencv::{
         BORDER_CONSTANT, CMP_EQ, CMP_GT, CV_8U, CV_32FC3, CV_32S, Mat, MatExprTraitConst, MatTrait,
         MatTraitConst, MatTraitConstManual, ModifyInplace, Point, Range, Rect, Scalar, Size,
         ToInputArray, Vec4b, Vector, add, add_weighted_def, bitwise_and_def, compare,
-        copy_make_border, divide2_def, extract_channel, find_non_zero, min_max_loc, no_array,
-        subtract_def, transpose_nd,
+        copy_make_border, divide2_def, extract_channel, find_non_zero, in_range, min_max_loc,
+        no_array, subtract_def, transpose_nd,
     },
     dnn::{
         ModelTrait, TextRecognitionModel, TextRecognitionModelTrait,
@@ -29,10 +32,11 @@ use opencv::{
     imgproc::{
         CC_STAT_AREA, CC_STAT_HEIGHT, CC_STAT_LEFT, CC_STAT_TOP, CC_STAT_WIDTH,
         CHAIN_APPROX_SIMPLE, COLOR_BGR2HSV_FULL, COLOR_BGRA2BGR, COLOR_BGRA2GRAY, COLOR_BGRA2RGB,
-        INTER_CUBIC, INTER_LINEAR, MORPH_RECT, RETR_EXTERNAL, THRESH_BINARY, TM_CCOEFF_NORMED,
-        TM_SQDIFF_NORMED, bounding_rect, connected_components_with_stats, contour_area,
-        cvt_color_def, dilate_def, find_contours_def, get_structuring_element_def, match_template,
-        min_area_rect, min_enclosing_triangle, resize, threshold,
+        INTER_AREA, INTER_CUBIC, INTER_LINEAR, MORPH_RECT, RETR_EXTERNAL, THRESH_BINARY,
+        THRESH_BINARY_INV, TM_CCOEFF_NORMED, TM_SQDIFF_NORMED, bounding_rect,
+        connected_components_with_stats, contour_area, cvt_color_def, dilate_def, erode_def,
+        find_contours_def, get_structuring_element_def, match_template, min_area_rect,
+        min_enclosing_triangle, resize, threshold,
     },
 };
 use ort::{
@@ -40,14 +44,38 @@ use ort::{
     session::{Session, SessionInputValue, SessionOutputs},
     value::TensorRef,
 };
+use rayon::{
+    ThreadPool, ThreadPoolBuilder,
+    iter::{IntoParallelRefIterator, ParallelIterator},
+};
 
 #[cfg(debug_assertions)]
 use crate::debug::{debug_mat, debug_spinning_arrows};
+use crate::{
+    ColorThresholds, DetectionRegion, GameTemplate, bridge::KeyKind, models::Localization,
+};
 use crate::{array::Array, mat::OwnedMat};
-use crate::{bridge::KeyKind, models::Localization};
 
 const MAX_ARROWS: usize = 4;
+const MAX_DETECTION_THREADS: usize = 4;
+const MIN_BUFF_DETECTION_SCALE_PERCENT: u32 = 25;
+/// The minimum confidence of a detected rune arrow.
+///
+/// Pressing a wrong key fails the rune and puts it on cooldown, so arrows below this confidence
+/// are discarded and the result is not used for solving.
+pub const MIN_ARROW_CONFIDENCE: f32 = 0.9;
+/// The lower HSV threshold of the player dot color in the minimap.
+///
+/// The player dot is yellow, which is around 42 hue in the full `0..=255` range.
+pub const PLAYER_DOT_COLOR_LOWER: [u8; 3] = [30, 120, 180];
+/// The upper HSV threshold of the player dot color in the minimap.
+pub const PLAYER_DOT_COLOR_UPPER: [u8; 3] = [50, 255, 255];
 const MAX_SPIN_ARROWS: usize = 2; // PRAY
+/// The confidence of a spinning arrow.
+///
+/// Spinning arrows are resolved from arrow head movements instead of the model so there is no
+/// score to report.
+const SPIN_ARROW_CONFIDENCE: f32 = 1.0;
 
 /// Struct for storing information about the spinning arrows.
 #[derive(Debug, Copy, Clone)]
@@ -60,15 +88,36 @@ struct SpinArrow {
     last_arrow_head: Option<Point>,
     /// Final result of spinning arrow.
     final_arrow: Option<KeyKind>,
+    /// Number of consecutive samples the spinning arrow has lagged.
+    lag_samples: u32,
     #[cfg(debug_assertions)]
     is_spin_testing: bool,
 }
 
+/// Parameters for calibrating spinning arrows.
+#[derive(Debug, Copy, Clone)]
+pub struct SpinArrowsConfig {
+    /// Number of consecutive lagging samples required to determine the spinning arrow.
+    pub lag_samples: u32,
+    /// The minimum rotation between two samples for the spinning arrow to be considered lagging.
+    pub lag_threshold: i32,
+}
+
+impl Default for SpinArrowsConfig {
+    fn default() -> Self {
+        Self {
+            lag_samples: 1,
+            lag_threshold: 30,
+        }
+    }
+}
+
 /// The current arrows detection/calibration state.
 #[derive(Debug)]
 pub enum ArrowsState {
     Calibrating(ArrowsCalibrating),
-    Complete([(Rect, KeyKind); MAX_ARROWS]),
+    /// The detected arrows and the confidence of each arrow in the same order.
+    Complete([(Rect, KeyKind); MAX_ARROWS], [f32; MAX_ARROWS]),
 }
 
 /// Struct representing arrows calibration in-progress
@@ -77,12 +126,29 @@ pub struct ArrowsCalibrating {
     spin_arrows: Option<Array<SpinArrow, MAX_SPIN_ARROWS>>,
     spin_arrows_calibrated: bool,
     rune_region: Option<Rect>,
-    normal_arrows: Option<Array<(Rect, KeyKind), MAX_ARROWS>>,
+    normal_arrows: Option<Array<(Rect, KeyKind, f32), MAX_ARROWS>>,
+    spin_config: SpinArrowsConfig,
     #[cfg(debug_assertions)]
     is_spin_testing: bool,
 }
 
 impl ArrowsCalibrating {
+    pub fn new(spin_config: SpinArrowsConfig) -> Self {
+        Self {
+            spin_config,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a new [`ArrowsCalibrating`] with the same parameters to calibrate from scratch.
+    pub fn restart(&self) -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            is_spin_testing: self.is_spin_testing,
+            ..Self::new(self.spin_config)
+        }
+    }
+
     #[cfg(debug_assertions)]
     pub fn enable_spin_test(&mut self) {
         self.is_spin_testing = true;
@@ -177,6 +243,9 @@ pub trait Detector: 'static + Send + DynClone + Debug {
     /// Detects the minimap name rectangle.
     fn detect_minimap_name(&self, minimap: Rect) -> Result<Rect>;
 
+    /// Detects the minimap name text using text recognition.
+    fn detect_minimap_name_text(&self, minimap: Rect) -> Result<String>;
+
     /// Detects whether the given `minimap_snapshot` and `minimap_name_snapshot` matches the one
     /// cropped by `minimap_name_bbox` and `minimap_bbox` rectangles.
     fn detect_minimap_match(
@@ -193,6 +262,11 @@ pub trait Detector: 'static + Send + DynClone + Debug {
     /// Returns `Rect` relative to `minimap` coordinate.
     fn detect_minimap_portals(&self, minimap: Rect) -> Vec<Rect>;
 
+    /// Detects the ropes and ladders from the given `minimap` rectangle.
+    ///
+    /// Returns `Rect` relative to `minimap` coordinate.
+    fn detect_minimap_ropes(&self, minimap: Rect) -> Vec<Rect>;
+
     /// Detects the rune from the given `minimap` rectangle.
     ///
     /// Returns `Rect` relative to `minimap` coordinate.
@@ -203,9 +277,31 @@ pub trait Detector: 'static + Send + DynClone + Debug {
     /// Returns `Rect` relative to `minimap` coordinate.
     fn detect_player(&self, minimap: Rect) -> Result<Rect>;
 
+    /// Detects the player in the provided `minimap` rectangle by the player dot color.
+    ///
+    /// This is a less accurate fallback for when [`Self::detect_player`] fails while the minimap
+    /// is still visible. When there are multiple candidates, the one closest to `near` is picked
+    /// if provided. Otherwise, the largest one is picked.
+    ///
+    /// `near` and the returned `Rect` are relative to `minimap` coordinate.
+    fn detect_player_by_color(&self, minimap: Rect, near: Option<Point>) -> Result<Rect>;
+
+    /// Detects the mask of pixels inside `minimap` with HSV values between `lower` and `upper`.
+    fn detect_minimap_color_mask(
+        &self,
+        minimap: Rect,
+        lower: [u8; 3],
+        upper: [u8; 3],
+    ) -> Result<Mat>;
+
     /// Detects whether a player of `kind` is in the minimap.
     fn detect_player_kind(&self, minimap: Rect, kind: OtherPlayerKind) -> bool;
 
+    /// Detects the names of other players visible on the screen.
+    ///
+    /// The names are read from the name tags below each character, excluding the player's own.
+    fn detect_player_names(&self) -> Vec<String>;
+
     /// Detects whether the player is dead.
     fn detect_player_is_dead(&self) -> bool;
 
@@ -283,6 +379,11 @@ pub trait Detector: 'static + Send + DynClone + Debug {
 
     /// Detects whether the state for VIP Booster in the quick slots.
     fn detect_booster(&self, kind: BoosterKind) -> BoosterState;
+
+    /// Detects the best match confidence of `template` in the whole image regardless of threshold.
+    ///
+    /// Returns the confidence and whether it passes the threshold used to detect `template`.
+    fn detect_template_confidence(&self, template: GameTemplate) -> Result<(f64, bool)>;
 }
 
 #[cfg(test)]
@@ -299,6 +400,7 @@ mock! {
         fn detect_elite_boss_bar(&self) -> bool;
         fn detect_minimap(&self, border_threshold: u8) -> Result<Rect>;
         fn detect_minimap_name(&self, minimap: Rect) -> Result<Rect>;
+        fn detect_minimap_name_text(&self, minimap: Rect) -> Result<String>;
         fn detect_minimap_match(
             &self,
             minimap_snapshot: &Mat,
@@ -308,9 +410,18 @@ mock! {
             minimap_name_bbox: Rect,
         ) -> Result<f64>;
         fn detect_minimap_portals(&self, minimap: Rect) -> Vec<Rect>;
+        fn detect_minimap_ropes(&self, minimap: Rect) -> Vec<Rect>;
         fn detect_minimap_rune(&self, minimap: Rect) -> Result<Rect>;
         fn detect_player(&self, minimap: Rect) -> Result<Rect>;
+        fn detect_player_by_color(&self, minimap: Rect, near: Option<Point>) -> Result<Rect>;
+        fn detect_minimap_color_mask(
+            &self,
+            minimap: Rect,
+            lower: [u8; 3],
+            upper: [u8; 3],
+        ) -> Result<Mat>;
         fn detect_player_kind(&self, minimap: Rect, kind: OtherPlayerKind) -> bool;
+        fn detect_player_names(&self) -> Vec<String>;
         fn detect_player_is_dead(&self) -> bool;
         fn detect_player_in_cash_shop(&self) -> bool;
         fn detect_player_health_bar(&self) -> Result<Rect>;
@@ -337,6 +448,7 @@ mock! {
         fn detect_admin_visible(&self) -> bool;
         fn detect_timer_visible(&self) -> bool;
         fn detect_booster(&self, kind: BoosterKind) -> BoosterState;
+        fn detect_template_confidence(&self, template: GameTemplate) -> Result<(f64, bool)>;
     }
 
     impl Debug for Detector {
@@ -350,17 +462,110 @@ mock! {
 
 type MatFn = Box<dyn FnOnce() -> Mat + Send>;
 
+/// Key identifying a cached detection result of a single frame.
+///
+/// Regions are stored as `(x, y, width, height)` and kinds as their discriminants.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum DetectionKey {
+    EscSettings,
+    EliteBossBar,
+    MinimapRune((i32, i32, i32, i32)),
+    Player((i32, i32, i32, i32)),
+    PlayerByColor((i32, i32, i32, i32), Option<(i32, i32)>),
+    PlayerKind((i32, i32, i32, i32), usize),
+    PlayerIsDead,
+    PlayerBuff(usize),
+}
+
+impl DetectionKey {
+    #[inline]
+    fn region(rect: Rect) -> (i32, i32, i32, i32) {
+        (rect.x, rect.y, rect.width, rect.height)
+    }
+}
+
+/// A cache of detection results for a single frame.
+///
+/// Repeated detections with the same [`DetectionKey`] reuse the first result instead of
+/// re-running template matching. Since a new [`DefaultDetector`] is created for each frame, the
+/// cache is naturally cleared per frame.
+#[derive(Default, Debug)]
+struct DetectionCache {
+    results: Mutex<HashMap<DetectionKey, Box<dyn Any + Send>>>,
+}
+
+impl DetectionCache {
+    fn get_or_detect<T: Clone + Send + 'static>(
+        &self,
+        key: DetectionKey,
+        detect: impl FnOnce() -> T,
+    ) -> T {
+        if let Some(result) = self
+            .results
+            .lock()
+            .unwrap()
+            .get(&key)
+            .and_then(|result| result.downcast_ref::<T>())
+        {
+            return result.clone();
+        }
+
+        // Lock is not held while detecting so that detections can be nested
+        let result = detect();
+        self.results
+            .lock()
+            .unwrap()
+            .insert(key, Box::new(result.clone()));
+        result
+    }
+
+    /// Same as [`Self::get_or_detect`] but for detections returning [`Result`].
+    ///
+    /// The error is cached as a message since [`anyhow::Error`] is not cloneable.
+    fn get_or_detect_result<T: Clone + Send + 'static>(
+        &self,
+        key: DetectionKey,
+        detect: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        self.get_or_detect(key, || detect().map_err(|err| err.to_string()))
+            .map_err(|err| anyhow!(err))
+    }
+}
+
 /// A detector that lazily transform `Mat`.
 #[derive(Clone, Debug)]
 pub struct DefaultDetector {
     mat: Arc<OwnedMat>,
     grayscale: Arc<LazyLock<Mat, MatFn>>,
     buffs_grayscale: Arc<LazyLock<Mat, MatFn>>,
+    buffs_grayscale_scaled: Arc<LazyLock<Mat, MatFn>>,
+    buffs_bgr: Arc<LazyLock<Mat, MatFn>>,
+    buffs_bgr_scaled: Arc<LazyLock<Mat, MatFn>>,
     localization: Arc<Localization>,
+    cache: Arc<DetectionCache>,
+    /// Whether to run independent detections in parallel.
+    parallel: bool,
+    /// The percentage to downscale the buffs region to before detecting buffs.
+    buff_scale_percent: u32,
+    /// The region of interest to detect buffs in.
+    buffs_region: DetectionRegion,
+    /// The region of interest to detect popups in.
+    popups_region: DetectionRegion,
+    /// The HSV thresholds to detect the player dot by color.
+    player_dot_color: ColorThresholds,
 }
 
 impl DefaultDetector {
-    pub fn new(mat: OwnedMat, localization: Arc<Localization>) -> DefaultDetector {
+    pub fn new(
+        mat: OwnedMat,
+        localization: Arc<Localization>,
+        parallel: bool,
+        buff_scale_percent: u32,
+        buffs_region: DetectionRegion,
+        popups_region: DetectionRegion,
+        player_dot_color: ColorThresholds,
+    ) -> DefaultDetector {
+        let buff_scale_percent = buff_scale_percent.clamp(MIN_BUFF_DETECTION_SCALE_PERCENT, 100);
         let mat = Arc::new(mat);
         let grayscale = mat.clone();
         let grayscale = Arc::new(LazyLock::<Mat, MatFn>::new(Box::new(move || {
@@ -368,15 +573,66 @@ impl DefaultDetector {
         })));
         let buffs_grayscale = grayscale.clone();
         let buffs_grayscale = Arc::new(LazyLock::<Mat, MatFn>::new(Box::new(move || {
-            to_buffs_region(&**buffs_grayscale).clone_pointee()
+            to_detection_region(&**buffs_grayscale, buffs_region)
+                .0
+                .clone_pointee()
+        })));
+        let buffs_grayscale_scaled = buffs_grayscale.clone();
+        let buffs_grayscale_scaled = Arc::new(LazyLock::<Mat, MatFn>::new(Box::new(move || {
+            to_scaled(&**buffs_grayscale_scaled, buff_scale_percent)
+        })));
+        let buffs_bgr = mat.clone();
+        let buffs_bgr = Arc::new(LazyLock::<Mat, MatFn>::new(Box::new(move || {
+            to_bgr(&to_detection_region(&*buffs_bgr, buffs_region).0)
+        })));
+        let buffs_bgr_scaled = buffs_bgr.clone();
+        let buffs_bgr_scaled = Arc::new(LazyLock::<Mat, MatFn>::new(Box::new(move || {
+            to_scaled(&**buffs_bgr_scaled, buff_scale_percent)
         })));
         Self {
             mat,
             grayscale,
             buffs_grayscale,
+            buffs_grayscale_scaled,
+            buffs_bgr,
+            buffs_bgr_scaled,
             localization,
+            cache: Arc::new(DetectionCache::default()),
+            parallel,
+            buff_scale_percent,
+            buffs_region,
+            popups_region,
+            player_dot_color,
         }
     }
+
+    fn detect_player_buff_uncached(&self, kind: BuffKind) -> bool {
+        let (mat, mat_scaled) = match kind {
+            BuffKind::Rune
+            | BuffKind::Familiar
+            | BuffKind::SayramElixir
+            | BuffKind::AureliaElixir
+            | BuffKind::ExpCouponX2
+            | BuffKind::ExpCouponX3
+            | BuffKind::ExpCouponX4
+            | BuffKind::BonusExpCoupon
+            | BuffKind::ForTheGuild
+            | BuffKind::HardHitter => (&self.buffs_grayscale, &self.buffs_grayscale_scaled),
+            BuffKind::LegionWealth
+            | BuffKind::LegionLuck
+            | BuffKind::WealthAcquisitionPotion
+            | BuffKind::ExpAccumulationPotion
+            | BuffKind::SmallWealthAcquisitionPotion
+            | BuffKind::SmallExpAccumulationPotion
+            | BuffKind::ExtremeRedPotion
+            | BuffKind::ExtremeBluePotion
+            | BuffKind::ExtremeGreenPotion
+            | BuffKind::ExtremeGoldPotion => (&self.buffs_bgr, &self.buffs_bgr_scaled),
+        };
+        let scaled =
+            (self.buff_scale_percent < 100).then(|| (&***mat_scaled, self.buff_scale_percent));
+        detect_player_buff(&***mat, kind, scaled)
+    }
 }
 
 impl Detector for DefaultDetector {
@@ -393,19 +649,31 @@ impl Detector for DefaultDetector {
     }
 
     fn detect_esc_settings(&self) -> bool {
-        detect_esc_settings(&**self.grayscale, &self.localization)
+        self.cache.get_or_detect(DetectionKey::EscSettings, || {
+            let (popups, _) = to_detection_region(&**self.grayscale, self.popups_region);
+            detect_esc_settings(
+                &**self.grayscale,
+                &popups,
+                &self.localization,
+                self.parallel,
+            )
+        })
     }
 
     fn detect_popup_confirm_button(&self) -> Result<Rect> {
-        detect_popup_confirm_button(&**self.grayscale, &self.localization)
+        let (popups, bbox) = to_detection_region(&**self.grayscale, self.popups_region);
+        detect_popup_confirm_button(&popups, &self.localization, bbox.tl())
     }
 
     fn detect_popup_ok_new_button(&self) -> Result<Rect> {
-        detect_popup_ok_new_button(&**self.grayscale, &self.localization)
+        let (popups, bbox) = to_detection_region(&**self.grayscale, self.popups_region);
+        detect_popup_ok_new_button(&popups, &self.localization, bbox.tl())
     }
 
     fn detect_elite_boss_bar(&self) -> bool {
-        detect_elite_boss_bar(&**self.grayscale)
+        self.cache.get_or_detect(DetectionKey::EliteBossBar, || {
+            detect_elite_boss_bar(&**self.grayscale)
+        })
     }
 
     fn detect_minimap(&self, border_threshold: u8) -> Result<Rect> {
@@ -416,6 +684,15 @@ impl Detector for DefaultDetector {
         detect_minimap_name(&**self.grayscale, minimap)
     }
 
+    fn detect_minimap_name_text(&self, minimap: Rect) -> Result<String> {
+        let name = detect_minimap_name(&**self.grayscale, minimap)?;
+        extract_texts(&*self.mat, &[name])
+            .into_iter()
+            .map(|name| name.trim().to_string())
+            .find(|name| !name.is_empty())
+            .ok_or(anyhow!("minimap name text not recognized"))
+    }
+
     fn detect_minimap_match(
         &self,
         minimap_snapshot: &Mat,
@@ -440,23 +717,63 @@ impl Detector for DefaultDetector {
         detect_minimap_portals(minimap_color)
     }
 
+    fn detect_minimap_ropes(&self, minimap: Rect) -> Vec<Rect> {
+        let minimap_grayscale = to_grayscale(&self.mat.roi(minimap).unwrap(), false);
+        detect_minimap_ropes(minimap_grayscale)
+    }
+
     fn detect_minimap_rune(&self, minimap: Rect) -> Result<Rect> {
-        let minimap_color = to_bgr(&self.mat.roi(minimap)?);
-        detect_minimap_rune(&minimap_color)
+        let key = DetectionKey::MinimapRune(DetectionKey::region(minimap));
+        self.cache.get_or_detect_result(key, || {
+            let minimap_color = to_bgr(&self.mat.roi(minimap)?);
+            detect_minimap_rune(&minimap_color)
+        })
     }
 
     fn detect_player(&self, minimap: Rect) -> Result<Rect> {
-        let minimap_color = to_bgr(&self.mat.roi(minimap)?);
-        detect_player(&minimap_color)
+        let key = DetectionKey::Player(DetectionKey::region(minimap));
+        self.cache.get_or_detect_result(key, || {
+            let minimap_color = to_bgr(&self.mat.roi(minimap)?);
+            detect_player(&minimap_color)
+        })
+    }
+
+    fn detect_player_by_color(&self, minimap: Rect, near: Option<Point>) -> Result<Rect> {
+        let key = DetectionKey::PlayerByColor(
+            DetectionKey::region(minimap),
+            near.map(|point| (point.x, point.y)),
+        );
+        self.cache.get_or_detect_result(key, || {
+            let minimap_hsv = to_hsv(&self.mat.roi(minimap)?);
+            detect_player_by_color(&minimap_hsv, self.player_dot_color, near)
+        })
+    }
+
+    fn detect_minimap_color_mask(
+        &self,
+        minimap: Rect,
+        lower: [u8; 3],
+        upper: [u8; 3],
+    ) -> Result<Mat> {
+        to_color_mask(&to_hsv(&self.mat.roi(minimap)?), lower, upper)
     }
 
     fn detect_player_kind(&self, minimap: Rect, kind: OtherPlayerKind) -> bool {
-        let minimap_color = to_bgr(&self.mat.roi(minimap).unwrap());
-        detect_player_kind(&minimap_color, kind)
+        let key = DetectionKey::PlayerKind(DetectionKey::region(minimap), kind as usize);
+        self.cache.get_or_detect(key, || {
+            let minimap_color = to_bgr(&self.mat.roi(minimap).unwrap());
+            detect_player_kind(&minimap_color, kind)
+        })
+    }
+
+    fn detect_player_names(&self) -> Vec<String> {
+        detect_player_names(&*self.mat, &**self.grayscale)
     }
 
     fn detect_player_is_dead(&self) -> bool {
-        detect_player_is_dead(&**self.grayscale)
+        self.cache.get_or_detect(DetectionKey::PlayerIsDead, || {
+            detect_player_is_dead(&**self.grayscale)
+        })
     }
 
     fn detect_player_in_cash_shop(&self) -> bool {
@@ -476,29 +793,10 @@ impl Detector for DefaultDetector {
     }
 
     fn detect_player_buff(&self, kind: BuffKind) -> bool {
-        let mat = match kind {
-            BuffKind::Rune
-            | BuffKind::Familiar
-            | BuffKind::SayramElixir
-            | BuffKind::AureliaElixir
-            | BuffKind::ExpCouponX2
-            | BuffKind::ExpCouponX3
-            | BuffKind::ExpCouponX4
-            | BuffKind::BonusExpCoupon
-            | BuffKind::ForTheGuild
-            | BuffKind::HardHitter => &**self.buffs_grayscale,
-            BuffKind::LegionWealth
-            | BuffKind::LegionLuck
-            | BuffKind::WealthAcquisitionPotion
-            | BuffKind::ExpAccumulationPotion
-            | BuffKind::SmallWealthAcquisitionPotion
-            | BuffKind::SmallExpAccumulationPotion
-            | BuffKind::ExtremeRedPotion
-            | BuffKind::ExtremeBluePotion
-            | BuffKind::ExtremeGreenPotion
-            | BuffKind::ExtremeGoldPotion => &to_bgr(&to_buffs_region(&*self.mat)),
-        };
-        detect_player_buff(mat, kind)
+        self.cache
+            .get_or_detect(DetectionKey::PlayerBuff(kind as usize), || {
+                self.detect_player_buff_uncached(kind)
+            })
     }
 
     fn detect_rune_arrows(&self, calibrating: ArrowsCalibrating) -> Result<ArrowsState> {
@@ -568,6 +866,167 @@ impl Detector for DefaultDetector {
     fn detect_booster(&self, kind: BoosterKind) -> BoosterState {
         detect_booster(&to_quick_slots_region(&**self.grayscale).0, kind)
     }
+
+    fn detect_template_confidence(&self, template: GameTemplate) -> Result<(f64, bool)> {
+        let spec = game_template_of(&self.localization, template);
+        let region = if spec.popup {
+            self.popups_region
+        } else {
+            DetectionRegion::default()
+        };
+        let confidence = if spec.grayscale {
+            let (mat, _) = to_detection_region(&**self.grayscale, region);
+            detect_template_confidence(&mat, &*spec.template())?
+        } else {
+            let bgr = to_bgr(&*self.mat);
+            let (mat, _) = to_detection_region(&bgr, region);
+            detect_template_confidence(&mat, &*spec.template())?
+        };
+
+        Ok((confidence, confidence >= spec.threshold))
+    }
+}
+
+/// Detection parameters of a [`GameTemplate`].
+///
+/// These are shared by the detection of the template and
+/// [`Detector::detect_template_confidence`] so that the self-test matches what is actually
+/// detected.
+struct GameTemplateSpec<'a> {
+    /// The localized base64 override of the template.
+    base64: Option<&'a String>,
+    /// The default template when there is no localized override.
+    default: &'static Mat,
+    /// Whether the template is matched in grayscale instead of BGR.
+    grayscale: bool,
+    /// Whether the template is only matched in the popups region.
+    popup: bool,
+    threshold: f64,
+}
+
+impl GameTemplateSpec<'_> {
+    /// Returns the localized template if it exists and is valid or the default template.
+    fn template(&self) -> Cow<'static, Mat> {
+        self.base64
+            .and_then(|base64| to_mat_from_base64(base64, self.grayscale).ok())
+            .map(Cow::Owned)
+            .unwrap_or(Cow::Borrowed(self.default))
+    }
+}
+
+/// Retrieves the [`GameTemplateSpec`] of `template`.
+fn game_template_of(localization: &Localization, template: GameTemplate) -> GameTemplateSpec<'_> {
+    match template {
+        GameTemplate::CashShop => GameTemplateSpec {
+            base64: localization.cash_shop_base64.as_ref(),
+            default: &*CASH_SHOP_TEMPLATE,
+            grayscale: true,
+            popup: false,
+            threshold: 0.7,
+        },
+        GameTemplate::ChangeChannel => GameTemplateSpec {
+            base64: localization.change_channel_base64.as_ref(),
+            default: &*CHANGE_CHANNEL_TEMPLATE,
+            grayscale: true,
+            popup: false,
+            threshold: 0.75,
+        },
+        GameTemplate::Timer => GameTemplateSpec {
+            base64: localization.timer_base64.as_ref(),
+            default: &*TIMER_TEMPLATE,
+            grayscale: true,
+            popup: false,
+            threshold: 0.75,
+        },
+        GameTemplate::PopupConfirm => GameTemplateSpec {
+            base64: localization.popup_confirm_base64.as_ref(),
+            default: &*POPUP_CONFIRM_TEMPLATE,
+            grayscale: true,
+            popup: true,
+            threshold: 0.75,
+        },
+        GameTemplate::PopupYes => GameTemplateSpec {
+            base64: localization.popup_yes_base64.as_ref(),
+            default: &*POPUP_YES_TEMPLATE,
+            grayscale: true,
+            popup: true,
+            threshold: 0.75,
+        },
+        GameTemplate::PopupNext => GameTemplateSpec {
+            base64: localization.popup_next_base64.as_ref(),
+            default: &*POPUP_NEXT_TEMPLATE,
+            grayscale: true,
+            popup: true,
+            threshold: 0.75,
+        },
+        GameTemplate::PopupEndChat => GameTemplateSpec {
+            base64: localization.popup_end_chat_base64.as_ref(),
+            default: &*POPUP_END_CHAT_TEMPLATE,
+            grayscale: true,
+            popup: true,
+            threshold: 0.75,
+        },
+        GameTemplate::PopupOkNew => GameTemplateSpec {
+            base64: localization.popup_ok_new_base64.as_ref(),
+            default: &*POPUP_OK_NEW_TEMPLATE,
+            grayscale: true,
+            popup: true,
+            threshold: 0.75,
+        },
+        GameTemplate::PopupOkOld => GameTemplateSpec {
+            base64: localization.popup_ok_old_base64.as_ref(),
+            default: &*POPUP_OK_OLD_TEMPLATE,
+            grayscale: true,
+            popup: true,
+            threshold: 0.75,
+        },
+        GameTemplate::PopupCancelNew => GameTemplateSpec {
+            base64: localization.popup_cancel_new_base64.as_ref(),
+            default: &*POPUP_CANCEL_NEW_TEMPLATE,
+            grayscale: true,
+            popup: true,
+            threshold: 0.75,
+        },
+        GameTemplate::PopupCancelOld => GameTemplateSpec {
+            base64: localization.popup_cancel_old_base64.as_ref(),
+            default: &*POPUP_CANCEL_OLD_TEMPLATE,
+            grayscale: true,
+            popup: true,
+            threshold: 0.75,
+        },
+        GameTemplate::FamiliarsLevelSort => GameTemplateSpec {
+            base64: localization.familiar_level_button_base64.as_ref(),
+            default: &*FAMILIAR_LEVEL_BUTTON_TEMPLATE,
+            grayscale: false,
+            popup: false,
+            threshold: 0.75,
+        },
+        GameTemplate::FamiliarsSaveButton => GameTemplateSpec {
+            base64: localization.familiar_save_button_base64.as_ref(),
+            default: &*FAMILIAR_SAVE_BUTTON_TEMPLATE,
+            grayscale: false,
+            popup: false,
+            threshold: 0.75,
+        },
+        GameTemplate::FamiliarsSetupButton => GameTemplateSpec {
+            base64: localization.familiar_setup_button_base64.as_ref(),
+            default: &*FAMILIAR_SETUP_BUTTON_TEMPLATE,
+            grayscale: false,
+            popup: false,
+            threshold: 0.75,
+        },
+    }
+}
+
+/// Detects `template` in `mat` with its [`GameTemplateSpec`].
+fn detect_game_template(
+    mat: &impl ToInputArray,
+    localization: &Localization,
+    template: GameTemplate,
+    offset: Point,
+) -> Result<Rect> {
+    let spec = game_template_of(localization, template);
+    detect_template(mat, &*spec.template(), offset, spec.threshold)
 }
 
 fn detect_mobs(
@@ -728,7 +1187,16 @@ pub static POPUP_CANCEL_OLD_TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
     .unwrap()
 });
 
-fn detect_esc_settings(mat: &impl ToInputArray, localization: &Localization) -> bool {
+/// Detects whether the ESC menu or any popup is opened.
+///
+/// Popup buttons are only detected in `popups`, the popups region of interest of `mat`. The ESC
+/// menu is not a popup so it is still detected in the whole `mat`.
+fn detect_esc_settings(
+    mat: &(impl ToInputArray + Sync),
+    popups: &(impl ToInputArray + Sync),
+    localization: &Localization,
+    parallel: bool,
+) -> bool {
     static ESC_MENU_X_TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
         imgcodecs::imdecode(
             include_bytes!(env!("ESC_MENU_X_TEMPLATE")),
@@ -737,130 +1205,66 @@ fn detect_esc_settings(mat: &impl ToInputArray, localization: &Localization) ->
         .unwrap()
     });
 
-    if detect_template(mat, &*ESC_MENU_X_TEMPLATE, Point::default(), 0.75).is_ok() {
-        return true;
-    }
-    if detect_popup_confirm_button(mat, localization).is_ok() {
-        return true;
-    }
-    if detect_popup_yes_button(mat, localization).is_ok() {
-        return true;
-    }
-    if detect_popup_next_button(mat, localization).is_ok() {
-        return true;
-    }
-    if detect_popup_end_chat_button(mat, localization).is_ok() {
-        return true;
-    }
-    if detect_popup_ok_new_button(mat, localization).is_ok() {
-        return true;
-    }
-    if detect_popup_ok_old_button(mat, localization).is_ok() {
-        return true;
-    }
-    if detect_popup_cancel_new_button(mat, localization).is_ok() {
-        return true;
-    }
-    if detect_popup_cancel_old_button(mat, localization).is_ok() {
-        return true;
-    }
+    let detections: [&(dyn Fn() -> bool + Sync); 9] = [
+        &|| detect_template(mat, &*ESC_MENU_X_TEMPLATE, Point::default(), 0.75).is_ok(),
+        &|| detect_popup_confirm_button(popups, localization, Point::default()).is_ok(),
+        &|| detect_popup_yes_button(popups, localization).is_ok(),
+        &|| detect_popup_next_button(popups, localization).is_ok(),
+        &|| detect_popup_end_chat_button(popups, localization).is_ok(),
+        &|| detect_popup_ok_new_button(popups, localization, Point::default()).is_ok(),
+        &|| detect_popup_ok_old_button(popups, localization).is_ok(),
+        &|| detect_popup_cancel_new_button(popups, localization).is_ok(),
+        &|| detect_popup_cancel_old_button(popups, localization).is_ok(),
+    ];
 
-    false
+    detect_any(&detections, parallel)
 }
 
 fn detect_popup_confirm_button(
     mat: &impl ToInputArray,
     localization: &Localization,
+    offset: Point,
 ) -> Result<Rect> {
-    let template = localization
-        .popup_confirm_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, true).ok());
-
-    detect_template(
-        mat,
-        template.as_ref().unwrap_or(&*POPUP_CONFIRM_TEMPLATE),
-        Point::default(),
-        0.75,
-    )
+    detect_game_template(mat, localization, GameTemplate::PopupConfirm, offset)
 }
 
 fn detect_popup_yes_button(mat: &impl ToInputArray, localization: &Localization) -> Result<Rect> {
-    let template = localization
-        .popup_yes_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, true).ok());
-
-    detect_template(
-        mat,
-        template.as_ref().unwrap_or(&*POPUP_YES_TEMPLATE),
-        Point::default(),
-        0.75,
-    )
+    detect_game_template(mat, localization, GameTemplate::PopupYes, Point::default())
 }
 
 fn detect_popup_next_button(mat: &impl ToInputArray, localization: &Localization) -> Result<Rect> {
-    let template = localization
-        .popup_next_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, true).ok());
-
-    detect_template(
-        mat,
-        template.as_ref().unwrap_or(&*POPUP_NEXT_TEMPLATE),
-        Point::default(),
-        0.75,
-    )
+    detect_game_template(mat, localization, GameTemplate::PopupNext, Point::default())
 }
 
 fn detect_popup_end_chat_button(
     mat: &impl ToInputArray,
     localization: &Localization,
 ) -> Result<Rect> {
-    let template = localization
-        .popup_end_chat_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, true).ok());
-
-    detect_template(
+    detect_game_template(
         mat,
-        template.as_ref().unwrap_or(&*POPUP_END_CHAT_TEMPLATE),
+        localization,
+        GameTemplate::PopupEndChat,
         Point::default(),
-        0.75,
     )
 }
 
 fn detect_popup_ok_new_button(
     mat: &impl ToInputArray,
     localization: &Localization,
+    offset: Point,
 ) -> Result<Rect> {
-    let template = localization
-        .popup_ok_new_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, true).ok());
-
-    detect_template(
-        mat,
-        template.as_ref().unwrap_or(&*POPUP_OK_NEW_TEMPLATE),
-        Point::default(),
-        0.75,
-    )
+    detect_game_template(mat, localization, GameTemplate::PopupOkNew, offset)
 }
 
 fn detect_popup_ok_old_button(
     mat: &impl ToInputArray,
     localization: &Localization,
 ) -> Result<Rect> {
-    let template = localization
-        .popup_ok_old_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, true).ok());
-
-    detect_template(
+    detect_game_template(
         mat,
-        template.as_ref().unwrap_or(&*POPUP_OK_OLD_TEMPLATE),
+        localization,
+        GameTemplate::PopupOkOld,
         Point::default(),
-        0.75,
     )
 }
 
@@ -868,16 +1272,11 @@ fn detect_popup_cancel_new_button(
     mat: &impl ToInputArray,
     localization: &Localization,
 ) -> Result<Rect> {
-    let template = localization
-        .popup_cancel_new_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, true).ok());
-
-    detect_template(
+    detect_game_template(
         mat,
-        template.as_ref().unwrap_or(&*POPUP_CANCEL_NEW_TEMPLATE),
+        localization,
+        GameTemplate::PopupCancelNew,
         Point::default(),
-        0.75,
     )
 }
 
@@ -885,16 +1284,11 @@ fn detect_popup_cancel_old_button(
     mat: &impl ToInputArray,
     localization: &Localization,
 ) -> Result<Rect> {
-    let template = localization
-        .popup_cancel_old_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, true).ok());
-
-    detect_template(
+    detect_game_template(
         mat,
-        template.as_ref().unwrap_or(&*POPUP_CANCEL_OLD_TEMPLATE),
+        localization,
+        GameTemplate::PopupCancelOld,
         Point::default(),
-        0.75,
     )
 }
 
@@ -1162,6 +1556,38 @@ fn detect_minimap_portals<T: MatTraitConst + ToInputArray>(minimap: T) -> Vec<Re
         .collect::<Vec<_>>()
 }
 
+fn detect_minimap_ropes(mut minimap: Mat) -> Vec<Rect> {
+    /// The grayscale value a pixel must be above to be part of a rope or ladder.
+    const ROPE_WHITENESS_THRESHOLD: f64 = 160.0;
+    /// Minimum height of a vertical line to be considered a rope or ladder.
+    const ROPE_MIN_HEIGHT: i32 = 8;
+    /// Maximum width of a vertical line to be considered a rope or ladder.
+    const ROPE_MAX_WIDTH: i32 = 3;
+
+    let width = minimap.cols();
+    // Opening with a vertical kernel only keeps vertical lines, which removes the platforms
+    let kernel = get_structuring_element_def(MORPH_RECT, Size::new(1, ROPE_MIN_HEIGHT)).unwrap();
+    unsafe {
+        // SAFETY: all of the functions below can be called in place.
+        minimap.modify_inplace(|mat, mat_mut| {
+            threshold(mat, mat_mut, ROPE_WHITENESS_THRESHOLD, 255.0, THRESH_BINARY).unwrap();
+            erode_def(mat, mat_mut, &kernel).unwrap();
+            dilate_def(mat, mat_mut, &kernel).unwrap();
+        });
+    }
+
+    let mut contours = Vector::<Vector<Point>>::new();
+    find_contours_def(&minimap, &mut contours, RETR_EXTERNAL, CHAIN_APPROX_SIMPLE).unwrap();
+    contours
+        .into_iter()
+        .map(|contour| bounding_rect(&contour).unwrap())
+        .filter(|bbox| {
+            // Excludes the minimap left and right borders
+            bbox.width <= ROPE_MAX_WIDTH && bbox.x > 0 && bbox.x + bbox.width < width
+        })
+        .collect::<Vec<_>>()
+}
+
 fn detect_minimap_rune(minimap: &impl ToInputArray) -> Result<Rect> {
     /// TODO: Support default ratio
     static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
@@ -1273,6 +1699,42 @@ fn detect_player(mat: &impl ToInputArray) -> Result<Rect> {
     Err(anyhow!("player not found"))
 }
 
+/// Detects the player dot from HSV `mat` with values between `thresholds`.
+fn detect_player_by_color(
+    mat: &impl ToInputArray,
+    thresholds: ColorThresholds,
+    near: Option<Point>,
+) -> Result<Rect> {
+    /// Minimum width and height of a blob to be considered the player dot.
+    const PLAYER_MIN_SIZE: i32 = 2;
+    /// Maximum width and height of a blob to be considered the player dot.
+    const PLAYER_MAX_SIZE: i32 = 8;
+
+    let mask = to_color_mask(mat, thresholds.lower, thresholds.upper)?;
+
+    let mut contours = Vector::<Vector<Point>>::new();
+    find_contours_def(&mask, &mut contours, RETR_EXTERNAL, CHAIN_APPROX_SIMPLE)?;
+    let size_range = PLAYER_MIN_SIZE..=PLAYER_MAX_SIZE;
+    let candidates = contours
+        .into_iter()
+        .map(|contour| bounding_rect(&contour).unwrap())
+        .filter(|bbox| size_range.contains(&bbox.width) && size_range.contains(&bbox.height));
+    let bbox = match near {
+        // Other yellow blobs (e.g. NPCs or quest markers) can be larger than the player dot, so
+        // prefers the one closest to where the player was last seen
+        Some(near) => candidates.min_by_key(|bbox| {
+            let x = bbox.x + bbox.width / 2 - near.x;
+            let y = bbox.y + bbox.height / 2 - near.y;
+            x * x + y * y
+        }),
+        None => candidates.max_by_key(|bbox| bbox.area()),
+    };
+    bbox
+        // Expands by 1 pixel to match the bounding box size of the template-based detection
+        .map(|bbox| expand_bbox(None, bbox, 1))
+        .ok_or(anyhow!("player not found"))
+}
+
 fn detect_player_kind(mat: &impl ToInputArray, kind: OtherPlayerKind) -> bool {
     /// TODO: Support default ratio
     static STRANGER_TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
@@ -1306,6 +1768,60 @@ fn detect_player_kind(mat: &impl ToInputArray, kind: OtherPlayerKind) -> bool {
     }
 }
 
+fn detect_player_names(mat: &impl MatTraitConst, grayscale: &impl MatTraitConst) -> Vec<String> {
+    let mut tags = detect_name_tags(grayscale);
+    if let Some(index) = own_name_tag_index(&tags, grayscale.cols()) {
+        tags.swap_remove(index);
+    }
+
+    extract_texts(mat, &tags)
+        .into_iter()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Returns the index of the player's own name tag in `tags` detected from an image of `width`.
+///
+/// The camera follows the player so the player's own name tag is the closest to the center.
+fn own_name_tag_index(tags: &[Rect], width: i32) -> Option<usize> {
+    let center_x = width / 2;
+    tags.iter()
+        .enumerate()
+        .min_by_key(|(_, tag)| (tag.x + tag.width / 2 - center_x).abs())
+        .map(|(index, _)| index)
+}
+
+fn detect_name_tags(grayscale: &impl MatTraitConst) -> Vec<Rect> {
+    const NAME_TAG_MAX_INTENSITY: f64 = 30.0;
+    const NAME_TAG_MIN_WIDTH: i32 = 24;
+    const NAME_TAG_MAX_WIDTH: i32 = 160;
+    const NAME_TAG_MIN_HEIGHT: i32 = 12;
+    const NAME_TAG_MAX_HEIGHT: i32 = 20;
+
+    // Name tags are white texts on top of a near black background box
+    let mut tags = Mat::default();
+    threshold(
+        grayscale,
+        &mut tags,
+        NAME_TAG_MAX_INTENSITY,
+        255.0,
+        THRESH_BINARY_INV,
+    )
+    .unwrap();
+
+    let mut contours = Vector::<Vector<Point>>::new();
+    find_contours_def(&tags, &mut contours, RETR_EXTERNAL, CHAIN_APPROX_SIMPLE).unwrap();
+    contours
+        .into_iter()
+        .map(|contour| bounding_rect(&contour).unwrap())
+        .filter(|bbox| {
+            (NAME_TAG_MIN_WIDTH..=NAME_TAG_MAX_WIDTH).contains(&bbox.width)
+                && (NAME_TAG_MIN_HEIGHT..=NAME_TAG_MAX_HEIGHT).contains(&bbox.height)
+        })
+        .collect()
+}
+
 fn detect_player_is_dead(mat: &impl ToInputArray) -> bool {
     /// TODO: Support default ratio
     static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
@@ -1321,18 +1837,7 @@ pub static CASH_SHOP_TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
 });
 
 fn detect_player_in_cash_shop(mat: &impl ToInputArray, localization: &Localization) -> bool {
-    let template = localization
-        .cash_shop_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, true).ok());
-
-    detect_template(
-        mat,
-        template.as_ref().unwrap_or(&*CASH_SHOP_TEMPLATE),
-        Point::default(),
-        0.7,
-    )
-    .is_ok()
+    detect_game_template(mat, localization, GameTemplate::CashShop, Point::default()).is_ok()
 }
 
 fn detect_player_health_bar<T: MatTraitConst + ToInputArray>(mat: &T) -> Result<Rect> {
@@ -1466,7 +1971,15 @@ fn detect_player_health(
     Ok((current_health.min(max_health), max_health))
 }
 
-fn detect_player_buff<T: MatTraitConst + ToInputArray>(mat: &T, kind: BuffKind) -> bool {
+/// Detects whether the player has a buff specified by `kind`.
+///
+/// When `scaled` is provided as `mat` already downscaled to a percentage, buffs without a mask are
+/// detected on it and the template downscaled to the same percentage to reduce matching cost.
+fn detect_player_buff<T: MatTraitConst + ToInputArray>(
+    mat: &T,
+    kind: BuffKind,
+    scaled: Option<(&Mat, u32)>,
+) -> bool {
     /// TODO: Support default ratio
     static RUNE_BUFF: LazyLock<Mat> = LazyLock::new(|| {
         imgcodecs::imdecode(include_bytes!(env!("RUNE_BUFF_TEMPLATE")), IMREAD_GRAYSCALE).unwrap()
@@ -1754,7 +2267,17 @@ fn detect_player_buff<T: MatTraitConst + ToInputArray>(mat: &T, kind: BuffKind)
                 })
                 .is_ok()
         }
-        _ => detect_template(mat, template, Point::default(), threshold).is_ok(),
+        _ => match scaled {
+            Some((mat_scaled, percent)) => detect_template_scaled(
+                mat_scaled,
+                template,
+                &scaled_buff_template(kind, template, percent),
+                percent,
+                threshold,
+            )
+            .is_ok(),
+            None => detect_template(mat, template, Point::default(), threshold).is_ok(),
+        },
     }
 }
 
@@ -1807,7 +2330,6 @@ fn detect_rune_arrows(
     ///
     /// Based on the rectangular region in-game with round border when detecting arrows.
     const RUNE_REGION_MIN_WIDTH: i32 = 260;
-    const SCORE_THRESHOLD: f32 = 0.8;
 
     if calibrating.rune_region.is_none() {
         // Detect until 4 arrows found even if there maybe spin arrows or a large enough rune region
@@ -1826,7 +2348,7 @@ fn detect_rune_arrows(
         // Cache result for later
         let filtered = result
             .into_iter()
-            .filter_map(|(rect, arrow, score)| (score >= SCORE_THRESHOLD).then_some((rect, arrow)))
+            .filter(|(_, _, score)| *score >= MIN_ARROW_CONFIDENCE)
             .collect::<Vec<_>>();
         if !filtered.is_empty() && filtered.len() <= MAX_ARROWS {
             info!(target: "rune", "initial rune region {filtered:?}");
@@ -1851,6 +2373,7 @@ fn detect_rune_arrows(
     }
 
     // After calibration is complete and there are spin arrows, prioritize its detection
+    let spin_config = calibrating.spin_config;
     if let Some(ref mut spin_arrows) = calibrating.spin_arrows
         && spin_arrows.iter().any(|arrow| arrow.final_arrow.is_none())
     {
@@ -1858,7 +2381,7 @@ fn detect_rune_arrows(
             .iter_mut()
             .filter(|arrow| arrow.final_arrow.is_none())
         {
-            detect_spin_arrow(mat, spin_arrow)?;
+            detect_spin_arrow(mat, spin_arrow, spin_config)?;
         }
         return Ok(ArrowsState::Calibrating(calibrating));
     }
@@ -1867,31 +2390,33 @@ fn detect_rune_arrows(
     if let Some(normal_arrows) = calibrating.normal_arrows.take() {
         if calibrating.spin_arrows.is_none() && normal_arrows.len() == MAX_ARROWS {
             info!(target: "rune", "reuse cached arrows result");
-            return Ok(ArrowsState::Complete(extract_rune_arrows_to_slice(
+            return Ok(complete_rune_arrows(
                 normal_arrows.into_iter().collect::<Vec<_>>(),
-            )));
+            ));
         }
 
         if let Some(ref spin_arrows) = calibrating.spin_arrows {
             let mut final_arrows = normal_arrows
                 .into_iter()
-                .filter(|(normal_arrow_region, _)| {
+                .filter(|(normal_arrow_region, _, _)| {
                     spin_arrows
                         .iter()
                         .map(|spin_arrow| spin_arrow.region)
                         .all(|spin_arrow_region| iou(*normal_arrow_region, spin_arrow_region) < 0.5)
                 })
-                .collect::<Vec<(Rect, KeyKind)>>();
+                .collect::<Vec<(Rect, KeyKind, f32)>>();
 
             for arrow in spin_arrows {
-                final_arrows.push((arrow.region, arrow.final_arrow.unwrap()));
+                final_arrows.push((
+                    arrow.region,
+                    arrow.final_arrow.unwrap(),
+                    SPIN_ARROW_CONFIDENCE,
+                ));
             }
             if final_arrows.len() == MAX_ARROWS {
-                final_arrows.sort_by_key(|(region, _)| region.x);
+                final_arrows.sort_by_key(|(region, _, _)| region.x);
                 info!(target: "rune", "reuse cached arrows result with spin arrows {calibrating:?}");
-                return Ok(ArrowsState::Complete(extract_rune_arrows_to_slice(
-                    final_arrows,
-                )));
+                return Ok(complete_rune_arrows(final_arrows));
             }
         }
 
@@ -1925,7 +2450,7 @@ fn detect_rune_arrows(
     let result = detect_rune_arrows_with_scores_regions(&mat_rune_region)
         .into_iter()
         .filter_map(|(rect, arrow, score)| {
-            (score >= SCORE_THRESHOLD).then_some((rect + rune_region.tl(), arrow))
+            (score >= MIN_ARROW_CONFIDENCE).then_some((rect + rune_region.tl(), arrow, score))
         })
         .collect::<Vec<_>>();
     // TODO: If there are spinning arrows, either set the limit internally
@@ -1940,15 +2465,21 @@ fn detect_rune_arrows(
             .take()
             .unwrap()
             .into_iter()
-            .map(|arrow| (arrow.region, arrow.final_arrow.unwrap()))
+            .map(|arrow| {
+                (
+                    arrow.region,
+                    arrow.final_arrow.unwrap(),
+                    SPIN_ARROW_CONFIDENCE,
+                )
+            })
             .chain(result)
             .collect::<Vec<_>>();
         vec.sort_by_key(|a| a.0.x);
-        return Ok(ArrowsState::Complete(extract_rune_arrows_to_slice(vec)));
+        return Ok(complete_rune_arrows(vec));
     }
 
     if result.len() == MAX_ARROWS {
-        Ok(ArrowsState::Complete(extract_rune_arrows_to_slice(result)))
+        Ok(complete_rune_arrows(result))
     } else {
         Err(anyhow!("no rune arrow detected"))
     }
@@ -2048,6 +2579,7 @@ fn calibrate_for_spin_arrows(
             region: rect,
             last_arrow_head: None,
             final_arrow: None,
+            lag_samples: 0,
             #[cfg(debug_assertions)]
             is_spin_testing: calibrating.is_spin_testing,
         });
@@ -2061,9 +2593,11 @@ fn calibrate_for_spin_arrows(
     Ok(())
 }
 
-fn detect_spin_arrow(mat: &impl MatTraitConst, spin_arrow: &mut SpinArrow) -> Result<()> {
-    const SPIN_LAG_THRESHOLD: i32 = 30;
-
+fn detect_spin_arrow(
+    mat: &impl MatTraitConst,
+    spin_arrow: &mut SpinArrow,
+    config: SpinArrowsConfig,
+) -> Result<()> {
     // Extract spin arrow region
     let spin_arrow_mat = to_hsv(&mat.roi(spin_arrow.region)?);
     let kernel = get_structuring_element_def(MORPH_RECT, Size::new(3, 3)).unwrap();
@@ -2111,16 +2645,52 @@ fn detect_spin_arrow(mat: &impl MatTraitConst, spin_arrow: &mut SpinArrow) -> Re
 
     let centroid = spin_arrow.centroid - spin_arrow.region.tl();
     let cur_arrow_head = arrow_head - centroid;
-
-    if spin_arrow.last_arrow_head.is_none() {
+    let Some(prev_arrow_head) = spin_arrow.last_arrow_head else {
         spin_arrow.last_arrow_head = Some(cur_arrow_head);
         return Ok(());
+    };
+
+    update_spin_arrow_head(spin_arrow, cur_arrow_head, config);
+
+    #[cfg(debug_assertions)]
+    if spin_arrow.is_spin_testing {
+        debug_spinning_arrows(
+            mat,
+            &triangle,
+            &contours,
+            spin_arrow.region,
+            prev_arrow_head,
+            cur_arrow_head,
+            spin_arrow.centroid,
+        );
     }
 
-    let prev_arrow_head = spin_arrow.last_arrow_head.unwrap();
+    Ok(())
+}
+
+/// Updates `spin_arrow` with the arrow head of the current sample.
+///
+/// The final arrow is determined once the spinning arrow has lagged for
+/// [`SpinArrowsConfig::lag_samples`] consecutive samples.
+fn update_spin_arrow_head(
+    spin_arrow: &mut SpinArrow,
+    cur_arrow_head: Point,
+    config: SpinArrowsConfig,
+) {
+    let Some(prev_arrow_head) = spin_arrow.last_arrow_head else {
+        spin_arrow.last_arrow_head = Some(cur_arrow_head);
+        return;
+    };
+
     // https://stackoverflow.com/a/13221874
     let dot = prev_arrow_head.x * -cur_arrow_head.y + prev_arrow_head.y * cur_arrow_head.x;
-    if dot >= SPIN_LAG_THRESHOLD {
+    if dot >= config.lag_threshold {
+        spin_arrow.lag_samples += 1;
+    } else {
+        spin_arrow.lag_samples = 0;
+    }
+
+    if spin_arrow.lag_samples >= config.lag_samples.max(1) {
         debug!(target: "rune", "spinning arrow lag detected");
         let directions = [
             (KeyKind::Up, prev_arrow_head.dot(Point::new(0, -1))),
@@ -2136,32 +2706,20 @@ fn detect_spin_arrow(mat: &impl MatTraitConst, spin_arrow: &mut SpinArrow) -> Re
         spin_arrow.final_arrow = Some(arrow);
     }
     spin_arrow.last_arrow_head = Some(cur_arrow_head);
-
-    #[cfg(debug_assertions)]
-    if spin_arrow.is_spin_testing {
-        debug_spinning_arrows(
-            mat,
-            &triangle,
-            &contours,
-            spin_arrow.region,
-            prev_arrow_head,
-            cur_arrow_head,
-            spin_arrow.centroid,
-        );
-    }
-
-    Ok(())
 }
 
 #[inline]
-fn extract_rune_arrows_to_slice(vec: Vec<(Rect, KeyKind)>) -> [(Rect, KeyKind); MAX_ARROWS] {
+fn complete_rune_arrows(vec: Vec<(Rect, KeyKind, f32)>) -> ArrowsState {
     debug_assert!(vec.len() == 4);
     let first = vec[0];
     let second = vec[1];
     let third = vec[2];
     let fourth = vec[3];
     info!( target: "player", "solving rune result {first:?} {second:?} {third:?} {fourth:?}");
-    [first, second, third, fourth]
+    ArrowsState::Complete(
+        [first, second, third, fourth].map(|(rect, arrow, _)| (rect, arrow)),
+        [first, second, third, fourth].map(|(_, _, score)| score),
+    )
 }
 
 fn detect_erda_shower(mat: &impl MatTraitConst) -> Result<Rect> {
@@ -2190,16 +2748,11 @@ fn detect_familiar_save_button(
     mat: &impl ToInputArray,
     localization: &Localization,
 ) -> Result<Rect> {
-    let template = localization
-        .familiar_save_button_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, false).ok());
-
-    detect_template(
+    detect_game_template(
         mat,
-        template.as_ref().unwrap_or(&*FAMILIAR_SAVE_BUTTON_TEMPLATE),
+        localization,
+        GameTemplate::FamiliarsSaveButton,
         Point::default(),
-        0.75,
     )
 }
 
@@ -2215,18 +2768,11 @@ fn detect_familiar_setup_button(
     mat: &impl ToInputArray,
     localization: &Localization,
 ) -> Result<Rect> {
-    let template = localization
-        .familiar_setup_button_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, false).ok());
-
-    detect_template(
+    detect_game_template(
         mat,
-        template
-            .as_ref()
-            .unwrap_or(&*FAMILIAR_SETUP_BUTTON_TEMPLATE),
+        localization,
+        GameTemplate::FamiliarsSetupButton,
         Point::default(),
-        0.75,
     )
 }
 
@@ -2242,18 +2788,11 @@ fn detect_familiar_level_button(
     mat: &impl ToInputArray,
     localization: &Localization,
 ) -> Result<Rect> {
-    let template = localization
-        .familiar_level_button_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, false).ok());
-
-    detect_template(
+    detect_game_template(
         mat,
-        template
-            .as_ref()
-            .unwrap_or(&*FAMILIAR_LEVEL_BUTTON_TEMPLATE),
+        localization,
+        GameTemplate::FamiliarsLevelSort,
         Point::default(),
-        0.75,
     )
 }
 
@@ -2456,16 +2995,11 @@ pub static CHANGE_CHANNEL_TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
 });
 
 fn detect_change_channel_menu_opened(mat: &impl ToInputArray, localization: &Localization) -> bool {
-    let template = localization
-        .change_channel_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, true).ok());
-
-    detect_template(
+    detect_game_template(
         mat,
-        template.as_ref().unwrap_or(&*CHANGE_CHANNEL_TEMPLATE),
+        localization,
+        GameTemplate::ChangeChannel,
         Point::default(),
-        0.75,
     )
     .is_ok()
 }
@@ -2491,18 +3025,7 @@ pub static TIMER_TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
 });
 
 fn detect_timer_visible(mat: &impl ToInputArray, localization: &Localization) -> bool {
-    let template = localization
-        .timer_base64
-        .as_ref()
-        .and_then(|base64| to_mat_from_base64(base64, true).ok());
-
-    detect_template(
-        mat,
-        template.as_ref().unwrap_or(&*TIMER_TEMPLATE),
-        Point::default(),
-        0.75,
-    )
-    .is_ok()
+    detect_game_template(mat, localization, GameTemplate::Timer, Point::default()).is_ok()
 }
 
 fn detect_booster<T: MatTraitConst + ToInputArray>(mat: &T, kind: BoosterKind) -> BoosterState {
@@ -2562,6 +3085,30 @@ fn detect_booster<T: MatTraitConst + ToInputArray>(mat: &T, kind: BoosterKind) -
     }
 }
 
+/// Runs `detections` and returns whether any of them detected.
+///
+/// When `parallel` is `true`, the detections run on a thread pool bounded by
+/// [`MAX_DETECTION_THREADS`]. Otherwise, they run sequentially and stop at the first detected.
+fn detect_any(detections: &[&(dyn Fn() -> bool + Sync)], parallel: bool) -> bool {
+    static POOL: LazyLock<ThreadPool> = LazyLock::new(|| {
+        let threads = thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1)
+            .min(MAX_DETECTION_THREADS);
+        ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("detection-{index}"))
+            .build()
+            .expect("can build detection thread pool")
+    });
+
+    if parallel {
+        POOL.install(|| detections.par_iter().any(|detect| detect()))
+    } else {
+        detections.iter().any(|detect| detect())
+    }
+}
+
 /// Detects a single match from `template` with the given BGR image `Mat`.
 #[inline]
 fn detect_template<T: ToInputArray + MatTraitConst>(
@@ -2573,6 +3120,44 @@ fn detect_template<T: ToInputArray + MatTraitConst>(
     detect_template_single(mat, template, no_array(), offset, threshold).map(|(bbox, _)| bbox)
 }
 
+/// Detects a single match from `template_scaled` with `mat_scaled`.
+///
+/// Both `mat_scaled` and `template_scaled` are expected to be downscaled to `percent` of their
+/// original sizes. Returns the match rectangle in the original `mat` coordinate.
+fn detect_template_scaled(
+    mat_scaled: &impl ToInputArray,
+    template: &impl MatTraitConst,
+    template_scaled: &Mat,
+    percent: u32,
+    threshold: f64,
+) -> Result<Rect> {
+    let scale = percent as f64 / 100.0;
+
+    detect_template(mat_scaled, template_scaled, Point::default(), threshold).map(|bbox| {
+        Rect::new(
+            (bbox.x as f64 / scale) as i32,
+            (bbox.y as f64 / scale) as i32,
+            template.cols(),
+            template.rows(),
+        )
+    })
+}
+
+/// Gets the `template` of buff `kind` downscaled to `percent` of its size.
+///
+/// Downscaled templates are cached so that each template is only resized once per percentage.
+fn scaled_buff_template(kind: BuffKind, template: &Mat, percent: u32) -> Arc<Mat> {
+    static TEMPLATES: LazyLock<Mutex<HashMap<(usize, u32), Arc<Mat>>>> =
+        LazyLock::new(Mutex::default);
+
+    TEMPLATES
+        .lock()
+        .unwrap()
+        .entry((kind as usize, percent))
+        .or_insert_with(|| Arc::new(to_scaled(template, percent)))
+        .clone()
+}
+
 /// Detects a single match with `mask` from `template` with the given BGR image `Mat`.
 #[inline]
 fn detect_template_single<T: ToInputArray + MatTraitConst>(
@@ -2589,6 +3174,19 @@ fn detect_template_single<T: ToInputArray + MatTraitConst>(
         .and_then(|x| x)
 }
 
+/// Detects the best match confidence of `template` in `mat` without any threshold.
+fn detect_template_confidence(
+    mat: &impl ToInputArray,
+    template: &impl ToInputArray,
+) -> Result<f64> {
+    let mut result = Mat::default();
+    match_template(mat, template, &mut result, TM_CCOEFF_NORMED, &no_array())?;
+
+    let mut score = 0f64;
+    min_max_loc(&result, None, Some(&mut score), None, None, &no_array())?;
+    Ok(score)
+}
+
 /// Detects multiple matches from `template` from the given BGR image `Mat` and returns up to
 /// `max_matches` best results.
 #[inline]
@@ -3064,15 +3662,23 @@ fn iou(first: Rect, second: Rect) -> f32 {
     intersection / union
 }
 
-/// Crops `mat` to the buffs region.
+/// Crops `mat` to `region` in percentages of `mat` size.
+///
+/// The region is clamped to `mat` and is at least one pixel in size.
 #[inline]
-fn to_buffs_region(mat: &impl MatTraitConst) -> BoxedRef<'_, Mat> {
+fn to_detection_region(
+    mat: &impl MatTraitConst,
+    region: DetectionRegion,
+) -> (BoxedRef<'_, Mat>, Rect) {
     let size = mat.size().unwrap();
-    // Crop to top right of the image for buffs region
-    let crop_x = size.width / 3;
-    let crop_y = size.height / 4;
-    let crop_bbox = Rect::new(size.width - crop_x, 0, crop_x, crop_y);
-    mat.roi(crop_bbox).unwrap()
+    let percent_of = |value: i32, percent: u32| (value * percent.min(100) as i32) / 100;
+    let x = percent_of(size.width, region.x_percent).min(size.width - 1);
+    let y = percent_of(size.height, region.y_percent).min(size.height - 1);
+    let width = percent_of(size.width, region.width_percent).clamp(1, size.width - x);
+    let height = percent_of(size.height, region.height_percent).clamp(1, size.height - y);
+    let crop_bbox = Rect::new(x, y, width, height);
+    let crop_roi = mat.roi(crop_bbox).unwrap();
+    (crop_roi, crop_bbox)
 }
 
 /// Crops `mat` to the bottom right of the image for quick slots region.
@@ -3086,6 +3692,15 @@ fn to_quick_slots_region(mat: &impl MatTraitConst) -> (BoxedRef<'_, Mat>, Rect)
     (crop_roi, crop_bbox)
 }
 
+/// Thresholds a 3-channel `Mat` image to a mask of pixels with values between `lower` and `upper`.
+#[inline]
+fn to_color_mask(mat: &impl ToInputArray, lower: [u8; 3], upper: [u8; 3]) -> Result<Mat> {
+    let to_scalar = |[a, b, c]: [u8; 3]| Scalar::new(a as f64, b as f64, c as f64, 0.0);
+    let mut mask = Mat::default();
+    in_range(mat, &to_scalar(lower), &to_scalar(upper), &mut mask)?;
+    Ok(mask)
+}
+
 /// Converts a BGRA `Mat` image to HSV.
 #[inline]
 fn to_hsv(mat: &impl MatTraitConst) -> Mat {
@@ -3113,6 +3728,14 @@ fn to_bgr(mat: &impl MatTraitConst) -> Mat {
     mat
 }
 
+/// Downscales `mat` to `percent` of its size.
+fn to_scaled(mat: &impl ToInputArray, percent: u32) -> Mat {
+    let scale = percent as f64 / 100.0;
+    let mut scaled = Mat::default();
+    resize(mat, &mut scaled, Size::default(), scale, scale, INTER_AREA).unwrap();
+    scaled
+}
+
 /// Converts a BGRA `Mat` image to grayscale.
 ///
 /// `add_contrast` can be set to `true` in order to increase contrast by a fixed amount
@@ -3196,3 +3819,209 @@ fn build_session(model: &[u8]) -> Result<Session> {
         Ok(Session::builder()?.commit_from_memory(model)?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use opencv::core::{CV_8UC3, Vec3b};
+
+    use super::*;
+
+    #[test]
+    fn detect_any_parallel_matches_sequential() {
+        let detected = || true;
+        let not_detected = || false;
+        let slow_detected = || {
+            thread::sleep(std::time::Duration::from_millis(5));
+            true
+        };
+        let cases: [&[&(dyn Fn() -> bool + Sync)]; 5] = [
+            &[],
+            &[&not_detected, &not_detected, &not_detected],
+            &[&detected, &not_detected, &not_detected],
+            &[&not_detected, &not_detected, &slow_detected],
+            &[&slow_detected, &detected, &slow_detected, &not_detected],
+        ];
+
+        for detections in cases {
+            assert_eq!(detect_any(detections, false), detect_any(detections, true));
+        }
+    }
+
+    #[test]
+    fn detect_player_buff_downscaled() {
+        let template =
+            imdecode(include_bytes!(env!("RUNE_BUFF_TEMPLATE")), IMREAD_GRAYSCALE).unwrap();
+        let mut mat = Mat::default();
+        copy_make_border(
+            &template,
+            &mut mat,
+            20,
+            20,
+            40,
+            40,
+            BORDER_CONSTANT,
+            Scalar::default(),
+        )
+        .unwrap();
+
+        assert!(detect_player_buff(&mat, BuffKind::Rune, None));
+        assert!(detect_player_buff(
+            &mat,
+            BuffKind::Rune,
+            Some((&to_scaled(&mat, 50), 50))
+        ));
+    }
+
+    #[test]
+    fn to_color_mask_matches_pixels_within_thresholds() {
+        let pixels = [
+            Vec3b::from([38, 191, 240]),  // Yellow player dot
+            Vec3b::from([30, 120, 180]),  // Lower threshold
+            Vec3b::from([50, 255, 255]),  // Upper threshold
+            Vec3b::from([149, 234, 240]), // Blue
+            Vec3b::from([51, 191, 240]),  // Slightly out of range
+        ];
+        let mat = Mat::from_slice(&pixels).unwrap().try_clone().unwrap();
+
+        let mask = to_color_mask(&mat, PLAYER_DOT_COLOR_LOWER, PLAYER_DOT_COLOR_UPPER).unwrap();
+
+        assert_eq!(mask.data_typed::<u8>().unwrap(), &[255, 255, 255, 0, 0]);
+    }
+
+    #[test]
+    fn detect_player_by_color_prefers_blob_near_position() {
+        // HSV image
+        let mut mat = Mat::zeros(20, 40, CV_8UC3).unwrap().to_mat().unwrap();
+        let blobs = [Rect::new(2, 2, 6, 6), Rect::new(30, 12, 3, 3)];
+        for blob in blobs {
+            for y in blob.y..blob.y + blob.height {
+                for x in blob.x..blob.x + blob.width {
+                    *mat.at_2d_mut::<Vec3b>(y, x).unwrap() = Vec3b::from([38, 191, 240]);
+                }
+            }
+        }
+        let thresholds = ColorThresholds::default();
+
+        let largest = detect_player_by_color(&mat, thresholds, None).unwrap();
+        let nearest = detect_player_by_color(&mat, thresholds, Some(Point::new(31, 13))).unwrap();
+
+        assert_eq!(largest, Rect::new(1, 1, 8, 8));
+        assert_eq!(nearest, Rect::new(29, 11, 5, 5));
+    }
+
+    #[test]
+    fn detect_popup_confirm_button_skips_outside_region() {
+        let localization = Localization::default();
+        let mut mat = Mat::default();
+        copy_make_border(
+            &*POPUP_CONFIRM_TEMPLATE,
+            &mut mat,
+            10,
+            200,
+            10,
+            200,
+            BORDER_CONSTANT,
+            Scalar::default(),
+        )
+        .unwrap();
+
+        let (popups, bbox) = to_detection_region(&mat, DetectionRegion::default());
+        let rect = detect_popup_confirm_button(&popups, &localization, bbox.tl()).unwrap();
+        assert_eq!(rect.tl(), Point::new(10, 10));
+
+        let region = DetectionRegion {
+            x_percent: 50,
+            y_percent: 50,
+            width_percent: 50,
+            height_percent: 50,
+        };
+        let (popups, bbox) = to_detection_region(&mat, region);
+        assert!(detect_popup_confirm_button(&popups, &localization, bbox.tl()).is_err());
+    }
+
+    #[test]
+    fn detection_cache_detects_once_for_same_key() {
+        let cache = DetectionCache::default();
+        let count = Cell::new(0);
+        let detect = || {
+            count.set(count.get() + 1);
+            true
+        };
+
+        assert!(cache.get_or_detect(DetectionKey::EliteBossBar, detect));
+        assert!(cache.get_or_detect(DetectionKey::EliteBossBar, detect));
+        assert_eq!(count.get(), 1);
+
+        assert!(cache.get_or_detect(DetectionKey::PlayerIsDead, detect));
+        assert_eq!(count.get(), 2);
+    }
+
+    #[test]
+    fn detection_cache_detects_once_for_same_region_with_error() {
+        let cache = DetectionCache::default();
+        let count = Cell::new(0);
+        let region = DetectionKey::region(Rect::new(0, 0, 10, 10));
+        let detect = || -> Result<Rect> {
+            count.set(count.get() + 1);
+            Err(anyhow!("player not found"))
+        };
+
+        let first = cache.get_or_detect_result(DetectionKey::Player(region), detect);
+        let second = cache.get_or_detect_result(DetectionKey::Player(region), detect);
+
+        assert_eq!(count.get(), 1);
+        assert_eq!(first.unwrap_err().to_string(), "player not found");
+        assert_eq!(second.unwrap_err().to_string(), "player not found");
+    }
+
+    #[test]
+    fn own_name_tag_index_closest_to_center() {
+        let tags = [
+            Rect::new(0, 100, 40, 14),
+            Rect::new(90, 100, 40, 14),
+            Rect::new(160, 100, 40, 14),
+        ];
+
+        assert_eq!(own_name_tag_index(&tags, 200), Some(1));
+        assert_eq!(own_name_tag_index(&[], 200), None);
+    }
+
+    #[test]
+    fn update_spin_arrow_head_takes_more_samples_with_more_lag_samples() {
+        let heads = [
+            Point::new(0, -10),
+            Point::new(-5, -9),
+            Point::new(-9, -5),
+            Point::new(-10, 0),
+        ];
+        let spin_arrow = SpinArrow {
+            centroid: Point::default(),
+            region: Rect::default(),
+            last_arrow_head: None,
+            final_arrow: None,
+            lag_samples: 0,
+            #[cfg(debug_assertions)]
+            is_spin_testing: false,
+        };
+        let samples_until_final = |lag_samples| {
+            let config = SpinArrowsConfig {
+                lag_samples,
+                ..SpinArrowsConfig::default()
+            };
+            let mut spin_arrow = spin_arrow;
+            heads
+                .into_iter()
+                .position(|head| {
+                    update_spin_arrow_head(&mut spin_arrow, head, config);
+                    spin_arrow.final_arrow.is_some()
+                })
+                .map(|index| (index + 1, spin_arrow.final_arrow.unwrap()))
+        };
+
+        assert_eq!(samples_until_final(1), Some((2, KeyKind::Up)));
+        assert_eq!(samples_until_final(3), Some((4, KeyKind::Left)));
+        assert_eq!(samples_until_final(4), None);
+    }
+}
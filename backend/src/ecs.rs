@@ -1,7 +1,6 @@
 #[cfg(debug_assertions)]
 use std::cell::RefCell;
-#[cfg(test)]
-use std::rc::Rc;
+use std::{cell::Cell, rc::Rc};
 
 use dyn_clone::clone_box;
 #[cfg(debug_assertions)]
@@ -98,6 +97,7 @@ pub struct Debug {
     auto_save: RefCell<bool>,
     last_rune_detector: RefCell<Option<Box<dyn Detector>>>,
     last_rune_result: RefCell<Option<[(Rect, KeyKind); 4]>>,
+    manual_rune_keys: RefCell<Option<[KeyKind; 4]>>,
 }
 
 #[cfg(debug_assertions)]
@@ -128,6 +128,89 @@ impl Debug {
         *self.last_rune_detector.borrow_mut() = Some(detector);
         *self.last_rune_result.borrow_mut() = Some(result);
     }
+
+    /// Sets the keys to press instead of the inferred keys when solving rune.
+    ///
+    /// The last rune result, if any, is also relabeled with `keys` so that the corrected result
+    /// is saved for training.
+    pub fn set_manual_rune_keys(&self, keys: [KeyKind; 4]) {
+        if let Some(result) = self.last_rune_result.borrow_mut().as_mut() {
+            for ((_, key), manual_key) in result.iter_mut().zip(keys) {
+                *key = manual_key;
+            }
+        }
+        *self.manual_rune_keys.borrow_mut() = Some(keys);
+    }
+
+    pub fn take_manual_rune_keys(&self) -> Option<[KeyKind; 4]> {
+        self.manual_rune_keys.borrow_mut().take()
+    }
+}
+
+/// A token shared between instances to ensure only one instance solves a rune at a time.
+///
+/// Each instance holds its own [`RuneSolvingToken`] created through [`Self::share`]. The token is
+/// released on drop if it is still acquired.
+#[derive(Debug)]
+pub struct RuneSolvingToken {
+    id: u32,
+    next_id: Rc<Cell<u32>>,
+    holder: Rc<Cell<Option<u32>>>,
+}
+
+impl Default for RuneSolvingToken {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            next_id: Rc::new(Cell::new(1)),
+            holder: Rc::new(Cell::new(None)),
+        }
+    }
+}
+
+impl RuneSolvingToken {
+    /// Creates a new [`RuneSolvingToken`] contending with this token.
+    pub fn share(&self) -> Self {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+
+        Self {
+            id,
+            next_id: self.next_id.clone(),
+            holder: self.holder.clone(),
+        }
+    }
+
+    /// Tries to acquire the token.
+    ///
+    /// Returns `true` if the token is free or already acquired by this token.
+    pub fn try_acquire(&self) -> bool {
+        match self.holder.get() {
+            Some(id) => id == self.id,
+            None => {
+                self.holder.set(Some(self.id));
+                true
+            }
+        }
+    }
+
+    /// Releases the token if it is acquired by this token.
+    pub fn release(&self) {
+        if self.is_acquired() {
+            self.holder.set(None);
+        }
+    }
+
+    #[inline]
+    pub fn is_acquired(&self) -> bool {
+        self.holder.get() == Some(self.id)
+    }
+}
+
+impl Drop for RuneSolvingToken {
+    fn drop(&mut self) {
+        self.release();
+    }
 }
 
 /// A struct containing shared resources.
@@ -148,6 +231,8 @@ pub struct Resources {
     pub detector: Option<Box<dyn Detector>>,
     /// A resource indicating current operation state.
     pub operation: Operation,
+    /// A resource for coordinating rune solving between instances.
+    pub rune_solving: RuneSolvingToken,
     /// A resource indicating current tick.
     pub tick: u64,
 }
@@ -163,6 +248,7 @@ impl Resources {
             notification: DiscordNotification::new(Rc::new(RefCell::new(Settings::default()))),
             detector: detector.map(|detector| Box::new(detector) as Box<dyn Detector>),
             operation: Operation::Running,
+            rune_solving: RuneSolvingToken::default(),
             tick: 0,
         }
     }
@@ -194,6 +280,12 @@ pub enum WorldEvent {
     PlayerDied,
     MinimapChanged,
     CaptureFailed,
+    CaptureWindowNotFound,
+    GameFrozen,
+    PlayerStrangerAppeared,
+    PlayerGuildieAppeared,
+    NavigationFailed,
+    MovementCalibrated(u32),
 }
 
 /// A container for entities.
@@ -204,3 +296,30 @@ pub struct World {
     pub skills: SkillEntities,
     pub buffs: BuffEntities,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rune_solving_token_acquired_by_one_at_a_time() {
+        let first = RuneSolvingToken::default();
+        let second = first.share();
+
+        assert!(first.try_acquire());
+        assert!(first.try_acquire());
+        assert!(!second.try_acquire());
+        assert!(first.is_acquired());
+        assert!(!second.is_acquired());
+
+        second.release();
+        assert!(first.is_acquired());
+
+        first.release();
+        assert!(second.try_acquire());
+        assert!(!first.try_acquire());
+
+        drop(second);
+        assert!(first.try_acquire());
+    }
+}
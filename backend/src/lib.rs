@@ -13,7 +13,7 @@ use std::{
     time::{Duration, Instant},
 };
 
-use strum::Display;
+use strum::{Display, EnumIter};
 use tokio::{
     sync::{
         broadcast, mpsc,
@@ -38,7 +38,10 @@ mod navigator;
 mod notification;
 mod operation;
 mod pathing;
+mod pipeline;
 mod player;
+#[cfg(debug_assertions)]
+mod replay;
 mod rng;
 mod rotator;
 mod rpc;
@@ -49,17 +52,21 @@ mod task;
 
 pub use {
     database::{
-        Action, ActionCondition, ActionConfiguration, ActionConfigurationCondition, ActionKey,
-        ActionKeyDirection, ActionKeyWith, ActionMove, Bound, CaptureMode, Character, Class,
-        CycleRunStopMode, DatabaseEvent, EliteBossBehavior, FamiliarRarity, Familiars, InputMethod,
-        KeyBinding, KeyBindingConfiguration, LinkKeyBinding, Minimap, MobbingKey, NavigationPath,
-        NavigationPaths, NavigationPoint, NavigationTransition, Notifications, Platform, Position,
-        PotionMode, RotationMode, Settings, SwappableFamiliars, database_event_receiver,
+        Action, ActionCondition, ActionConfiguration, ActionConfigurationCondition,
+        ActionHoldPosition, ActionKey, ActionKeyDirection, ActionKeyWith, ActionMove,
+        ActionSnippet, ActionWaitForBuff, Bound, CaptureMode, Character, CharacterBuffKind, Class,
+        ColorThresholds, CycleRunStopMode, DatabaseEvent, DetectionRegion, EliteBossBehavior,
+        FamiliarRarity, Familiars, InputMethod, KeyBinding, KeyBindingConfiguration,
+        LinkKeyBinding, LogLevel, Minimap, MobbingKey, NavigationPath, NavigationPaths,
+        NavigationPoint, NavigationTransition, Notifications, OtherPlayerBehavior, Platform,
+        Position, PotionConfiguration, PotionMode, PresetMetadata, RotationMode, Settings,
+        SwappableFamiliars, database_event_receiver,
     },
     models::*,
     pathing::MAX_PLATFORMS_COUNT,
     rotator::RotatorMode,
     run::init,
+    services::{set_character_actions_enabled, validate_character_keys},
     strum::{EnumMessage, IntoEnumIterator, ParseError},
 };
 
@@ -105,8 +112,16 @@ enum Request {
     RotateActions(RotateKind),
     CreateMinimap(String),
     UpdateMinimap(Option<String>, Option<Minimap>),
+    DuplicatePreset(String, String),
+    RenamePreset(String, String),
+    ImportActionsFrom(i64, String),
+    InsertActionSnippet(i64),
+    InsertCharacterActionSnippet(i64),
+    UndoMinimapActions,
+    RedoMinimapActions,
     CreateNavigationPath,
     RecaptureNavigationPath(NavigationPath),
+    ReorderNavigationPath(NavigationPath, Vec<usize>),
     NavigationSnapshotAsGrayscale(String),
     UpdateCharacter(Option<Character>),
     RedetectMinimap,
@@ -117,6 +132,11 @@ enum Request {
     SelectCaptureHandle(Option<usize>),
     QueryTemplate(GameTemplate),
     ConvertImageToBase64(Vec<u8>, bool),
+    QueryKeyConflicts,
+    QueryInstances,
+    CreateInstance,
+    RemoveInstance(usize),
+    SelectInstance(usize),
     #[cfg(debug_assertions)]
     DebugStateReceiver,
     #[cfg(debug_assertions)]
@@ -126,11 +146,21 @@ enum Request {
     #[cfg(debug_assertions)]
     InferRune,
     #[cfg(debug_assertions)]
+    ManualRuneKeys([KeyBinding; 4]),
+    #[cfg(debug_assertions)]
     InferMinimap,
     #[cfg(debug_assertions)]
     RecordImages(bool),
     #[cfg(debug_assertions)]
+    DumpRecentFrames,
+    #[cfg(debug_assertions)]
     TestSpinRune,
+    #[cfg(debug_assertions)]
+    ReplayActions,
+    #[cfg(debug_assertions)]
+    DetectorSelfTest,
+    #[cfg(debug_assertions)]
+    QueryMinimapColorMask(ColorThresholds),
 }
 
 /// Represents response to UI [`Request`].
@@ -141,9 +171,17 @@ enum Request {
 enum Response {
     RotateActions,
     CreateMinimap(Option<Minimap>),
-    UpdateMinimap,
+    UpdateMinimap(Option<Minimap>, Vec<String>),
+    DuplicatePreset(Option<Minimap>),
+    RenamePreset(Option<Minimap>),
+    ImportActionsFrom(Option<Minimap>),
+    InsertActionSnippet(Option<Minimap>),
+    InsertCharacterActionSnippet(Option<Character>),
+    UndoMinimapActions(Option<Minimap>),
+    RedoMinimapActions(Option<Minimap>),
     CreateNavigationPath(Option<NavigationPath>),
     RecaptureNavigationPath(NavigationPath),
+    ReorderNavigationPath(NavigationPath),
     NavigationSnapshotAsGrayscale(String),
     UpdateCharacter,
     RedetectMinimap,
@@ -154,6 +192,11 @@ enum Response {
     SelectCaptureHandle,
     QueryTemplate(String),
     ConvertImageToBase64(Option<String>),
+    QueryKeyConflicts(Vec<String>),
+    QueryInstances((usize, usize)),
+    CreateInstance,
+    RemoveInstance,
+    SelectInstance,
     #[cfg(debug_assertions)]
     DebugStateReceiver(broadcast::Receiver<DebugState>),
     #[cfg(debug_assertions)]
@@ -163,11 +206,21 @@ enum Response {
     #[cfg(debug_assertions)]
     InferRune,
     #[cfg(debug_assertions)]
+    ManualRuneKeys,
+    #[cfg(debug_assertions)]
     InferMinimap,
     #[cfg(debug_assertions)]
     RecordImages,
     #[cfg(debug_assertions)]
+    DumpRecentFrames,
+    #[cfg(debug_assertions)]
     TestSpinRune,
+    #[cfg(debug_assertions)]
+    ReplayActions,
+    #[cfg(debug_assertions)]
+    DetectorSelfTest(Vec<TemplateSelfTest>),
+    #[cfg(debug_assertions)]
+    QueryMinimapColorMask(Option<String>),
 }
 
 /// Request handler of incoming requests from UI.
@@ -176,12 +229,36 @@ pub(crate) trait RequestHandler {
 
     fn on_create_minimap(&self, name: String) -> Option<Minimap>;
 
-    fn on_update_minimap(&mut self, preset: Option<String>, minimap: Option<Minimap>);
+    fn on_update_minimap(
+        &mut self,
+        preset: Option<String>,
+        minimap: Option<Minimap>,
+    ) -> (Option<Minimap>, Vec<String>);
+
+    fn on_duplicate_preset(&mut self, src: String, dst: String) -> Option<Minimap>;
+
+    fn on_rename_preset(&mut self, old: String, new: String) -> Option<Minimap>;
+
+    fn on_import_actions_from(&mut self, minimap_id: i64, preset: String) -> Option<Minimap>;
+
+    fn on_insert_action_snippet(&mut self, snippet_id: i64) -> Option<Minimap>;
+
+    fn on_insert_character_action_snippet(&mut self, snippet_id: i64) -> Option<Character>;
+
+    fn on_undo_minimap_actions(&mut self) -> Option<Minimap>;
+
+    fn on_redo_minimap_actions(&mut self) -> Option<Minimap>;
 
     fn on_create_navigation_path(&self) -> Option<NavigationPath>;
 
     fn on_recapture_navigation_path(&self, path: NavigationPath) -> NavigationPath;
 
+    fn on_reorder_navigation_path(
+        &self,
+        path: NavigationPath,
+        new_order: Vec<usize>,
+    ) -> NavigationPath;
+
     fn on_navigation_snapshot_as_grayscale(&self, base64: String) -> String;
 
     fn on_update_character(&mut self, character: Option<Character>);
@@ -202,11 +279,21 @@ pub(crate) trait RequestHandler {
 
     fn on_convert_image_to_base64(&self, image: Vec<u8>, is_grayscale: bool) -> Option<String>;
 
+    fn on_query_key_conflicts(&self) -> Vec<String>;
+
+    fn on_query_instances(&self) -> (usize, usize);
+
+    fn on_create_instance(&mut self);
+
+    fn on_remove_instance(&mut self, index: usize);
+
+    fn on_select_instance(&mut self, index: usize);
+
     #[cfg(debug_assertions)]
     fn on_debug_state_receiver(&self) -> broadcast::Receiver<DebugState>;
 
     #[cfg(debug_assertions)]
-    fn on_auto_save_rune(&self, auto_save: bool);
+    fn on_auto_save_rune(&mut self, auto_save: bool);
 
     #[cfg(debug_assertions)]
     fn on_capture_image(&self, is_grayscale: bool);
@@ -214,17 +301,32 @@ pub(crate) trait RequestHandler {
     #[cfg(debug_assertions)]
     fn on_infer_rune(&mut self);
 
+    #[cfg(debug_assertions)]
+    fn on_manual_rune_keys(&self, keys: [bridge::KeyKind; 4]);
+
     #[cfg(debug_assertions)]
     fn on_infer_minimap(&self);
 
     #[cfg(debug_assertions)]
     fn on_record_images(&mut self, start: bool);
 
+    #[cfg(debug_assertions)]
+    fn on_dump_recent_frames(&self);
+
     #[cfg(debug_assertions)]
     fn on_test_spin_rune(&self);
+
+    #[cfg(debug_assertions)]
+    fn on_replay_actions(&mut self);
+
+    #[cfg(debug_assertions)]
+    fn on_detector_self_test(&self) -> Vec<TemplateSelfTest>;
+
+    #[cfg(debug_assertions)]
+    fn on_query_minimap_color_mask(&self, thresholds: ColorThresholds) -> Option<String>;
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, EnumIter)]
 pub enum GameTemplate {
     CashShop,
     ChangeChannel,
@@ -259,6 +361,17 @@ pub struct DebugState {
     pub is_rune_auto_saving: bool,
 }
 
+/// The result of matching a [`GameTemplate`] against the current frame.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg(debug_assertions)]
+pub struct TemplateSelfTest {
+    pub template: GameTemplate,
+    /// Whether the template is detected using its detection threshold.
+    pub detected: bool,
+    /// The best match confidence or [`None`] if matching failed.
+    pub confidence: Option<f32>,
+}
+
 /// A struct for storing game information.
 #[derive(Clone, Debug)]
 pub struct GameState {
@@ -272,8 +385,13 @@ pub struct GameState {
     pub operation: GameOperation,
     pub frame: Option<(Vec<u8>, usize, usize)>,
     pub platforms_bound: Option<Bound>,
+    /// The bound used by the current [`RotationMode::AutoMobbing`], [`RotationMode::PingPong`] or
+    /// [`RotationMode::Patrol`] rotation.
+    pub rotation_bound: Option<Bound>,
     pub portals: Vec<Bound>,
     pub auto_mob_quadrant: Option<BoundQuadrant>,
+    /// The current navigation hop index starting from 1 and the total number of hops.
+    pub navigation_progress: Option<(usize, usize)>,
 }
 
 #[derive(PartialEq, Clone, Copy, Debug)]
@@ -362,8 +480,90 @@ pub async fn upsert_minimap(mut minimap: Minimap) -> Option<Minimap> {
 }
 
 /// Updates the current minimap used by the main game loop.
-pub async fn update_minimap(preset: Option<String>, minimap: Option<Minimap>) {
-    send_request!(UpdateMinimap(preset, minimap))
+///
+/// Returns the corrected `minimap` and the issues found while validating it. Invalid bounds are
+/// clamped to within the minimap. The corrected minimap is [`None`] when there is no issue and
+/// is not persisted to the database.
+pub async fn update_minimap(
+    preset: Option<String>,
+    minimap: Option<Minimap>,
+) -> (Option<Minimap>, Vec<String>) {
+    send_request!(UpdateMinimap(preset, minimap) => (minimap, issues))
+}
+
+/// Duplicates the actions of preset `src` to a new preset `dst` in the current minimap.
+///
+/// The updated minimap is applied the same as [`update_minimap`]. This function does not upsert
+/// the updated minimap into the database.
+///
+/// Returns the updated [`Minimap`] or [`None`] if `src` does not exist or `dst` already exists.
+pub async fn duplicate_preset(src: String, dst: String) -> Option<Minimap> {
+    send_request!(DuplicatePreset(src, dst) => (minimap))
+}
+
+/// Renames preset `old` to `new` in the current minimap while preserving its actions.
+///
+/// The updated minimap is applied and the preset in use is also renamed if it is `old`. This
+/// function does not upsert the updated minimap into the database.
+///
+/// Returns the updated [`Minimap`] or [`None`] if `old` does not exist or `new` already exists.
+pub async fn rename_preset(old: String, new: String) -> Option<Minimap> {
+    send_request!(RenamePreset(old, new) => (minimap))
+}
+
+/// Copies the actions of `preset` from the minimap with `minimap_id` into the preset currently
+/// in use of the current minimap.
+///
+/// Positions are scaled if the two minimaps have different sizes. This function does not upsert
+/// the updated minimap into the database.
+///
+/// Returns the updated [`Minimap`] or [`None`] if the source minimap or `preset` does not exist.
+pub async fn import_actions_from(minimap_id: i64, preset: String) -> Option<Minimap> {
+    send_request!(ImportActionsFrom(minimap_id, preset) => (minimap))
+}
+
+/// Appends the actions of the snippet with `snippet_id` to the preset currently in use of the
+/// current minimap.
+///
+/// The main game loop uses the updated actions immediately and the edit can be undone. This
+/// function does not upsert the updated minimap into the database.
+///
+/// Returns the updated [`Minimap`] or [`None`] if the snippet does not exist or there is no
+/// preset in use.
+pub async fn insert_action_snippet(snippet_id: i64) -> Option<Minimap> {
+    send_request!(InsertActionSnippet(snippet_id) => (minimap))
+}
+
+/// Appends the actions of the snippet with `snippet_id` to the actions of the current character.
+///
+/// Only key actions without a position and with a fixed interval or linked condition are
+/// appended. The main game loop uses the updated actions immediately. This function does not
+/// upsert the updated character into the database.
+///
+/// Returns the updated [`Character`] or [`None`] if the snippet does not exist or there is no
+/// character in use.
+pub async fn insert_character_action_snippet(snippet_id: i64) -> Option<Character> {
+    send_request!(InsertCharacterActionSnippet(snippet_id) => (character))
+}
+
+/// Undoes the last actions edit of the current minimap preset.
+///
+/// The main game loop uses the restored actions immediately. This function does not upsert the
+/// updated minimap into the database.
+///
+/// Returns the updated [`Minimap`] or [`None`] if there is nothing to undo.
+pub async fn undo_minimap_actions() -> Option<Minimap> {
+    send_request!(UndoMinimapActions => (minimap))
+}
+
+/// Redoes the last undone actions edit of the current minimap preset.
+///
+/// The main game loop uses the restored actions immediately. This function does not upsert the
+/// updated minimap into the database.
+///
+/// Returns the updated [`Minimap`] or [`None`] if there is nothing to redo.
+pub async fn redo_minimap_actions() -> Option<Minimap> {
+    send_request!(RedoMinimapActions => (minimap))
 }
 
 /// Deletes `minimap` from the database.
@@ -411,6 +611,19 @@ pub async fn recapture_navigation_path(path: NavigationPath) -> NavigationPath {
     send_request!(RecaptureNavigationPath(path) => (path))
 }
 
+/// Reorders the points of `path` without recapturing its snapshots.
+///
+/// `new_order` contains the original point indices in their new order. It must be a permutation
+/// of all point indices.
+///
+/// Returns the reordered [`NavigationPath`] or original if `new_order` is invalid.
+pub async fn reorder_navigation_path(
+    path: NavigationPath,
+    new_order: Vec<usize>,
+) -> NavigationPath {
+    send_request!(ReorderNavigationPath(path, new_order) => (path))
+}
+
 pub async fn navigation_snapshot_as_grayscale(base64: String) -> String {
     send_request!(NavigationSnapshotAsGrayscale(base64) => (base64))
 }
@@ -424,6 +637,39 @@ pub async fn delete_navigation_paths(paths: NavigationPaths) -> bool {
         .unwrap()
 }
 
+/// Queries action snippets from the database.
+pub async fn query_action_snippets() -> Option<Vec<ActionSnippet>> {
+    spawn_blocking(database::query_action_snippets)
+        .await
+        .unwrap()
+        .ok()
+}
+
+/// Upserts `snippet` to the database.
+///
+/// If `snippet` does not previously exist, a new one will be created and its `id` will
+/// be updated.
+///
+/// Returns the updated [`ActionSnippet`] on success.
+pub async fn upsert_action_snippet(mut snippet: ActionSnippet) -> Option<ActionSnippet> {
+    spawn_blocking(move || {
+        database::upsert_action_snippet(&mut snippet)
+            .is_ok()
+            .then_some(snippet)
+    })
+    .await
+    .unwrap()
+}
+
+/// Deletes `snippet` from the database.
+///
+/// Returns `true` if the `snippet` was deleted.
+pub async fn delete_action_snippet(snippet: ActionSnippet) -> bool {
+    spawn_blocking(move || database::delete_action_snippet(&snippet).is_ok())
+        .await
+        .unwrap()
+}
+
 /// Queries characters from the database.
 pub async fn query_characters() -> Option<Vec<Character>> {
     spawn_blocking(database::query_characters)
@@ -494,6 +740,35 @@ pub async fn convert_image_to_base64(image: Vec<u8>, is_grayscale: bool) -> Opti
     send_request!(ConvertImageToBase64(image, is_grayscale) => (base64))
 }
 
+/// Queries key binding conflicts between the current settings hotkeys and character keys.
+pub async fn query_key_conflicts() -> Vec<String> {
+    send_request!(QueryKeyConflicts => (conflicts))
+}
+
+/// Queries the number of running instances and the index of the currently selected instance.
+///
+/// The selected instance is the one receiving all other requests.
+pub async fn query_instances() -> (usize, usize) {
+    send_request!(QueryInstances => (pair))
+}
+
+/// Creates a new instance for controlling another game window.
+pub async fn create_instance() {
+    send_request!(CreateInstance)
+}
+
+/// Removes the instance at `index`.
+///
+/// The first instance cannot be removed.
+pub async fn remove_instance(index: usize) {
+    send_request!(RemoveInstance(index))
+}
+
+/// Selects the instance at `index` to receive all other requests.
+pub async fn select_instance(index: usize) {
+    send_request!(SelectInstance(index))
+}
+
 #[cfg(debug_assertions)]
 pub async fn debug_state_receiver() -> broadcast::Receiver<DebugState> {
     send_request!(DebugStateReceiver => (receiver))
@@ -514,6 +789,12 @@ pub async fn infer_rune() {
     send_request!(InferRune)
 }
 
+/// Presses `keys` instead of the inferred keys when solving rune.
+#[cfg(debug_assertions)]
+pub async fn manual_rune_keys(keys: [KeyBinding; 4]) {
+    send_request!(ManualRuneKeys(keys))
+}
+
 #[cfg(debug_assertions)]
 pub async fn infer_minimap() {
     send_request!(InferMinimap)
@@ -524,11 +805,36 @@ pub async fn record_images(start: bool) {
     send_request!(RecordImages(start))
 }
 
+#[cfg(debug_assertions)]
+pub async fn dump_recent_frames() {
+    send_request!(DumpRecentFrames)
+}
+
 #[cfg(debug_assertions)]
 pub async fn test_spin_rune() {
     send_request!(TestSpinRune)
 }
 
+/// Replays the recently rotated normal actions in order in place of the built actions.
+#[cfg(debug_assertions)]
+pub async fn replay_actions() {
+    send_request!(ReplayActions)
+}
+
+/// Matches all templates against the current frame for troubleshooting localization.
+#[cfg(debug_assertions)]
+pub async fn detector_self_test() -> Vec<TemplateSelfTest> {
+    send_request!(DetectorSelfTest => (results))
+}
+
+/// Retrieves the base64-encoded PNG mask of the current minimap for `thresholds`.
+///
+/// Returns [`None`] if there is no minimap detected.
+#[cfg(debug_assertions)]
+pub async fn query_minimap_color_mask(thresholds: ColorThresholds) -> Option<String> {
+    send_request!(QueryMinimapColorMask(thresholds) => (mask))
+}
+
 pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
     if let Ok((request, sender)) = LazyLock::force(&REQUESTS).1.lock().unwrap().try_recv() {
         let result = match request {
@@ -540,8 +846,31 @@ pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
                 Response::CreateMinimap(handler.on_create_minimap(name))
             }
             Request::UpdateMinimap(preset, minimap) => {
-                handler.on_update_minimap(preset, minimap);
-                Response::UpdateMinimap
+                let (minimap, issues) = handler.on_update_minimap(preset, minimap);
+                Response::UpdateMinimap(minimap, issues)
+            }
+            Request::DuplicatePreset(src, dst) => {
+                Response::DuplicatePreset(handler.on_duplicate_preset(src, dst))
+            }
+            Request::RenamePreset(old, new) => {
+                Response::RenamePreset(handler.on_rename_preset(old, new))
+            }
+            Request::ImportActionsFrom(minimap_id, preset) => {
+                Response::ImportActionsFrom(handler.on_import_actions_from(minimap_id, preset))
+            }
+            Request::InsertActionSnippet(snippet_id) => {
+                Response::InsertActionSnippet(handler.on_insert_action_snippet(snippet_id))
+            }
+            Request::InsertCharacterActionSnippet(snippet_id) => {
+                Response::InsertCharacterActionSnippet(
+                    handler.on_insert_character_action_snippet(snippet_id),
+                )
+            }
+            Request::UndoMinimapActions => {
+                Response::UndoMinimapActions(handler.on_undo_minimap_actions())
+            }
+            Request::RedoMinimapActions => {
+                Response::RedoMinimapActions(handler.on_redo_minimap_actions())
             }
             Request::CreateNavigationPath => {
                 Response::CreateNavigationPath(handler.on_create_navigation_path())
@@ -549,6 +878,9 @@ pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
             Request::RecaptureNavigationPath(path) => {
                 Response::RecaptureNavigationPath(handler.on_recapture_navigation_path(path))
             }
+            Request::ReorderNavigationPath(path, new_order) => {
+                Response::ReorderNavigationPath(handler.on_reorder_navigation_path(path, new_order))
+            }
             Request::NavigationSnapshotAsGrayscale(base64) => {
                 Response::NavigationSnapshotAsGrayscale(
                     handler.on_navigation_snapshot_as_grayscale(base64),
@@ -583,6 +915,22 @@ pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
             Request::ConvertImageToBase64(image, is_grayscale) => Response::ConvertImageToBase64(
                 handler.on_convert_image_to_base64(image, is_grayscale),
             ),
+            Request::QueryKeyConflicts => {
+                Response::QueryKeyConflicts(handler.on_query_key_conflicts())
+            }
+            Request::QueryInstances => Response::QueryInstances(handler.on_query_instances()),
+            Request::CreateInstance => {
+                handler.on_create_instance();
+                Response::CreateInstance
+            }
+            Request::RemoveInstance(index) => {
+                handler.on_remove_instance(index);
+                Response::RemoveInstance
+            }
+            Request::SelectInstance(index) => {
+                handler.on_select_instance(index);
+                Response::SelectInstance
+            }
             #[cfg(debug_assertions)]
             Request::DebugStateReceiver => {
                 Response::DebugStateReceiver(handler.on_debug_state_receiver())
@@ -603,6 +951,11 @@ pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
                 Response::InferRune
             }
             #[cfg(debug_assertions)]
+            Request::ManualRuneKeys(keys) => {
+                handler.on_manual_rune_keys(keys.map(bridge::KeyKind::from));
+                Response::ManualRuneKeys
+            }
+            #[cfg(debug_assertions)]
             Request::InferMinimap => {
                 handler.on_infer_minimap();
                 Response::InferMinimap
@@ -613,10 +966,28 @@ pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
                 Response::RecordImages
             }
             #[cfg(debug_assertions)]
+            Request::DumpRecentFrames => {
+                handler.on_dump_recent_frames();
+                Response::DumpRecentFrames
+            }
+            #[cfg(debug_assertions)]
             Request::TestSpinRune => {
                 handler.on_test_spin_rune();
                 Response::TestSpinRune
             }
+            #[cfg(debug_assertions)]
+            Request::ReplayActions => {
+                handler.on_replay_actions();
+                Response::ReplayActions
+            }
+            #[cfg(debug_assertions)]
+            Request::DetectorSelfTest => {
+                Response::DetectorSelfTest(handler.on_detector_self_test())
+            }
+            #[cfg(debug_assertions)]
+            Request::QueryMinimapColorMask(thresholds) => {
+                Response::QueryMinimapColorMask(handler.on_query_minimap_color_mask(thresholds))
+            }
         };
         let _ = sender.send(result);
     }
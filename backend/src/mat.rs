@@ -1,4 +1,8 @@
-use std::ffi::c_void;
+use std::{
+    ffi::c_void,
+    mem,
+    sync::{Arc, Mutex},
+};
 
 use opencv::{
     boxed_ref::BoxedRef,
@@ -6,18 +10,50 @@ use opencv::{
 };
 use platforms::capture::Frame;
 
+/// The maximum number of buffers kept in a [`FramePool`].
+const MAX_POOLED_BUFFERS: usize = 4;
+
+/// A pool of frame buffers reused across frames to reduce per-frame allocations.
+///
+/// A buffer is taken from the pool before grabbing a frame and returned when grabbing fails or
+/// when the [`OwnedMat`] created from that frame is dropped.
+#[derive(Clone, Debug, Default)]
+pub struct FramePool {
+    buffers: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl FramePool {
+    /// Takes a buffer from the pool or an empty buffer if the pool is empty.
+    pub fn take(&self) -> Vec<u8> {
+        self.buffers.lock().unwrap().pop().unwrap_or_default()
+    }
+
+    /// Returns `buffer` to the pool or drops it if the pool is full.
+    pub fn put(&self, buffer: Vec<u8>) {
+        let mut buffers = self.buffers.lock().unwrap();
+        if buffers.len() < MAX_POOLED_BUFFERS {
+            buffers.push(buffer);
+        }
+    }
+}
+
 // A Mat that owns the external buffer.
 #[derive(Debug)]
 pub struct OwnedMat {
     mat: BoxedRef<'static, Mat>,
     #[allow(unused)]
     data: Vec<u8>,
+    /// The pool to return `data` to when dropped.
+    pool: Option<FramePool>,
 }
 
 impl OwnedMat {
+    /// Creates a new [`OwnedMat`] from `frame` with the frame buffer returned to `pool` on drop.
     #[inline]
-    pub fn new_from_frame(frame: Frame) -> Self {
-        Self::new_from_bytes(frame.data, frame.width, frame.height, CV_8UC4)
+    pub fn new_from_frame(frame: Frame, pool: &FramePool) -> Self {
+        let mut mat = Self::new_from_bytes(frame.data, frame.width, frame.height, CV_8UC4);
+        mat.pool = Some(pool.clone());
+        mat
     }
 
     #[inline]
@@ -31,7 +67,19 @@ impl OwnedMat {
             .unwrap()
         });
 
-        Self { data, mat }
+        Self {
+            data,
+            mat,
+            pool: None,
+        }
+    }
+}
+
+impl Drop for OwnedMat {
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            pool.put(mem::take(&mut self.data));
+        }
     }
 }
 
@@ -41,6 +89,7 @@ impl From<Mat> for OwnedMat {
         Self {
             mat: BoxedRef::from(value),
             data: vec![],
+            pool: None,
         }
     }
 }
@@ -56,3 +105,41 @@ impl MatTraitConst for OwnedMat {
         self.mat.as_raw_Mat()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use opencv::core::MatTraitConstManual;
+
+    use super::*;
+
+    fn frame(buffer: Vec<u8>, value: u8) -> Frame {
+        let mut data = buffer;
+        data.clear();
+        data.resize(2 * 2 * 4, value);
+        Frame {
+            width: 2,
+            height: 2,
+            data,
+        }
+    }
+
+    #[test]
+    fn frame_pool_reuses_buffer_without_corruption() {
+        let pool = FramePool::default();
+
+        let first = OwnedMat::new_from_frame(frame(pool.take(), 1), &pool);
+        let first_ptr = first.data.as_ptr();
+        assert!(first.data_bytes().unwrap().iter().all(|&byte| byte == 1));
+        drop(first);
+
+        let second = OwnedMat::new_from_frame(frame(pool.take(), 2), &pool);
+        assert_eq!(second.data.as_ptr(), first_ptr);
+        assert!(second.data_bytes().unwrap().iter().all(|&byte| byte == 2));
+
+        // Pool is empty while the only buffer is in use
+        let third = OwnedMat::new_from_frame(frame(pool.take(), 3), &pool);
+        assert_ne!(third.data.as_ptr(), first_ptr);
+        assert!(second.data_bytes().unwrap().iter().all(|&byte| byte == 2));
+        assert!(third.data_bytes().unwrap().iter().all(|&byte| byte == 3));
+    }
+}
@@ -17,12 +17,19 @@ use crate::{
         MAX_PLATFORMS_COUNT, Platform, PlatformWithNeighbors, find_neighbors, find_platforms_bound,
     },
     player::{DOUBLE_JUMP_THRESHOLD, GRAPPLING_MAX_THRESHOLD, JUMP_THRESHOLD, Player},
+    run::FPS,
     task::{Task, Update, update_detection_task},
     transition, transition_if, try_some_transition,
 };
 
 const MINIMAP_BORDER_WHITENESS_THRESHOLD: u8 = 160;
 const MAX_PORTALS_COUNT: usize = 16;
+const MAX_ROPES_COUNT: usize = 16;
+
+/// Maximum number of ticks to wait for the map to load before assuming it has loaded.
+///
+/// The player health bar used to detect loaded can be hidden or covered by other UIs.
+const MAX_LOADING_TICKS: u32 = FPS * 10;
 
 /// A wrapper struct for [`Rect`] that implements [`Hash`].
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -60,6 +67,8 @@ pub struct MinimapContext {
     /// If there is any false-positive portal, this helps remove that portal over time to ensure
     /// player's action will not get wrongly cancelled (e.g. in up jump).
     portals_invalidate_map: HashMap<HashedRect, u32>,
+    /// Task to detect the current minimap's ropes and ladders.
+    ropes_task: Option<Task<Result<Vec<Rect>>>>,
     /// Task to detect elite boss.
     has_elite_boss_task: Option<Task<Result<()>>>,
     /// Task to detect guildie player(s) in the minimap.
@@ -68,6 +77,12 @@ pub struct MinimapContext {
     has_stranger_player_task: Option<Task<Result<()>>>,
     /// Task to detect firend player(s) in the minimap.
     has_friend_player_task: Option<Task<Result<()>>>,
+    /// Task to detect whether the map has fully loaded after the minimap is detected.
+    loaded_task: Option<Task<Result<Rect>>>,
+    /// The anchors of the last detected minimap.
+    ///
+    /// Used to only wait for the map to load when the newly detected minimap is a different map.
+    last_anchors: Option<Anchors>,
 
     platforms: Vec<Platform>,
     /// Whether to update the [`MinimapIdle::platforms`].
@@ -88,8 +103,8 @@ impl MinimapContext {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-#[cfg_attr(test, derive(Default, PartialEq))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(test, derive(Default))]
 struct Anchors {
     tl: (Point, Vec4b),
     br: (Point, Vec4b),
@@ -129,6 +144,12 @@ pub struct MinimapIdle {
     ///
     /// It is partially overlapped by other UIs if one of the anchor mismatches.
     pub partially_overlapping: bool,
+    /// Whether the map is still loading after the minimap of a different map is detected.
+    ///
+    /// The minimap can render before the map is interactable after a map transition.
+    pub loading: bool,
+    /// Number of ticks the map has been loading for.
+    loading_ticks: u32,
     /// The rune position.
     ///
     /// The rune position is in player-relative coordinate, which is bottom-left.
@@ -147,6 +168,10 @@ pub struct MinimapIdle {
     ///
     /// The portals are in player-relative coordinate, which is bottom-left.
     portals: Array<Rect, MAX_PORTALS_COUNT>,
+    /// The rope and ladder positions.
+    ///
+    /// The ropes are in player-relative coordinate, which is bottom-left.
+    ropes: Array<Rect, MAX_ROPES_COUNT>,
     /// The user provided platforms.
     ///
     /// The platforms are in player-relative coordinate, which is bottom-left.
@@ -168,16 +193,41 @@ impl MinimapIdle {
         self.rune.value = Some(rune);
     }
 
+    #[cfg(test)]
+    pub fn set_portals(&mut self, portals: Array<Rect, MAX_PORTALS_COUNT>) {
+        self.portals = portals;
+    }
+
     #[inline]
     pub fn portals(&self) -> Array<Rect, MAX_PORTALS_COUNT> {
         self.portals
     }
 
+    #[cfg(test)]
+    pub fn set_ropes(&mut self, ropes: Array<Rect, MAX_ROPES_COUNT>) {
+        self.ropes = ropes;
+    }
+
+    #[inline]
+    pub fn ropes(&self) -> Array<Rect, MAX_ROPES_COUNT> {
+        self.ropes
+    }
+
     #[inline]
     pub fn has_elite_boss(&self) -> bool {
         self.has_elite_boss.value.is_some()
     }
 
+    #[inline]
+    pub fn has_guildie_player(&self) -> bool {
+        self.has_guildie_player.value.is_some()
+    }
+
+    #[inline]
+    pub fn has_stranger_player(&self) -> bool {
+        self.has_stranger_player.value.is_some()
+    }
+
     #[inline]
     pub fn has_any_other_player(&self) -> bool {
         self.has_guildie_player.value.is_some()
@@ -185,11 +235,12 @@ impl MinimapIdle {
             || self.has_friend_player.value.is_some()
     }
 
+    /// Checks whether `pos` is inside any of the portals expanded by `margin` on all sides.
     #[inline]
-    pub fn is_position_inside_portal(&self, pos: Point) -> bool {
+    pub fn is_position_inside_portal(&self, pos: Point, margin: i32) -> bool {
         for portal in self.portals {
-            let x_range = portal.x..(portal.x + portal.width);
-            let y_range = portal.y..(portal.y + portal.height);
+            let x_range = (portal.x - margin)..(portal.x + portal.width + margin);
+            let y_range = (portal.y - margin)..(portal.y + portal.height + margin);
 
             if x_range.contains(&pos.x) && y_range.contains(&pos.y) {
                 info!(target: "minimap", "position {pos:?} is inside portal {portal:?}");
@@ -240,20 +291,28 @@ fn update_detecting_state(resources: &Resources, minimap: &mut MinimapEntity) {
     minimap.context.rune_task = None;
     minimap.context.portals_task = None;
     minimap.context.portals_invalidate_map.clear();
+    minimap.context.ropes_task = None;
     minimap.context.has_elite_boss_task = None;
     minimap.context.has_guildie_player_task = None;
     minimap.context.has_stranger_player_task = None;
     minimap.context.has_friend_player_task = None;
+    minimap.context.loaded_task = None;
+    // Re-detecting the same minimap (e.g. after UI overlapping) is not a map transition
+    let loading = minimap.context.last_anchors != Some(anchors);
+    minimap.context.last_anchors = Some(anchors);
     minimap.state = Minimap::Idle(MinimapIdle {
         anchors,
         bbox,
         partially_overlapping: false,
+        loading,
+        loading_ticks: 0,
         rune: Threshold::new(3),
         has_elite_boss: Threshold::new(2),
         has_guildie_player: Threshold::new(2),
         has_stranger_player: Threshold::new(2),
         has_friend_player: Threshold::new(2),
         portals: Array::new(),
+        ropes: Array::new(),
         platforms,
         platforms_bound,
     });
@@ -270,12 +329,15 @@ fn update_idle_state(
     let MinimapIdle {
         anchors,
         bbox,
+        loading,
+        loading_ticks,
         rune,
         has_elite_boss,
         has_guildie_player,
         has_stranger_player,
         has_friend_player,
         portals,
+        ropes,
         mut platforms,
         mut platforms_bound,
         ..
@@ -305,6 +367,10 @@ fn update_idle_state(
     }
 
     let partially_overlapping = (tl_match && !br_match) || (!tl_match && br_match);
+    let loading_ticks = loading_ticks + loading as u32;
+    let loading = loading
+        && loading_ticks < MAX_LOADING_TICKS
+        && !update_loaded_task(resources, &mut minimap.context.loaded_task);
     let rune = update_rune_task(
         resources,
         &mut minimap.context.rune_task,
@@ -345,6 +411,7 @@ fn update_idle_state(
         portals,
         bbox,
     );
+    let ropes = update_ropes_task(resources, &mut minimap.context.ropes_task, ropes, bbox);
 
     if minimap.context.platforms_dirty {
         let (updated_platforms, updated_bound) =
@@ -356,12 +423,15 @@ fn update_idle_state(
 
     minimap.state = Minimap::Idle(MinimapIdle {
         partially_overlapping,
+        loading,
+        loading_ticks,
         rune,
         has_elite_boss,
         has_guildie_player,
         has_stranger_player,
         has_friend_player,
         portals,
+        ropes,
         platforms,
         platforms_bound,
         ..minimap_state
@@ -379,6 +449,19 @@ fn anchor_match(anchor: Vec4b, pixel: Vec4b) -> bool {
     avg <= ANCHOR_ACCEPTABLE_ERROR_RANGE
 }
 
+/// Detects whether the map has fully loaded and is interactable.
+///
+/// The player health bar in the status bar is only rendered once the map has finished loading.
+#[inline]
+fn update_loaded_task(resources: &Resources, task: &mut Option<Task<Result<Rect>>>) -> bool {
+    matches!(
+        update_detection_task(resources, 500, task, |detector| {
+            detector.detect_player_health_bar()
+        }),
+        Update::Ok(_)
+    )
+}
+
 #[inline]
 fn update_rune_task(
     resources: &Resources,
@@ -495,6 +578,29 @@ fn update_portals_task(
     }
 }
 
+#[inline]
+fn update_ropes_task(
+    resources: &Resources,
+    task: &mut Option<Task<Result<Vec<Rect>>>>,
+    ropes: Array<Rect, MAX_ROPES_COUNT>,
+    minimap: Rect,
+) -> Array<Rect, MAX_ROPES_COUNT> {
+    let update = update_detection_task(resources, 5000, task, move |detector| {
+        Ok(detector.detect_minimap_ropes(minimap))
+    });
+    match update {
+        Update::Ok(vec) => Array::from_iter(vec.into_iter().take(MAX_ROPES_COUNT).map(|rope| {
+            Rect::new(
+                rope.x,
+                minimap.height - rope.br().y, // Flip coordinate to bottom-left
+                rope.width,
+                rope.height,
+            )
+        })),
+        Update::Err(_) | Update::Pending => ropes,
+    }
+}
+
 fn merge_portals_and_invalidate_if_needed(
     old_portals: HashSet<HashedRect>,
     new_portals: HashSet<HashedRect>,
@@ -640,6 +746,7 @@ mod tests {
     enum TaskType {
         Rune,
         Minimap,
+        Loaded,
     }
 
     fn create_test_mat() -> (Mat, Anchors) {
@@ -677,6 +784,9 @@ mod tests {
             .expect_detect_minimap()
             .with(eq(MINIMAP_BORDER_WHITENESS_THRESHOLD))
             .returning(move |_| Ok(bbox));
+        detector
+            .expect_detect_player_health_bar()
+            .returning(|| Ok(Rect::new(0, 0, 10, 2)));
         detector.expect_mat().return_const(mat.into());
         (detector, bbox, anchors, rune_bbox)
     }
@@ -695,6 +805,10 @@ mod tests {
                 .minimap_task
                 .as_ref()
                 .is_some_and(|task| task.completed()),
+            TaskType::Loaded => context
+                .loaded_task
+                .as_ref()
+                .is_some_and(|task| task.completed()),
         };
         while !completed(&minimap.context) {
             run_system(resources, minimap, Player::Idle);
@@ -718,10 +832,12 @@ mod tests {
                 assert_eq!(idle.anchors, anchors);
                 assert_eq!(idle.bbox, bbox);
                 assert!(!idle.partially_overlapping);
+                assert!(idle.loading);
                 assert_eq!(idle.rune.value, None);
                 assert!(!idle.has_elite_boss());
                 assert!(!idle.has_any_other_player());
                 assert!(idle.portals.is_empty());
+                assert!(idle.ropes.is_empty());
 
                 assert_matches!(minimap.context.minimap_task, Some(_));
                 assert_matches!(minimap.context.rune_task, None);
@@ -730,12 +846,30 @@ mod tests {
                 assert_matches!(minimap.context.has_stranger_player_task, None);
                 assert_matches!(minimap.context.has_friend_player_task, None);
                 assert_matches!(minimap.context.portals_task, None);
+                assert_matches!(minimap.context.ropes_task, None);
                 assert!(minimap.context.portals_invalidate_map.is_empty());
             }
             _ => panic!(),
         }
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn run_system_minimap_detecting_same_anchors_not_loading() {
+        let (detector, _, anchors, _) = create_mock_detector();
+        let mut minimap = MinimapEntity {
+            state: Minimap::Detecting,
+            context: MinimapContext {
+                last_anchors: Some(anchors),
+                ..MinimapContext::default()
+            },
+        };
+        let resources = Resources::new(None, Some(detector));
+
+        run_system_until_task_completed(&resources, &mut minimap, TaskType::Minimap).await;
+
+        assert_matches!(minimap.state, Minimap::Idle(idle) if !idle.loading);
+    }
+
     #[tokio::test(start_paused = true)]
     async fn run_system_minimap_idle_rune_detection() {
         let (detector, bbox, anchors, rune_bbox) = create_mock_detector();
@@ -743,12 +877,15 @@ mod tests {
             anchors,
             bbox,
             partially_overlapping: false,
+            loading: false,
+            loading_ticks: 0,
             rune: Threshold::new(3),
             has_elite_boss: Threshold::default(),
             has_guildie_player: Threshold::default(),
             has_stranger_player: Threshold::default(),
             has_friend_player: Threshold::default(),
             portals: Array::new(),
+            ropes: Array::new(),
             platforms: Array::new(),
             platforms_bound: None,
         };
@@ -768,6 +905,50 @@ mod tests {
         }
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn run_system_minimap_idle_loaded_after_detection() {
+        let (detector, bbox, anchors, _) = create_mock_detector();
+        let idle = MinimapIdle {
+            anchors,
+            bbox,
+            loading: true,
+            ..Default::default()
+        };
+        let mut minimap = MinimapEntity {
+            state: Minimap::Idle(idle),
+            context: MinimapContext::default(),
+        };
+        let resources = Resources::new(None, Some(detector));
+
+        run_system_until_task_completed(&resources, &mut minimap, TaskType::Loaded).await;
+
+        assert_matches!(minimap.state, Minimap::Idle(idle) if !idle.loading);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn run_system_minimap_idle_loaded_after_max_loading_ticks() {
+        let (detector, bbox, anchors, _) = create_mock_detector();
+        let idle = MinimapIdle {
+            anchors,
+            bbox,
+            loading: true,
+            loading_ticks: MAX_LOADING_TICKS - 2,
+            ..Default::default()
+        };
+        let mut minimap = MinimapEntity {
+            state: Minimap::Idle(idle),
+            context: MinimapContext::default(),
+        };
+        let resources = Resources::new(None, Some(detector));
+
+        // Loaded task is still pending
+        run_system(&resources, &mut minimap, Player::Idle);
+        assert_matches!(minimap.state, Minimap::Idle(idle) if idle.loading);
+
+        run_system(&resources, &mut minimap, Player::Idle);
+        assert_matches!(minimap.state, Minimap::Idle(idle) if !idle.loading);
+    }
+
     #[tokio::test(start_paused = true)]
     async fn update_threshold_detection_success_resets_fail_count() {
         let mut threshold = Threshold::new(2);
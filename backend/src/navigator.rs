@@ -3,8 +3,9 @@ use std::{
     collections::{HashMap, HashSet},
     fmt::{Debug, Formatter},
     hash::Hash,
+    mem,
     rc::Rc,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use anyhow::{Result, anyhow};
@@ -83,7 +84,26 @@ enum PointState {
     Dirty,
     Completed,
     Unreachable,
-    Next(i32, i32, NavigationTransition, Option<Rc<RefCell<Path>>>),
+    /// The next point with the remaining number of hops, including this one, to the destination.
+    Next(
+        i32,
+        i32,
+        NavigationTransition,
+        Option<Rc<RefCell<Path>>>,
+        usize,
+    ),
+}
+
+/// The hop currently being navigated to reach the next path.
+#[derive(Debug)]
+struct Hop {
+    x: i32,
+    y: i32,
+    next_path_id: Option<String>,
+    /// When the hop started or was last retried.
+    started: Instant,
+    /// Number of times the hop has been retried after timing out.
+    retry_count: u32,
 }
 
 /// Update state when [`Navigator::path_dirty`] is `true`.
@@ -118,6 +138,16 @@ pub trait Navigator: Debug + 'static {
     /// Same as [`Self::mark_dirty`] with `invalidate_cache` as `false` but also sets
     /// the navigation destination.
     fn mark_dirty_with_destination(&mut self, paths_id_index: Option<(i64, usize)>);
+
+    /// Whether the current hop has timed out after exhausting all retries.
+    ///
+    /// The failure is reset after this call.
+    fn take_hop_failed(&mut self) -> bool;
+
+    /// The current hop index starting from 1 and the total number of hops to the destination.
+    ///
+    /// Returns [`None`] if the player is not navigating.
+    fn navigation_progress(&self) -> Option<(usize, usize)>;
 }
 
 #[derive(Debug)]
@@ -141,6 +171,12 @@ pub struct DefaultNavigator {
     /// Cached next point navigation computation.
     last_point_state: Option<PointState>,
     destination_path_id: Option<String>,
+    /// The hop currently being navigated.
+    hop: Option<Hop>,
+    /// Whether [`Self::hop`] has timed out after exhausting all retries.
+    hop_failed: bool,
+    /// Total number of hops since the destination was set.
+    hops_total: usize,
     event_receiver: Receiver<WorldEvent>,
 }
 
@@ -162,6 +198,9 @@ impl DefaultNavigator {
             path_last_update: Instant::now(),
             last_point_state: None,
             destination_path_id: None,
+            hop: None,
+            hop_failed: false,
+            hops_total: 0,
             event_receiver,
         }
     }
@@ -191,7 +230,7 @@ impl DefaultNavigator {
     }
 
     fn compute_next_point(&self) -> PointState {
-        fn search_point(from: Rc<RefCell<Path>>, to_id: String) -> Option<Point> {
+        fn search_point(from: Rc<RefCell<Path>>, to_id: String) -> Option<(Point, usize)> {
             type CameFrom = (Option<Rc<RefCell<Path>>>, Option<Point>);
 
             let from_id = from.borrow().id.clone();
@@ -222,14 +261,16 @@ impl DefaultNavigator {
                         .expect("not visited");
                     if path_id == to_id {
                         let mut current = path_id.clone();
+                        let mut hops = 1;
                         while let Some((Some(from_path), Some(from_point))) =
                             came_from.get(&current)
                         {
                             if from_path.borrow().id == from_id {
-                                point = Some(from_point.clone());
+                                point = Some((from_point.clone(), hops));
                                 return false;
                             }
                             current = from_path.borrow().id.clone();
+                            hops += 1;
                         }
                     }
 
@@ -246,7 +287,7 @@ impl DefaultNavigator {
         // Re-use cached point
         if matches!(
             self.last_point_state,
-            Some(PointState::Next(_, _, _, _) | PointState::Completed | PointState::Unreachable)
+            Some(PointState::Next(_, _, _, _, _) | PointState::Completed | PointState::Unreachable)
         ) {
             return self.last_point_state.clone().expect("has value");
         }
@@ -264,8 +305,8 @@ impl DefaultNavigator {
         self.current_path
             .clone()
             .and_then(|path| search_point(path, path_id))
-            .map_or(PointState::Unreachable, |point| {
-                PointState::Next(point.x, point.y, point.transition, point.next_path.clone())
+            .map_or(PointState::Unreachable, |(point, hops)| {
+                PointState::Next(point.x, point.y, point.transition, point.next_path, hops)
             })
     }
 
@@ -298,7 +339,7 @@ impl DefaultNavigator {
         };
 
         // Try from next_path if previously exists due to player navigating
-        if let Some(PointState::Next(_, _, _, Some(next_path))) = self.last_point_state.take()
+        if let Some(PointState::Next(_, _, _, Some(next_path), _)) = self.last_point_state.take()
             && let Ok(current_path) =
                 find_current_from_base_path(next_path, detector, minimap_bbox, minimap_name_bbox)
         {
@@ -365,6 +406,48 @@ impl DefaultNavigator {
         UpdateState::NoMatch
     }
 
+    /// Updates the current hop to (`x`, `y`) and `next_path` and returns whether it has timed out.
+    ///
+    /// On time out, the hop is retried by marking paths dirty until the maximum retry count is
+    /// reached. After that, the hop is marked as failed.
+    #[inline]
+    fn update_hop_timed_out(
+        &mut self,
+        x: i32,
+        y: i32,
+        next_path: Option<Rc<RefCell<Path>>>,
+    ) -> bool {
+        const HOP_TIMEOUT: Duration = Duration::from_secs(60);
+        const HOP_MAX_RETRY_COUNT: u32 = 1;
+
+        let next_path_id = next_path.map(|path| path.borrow().id.clone());
+        let hop = match self.hop.as_mut() {
+            Some(hop) if hop.x == x && hop.y == y && hop.next_path_id == next_path_id => hop,
+            _ => self.hop.insert(Hop {
+                x,
+                y,
+                next_path_id,
+                started: Instant::now(),
+                retry_count: 0,
+            }),
+        };
+        if hop.started.elapsed() < HOP_TIMEOUT {
+            return false;
+        }
+
+        if hop.retry_count < HOP_MAX_RETRY_COUNT {
+            info!(target: "navigator", "hop to ({x}, {y}) timed out, retrying...");
+            hop.retry_count += 1;
+            hop.started = Instant::now();
+            self.mark_dirty(false);
+        } else {
+            info!(target: "navigator", "hop to ({x}, {y}) timed out after retries");
+            self.hop = None;
+            self.hop_failed = true;
+        }
+        true
+    }
+
     #[inline]
     fn did_minimap_changed(&mut self) -> bool {
         matches!(
@@ -401,32 +484,25 @@ impl Navigator for DefaultNavigator {
                 }
                 false
             }
-            PointState::Completed | PointState::Unreachable => true,
-            PointState::Next(x, y, transition, _) => {
-                match transition {
-                    NavigationTransition::Portal => {
-                        if !player_context.has_priority_action() {
-                            let position = Position {
-                                x,
-                                y,
-                                x_random_range: 0,
-                                allow_adjusting: true,
-                            };
-                            let key = Key {
-                                key: KeyBinding::Up,
-                                link_key: None,
-                                count: 1,
-                                position: Some(position),
-                                direction: ActionKeyDirection::Any,
-                                with: ActionKeyWith::Stationary,
-                                wait_before_use_ticks: 5,
-                                wait_before_use_ticks_random_range: 0,
-                                wait_after_use_ticks: 0,
-                                wait_after_use_ticks_random_range: 0,
-                            };
-                            player_context.set_priority_action(None, PlayerAction::Key(key));
+            PointState::Completed | PointState::Unreachable => {
+                self.hop = None;
+                true
+            }
+            PointState::Next(x, y, transition, next_path, hops) => {
+                self.hops_total = self.hops_total.max(hops);
+                if self.update_hop_timed_out(x, y, next_path) {
+                    player_context.take_priority_action();
+                    return self.hop_failed;
+                }
+
+                if !player_context.has_priority_action() {
+                    let key = match transition {
+                        NavigationTransition::Portal => KeyBinding::Up,
+                        NavigationTransition::PortalInteract => {
+                            player_context.config.interact_key.into()
                         }
-                    }
+                    };
+                    player_context.set_priority_action(None, portal_key_action(key, x, y));
                 }
 
                 false
@@ -438,7 +514,7 @@ impl Navigator for DefaultNavigator {
     fn was_last_point_available_or_completed(&self) -> bool {
         matches!(
             self.last_point_state,
-            Some(PointState::Next(_, _, _, _) | PointState::Completed)
+            Some(PointState::Next(_, _, _, _, _) | PointState::Completed)
         )
     }
 
@@ -457,8 +533,49 @@ impl Navigator for DefaultNavigator {
     fn mark_dirty_with_destination(&mut self, paths_id_index: Option<(i64, usize)>) {
         self.destination_path_id =
             paths_id_index.map(|(id, index)| path_id_from_paths_id_index(id, index));
+        self.hop = None;
+        self.hops_total = 0;
         self.mark_dirty(false);
     }
+
+    #[inline]
+    fn take_hop_failed(&mut self) -> bool {
+        mem::take(&mut self.hop_failed)
+    }
+
+    fn navigation_progress(&self) -> Option<(usize, usize)> {
+        self.destination_path_id.as_ref()?;
+        match self.last_point_state {
+            Some(PointState::Next(_, _, _, _, hops)) => {
+                let total = self.hops_total.max(hops);
+                Some((total - hops + 1, total))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Creates an action that moves the player to the portal at (`x`, `y`) and enters it with `key`.
+fn portal_key_action(key: KeyBinding, x: i32, y: i32) -> PlayerAction {
+    let position = Position {
+        x,
+        y,
+        x_random_range: 0,
+        allow_adjusting: true,
+    };
+    PlayerAction::Key(Key {
+        key,
+        link_key: None,
+        count: 1,
+        position: Some(position),
+        direction: ActionKeyDirection::Any,
+        with: ActionKeyWith::Stationary,
+        wait_before_use_ticks: 5,
+        wait_before_use_ticks_random_range: 0,
+        wait_after_use_ticks: 0,
+        wait_after_use_ticks_random_range: 0,
+        force: false,
+    })
 }
 
 fn build_base_path_from(
@@ -636,7 +753,9 @@ mod tests {
     use tokio::sync::broadcast::channel;
 
     use super::*;
-    use crate::{database::NavigationPoint, detect::MockDetector, minimap::MinimapIdle};
+    use crate::{
+        bridge::KeyKind, database::NavigationPoint, detect::MockDetector, minimap::MinimapIdle,
+    };
 
     impl Default for DefaultNavigator {
         fn default() -> Self {
@@ -829,7 +948,8 @@ mod tests {
         let result = navigator.compute_next_point();
 
         match result {
-            PointState::Next(x, y, transition, Some(next_path)) => {
+            PointState::Next(x, y, transition, Some(next_path), hops) => {
+                assert_eq!(hops, 1);
                 assert_eq!(x, 100);
                 assert_eq!(y, 200);
                 assert_eq!(transition, NavigationTransition::Portal);
@@ -839,6 +959,193 @@ mod tests {
         }
     }
 
+    #[test]
+    fn navigate_player_next_point_portal_sets_up_key_priority_action() {
+        let mut navigator = DefaultNavigator::default();
+        let target_path = Path {
+            id: 2.to_string(),
+            minimap_snapshot_base64: "".into(),
+            name_snapshot_base64: "".into(),
+            minimap_snapshot_grayscale: false,
+            points: vec![],
+        };
+        let point = Point {
+            x: 100,
+            y: 200,
+            transition: NavigationTransition::Portal,
+            next_path: Some(Rc::new(RefCell::new(target_path))),
+        };
+        let path = Path {
+            id: 1.to_string(),
+            minimap_snapshot_base64: "".into(),
+            name_snapshot_base64: "".into(),
+            minimap_snapshot_grayscale: false,
+            points: vec![point],
+        };
+        navigator.current_path = Some(Rc::new(RefCell::new(path)));
+        navigator.destination_path_id = Some(2.to_string());
+        navigator.path_dirty = false;
+        let resources = Resources::new(None, None);
+        let mut player_context = PlayerContext::default();
+
+        let completed =
+            navigator.navigate_player(&resources, &mut player_context, Minimap::Detecting);
+
+        assert!(!completed);
+        assert_matches!(
+            player_context.priority_action(),
+            Some(PlayerAction::Key(Key {
+                key: KeyBinding::Up,
+                position: Some(Position { x: 100, y: 200, .. }),
+                with: ActionKeyWith::Stationary,
+                ..
+            }))
+        );
+        assert_eq!(player_context.priority_action_id(), None);
+    }
+
+    #[test]
+    fn navigate_player_next_point_portal_interact_sets_interact_key_priority_action() {
+        let mut navigator = DefaultNavigator::default();
+        let target_path = Path {
+            id: 2.to_string(),
+            minimap_snapshot_base64: "".into(),
+            name_snapshot_base64: "".into(),
+            minimap_snapshot_grayscale: false,
+            points: vec![],
+        };
+        let point = Point {
+            x: 100,
+            y: 200,
+            transition: NavigationTransition::PortalInteract,
+            next_path: Some(Rc::new(RefCell::new(target_path))),
+        };
+        let path = Path {
+            id: 1.to_string(),
+            minimap_snapshot_base64: "".into(),
+            name_snapshot_base64: "".into(),
+            minimap_snapshot_grayscale: false,
+            points: vec![point],
+        };
+        navigator.current_path = Some(Rc::new(RefCell::new(path)));
+        navigator.destination_path_id = Some(2.to_string());
+        navigator.path_dirty = false;
+        let resources = Resources::new(None, None);
+        let mut player_context = PlayerContext::default();
+        player_context.config.interact_key = KeyKind::Space;
+
+        navigator.navigate_player(&resources, &mut player_context, Minimap::Detecting);
+
+        assert_matches!(
+            player_context.priority_action(),
+            Some(PlayerAction::Key(Key {
+                key: KeyBinding::Space,
+                position: Some(Position { x: 100, y: 200, .. }),
+                ..
+            }))
+        );
+    }
+
+    #[test]
+    fn navigate_player_stalled_hop_times_out_then_fails() {
+        let mut navigator = DefaultNavigator::default();
+        let target_path = Rc::new(RefCell::new(Path {
+            id: 2.to_string(),
+            minimap_snapshot_base64: "".into(),
+            name_snapshot_base64: "".into(),
+            minimap_snapshot_grayscale: false,
+            points: vec![],
+        }));
+        let point = Point {
+            x: 100,
+            y: 200,
+            transition: NavigationTransition::Portal,
+            next_path: Some(target_path.clone()),
+        };
+        let path = Path {
+            id: 1.to_string(),
+            minimap_snapshot_base64: "".into(),
+            name_snapshot_base64: "".into(),
+            minimap_snapshot_grayscale: false,
+            points: vec![point],
+        };
+        navigator.current_path = Some(Rc::new(RefCell::new(path)));
+        navigator.destination_path_id = Some(2.to_string());
+        navigator.path_dirty = false;
+        let resources = Resources::new(None, None);
+        let mut player_context = PlayerContext::default();
+
+        // Starts the hop
+        assert!(!navigator.navigate_player(&resources, &mut player_context, Minimap::Detecting));
+        assert!(player_context.has_priority_action());
+
+        // Stalled, retries
+        navigator.hop.as_mut().unwrap().started = Instant::now() - Duration::from_secs(61);
+        assert!(!navigator.navigate_player(&resources, &mut player_context, Minimap::Detecting));
+        assert!(!player_context.has_priority_action());
+        assert!(navigator.path_dirty);
+        assert_eq!(navigator.hop.as_ref().unwrap().retry_count, 1);
+        assert!(!navigator.take_hop_failed());
+
+        // Stalled again after retry, escalates
+        navigator.path_dirty = false;
+        navigator.hop.as_mut().unwrap().started = Instant::now() - Duration::from_secs(61);
+        assert!(navigator.navigate_player(&resources, &mut player_context, Minimap::Detecting));
+        assert!(!player_context.has_priority_action());
+        assert!(navigator.hop.is_none());
+        assert!(navigator.take_hop_failed());
+        assert!(!navigator.take_hop_failed());
+    }
+
+    #[test]
+    fn navigate_player_advances_navigation_progress() {
+        fn path_to(id: u32, next_path: Option<Rc<RefCell<Path>>>) -> Rc<RefCell<Path>> {
+            Rc::new(RefCell::new(Path {
+                id: id.to_string(),
+                minimap_snapshot_base64: "".into(),
+                name_snapshot_base64: "".into(),
+                minimap_snapshot_grayscale: false,
+                points: next_path
+                    .map(|next_path| Point {
+                        x: 100,
+                        y: 200,
+                        transition: NavigationTransition::Portal,
+                        next_path: Some(next_path),
+                    })
+                    .into_iter()
+                    .collect(),
+            }))
+        }
+
+        let mut navigator = DefaultNavigator::default();
+        let path_3 = path_to(3, None);
+        let path_2 = path_to(2, Some(path_3.clone()));
+        let path_1 = path_to(1, Some(path_2.clone()));
+        navigator.mark_dirty_with_destination(Some((3, 0)));
+        navigator.destination_path_id = Some(3.to_string());
+        navigator.current_path = Some(path_1);
+        navigator.path_dirty = false;
+        let resources = Resources::new(None, None);
+        let mut player_context = PlayerContext::default();
+
+        assert_eq!(navigator.navigation_progress(), None);
+
+        navigator.navigate_player(&resources, &mut player_context, Minimap::Detecting);
+        assert_eq!(navigator.navigation_progress(), Some((1, 2)));
+
+        // Simulate arriving at the next map
+        navigator.current_path = Some(path_2);
+        navigator.last_point_state = None;
+        player_context.take_priority_action();
+        navigator.navigate_player(&resources, &mut player_context, Minimap::Detecting);
+        assert_eq!(navigator.navigation_progress(), Some((2, 2)));
+
+        navigator.current_path = Some(path_3);
+        navigator.last_point_state = None;
+        navigator.navigate_player(&resources, &mut player_context, Minimap::Detecting);
+        assert_eq!(navigator.navigation_progress(), None);
+    }
+
     #[test]
     fn compute_next_point_unreachable_when_not_in_any_path() {
         let mut navigator = DefaultNavigator::default();
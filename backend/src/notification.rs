@@ -36,6 +36,7 @@ pub enum NotificationKind {
     PlayerStrangerAppear,
     PlayerFriendAppear,
     PlayerIsDead,
+    GameFrozen,
 }
 
 impl From<NotificationKind> for usize {
@@ -120,6 +121,7 @@ impl DiscordNotification {
             NotificationKind::PlayerFriendAppear => {
                 settings.notifications.notify_on_player_friend_appear
             }
+            NotificationKind::GameFrozen => settings.notifications.notify_on_game_frozen,
         };
         if !is_enabled {
             bail!("notification not enabled");
@@ -173,6 +175,13 @@ impl DiscordNotification {
             NotificationKind::PlayerFriendAppear => {
                 format!("{user_id}Bot has detected friend player(s)")
             }
+            NotificationKind::GameFrozen => {
+                if settings.stop_on_game_frozen {
+                    format!("{user_id}Bot stopped because the game appears to be frozen")
+                } else {
+                    format!("{user_id}The game appears to be frozen")
+                }
+            }
         };
         let frames = match kind {
             NotificationKind::FailOrMapChange => vec![(None, 2), (None, 4)],
@@ -181,6 +190,7 @@ impl DiscordNotification {
             | NotificationKind::PlayerGuildieAppear
             | NotificationKind::PlayerStrangerAppear
             | NotificationKind::PlayerFriendAppear
+            | NotificationKind::GameFrozen
             | NotificationKind::RuneAppear => vec![(None, 2)],
         };
         let delay = match kind {
@@ -190,6 +200,7 @@ impl DiscordNotification {
             | NotificationKind::PlayerGuildieAppear
             | NotificationKind::PlayerStrangerAppear
             | NotificationKind::PlayerFriendAppear
+            | NotificationKind::GameFrozen
             | NotificationKind::RuneAppear => 3,
         };
 
@@ -0,0 +1,120 @@
+use std::{
+    sync::mpsc::{Receiver, Sender, TryRecvError, channel},
+    thread,
+};
+
+/// A pipeline that processes inputs on a dedicated worker thread.
+///
+/// At most one input is processed at a time and outputs are received in the same order the inputs
+/// were submitted. Inputs submitted while the worker is busy are dropped so that the caller never
+/// blocks waiting for a slow worker.
+#[derive(Debug)]
+pub struct Pipeline<I, O> {
+    input_tx: Sender<I>,
+    output_rx: Receiver<O>,
+    in_flight: bool,
+}
+
+impl<I: Send + 'static, O: Send + 'static> Pipeline<I, O> {
+    pub fn new<F>(name: &str, mut f: F) -> Self
+    where
+        F: FnMut(I) -> O + Send + 'static,
+    {
+        let (input_tx, input_rx) = channel::<I>();
+        let (output_tx, output_rx) = channel::<O>();
+        thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                // Exits when the pipeline is dropped
+                while let Ok(input) = input_rx.recv() {
+                    if output_tx.send(f(input)).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("can spawn pipeline thread");
+
+        Self {
+            input_tx,
+            output_rx,
+            in_flight: false,
+        }
+    }
+
+    /// Submits `input` to the worker.
+    ///
+    /// Returns `false` and drops `input` if the worker is still processing the previous input.
+    pub fn submit(&mut self, input: I) -> bool {
+        if self.in_flight {
+            return false;
+        }
+
+        self.in_flight = self.input_tx.send(input).is_ok();
+        self.in_flight
+    }
+
+    /// Tries to receive the output of the last submitted input without blocking.
+    pub fn try_recv(&mut self) -> Option<O> {
+        match self.output_rx.try_recv() {
+            Ok(output) => {
+                self.in_flight = false;
+                Some(output)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.in_flight = false;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn recv_blocking(pipeline: &mut Pipeline<u32, u32>) -> u32 {
+        loop {
+            if let Some(output) = pipeline.try_recv() {
+                return output;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn pipeline_outputs_in_submission_order() {
+        let mut pipeline = Pipeline::new("test", |input: u32| {
+            thread::sleep(Duration::from_millis((5 - input % 5) as u64));
+            input * 10
+        });
+
+        let outputs = (0..10)
+            .map(|input| {
+                assert!(pipeline.submit(input));
+                recv_blocking(&mut pipeline)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(outputs, (0..10).map(|input| input * 10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn pipeline_drops_input_while_in_flight() {
+        let mut pipeline = Pipeline::new("test", |input: u32| {
+            thread::sleep(Duration::from_millis(20));
+            input
+        });
+
+        assert!(pipeline.submit(1));
+        assert!(!pipeline.submit(2));
+        assert_eq!(pipeline.try_recv(), None);
+        assert_eq!(recv_blocking(&mut pipeline), 1);
+
+        assert!(pipeline.submit(3));
+        assert_eq!(recv_blocking(&mut pipeline), 3);
+        assert_eq!(pipeline.try_recv(), None);
+    }
+}
@@ -5,10 +5,10 @@ use strum::Display;
 
 use super::{Player, PlayerContext, use_key::UseKey};
 use crate::{
-    Action, ActionKey, ActionKeyDirection, ActionKeyWith, ActionMove, FamiliarRarity, KeyBinding,
-    Position, SwappableFamiliars, array::Array, bridge::KeyKind, database::LinkKeyBinding,
-    ecs::Resources, minimap::Minimap, player::PlayerEntity, run::MS_PER_TICK, transition,
-    transition_if,
+    Action, ActionHoldPosition, ActionKey, ActionKeyDirection, ActionKeyWith, ActionMove,
+    FamiliarRarity, KeyBinding, Position, SwappableFamiliars, array::Array, bridge::KeyKind,
+    buff::BuffKind, database::LinkKeyBinding, ecs::Resources, minimap::Minimap,
+    player::PlayerEntity, run::MS_PER_TICK, transition, transition_if,
 };
 
 /// The minimum x distance required to transition to [`Player::UseKey`] in auto mob action.
@@ -32,6 +32,7 @@ pub struct Key {
     pub wait_before_use_ticks_random_range: u32,
     pub wait_after_use_ticks: u32,
     pub wait_after_use_ticks_random_range: u32,
+    pub force: bool,
 }
 
 impl From<ActionKey> for Key {
@@ -47,6 +48,7 @@ impl From<ActionKey> for Key {
             wait_before_use_millis_random_range,
             wait_after_use_millis,
             wait_after_use_millis_random_range,
+            force,
             ..
         }: ActionKey,
     ) -> Self {
@@ -69,6 +71,7 @@ impl From<ActionKey> for Key {
             wait_before_use_ticks_random_range,
             wait_after_use_ticks,
             wait_after_use_ticks_random_range,
+            force,
         }
     }
 }
@@ -80,6 +83,8 @@ impl From<ActionKey> for Key {
 pub struct Move {
     pub position: Position,
     pub wait_after_move_ticks: u32,
+    /// Maximum distance in both axes from the destination to consider the move arrived.
+    pub arrival_tolerance: i32,
 }
 
 impl From<ActionMove> for Move {
@@ -87,12 +92,14 @@ impl From<ActionMove> for Move {
         ActionMove {
             position,
             wait_after_move_millis,
+            arrival_tolerance,
             ..
         }: ActionMove,
     ) -> Self {
         Self {
             position,
             wait_after_move_ticks: (wait_after_move_millis / MS_PER_TICK) as u32,
+            arrival_tolerance: arrival_tolerance.max(0),
         }
     }
 }
@@ -155,6 +162,7 @@ pub enum PingPongDirection {
 pub struct FamiliarsSwap {
     pub swappable_slots: SwappableFamiliars,
     pub swappable_rarities: Array<FamiliarRarity, 2>,
+    pub sort_by_level: bool,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -184,6 +192,32 @@ pub struct UseBooster {
     pub kind: Booster,
 }
 
+/// Represents an action that keeps the player stationary.
+///
+/// Useful for waiting out mechanics without moving or using any key.
+#[derive(Clone, Copy, Debug)]
+pub struct HoldPosition {
+    /// Number of ticks to hold the position for.
+    pub ticks: u32,
+    /// The direction to face before holding.
+    pub direction: ActionKeyDirection,
+}
+
+impl From<ActionHoldPosition> for HoldPosition {
+    fn from(
+        ActionHoldPosition {
+            hold_millis,
+            direction,
+            ..
+        }: ActionHoldPosition,
+    ) -> Self {
+        Self {
+            ticks: (hold_millis / MS_PER_TICK) as u32,
+            direction,
+        }
+    }
+}
+
 /// Represents an action the [`Rotator`] can use.
 #[derive(Clone, Debug, Display)]
 pub enum PlayerAction {
@@ -206,6 +240,10 @@ pub enum PlayerAction {
     Chat(Chat),
     /// Use VIP or HEXA booster action.
     UseBooster(UseBooster),
+    /// Holds the player's position action.
+    HoldPosition(HoldPosition),
+    /// Waits for a buff to be present action.
+    WaitForBuff(BuffKind),
 }
 
 impl From<Action> for PlayerAction {
@@ -213,6 +251,8 @@ impl From<Action> for PlayerAction {
         match action {
             Action::Move(action) => PlayerAction::Move(action.into()),
             Action::Key(action) => PlayerAction::Key(action.into()),
+            Action::HoldPosition(action) => PlayerAction::HoldPosition(action.into()),
+            Action::WaitForBuff(action) => PlayerAction::WaitForBuff(action.buff.into()),
         }
     }
 }
@@ -220,12 +260,14 @@ impl From<Action> for PlayerAction {
 #[macro_export]
 macro_rules! transition_to_moving {
     ($player:expr, $moving:expr) => {{
+        $player.context.track_move_response(&$moving);
         $player.state = Player::Moving($moving.dest, $moving.exact, $moving.intermediates);
         return;
     }};
 
     ($player:expr, $moving:expr, $block:block) => {{
         $block
+        $player.context.track_move_response(&$moving);
         $player.state = Player::Moving($moving.dest, $moving.exact, $moving.intermediates);
         return;
     }};
@@ -235,6 +277,7 @@ macro_rules! transition_to_moving {
 macro_rules! transition_to_moving_if {
     ($player:expr, $moving:expr, $cond:expr) => {{
         if $cond {
+            $player.context.track_move_response(&$moving);
             $player.state = Player::Moving($moving.dest, $moving.exact, $moving.intermediates);
             return;
         }
@@ -243,6 +286,7 @@ macro_rules! transition_to_moving_if {
     ($player:expr, $moving:expr, $cond:expr, $block:block) => {{
         if $cond {
             $block
+            $player.context.track_move_response(&$moving);
             $player.state = Player::Moving($moving.dest, $moving.exact, $moving.intermediates);
             return;
         }
@@ -316,11 +360,11 @@ pub(super) fn update_from_ping_pong_action(
     cur_pos: Point,
 ) {
     let direction = ping_pong.direction;
-    let bound = ping_pong.bound;
-    let hit_x_bound_edge = match direction {
-        PingPongDirection::Left => cur_pos.x - bound.x <= 0,
-        PingPongDirection::Right => cur_pos.x - bound.x - bound.width >= 0,
-    };
+    let hit_x_bound_edge = ping_pong_hit_x_bound_edge(
+        ping_pong,
+        cur_pos,
+        player.context.config.ping_pong_bound_inset,
+    );
     if hit_x_bound_edge {
         transition_from_action!(player, Player::Idle);
     }
@@ -338,6 +382,20 @@ pub(super) fn update_from_ping_pong_action(
     transition!(player, moving)
 }
 
+/// Checks whether `cur_pos` has hit the x edge of [`PingPong::bound`] in the current direction.
+///
+/// The edge is moved inward by `inset` so the player turns around slightly before the exact
+/// bound. The inset is clamped to half of the bound width.
+#[inline]
+pub(super) fn ping_pong_hit_x_bound_edge(ping_pong: PingPong, cur_pos: Point, inset: u32) -> bool {
+    let bound = ping_pong.bound;
+    let inset = (inset as i32).min(bound.width / 2);
+    match ping_pong.direction {
+        PingPongDirection::Left => cur_pos.x - bound.x - inset <= 0,
+        PingPongDirection::Right => cur_pos.x - bound.x - bound.width + inset >= 0,
+    }
+}
+
 /// Checks proximity in [`PlayerAction::AutoMob`] for transitioning to [`Player::UseKey`].
 ///
 /// If `state` is [`Some`], this function will attempt to use key when auto mob is currently
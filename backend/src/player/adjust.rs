@@ -29,6 +29,9 @@ pub const ADJUSTING_SHORT_THRESHOLD: i32 = 1;
 /// Minimum x distance from the destination required to walk.
 pub const ADJUSTING_MEDIUM_THRESHOLD: i32 = 3;
 
+/// Number of ticks between each small movement key tap.
+///
+/// This is a key cadence rather than a movement timeout so it is not scaled.
 const ADJUSTING_SHORT_TIMEOUT: u32 = MOVE_TIMEOUT + 3;
 
 /// Minimium y distance required to perform a fall and then walk.
@@ -82,11 +85,12 @@ pub fn update_adjusting_state(
     };
     let context = &mut player.context;
     let cur_pos = context.last_known_pos.expect("in positional state");
+    let timeout = context.move_timeout(MOVE_TIMEOUT);
 
     let moving = adjusting.moving;
     let is_intermediate = moving.is_destination_intermediate();
 
-    match next_moving_lifecycle_with_axis(moving, cur_pos, MOVE_TIMEOUT, ChangeAxis::Both) {
+    match next_moving_lifecycle_with_axis(moving, cur_pos, timeout, ChangeAxis::Both) {
         MovingLifecycle::Started(moving) => {
             // Check to perform a fall and returns to walk
             let (x_distance, _) = moving.x_distance_direction_from(true, moving.pos);
@@ -152,6 +156,10 @@ pub fn update_adjusting_state(
                         resources.input.send_key_up(up_key);
                         resources.input.send_key_down(down_key);
                         context.last_known_direction = dir;
+                        // Samples only after walking for a while as velocity is still ramping up
+                        if moving.timeout.total >= MOVE_TIMEOUT {
+                            context.track_walk_speed();
+                        }
                     }
                     (false, true, Some((down_key, up_key, dir))) => {
                         adjusting.update_adjusting(resources, Some((up_key, down_key)));
@@ -176,7 +184,7 @@ pub fn update_adjusting_state(
                 // Exact adjusting incomplete
                 moving.completed(false).timeout_current(0)
             } else {
-                moving.timeout_current(MOVE_TIMEOUT)
+                moving.timeout_current(timeout)
             };
             player.state = Player::Adjusting(adjusting.moving(next_moving));
 
@@ -241,7 +249,7 @@ fn update_from_action(
         None
         | Some(
             PlayerAction::Key(Key {
-                with: ActionKeyWith::Stationary,
+                with: ActionKeyWith::Stationary | ActionKeyWith::Falling | ActionKeyWith::OnGround,
                 ..
             })
             | PlayerAction::SolveRune
@@ -0,0 +1,192 @@
+use opencv::core::{Point, Rect};
+
+use super::{
+    Player,
+    moving::{MOVE_TIMEOUT, Moving},
+    state::LastMovement,
+    timeout::{ChangeAxis, MovingLifecycle, next_moving_lifecycle_with_axis},
+};
+use crate::{
+    bridge::KeyKind, ecs::Resources, minimap::Minimap, player::PlayerEntity, transition,
+    transition_to_moving,
+};
+
+/// Maximum x distance from the rope center allowed to climb the rope.
+const CLIMBING_X_THRESHOLD: i32 = 2;
+
+/// Maximum y distance from the player to the rope bottom allowed to reach the rope by jumping.
+const CLIMBING_REACH_THRESHOLD: i32 = 5;
+
+/// Maximum y distance from the rope top to the destination allowed to climb off onto the
+/// destination platform.
+const CLIMBING_OFF_THRESHOLD: i32 = 5;
+
+const TIMEOUT: u32 = MOVE_TIMEOUT * 2;
+
+/// Finds a rope or ladder in `minimap_state` that can be climbed from `pos` towards `dest`.
+#[inline]
+pub fn find_climbable_rope(minimap_state: Minimap, pos: Point, dest: Point) -> Option<Rect> {
+    let Minimap::Idle(idle) = minimap_state else {
+        return None;
+    };
+
+    idle.ropes().into_iter().find(|rope| {
+        let center_x = rope.x + rope.width / 2;
+        let bottom = rope.y;
+        let top = rope.y + rope.height;
+
+        (center_x - pos.x).abs() <= CLIMBING_X_THRESHOLD
+            && (center_x - dest.x).abs() <= CLIMBING_X_THRESHOLD
+            && bottom <= pos.y + CLIMBING_REACH_THRESHOLD
+            && top > pos.y
+            && top + CLIMBING_OFF_THRESHOLD >= dest.y
+    })
+}
+
+/// Updates the [`Player::Climbing`] contextual state.
+///
+/// This state can only be transitioned via [`Player::Moving`] when the player has reached the
+/// destination x-wise and there is a rope or ladder towards the destination. It jumps onto the
+/// rope and holds the up key until the destination is reached y-wise. If the climb up interval is
+/// configured, the up key is not held but tapped at that interval instead.
+pub fn update_climbing_state(resources: &Resources, player: &mut PlayerEntity, moving: Moving) {
+    match next_moving_lifecycle_with_axis(
+        moving,
+        player.context.last_known_pos.expect("in positional state"),
+        player.context.move_timeout(TIMEOUT),
+        ChangeAxis::Vertical,
+    ) {
+        MovingLifecycle::Started(moving) => transition!(player, Player::Climbing(moving), {
+            if player.context.config.climb_up_interval_ticks > 0 {
+                resources.input.send_key(KeyKind::Up);
+            } else {
+                resources.input.send_key_down(KeyKind::Up);
+            }
+            resources.input.send_key(player.context.config.jump_key);
+            player.context.last_movement = Some(LastMovement::Climbing);
+        }),
+        MovingLifecycle::Ended(moving) => transition_to_moving!(player, moving, {
+            resources.input.send_key_up(KeyKind::Up);
+        }),
+        MovingLifecycle::Updated(mut moving) => {
+            let interval = player.context.config.climb_up_interval_ticks;
+            let (_, y_direction) = moving.y_distance_direction_from(true, moving.pos);
+            if !moving.completed && y_direction <= 0 {
+                resources.input.send_key_up(KeyKind::Up);
+                moving.completed = true;
+            } else if !moving.completed
+                && interval > 0
+                && moving.timeout.total.is_multiple_of(interval)
+            {
+                resources.input.send_key(KeyKind::Up);
+            }
+            transition!(player, Player::Climbing(moving));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use super::*;
+    use crate::{array::Array, bridge::MockInput, minimap::MinimapIdle, player::PlayerContext};
+
+    fn minimap_with_rope(rope: Rect) -> Minimap {
+        let mut ropes = Array::new();
+        ropes.push(rope);
+        let mut idle = MinimapIdle::default();
+        idle.set_ropes(ropes);
+        Minimap::Idle(idle)
+    }
+
+    #[test]
+    fn find_climbable_rope_within_range() {
+        let minimap = minimap_with_rope(Rect::new(9, 2, 1, 30));
+
+        assert!(find_climbable_rope(minimap, Point::new(10, 0), Point::new(10, 35)).is_some());
+        // Too far x-wise
+        assert!(find_climbable_rope(minimap, Point::new(20, 0), Point::new(20, 35)).is_none());
+        // Rope bottom out of reach
+        assert!(find_climbable_rope(minimap, Point::new(10, -10), Point::new(10, 35)).is_none());
+        // Rope top too far below the destination
+        assert!(find_climbable_rope(minimap, Point::new(10, 0), Point::new(10, 50)).is_none());
+    }
+
+    #[test]
+    fn update_climbing_state_started_holds_up_and_jumps() {
+        let mut player = PlayerEntity {
+            state: Player::Idle,
+            context: PlayerContext::default(),
+        };
+        player.context.last_known_pos = Some(Point::new(10, 0));
+        player.context.config.jump_key = KeyKind::Space;
+        let mut keys = MockInput::new();
+        keys.expect_send_key_down()
+            .withf(|k| *k == KeyKind::Up)
+            .once();
+        keys.expect_send_key()
+            .withf(|k| *k == KeyKind::Space)
+            .once();
+        let resources = Resources::new(Some(keys), None);
+        let moving = Moving::new(Point::new(10, 0), Point::new(10, 35), true, None);
+
+        update_climbing_state(&resources, &mut player, moving);
+
+        assert_matches!(player.state, Player::Climbing(_));
+        assert_matches!(player.context.last_movement, Some(LastMovement::Climbing));
+    }
+
+    #[test]
+    fn update_climbing_state_started_taps_up_when_interval_configured() {
+        let mut player = PlayerEntity {
+            state: Player::Idle,
+            context: PlayerContext::default(),
+        };
+        player.context.last_known_pos = Some(Point::new(10, 0));
+        player.context.config.jump_key = KeyKind::Space;
+        player.context.config.climb_up_interval_ticks = 3;
+        let mut keys = MockInput::new();
+        keys.expect_send_key_down().never();
+        keys.expect_send_key().withf(|k| *k == KeyKind::Up).once();
+        keys.expect_send_key()
+            .withf(|k| *k == KeyKind::Space)
+            .once();
+        let resources = Resources::new(Some(keys), None);
+        let moving = Moving::new(Point::new(10, 0), Point::new(10, 35), true, None);
+
+        update_climbing_state(&resources, &mut player, moving);
+
+        assert_matches!(player.state, Player::Climbing(_));
+    }
+
+    #[test]
+    fn update_climbing_state_updated_taps_up_at_configured_interval() {
+        let mut player = PlayerEntity {
+            state: Player::Idle,
+            context: PlayerContext::default(),
+        };
+        player.context.last_known_pos = Some(Point::new(10, 0));
+        player.context.config.climb_up_interval_ticks = 3;
+        let mut moving = Moving::new(Point::new(10, 0), Point::new(10, 35), true, None);
+        moving.timeout.started = true;
+        moving.timeout.total = 1;
+
+        // Not tapped before the interval
+        let mut keys = MockInput::new();
+        keys.expect_send_key().never();
+        let resources = Resources::new(Some(keys), None);
+        update_climbing_state(&resources, &mut player, moving);
+
+        // Tapped at the interval
+        let Player::Climbing(moving) = player.state else {
+            panic!("state is not climbing");
+        };
+        let mut keys = MockInput::new();
+        keys.expect_send_key().withf(|k| *k == KeyKind::Up).once();
+        let resources = Resources::new(Some(keys), None);
+        update_climbing_state(&resources, &mut player, moving);
+
+        assert_matches!(player.state, Player::Climbing(_));
+    }
+}
@@ -4,7 +4,7 @@ use opencv::core::Point;
 
 use super::{
     Key, PingPongDirection, Player, PlayerAction,
-    actions::{PingPong, update_from_auto_mob_action},
+    actions::{PingPong, ping_pong_hit_x_bound_edge, update_from_auto_mob_action},
     moving::Moving,
     timeout::{
         Lifecycle, MovingLifecycle, next_moving_lifecycle_with_axis, next_timeout_lifecycle,
@@ -48,7 +48,8 @@ const TIMEOUT_FORCED: u32 = MOVE_TIMEOUT + 3;
 /// Number of ticks to wait after a double jump.
 ///
 /// A heuristic to mostly avoid mid-air jump keys sending. The current approach of using velocity
-/// does not send much keys after double jumped, but only few are sent mid-air.
+/// does not send much keys after double jumped, but only few are sent mid-air. This is not a
+/// movement timeout so it is not scaled.
 const COOLDOWN_TIMEOUT: u32 = MOVE_TIMEOUT;
 
 /// Minimum x distance from the destination required to transition to [`Player::Grappling`].
@@ -133,11 +134,11 @@ pub fn update_double_jumping_state(
     let moving = double_jumping.moving;
     let ignore_grappling = double_jumping.forced || player.context.should_disable_grappling();
     let is_intermediate = moving.is_destination_intermediate();
-    let timeout = if double_jumping.forced {
+    let timeout = player.context.move_timeout(if double_jumping.forced {
         TIMEOUT_FORCED
     } else {
         TIMEOUT
-    };
+    });
     let axis = if double_jumping.forced {
         // This ensures it won't double jump forever when jumping towards either
         // edges of the map.
@@ -233,6 +234,13 @@ pub fn update_double_jumping_state(
                                 .teleport_key
                                 .unwrap_or(player.context.config.jump_key),
                         );
+                        if player.context.config.use_flash_jump
+                            && player.context.config.teleport_key.is_none()
+                            && let Some(key) =
+                                flash_jump_direction_key(player.context.last_known_direction)
+                        {
+                            resources.input.send_key(key);
+                        }
                     } else {
                         double_jumping.update_jump_cooldown();
                     }
@@ -244,7 +252,13 @@ pub fn update_double_jumping_state(
             }
 
             // Computes and sets initial next state first
-            player.state = next_updated_state(double_jumping, moving, x_distance, ignore_grappling);
+            player.state = next_updated_state(
+                double_jumping,
+                moving,
+                x_distance,
+                ignore_grappling,
+                player.context.move_timeout(TIMEOUT),
+            );
             update_from_action(
                 resources,
                 player,
@@ -256,11 +270,22 @@ pub fn update_double_jumping_state(
     }
 }
 
+/// Converts `direction` to the direction key tapped after the jump key for flash jump.
+#[inline]
+fn flash_jump_direction_key(direction: ActionKeyDirection) -> Option<KeyKind> {
+    match direction {
+        ActionKeyDirection::Left => Some(KeyKind::Left),
+        ActionKeyDirection::Right => Some(KeyKind::Right),
+        ActionKeyDirection::Any => None,
+    }
+}
+
 fn next_updated_state(
     double_jumping: DoubleJumping,
     moving: Moving,
     x_distance: i32,
     ignore_grappling: bool,
+    timeout: u32,
 ) -> Player {
     if !ignore_grappling && moving.completed && x_distance <= GRAPPLING_THRESHOLD {
         let (_, y_direction) = moving.y_distance_direction_from(true, moving.pos);
@@ -272,7 +297,7 @@ fn next_updated_state(
     }
 
     if moving.completed {
-        Player::DoubleJumping(double_jumping.moving(moving.timeout_current(TIMEOUT)))
+        Player::DoubleJumping(double_jumping.moving(moving.timeout_current(timeout)))
     } else {
         Player::DoubleJumping(double_jumping.moving(moving))
     }
@@ -333,7 +358,7 @@ fn update_from_action(
         None
         | Some(
             PlayerAction::Key(Key {
-                with: ActionKeyWith::Stationary,
+                with: ActionKeyWith::Stationary | ActionKeyWith::Falling | ActionKeyWith::OnGround,
                 ..
             })
             | PlayerAction::SolveRune
@@ -349,7 +374,8 @@ fn update_from_action(
 /// - Transition to [`Player::Idle`] when player hits horizontal bounds
 /// - If the player has double jumped or already flying:
 ///   - Transition to [`Player::Falling`] or [`Player::UpJumping`] with a chance to simulate vertical movement
-///   - Transition to [`Player::UseKey`] otherwise
+///   - Turn around if enough double jumps have been chained in the current direction
+///   - Transition to [`Player::UseKey`]
 #[inline]
 fn update_from_ping_pong_action(
     resources: &Resources,
@@ -359,13 +385,17 @@ fn update_from_ping_pong_action(
     double_jumped: bool,
 ) {
     let bound = ping_pong.bound;
-    let hit_x_bound_edge = match ping_pong.direction {
-        PingPongDirection::Left => cur_pos.x - bound.x <= 0,
-        PingPongDirection::Right => cur_pos.x - bound.x - bound.width >= 0,
-    };
+    let hit_x_bound_edge = ping_pong_hit_x_bound_edge(
+        ping_pong,
+        cur_pos,
+        player.context.config.ping_pong_bound_inset,
+    );
     if hit_x_bound_edge {
         transition_from_action!(player, Player::Idle);
     }
+    let should_turn = player
+        .context
+        .ping_pong_update_double_jump_chain(resources, double_jumped);
     transition_if!(!double_jumped);
 
     resources.input.send_key_up(KeyKind::Left);
@@ -387,6 +417,7 @@ fn update_from_ping_pong_action(
             .random_perlin_bool(cur_pos.x, cur_pos.y, resources.tick + 100, 0.25);
 
     if cur_pos.y < bound.y || should_upward {
+        player.context.reset_ping_pong_double_jump_chain();
         let moving = Moving::new(
             cur_pos,
             Point::new(cur_pos.x, bound.y + bound.height),
@@ -408,8 +439,30 @@ fn update_from_ping_pong_action(
             anchor: cur_pos,
             timeout_on_complete: true,
         },
-        cur_pos.y > bound_y_max || should_downward
+        cur_pos.y > bound_y_max || should_downward,
+        {
+            player.context.reset_ping_pong_double_jump_chain();
+        }
     );
+
+    let ping_pong = if should_turn {
+        let direction = match ping_pong.direction {
+            PingPongDirection::Left => PingPongDirection::Right,
+            PingPongDirection::Right => PingPongDirection::Left,
+        };
+        let ping_pong = PingPong {
+            direction,
+            ..ping_pong
+        };
+        // Keeps the same action id while only updating the direction
+        let id = player.context.normal_action_id();
+        player
+            .context
+            .set_normal_action(id, PlayerAction::PingPong(ping_pong));
+        ping_pong
+    } else {
+        ping_pong
+    };
     transition!(player, Player::UseKey(UseKey::from_ping_pong(ping_pong)));
 }
 
@@ -440,7 +493,7 @@ fn get_mage_teleport_direction(
 mod tests {
     use std::assert_matches::assert_matches;
 
-    use mockall::predicate::eq;
+    use mockall::{Sequence, predicate::eq};
     use opencv::core::{Point, Rect};
 
     use super::{update_double_jumping_state, update_from_ping_pong_action};
@@ -483,6 +536,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_double_jumping_state_updated_uses_scaled_timeout() {
+        let pos = Point::new(50, 50);
+        let moving = Moving {
+            pos,
+            dest: pos,
+            completed: true,
+            timeout: Timeout {
+                started: true,
+                current: TIMEOUT,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut player = make_player_with_state(Player::DoubleJumping(DoubleJumping::new(
+            moving, false, false,
+        )));
+        player.context.last_known_pos = Some(pos);
+        player.context.config.move_timeout_scale_percent = 200;
+        let resources = Resources::new(None, None);
+
+        update_double_jumping_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(
+            player.state,
+            Player::DoubleJumping(DoubleJumping {
+                moving: Moving {
+                    timeout: Timeout { current, .. },
+                    ..
+                },
+                ..
+            }) if current == TIMEOUT * 2
+        );
+    }
+
     #[test]
     fn update_double_jumping_state_started_transitions_to_falling_if_above_and_close() {
         let pos = Point::new(0, 10);
@@ -542,6 +630,50 @@ mod tests {
         assert_matches!(player.state, Player::DoubleJumping(_));
     }
 
+    #[test]
+    fn update_double_jumping_state_updated_flash_jump_taps_direction_after_jump() {
+        let pos = Point::new(100, 50);
+        let dest = Point::new(50, 50); // Move left
+        let moving = Moving {
+            pos,
+            dest,
+            timeout: Timeout {
+                started: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut player = make_player_with_state(Player::DoubleJumping(DoubleJumping::new(
+            moving, false, false,
+        )));
+        player.context.last_known_pos = Some(pos);
+        player.context.config.jump_key = KeyKind::Space;
+        player.context.config.use_flash_jump = true;
+        let mut sequence = Sequence::new();
+        let mut keys = MockInput::new();
+        keys.expect_send_key_down()
+            .withf(|k| matches!(k, KeyKind::Left))
+            .once()
+            .in_sequence(&mut sequence);
+        keys.expect_send_key_up()
+            .withf(|k| matches!(k, KeyKind::Right))
+            .once()
+            .in_sequence(&mut sequence);
+        keys.expect_send_key()
+            .withf(|k| matches!(k, KeyKind::Space))
+            .once()
+            .in_sequence(&mut sequence);
+        keys.expect_send_key()
+            .withf(|k| matches!(k, KeyKind::Left))
+            .once()
+            .in_sequence(&mut sequence);
+        let resources = Resources::new(Some(keys), None);
+
+        update_double_jumping_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(player.state, Player::DoubleJumping(_));
+    }
+
     #[test]
     fn update_double_jumping_state_forced_only_presses_jump() {
         let mut player = make_player_with_state(Player::DoubleJumping(DoubleJumping::new(
@@ -632,6 +764,36 @@ mod tests {
         assert_matches!(player.state, Player::Idle);
     }
 
+    #[test]
+    fn update_from_ping_pong_action_turns_around_at_inset_bound() {
+        let cur_pos = Point::new(56, 100);
+        let bound = Rect::new(20, 90, 40, 20);
+        let ping_pong = PingPong {
+            bound,
+            direction: PingPongDirection::Right,
+            ..Default::default()
+        };
+        let mut player = make_player_with_state(Player::DoubleJumping(DoubleJumping::new(
+            Moving::new(cur_pos, Point::new(70, 100), false, None),
+            false,
+            false,
+        )));
+        player
+            .context
+            .set_normal_action(None, PlayerAction::PingPong(ping_pong));
+        let resources = Resources::new(None, None);
+
+        // Not yet at the exact bound edge
+        update_from_ping_pong_action(&resources, &mut player, ping_pong, cur_pos, false);
+        assert_matches!(player.state, Player::DoubleJumping(_));
+
+        // Inside the inset edge
+        player.context.config.ping_pong_bound_inset = 4;
+        update_from_ping_pong_action(&resources, &mut player, ping_pong, cur_pos, false);
+        assert_matches!(player.state, Player::Idle);
+        assert!(!player.context.has_normal_action());
+    }
+
     #[test]
     fn update_from_ping_pong_action_before_double_jump_no_transition() {
         let cur_pos = Point::new(30, 100);
@@ -716,4 +878,108 @@ mod tests {
 
         assert_matches!(player.state, Player::Falling { .. });
     }
+
+    #[test]
+    fn update_from_ping_pong_action_turns_around_after_chained_double_jumps() {
+        let cur_pos = Point::new(30, 90);
+        let bound = Rect::new(20, 80, 40, 20);
+        let ping_pong = PingPong {
+            bound,
+            direction: PingPongDirection::Right,
+            ..Default::default()
+        };
+        let mut player = make_player_with_state(Player::DoubleJumping(DoubleJumping::new(
+            Moving::new(cur_pos, Point::new(40, 90), false, None),
+            false,
+            false,
+        )));
+        player
+            .context
+            .set_normal_action(None, PlayerAction::PingPong(ping_pong));
+        player.context.config.ping_pong_double_jump_weights = [0, 1, 0];
+        let mut keys = MockInput::new();
+        keys.expect_send_key_up();
+        let resources = Resources::new(Some(keys), None);
+
+        // First double jump keeps the direction
+        update_from_ping_pong_action(&resources, &mut player, ping_pong, cur_pos, true);
+        assert_matches!(player.state, Player::UseKey(_));
+        assert_matches!(
+            player.context.normal_action(),
+            Some(PlayerAction::PingPong(PingPong {
+                direction: PingPongDirection::Right,
+                ..
+            }))
+        );
+        assert_eq!(player.context.ping_pong_double_jump_chain, Some((1, 2)));
+
+        // Second double jump turns around and resets the chain
+        update_from_ping_pong_action(&resources, &mut player, ping_pong, cur_pos, false);
+        update_from_ping_pong_action(&resources, &mut player, ping_pong, cur_pos, true);
+        assert_matches!(player.state, Player::UseKey(_));
+        assert_matches!(
+            player.context.normal_action(),
+            Some(PlayerAction::PingPong(PingPong {
+                direction: PingPongDirection::Left,
+                ..
+            }))
+        );
+        assert_eq!(player.context.ping_pong_double_jump_chain, None);
+    }
+
+    #[test]
+    fn update_from_ping_pong_action_vertical_transition_resets_chain() {
+        let cur_pos = Point::new(30, 101);
+        let bound = Rect::new(20, 80, 40, 20);
+        let ping_pong = PingPong {
+            bound,
+            direction: PingPongDirection::Right,
+            ..Default::default()
+        };
+        let mut player = make_player_with_state(Player::DoubleJumping(DoubleJumping::new(
+            Moving::new(cur_pos, Point::new(40, 101), false, None),
+            false,
+            false,
+        )));
+        player
+            .context
+            .set_normal_action(None, PlayerAction::PingPong(ping_pong));
+        player.context.config.ping_pong_double_jump_weights = [0, 0, 1];
+        player.context.ping_pong_double_jump_chain = Some((1, 3));
+        let mut keys = MockInput::new();
+        keys.expect_send_key_up();
+        let resources = Resources::new(Some(keys), None);
+
+        update_from_ping_pong_action(&resources, &mut player, ping_pong, cur_pos, true);
+
+        assert_matches!(player.state, Player::Falling { .. });
+        assert_eq!(player.context.ping_pong_double_jump_chain, None);
+    }
+
+    #[test]
+    fn update_from_ping_pong_action_hits_bound_resets_chain() {
+        let cur_pos = Point::new(10, 100);
+        let bound = Rect::new(20, 90, 40, 20);
+        let ping_pong = PingPong {
+            bound,
+            direction: PingPongDirection::Left,
+            ..Default::default()
+        };
+        let mut player = make_player_with_state(Player::DoubleJumping(DoubleJumping::new(
+            Moving::new(cur_pos, Point::new(30, 100), false, None),
+            false,
+            false,
+        )));
+        player
+            .context
+            .set_normal_action(None, PlayerAction::PingPong(ping_pong));
+        player.context.config.ping_pong_double_jump_weights = [0, 0, 1];
+        player.context.ping_pong_double_jump_chain = Some((2, 3));
+        let resources = Resources::new(None, None);
+
+        update_from_ping_pong_action(&resources, &mut player, ping_pong, cur_pos, false);
+
+        assert_matches!(player.state, Player::Idle);
+        assert_eq!(player.context.ping_pong_double_jump_chain, None);
+    }
 }
@@ -28,6 +28,15 @@ const FALLING_TO_USE_KEY_THRESHOLD: i32 = 5;
 /// Tick to stop helding down [`KeyKind::Down`] at.
 const STOP_DOWN_KEY_TICK: u32 = 3;
 
+/// Maximum x velocity to consider the player is climbing down a rope.
+const CLIMBING_DOWN_X_VELOCITY_THRESHOLD: f32 = 0.2;
+
+/// Maximum y velocity to consider the player is climbing down a rope instead of free falling.
+const CLIMBING_DOWN_Y_VELOCITY_THRESHOLD: f32 = 1.0;
+
+/// Maximum y distance above the destination to jump off the rope when climbing down.
+const JUMP_OFF_ROPE_THRESHOLD: i32 = 2;
+
 /// Maximum number of ticks before timing out.
 const TIMEOUT: u32 = MOVE_TIMEOUT + 3;
 
@@ -45,6 +54,10 @@ const TELEPORT_FALL_THRESHOLD: i32 = 16;
 /// Before performing a drop down, it will wait for player to become stationary in case the player
 /// is already moving. Or if the player is already at destination or lower, it will returns
 /// to [`Player::Moving`].
+///
+/// If the player is climbing down a rope instead of free falling, [`KeyKind::Down`] is kept
+/// held and the player jumps off the rope toward the destination once close to the
+/// destination `y`.
 pub fn update_falling_state(
     resources: &Resources,
     player: &mut PlayerEntity,
@@ -62,7 +75,7 @@ pub fn update_falling_state(
     match next_moving_lifecycle_with_axis(
         moving,
         player.context.last_known_pos.expect("in positional state"),
-        TIMEOUT,
+        player.context.move_timeout(TIMEOUT),
         ChangeAxis::Vertical,
     ) {
         MovingLifecycle::Started(moving) => {
@@ -127,16 +140,27 @@ fn update_falling(
     anchor: Point,
     timeout_on_complete: bool,
 ) {
-    if moving.timeout.total == STOP_DOWN_KEY_TICK {
-        resources.input.send_key_up(KeyKind::Down);
-    }
     if !moving.completed {
         let y_changed = moving.pos.y - anchor.y;
         if y_changed < 0 {
             moving.completed = true;
         }
     } else if timeout_on_complete {
-        moving.timeout.current = TIMEOUT;
+        moving.timeout.current = player.context.move_timeout(TIMEOUT);
+    }
+    let is_climbing_down = moving.completed && is_climbing_down(player.context.velocity);
+    let climb_down_interval = player.context.config.climb_down_interval_ticks;
+    // The down key is also released when climbing down with an interval so that it can be tapped
+    if moving.timeout.total == STOP_DOWN_KEY_TICK && (!is_climbing_down || climb_down_interval > 0)
+    {
+        resources.input.send_key_up(KeyKind::Down);
+    }
+    if is_climbing_down
+        && climb_down_interval > 0
+        && moving.timeout.total > STOP_DOWN_KEY_TICK
+        && moving.timeout.total.is_multiple_of(climb_down_interval)
+    {
+        resources.input.send_key(KeyKind::Down);
     }
     // Sets initial next state first
     player.state = Player::Falling {
@@ -148,6 +172,22 @@ fn update_falling(
     let cur_pos = moving.pos;
     let (y_distance, y_direction) = moving.y_distance_direction_from(true, cur_pos);
     let has_teleport_key = player.context.config.teleport_key.is_some();
+
+    // Jump off the rope instead of climbing all the way down
+    if is_climbing_down && y_direction < 0 && y_distance <= JUMP_OFF_ROPE_THRESHOLD {
+        let (_, x_direction) = moving.x_distance_direction_from(false, cur_pos);
+        let key = if x_direction >= 0 {
+            KeyKind::Right
+        } else {
+            KeyKind::Left
+        };
+        transition_to_moving!(player, moving, {
+            resources.input.send_key_down(key);
+            resources.input.send_key(player.context.config.jump_key);
+            resources.input.send_key_up(key);
+            resources.input.send_key_up(KeyKind::Down);
+        });
+    }
     match next_action(&player.context) {
         Some(PlayerAction::AutoMob(mob)) => {
             // Ignore `timeout_on_complete` for auto-mobbing intermediate destination
@@ -185,9 +225,22 @@ fn update_falling(
                 !has_teleport_key && moving.completed && y_distance < FALLING_TO_USE_KEY_THRESHOLD
             )
         }
+        Some(PlayerAction::Key(
+            key @ Key {
+                with: ActionKeyWith::Falling,
+                ..
+            },
+        )) => {
+            transition_if!(
+                player,
+                Player::UseKey(UseKey::from_key(key)),
+                moving.completed && !is_climbing_down && y_direction < 0
+            )
+        }
         Some(
             PlayerAction::Key(Key {
-                with: ActionKeyWith::Stationary | ActionKeyWith::DoubleJump,
+                with:
+                    ActionKeyWith::Stationary | ActionKeyWith::DoubleJump | ActionKeyWith::OnGround,
                 ..
             })
             | PlayerAction::PingPong(_)
@@ -199,6 +252,14 @@ fn update_falling(
     }
 }
 
+/// Whether the player is climbing down a rope based on its `velocity`.
+#[inline]
+fn is_climbing_down((x_velocity, y_velocity): (f32, f32)) -> bool {
+    x_velocity <= CLIMBING_DOWN_X_VELOCITY_THRESHOLD
+        && y_velocity > 0.0
+        && y_velocity <= CLIMBING_DOWN_Y_VELOCITY_THRESHOLD
+}
+
 #[cfg(test)]
 mod tests {
     use std::assert_matches::assert_matches;
@@ -402,5 +463,129 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_falling_jumps_off_rope_near_destination_y() {
+        let mut moving = mock_moving(POS, Point::new(POS.x + 10, POS.y - 2))
+            .completed(true)
+            .timeout_started(true);
+        moving.timeout.total = STOP_DOWN_KEY_TICK + 2;
+        let mut player = mock_player_entity_with_jump(POS);
+        player.context.velocity = (0.0, 0.6);
+        player.state = Player::Falling {
+            moving,
+            anchor: Point::default(),
+            timeout_on_complete: false,
+        };
+
+        let mut keys = MockInput::new();
+        keys.expect_send_key_down().once().with(eq(KeyKind::Right));
+        keys.expect_send_key().once().with(eq(KeyKind::Space));
+        keys.expect_send_key_up().once().with(eq(KeyKind::Right));
+        keys.expect_send_key_up().once().with(eq(KeyKind::Down));
+        let resources = Resources::new(Some(keys), None);
+
+        update_falling_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(player.state, Player::Moving(_, _, _));
+    }
+
+    #[test]
+    fn update_falling_keeps_climbing_down_rope_far_from_destination_y() {
+        let mut moving = mock_moving(POS, Point::new(POS.x + 10, POS.y - 10))
+            .completed(true)
+            .timeout_started(true);
+        moving.timeout.total = STOP_DOWN_KEY_TICK - 1;
+        let mut player = mock_player_entity_with_jump(POS);
+        player.context.velocity = (0.0, 0.6);
+        player.state = Player::Falling {
+            moving,
+            anchor: Point::default(),
+            timeout_on_complete: false,
+        };
+
+        let mut keys = MockInput::new();
+        keys.expect_send_key_up().never();
+        keys.expect_send_key().never();
+        let resources = Resources::new(Some(keys), None);
+
+        update_falling_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(player.state, Player::Falling { .. });
+    }
+
+    #[test]
+    fn update_falling_releases_down_before_tapping_at_climb_down_interval() {
+        let mut moving = mock_moving(POS, Point::new(POS.x + 10, POS.y - 10))
+            .completed(true)
+            .timeout_started(true);
+        moving.timeout.total = STOP_DOWN_KEY_TICK - 1;
+        let mut player = mock_player_entity_with_jump(POS);
+        player.context.velocity = (0.0, 0.6);
+        player.context.config.climb_down_interval_ticks = 1;
+        player.state = Player::Falling {
+            moving,
+            anchor: Point::default(),
+            timeout_on_complete: false,
+        };
+
+        let mut keys = MockInput::new();
+        keys.expect_send_key_up().once().with(eq(KeyKind::Down));
+        keys.expect_send_key().never();
+        let resources = Resources::new(Some(keys), None);
+
+        update_falling_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(player.state, Player::Falling { .. });
+    }
+
+    #[test]
+    fn update_falling_taps_down_at_configured_climb_down_interval() {
+        let mut moving = mock_moving(POS, Point::new(POS.x + 10, POS.y - 10))
+            .completed(true)
+            .timeout_started(true);
+        moving.timeout.total = STOP_DOWN_KEY_TICK;
+        let mut player = mock_player_entity_with_jump(POS);
+        player.context.velocity = (0.0, 0.6);
+        player.context.config.climb_down_interval_ticks = 4;
+        player.state = Player::Falling {
+            moving,
+            anchor: Point::default(),
+            timeout_on_complete: false,
+        };
+
+        let mut keys = MockInput::new();
+        keys.expect_send_key().once().with(eq(KeyKind::Down));
+        keys.expect_send_key_up().never();
+        let resources = Resources::new(Some(keys), None);
+
+        update_falling_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(player.state, Player::Falling { .. });
+    }
+
+    #[test]
+    fn update_falling_does_not_jump_off_when_free_falling() {
+        let mut moving = mock_moving(POS, Point::new(POS.x + 10, POS.y - 2))
+            .completed(true)
+            .timeout_started(true);
+        moving.timeout.total = STOP_DOWN_KEY_TICK + 2;
+        let mut player = mock_player_entity_with_jump(POS);
+        player.context.velocity = (0.0, 3.0);
+        player.state = Player::Falling {
+            moving,
+            anchor: Point::default(),
+            timeout_on_complete: false,
+        };
+
+        let mut keys = MockInput::new();
+        keys.expect_send_key().never();
+        keys.expect_send_key_down().never();
+        let resources = Resources::new(Some(keys), None);
+
+        update_falling_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(player.state, Player::Falling { .. });
+    }
+
     // TODO: Add tests for action transitions (AutoMob, UseKey, etc.)
 }
@@ -59,6 +59,8 @@ pub struct FamiliarsSwapping {
     swappable_slots: SwappableFamiliars,
     /// Only familiars with these rarities will be considered for swapping.
     swappable_rarities: Array<FamiliarRarity, 2>,
+    /// Whether to sort the familiar cards by level before finding cards.
+    sort_by_level: bool,
     /// Mouse rest point for other operations.
     mouse_rest: Point,
 }
@@ -85,6 +87,7 @@ impl FamiliarsSwapping {
     pub fn new(
         swappable_slots: SwappableFamiliars,
         swappable_rarities: Array<FamiliarRarity, 2>,
+        sort_by_level: bool,
     ) -> Self {
         Self {
             state: State::OpenMenu(Timeout::default(), 0),
@@ -92,6 +95,7 @@ impl FamiliarsSwapping {
             cards: Array::new(),
             swappable_slots,
             swappable_rarities,
+            sort_by_level,
             mouse_rest: Point::new(50, 50),
         }
     }
@@ -255,7 +259,9 @@ fn update_free_slots(resources: &Resources, swapping: &mut FamiliarsSwapping) {
             State::FindCards(Timeout::default()),
             swapping.slots.iter().any(|slot| slot.1),
             {
-                if let Ok(bbox) = resources.detector().detect_familiar_level_button() {
+                if swapping.sort_by_level
+                    && let Ok(bbox) = resources.detector().detect_familiar_level_button()
+                {
                     // Optionally sort the familiar cards first so that the lowest-level one are on top
                     // by clicking level button
                     let (x, y) = bbox_click_point(bbox);
@@ -586,12 +592,17 @@ fn update_saving(resources: &Resources, swapping: &mut FamiliarsSwapping) {
         panic!("familiars swapping state is not saving")
     };
 
+    let retry_or_complete = if retry_count < MAX_RETRY {
+        State::Saving(Timeout::default(), retry_count + 1)
+    } else {
+        State::Completing(Timeout::default(), false)
+    };
+
     match next_timeout_lifecycle(timeout, SAVING_TIMEOUT) {
         Lifecycle::Started(timeout) => {
-            // TODO: recoverable?
             let button = try_ok_transition!(
                 swapping,
-                State::Completing(Timeout::default(), false),
+                retry_or_complete,
                 resources.detector().detect_familiar_save_button()
             );
 
@@ -600,19 +611,26 @@ fn update_saving(resources: &Resources, swapping: &mut FamiliarsSwapping) {
                 resources.input.send_mouse(x, y, MouseKind::Click);
             });
         }
-        Lifecycle::Ended => transition_if!(
-            swapping,
-            State::Saving(Timeout::default(), retry_count + 1),
-            State::Completing(Timeout::default(), false),
-            resources.detector().detect_familiar_menu_opened() && retry_count < MAX_RETRY
-        ),
+        Lifecycle::Ended => {
+            // Save only succeeded if the menu actually closed after pressing ESC
+            transition_if!(
+                swapping,
+                State::Completing(Timeout::default(), true),
+                !resources.detector().detect_familiar_menu_opened()
+            );
+            transition!(swapping, retry_or_complete);
+        }
         Lifecycle::Updated(timeout) => {
             match timeout.current {
                 PRESS_OK_AT => {
-                    if let Ok(button) = resources.detector().detect_popup_confirm_button() {
-                        let (x, y) = bbox_click_point(button);
-                        resources.input.send_mouse(x, y, MouseKind::Click);
-                    }
+                    // Save did not go through if there is no confirm popup
+                    let button = try_ok_transition!(
+                        swapping,
+                        retry_or_complete,
+                        resources.detector().detect_popup_confirm_button()
+                    );
+                    let (x, y) = bbox_click_point(button);
+                    resources.input.send_mouse(x, y, MouseKind::Click);
                 }
                 PRESS_ESC_AT => {
                     resources.input.send_key(KeyKind::Esc);
@@ -657,6 +675,7 @@ fn bbox_click_point(bbox: Rect) -> (i32, i32) {
 mod tests {
     use std::assert_matches::assert_matches;
 
+    use anyhow::anyhow;
     use mockall::predicate::{eq, function};
 
     use super::*;
@@ -665,7 +684,7 @@ mod tests {
     #[test]
     fn update_free_slots_advance_index_if_already_free() {
         let resources = Resources::new(None, None);
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
         let bbox = Default::default();
         swapping.slots.push((bbox, false));
         swapping.slots.push((bbox, true)); // Index 1 already free
@@ -676,6 +695,29 @@ mod tests {
         assert_matches!(swapping.state, State::FreeSlots(0, false));
     }
 
+    #[test]
+    fn update_free_slots_move_to_find_cards_without_sort_by_level() {
+        let mut detector = MockDetector::default();
+        detector.expect_detect_familiar_level_button().never();
+        let mut keys = MockInput::default();
+        keys.expect_send_mouse()
+            .with(
+                eq(50),
+                eq(50),
+                function(|action| matches!(action, MouseKind::Move)),
+            )
+            .once();
+        let resources = Resources::new(Some(keys), Some(detector));
+
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), false);
+        swapping.slots.push((Default::default(), true));
+        swapping.state = State::FreeSlots(0, false);
+
+        update_free_slots(&resources, &mut swapping);
+
+        assert_matches!(swapping.state, State::FindCards(_));
+    }
+
     #[test]
     fn update_free_slots_move_to_find_cards() {
         let bbox = Rect::new(10, 10, 10, 10);
@@ -694,7 +736,7 @@ mod tests {
             .once();
         let resources = Resources::new(Some(keys), Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
         let bbox_default = Default::default();
         swapping.slots.push((bbox_default, true));
         swapping.state = State::FreeSlots(0, false);
@@ -707,7 +749,7 @@ mod tests {
     #[test]
     fn update_free_slots_can_free() {
         let resources = Resources::new(None, None);
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
         let bbox = Default::default();
         swapping.slots.push((bbox, false));
         // Second slot not free but can free because of SwappableFamiliars::All
@@ -722,7 +764,7 @@ mod tests {
     #[test]
     fn update_free_slots_cannot_free() {
         let resources = Resources::new(None, None);
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::Last, Array::new());
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::Last, Array::new(), true);
         let bbox = Default::default();
         swapping.slots.push((bbox, false));
         // Second slot not free but also cannot free because of SwappableFamiliars::Last
@@ -747,7 +789,7 @@ mod tests {
             .returning(|| Ok(FamiliarLevel::Level5));
         let resources = Resources::new(Some(keys), Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
         let bbox = Default::default();
         swapping.slots.push((bbox, false));
         swapping.state = State::FreeSlot(
@@ -774,7 +816,7 @@ mod tests {
             .returning(|_| true);
         let resources = Resources::new(None, Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
         let bbox = Default::default();
         swapping.slots.push((bbox, false));
         swapping.state = State::FreeSlot(
@@ -808,7 +850,7 @@ mod tests {
             .returning(|| Ok(FamiliarLevel::Level5));
         let resources = Resources::new(Some(keys), Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
         let bbox = Default::default();
         swapping.cards.push(bbox);
         swapping.state = State::Swapping(
@@ -838,7 +880,7 @@ mod tests {
             .returning(|| Ok(FamiliarLevel::LevelOther));
         let resources = Resources::new(Some(keys), Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
         let bbox = Default::default();
         swapping.cards.push(bbox);
         swapping.state = State::Swapping(
@@ -864,7 +906,7 @@ mod tests {
             .returning(|_| true);
         let resources = Resources::new(None, Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
         let bbox = Default::default();
         swapping.cards.push(bbox);
         swapping.cards.push(bbox);
@@ -897,7 +939,7 @@ mod tests {
             .returning(|_| true);
         let resources = Resources::new(Some(keys), Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
         let bbox = Default::default();
         swapping.cards.push(bbox);
         for _ in 0..FAMILIAR_SLOTS {
@@ -928,7 +970,7 @@ mod tests {
             .returning(|| Ok(Default::default()));
         let resources = Resources::new(Some(keys), Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
         swapping.state = State::Saving(Timeout::default(), 0);
 
         update_saving(&resources, &mut swapping);
@@ -949,7 +991,7 @@ mod tests {
             .returning(|| Ok(Default::default()));
 
         let resources = Resources::new(Some(keys), Some(detector));
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
 
         swapping.state = State::Saving(
             Timeout {
@@ -974,5 +1016,124 @@ mod tests {
         assert_matches!(swapping.state, State::Saving(_, 0));
     }
 
+    #[test]
+    fn update_saving_retry_if_save_button_missing() {
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_familiar_save_button()
+            .once()
+            .returning(|| Err(anyhow!("missing")));
+        let resources = Resources::new(None, Some(detector));
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
+        swapping.state = State::Saving(Timeout::default(), 0);
+
+        update_saving(&resources, &mut swapping);
+
+        assert_matches!(
+            swapping.state,
+            State::Saving(Timeout { started: false, .. }, 1)
+        );
+    }
+
+    #[test]
+    fn update_saving_retry_if_confirm_button_missing() {
+        let mut keys = MockInput::default();
+        keys.expect_send_mouse().never();
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_popup_confirm_button()
+            .once()
+            .returning(|| Err(anyhow!("missing")));
+        let resources = Resources::new(Some(keys), Some(detector));
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
+        swapping.state = State::Saving(
+            Timeout {
+                current: 14, // PRESS_OK_AT
+                started: true,
+                ..Default::default()
+            },
+            0,
+        );
+
+        update_saving(&resources, &mut swapping);
+
+        assert_matches!(
+            swapping.state,
+            State::Saving(Timeout { started: false, .. }, 1)
+        );
+    }
+
+    #[test]
+    fn update_saving_complete_if_confirm_button_missing_after_max_retry() {
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_popup_confirm_button()
+            .once()
+            .returning(|| Err(anyhow!("missing")));
+        let resources = Resources::new(None, Some(detector));
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
+        swapping.state = State::Saving(
+            Timeout {
+                current: 14, // PRESS_OK_AT
+                started: true,
+                ..Default::default()
+            },
+            MAX_RETRY,
+        );
+
+        update_saving(&resources, &mut swapping);
+
+        assert_matches!(swapping.state, State::Completing(_, false));
+    }
+
+    #[test]
+    fn update_saving_complete_if_menu_closed() {
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_familiar_menu_opened()
+            .once()
+            .return_const(false);
+        let resources = Resources::new(None, Some(detector));
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
+        swapping.state = State::Saving(
+            Timeout {
+                current: 30, // SAVING_TIMEOUT
+                started: true,
+                ..Default::default()
+            },
+            0,
+        );
+
+        update_saving(&resources, &mut swapping);
+
+        assert_matches!(swapping.state, State::Completing(_, true));
+    }
+
+    #[test]
+    fn update_saving_retry_if_menu_still_opened() {
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_familiar_menu_opened()
+            .once()
+            .return_const(true);
+        let resources = Resources::new(None, Some(detector));
+        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new(), true);
+        swapping.state = State::Saving(
+            Timeout {
+                current: 30, // SAVING_TIMEOUT
+                started: true,
+                ..Default::default()
+            },
+            0,
+        );
+
+        update_saving(&resources, &mut swapping);
+
+        assert_matches!(
+            swapping.state,
+            State::Saving(Timeout { started: false, .. }, 1)
+        );
+    }
+
     // TODO: more tests
 }
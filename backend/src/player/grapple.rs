@@ -66,11 +66,11 @@ pub fn update_grappling_state(
         .grappling_key
         .expect("cannot transition if not set");
     let prev_pos = grappling.moving.pos;
-    let timeout = if grappling.did_y_changed {
+    let timeout = player.context.move_timeout(if grappling.did_y_changed {
         STOPPING_TIMEOUT
     } else {
         INITIAL_TIMEOUT
-    };
+    });
 
     match next_moving_lifecycle_with_axis(
         grappling.moving,
@@ -236,6 +236,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_grappling_state_updated_uses_scaled_timeout() {
+        let resources = Resources::new(None, None);
+        let mut moving = mock_moving(POS);
+        moving.timeout.started = true;
+        moving.timeout.current = INITIAL_TIMEOUT;
+        moving.dest = Point {
+            y: POS.y + 100,
+            x: POS.x,
+        };
+        let mut player = mock_player_entity_with_grapple(moving.pos);
+        player.context.config.move_timeout_scale_percent = 200;
+        player.state = Player::Grappling(Grappling::new(moving));
+
+        update_grappling_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(
+            player.state,
+            Player::Grappling(Grappling {
+                moving: Moving {
+                    timeout: Timeout { current, .. },
+                    ..
+                },
+                ..
+            }) if current == INITIAL_TIMEOUT + 1
+        );
+    }
+
     #[test]
     fn update_grappling_state_sets_did_y_changed() {
         let resources = Resources::new(None, None);
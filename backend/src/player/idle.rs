@@ -8,6 +8,7 @@ use super::{
     familiars_swap::FamiliarsSwapping,
     moving::{Moving, find_intermediate_points},
     panic::Panicking,
+    timeout::Timeout,
     use_key::UseKey,
 };
 use crate::{
@@ -105,6 +106,14 @@ fn update_from_action(resources: &Resources, player: &mut PlayerEntity, minimap_
             );
         }
 
+        Some(PlayerAction::Key(
+            key @ Key {
+                position: None,
+                force: true,
+                ..
+            },
+        )) => transition!(player, Player::UseKey(UseKey::from_key(key))),
+
         Some(PlayerAction::Key(
             key @ Key {
                 position: None,
@@ -131,7 +140,11 @@ fn update_from_action(resources: &Resources, player: &mut PlayerEntity, minimap_
         Some(PlayerAction::Key(
             key @ Key {
                 position: None,
-                with: ActionKeyWith::Any | ActionKeyWith::Stationary,
+                with:
+                    ActionKeyWith::Any
+                    | ActionKeyWith::Stationary
+                    | ActionKeyWith::Falling
+                    | ActionKeyWith::OnGround,
                 ..
             },
         )) => transition!(player, Player::UseKey(UseKey::from_key(key))),
@@ -187,7 +200,8 @@ fn update_from_action(resources: &Resources, player: &mut PlayerEntity, minimap_
             player,
             Player::FamiliarsSwapping(FamiliarsSwapping::new(
                 swapping.swappable_slots,
-                swapping.swappable_rarities
+                swapping.swappable_rarities,
+                swapping.sort_by_level
             ))
         ),
 
@@ -204,6 +218,29 @@ fn update_from_action(resources: &Resources, player: &mut PlayerEntity, minimap_
             transition!(player, Player::UsingBooster(UsingBooster::new(using.kind)))
         }
 
+        Some(PlayerAction::HoldPosition(hold)) => {
+            let key = match hold.direction {
+                ActionKeyDirection::Left => Some(KeyKind::Left),
+                ActionKeyDirection::Right => Some(KeyKind::Right),
+                ActionKeyDirection::Any => None,
+            };
+            if let Some(key) = key
+                && hold.direction != context.last_known_direction
+            {
+                resources.input.send_key(key);
+                context.last_known_direction = hold.direction;
+            }
+
+            transition!(
+                player,
+                Player::Stalling(Timeout::default(), hold.ticks.max(1))
+            )
+        }
+
+        Some(PlayerAction::WaitForBuff(kind)) => {
+            transition!(player, Player::WaitingForBuff(Timeout::default(), kind))
+        }
+
         None => (),
     }
 }
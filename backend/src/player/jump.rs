@@ -12,7 +12,7 @@ pub fn update_jumping_state(resources: &Resources, player: &mut PlayerEntity, mo
     match next_moving_lifecycle_with_axis(
         moving,
         player.context.last_known_pos.expect("in positional state"),
-        TIMEOUT,
+        player.context.move_timeout(TIMEOUT),
         ChangeAxis::Vertical,
     ) {
         MovingLifecycle::Started(moving) => transition!(player, Player::Jumping(moving), {
@@ -1,6 +1,7 @@
 use actions::next_action;
 use adjust::{Adjusting, update_adjusting_state};
 use cash_shop::{CashShop, update_cash_shop_state};
+use climb::update_climbing_state;
 use double_jump::{DoubleJumping, update_double_jumping_state};
 use fall::update_falling_state;
 use familiars_swap::{FamiliarsSwapping, update_familiars_swapping_state};
@@ -18,10 +19,11 @@ use timeout::Timeout;
 use unstuck::update_unstucking_state;
 use up_jump::{UpJumping, update_up_jumping_state};
 use use_key::{UseKey, update_use_key_state};
+use wait_buff::update_waiting_for_buff_state;
 
 use crate::{
     bridge::KeyKind,
-    buff::BuffEntities,
+    buff::{BuffEntities, BuffKind},
     database::ActionKeyDirection,
     ecs::Resources,
     minimap::{Minimap, MinimapEntity},
@@ -37,6 +39,7 @@ mod actions;
 mod adjust;
 mod cash_shop;
 mod chat;
+mod climb;
 mod double_jump;
 mod fall;
 mod familiars_swap;
@@ -53,11 +56,14 @@ mod unstuck;
 mod up_jump;
 mod use_booster;
 mod use_key;
+mod wait_buff;
 
 pub use actions::*;
 pub use {
     chat::ChattingContent, double_jump::DOUBLE_JUMP_THRESHOLD, grapple::GRAPPLING_MAX_THRESHOLD,
-    grapple::GRAPPLING_THRESHOLD, panic::Panicking, state::PlayerContext, state::Quadrant,
+    grapple::GRAPPLING_THRESHOLD, panic::Panicking, solve_rune::RUNE_COOLDOWN_TICKS,
+    solve_rune::RUNE_PRESS_KEY_INTERVAL_TICKS, state::MAX_EXTRA_POTIONS, state::PlayerContext,
+    state::Quadrant,
 };
 
 /// Minimum y distance from the destination required to perform a jump.
@@ -93,6 +99,8 @@ pub enum Player {
     Jumping(Moving),
     /// Performs an up jump action.
     UpJumping(UpJumping),
+    /// Climbs a rope or ladder.
+    Climbing(Moving),
     /// Performs a falling action.
     Falling {
         moving: Moving,
@@ -112,6 +120,8 @@ pub enum Player {
     Panicking(Panicking),
     Chatting(Chatting),
     UsingBooster(UsingBooster),
+    /// Waits for a buff to be present or until timing out.
+    WaitingForBuff(Timeout, BuffKind),
 }
 
 impl Player {
@@ -140,6 +150,7 @@ impl Player {
             }
             Player::Grappling(Grappling { moving, .. })
             | Player::Jumping(moving)
+            | Player::Climbing(moving)
             | Player::UpJumping(UpJumping { moving, .. })
             | Player::Falling {
                 moving,
@@ -155,6 +166,7 @@ impl Player {
             | Player::Chatting(_)
             | Player::Panicking(_)
             | Player::UsingBooster(_)
+            | Player::WaitingForBuff(_, _)
             | Player::Stalling(_, _) => false,
         }
     }
@@ -166,6 +178,10 @@ pub fn run_system(
     minimap: &MinimapEntity,
     buffs: &BuffEntities,
 ) {
+    if !matches!(player.state, Player::SolvingRune(_)) {
+        resources.rune_solving.release();
+    }
+
     transition_if!(
         player,
         Player::CashShopThenExit(CashShop::new()),
@@ -180,6 +196,13 @@ pub fn run_system(
         }
     );
 
+    // Waits for the map to fully load after a map transition before acting. Cash shop is exempted
+    // because minimap does not update while in cash shop.
+    transition_if!(
+        !matches!(player.state, Player::CashShopThenExit(_))
+            && matches!(minimap.state, Minimap::Idle(idle) if idle.loading)
+    );
+
     let did_update = player
         .context
         .update_state(resources, player.state, minimap.state, buffs);
@@ -195,6 +218,7 @@ pub fn run_system(
             resources,
             player,
             minimap.state,
+            buffs,
             true
         ));
 
@@ -221,7 +245,7 @@ pub fn run_system(
         player.state = Player::Idle;
     }
 
-    if !update_non_positional_state(resources, player, minimap.state, false) {
+    if !update_non_positional_state(resources, player, minimap.state, buffs, false) {
         update_positional_state(resources, player, minimap.state);
     }
 }
@@ -234,6 +258,7 @@ fn update_non_positional_state(
     resources: &Resources,
     player: &mut PlayerEntity,
     minimap_state: Minimap,
+    buffs: &BuffEntities,
     failed_to_detect_player: bool,
 ) -> bool {
     match player.state {
@@ -266,6 +291,9 @@ fn update_non_positional_state(
         }
         Player::Chatting(chatting) => update_chatting_state(resources, player, chatting),
         Player::UsingBooster(_) => update_using_booster_state(resources, player),
+        Player::WaitingForBuff(timeout, kind) => {
+            update_waiting_for_buff_state(player, buffs, timeout, kind);
+        }
         Player::Detecting
         | Player::Idle
         | Player::Moving(_, _, _)
@@ -274,6 +302,7 @@ fn update_non_positional_state(
         | Player::Grappling(_)
         | Player::Jumping(_)
         | Player::UpJumping(_)
+        | Player::Climbing(_)
         | Player::Falling {
             moving: _,
             anchor: _,
@@ -300,6 +329,7 @@ fn update_positional_state(
         Player::Grappling(_) => update_grappling_state(resources, player, minimap_state),
         Player::UpJumping(_) => update_up_jumping_state(resources, player, minimap_state),
         Player::Jumping(moving) => update_jumping_state(resources, player, moving),
+        Player::Climbing(moving) => update_climbing_state(resources, player, moving),
         Player::Falling { .. } => update_falling_state(resources, player, minimap_state),
         Player::UseKey(_)
         | Player::Unstucking(_, _)
@@ -309,6 +339,53 @@ fn update_positional_state(
         | Player::Panicking(_)
         | Player::Chatting(_)
         | Player::UsingBooster(_)
+        | Player::WaitingForBuff(_, _)
         | Player::CashShopThenExit(_) => unreachable!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::{
+        buff::{Buff, BuffContext, BuffEntity},
+        minimap::{MinimapContext, MinimapIdle},
+    };
+
+    #[test]
+    fn run_system_waits_until_map_loaded() {
+        let resources = Resources::new(None, None);
+        let buffs: BuffEntities = BuffKind::iter()
+            .map(|kind| BuffEntity {
+                state: Buff::No,
+                context: BuffContext::new(kind),
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        let mut idle = MinimapIdle::default();
+        idle.loading = true;
+        let minimap = MinimapEntity {
+            state: Minimap::Idle(idle),
+            context: MinimapContext::default(),
+        };
+        let mut context = PlayerContext::default();
+        context.set_normal_action(None, PlayerAction::WaitForBuff(BuffKind::SayramElixir));
+        let mut player = PlayerEntity {
+            state: Player::Idle,
+            context,
+        };
+
+        // Neither detects nor executes the action while loading
+        for _ in 0..3 {
+            run_system(&resources, &mut player, &minimap, &buffs);
+            assert_matches!(player.state, Player::Idle);
+            assert!(player.context.has_normal_action());
+            assert!(player.context.last_known_pos.is_none());
+        }
+    }
+}
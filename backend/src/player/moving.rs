@@ -6,6 +6,7 @@ use opencv::core::Point;
 use super::{
     GRAPPLING_MAX_THRESHOLD, JUMP_THRESHOLD, Player, PlayerContext,
     actions::{Key, Move, PlayerAction},
+    climb::find_climbable_rope,
     double_jump::{DOUBLE_JUMP_THRESHOLD, DoubleJumping},
     state::LastMovement,
     timeout::Timeout,
@@ -36,6 +37,12 @@ pub const MOVE_TIMEOUT: u32 = 5;
 const JUMPABLE_RANGE: Range<i32> = 4..JUMP_THRESHOLD;
 const UP_JUMP_THRESHOLD: i32 = 10;
 
+/// Maximum number of teleport hops before the destination.
+const MAX_TELEPORT_HOPS: usize = 15;
+
+/// Maximum y distance between the player and a platform to be considered standing on it.
+const PLATFORM_EDGE_Y_THRESHOLD: i32 = 2;
+
 /// Intermediate points to move by.
 ///
 /// The last point is the destination.
@@ -94,6 +101,10 @@ pub struct Moving {
     ///
     /// When [`Some`], the last point is the destination.
     pub intermediates: Option<MovingIntermediates>,
+    /// Number of ticks it took for the player position to start changing.
+    ///
+    /// Set once by [`next_moving_lifecycle_with_axis`] on the first position change.
+    pub response_ticks: Option<u32>,
 }
 
 /// Convenient implementations
@@ -112,6 +123,7 @@ impl Moving {
             completed: false,
             timeout: Timeout::default(),
             intermediates,
+            response_ticks: None,
         }
     }
 
@@ -250,8 +262,9 @@ impl Moving {
 /// state looping and advancing `intermediates` when the current destination is reached.
 ///
 /// It will first transition to [`Player::DoubleJumping`] and [`Player::Adjusting`] for
-/// matching `x` of `dest`. Then, [`Player::Grappling`], [`Player::UpJumping`], [`Player::Jumping`]
-/// or [`Player::Falling`] for matching `y` of `dest`. (e.g. horizontal then vertical)
+/// matching `x` of `dest`. Then, [`Player::Climbing`], [`Player::Grappling`],
+/// [`Player::UpJumping`], [`Player::Jumping`] or [`Player::Falling`] for matching `y` of `dest`.
+/// (e.g. horizontal then vertical)
 ///
 /// In auto mob or intermediate destination, most of the movement thresholds are relaxed for
 /// more fluid movement.
@@ -273,15 +286,47 @@ pub fn update_moving_state(
     );
 
     let cur_pos = context.last_known_pos.unwrap();
+    let dest = if intermediates.is_none()
+        && context.config.stop_at_platform_edge
+        && let Minimap::Idle(idle) = minimap_state
+    {
+        clamp_to_platform_edge(idle.platforms.as_slice(), cur_pos, dest)
+    } else {
+        dest
+    };
     let moving = Moving::new(cur_pos, dest, exact, intermediates);
     let is_intermediate = moving.is_destination_intermediate();
-    let skip_destination = moving.auto_mob_can_skip_current_destination(context);
 
     let (x_distance, _) = moving.x_distance_direction_from(true, cur_pos);
     let (y_distance, y_direction) = moving.y_distance_direction_from(true, cur_pos);
 
+    let within_arrival_tolerance = !is_intermediate
+        && matches!(
+            next_action(context),
+            Some(PlayerAction::Move(Move { arrival_tolerance, .. }))
+                if arrival_tolerance > 0
+                    && x_distance <= arrival_tolerance
+                    && y_distance <= arrival_tolerance
+        );
+    let skip_destination =
+        within_arrival_tolerance || moving.auto_mob_can_skip_current_destination(context);
+
     let disable_double_jumping = context.config.disable_double_jumping;
     let disable_adjusting = context.config.disable_adjusting;
+    let teleport_range = context.config.teleport_range;
+
+    // Check to break long horizontal teleport into hops
+    if !skip_destination
+        && !disable_double_jumping
+        && intermediates.is_none()
+        && context.config.teleport_key.is_some()
+        && teleport_range > 0
+        && x_distance > teleport_range
+    {
+        let mut intermediates = find_teleport_hop_points(cur_pos, dest, exact, teleport_range);
+        let (dest, exact) = intermediates.next().expect("has at least one point");
+        transition!(player, Player::Moving(dest, exact, Some(intermediates)));
+    }
 
     // Check to double jump
     if !skip_destination
@@ -312,6 +357,15 @@ pub fn update_moving_state(
         );
     }
 
+    // Check to climb and prefer it over grappling and up jumping when a rope is nearby
+    if !skip_destination
+        && y_direction > 0
+        && y_distance >= UP_JUMP_THRESHOLD
+        && find_climbable_rope(minimap_state, cur_pos, dest).is_some()
+    {
+        return abort_action_on_state_repeat(player, Player::Climbing(moving), minimap_state);
+    }
+
     // Check to grapple
     let has_teleport_key = context.config.teleport_key.is_some();
     if !skip_destination
@@ -403,6 +457,35 @@ pub fn update_moving_state(
     update_from_action(player, moving);
 }
 
+/// Clamps `dest` x to the edge of the platform the player at `pos` is standing on.
+///
+/// This prevents walking or double jumping off the platform edge when `dest` is on the same level
+/// as the player but there is no platform beyond the edge to land on. `dest` is returned as-is
+/// if the player is not on any of the `platforms`.
+fn clamp_to_platform_edge(platforms: &[PlatformWithNeighbors], pos: Point, dest: Point) -> Point {
+    if (dest.y - pos.y).abs() > PLATFORM_EDGE_Y_THRESHOLD {
+        return dest;
+    }
+
+    let is_on = |platform: &PlatformWithNeighbors, point: Point| {
+        platform.xs().contains(&point.x)
+            && (platform.y() - point.y).abs() <= PLATFORM_EDGE_Y_THRESHOLD
+    };
+    let Some(platform) = platforms.iter().find(|platform| is_on(platform, pos)) else {
+        return dest;
+    };
+    if platforms.iter().any(|platform| is_on(platform, dest)) {
+        return dest;
+    }
+
+    let xs = platform.xs();
+    let x = dest.x.clamp(xs.start, xs.end - 1);
+    if x != dest.x {
+        debug!(target: "player", "clamped destination {dest:?} to platform edge x {x}");
+    }
+    Point::new(x, dest.y)
+}
+
 /// Aborts the action when state starts looping.
 ///
 /// Note: Initially, this is only intended for auto mobbing until rune pathing is added...
@@ -457,7 +540,11 @@ fn update_from_action(player: &mut PlayerEntity, moving: Moving) {
 
         Some(PlayerAction::Key(
             key @ Key {
-                with: ActionKeyWith::Any | ActionKeyWith::Stationary,
+                with:
+                    ActionKeyWith::Any
+                    | ActionKeyWith::Stationary
+                    | ActionKeyWith::Falling
+                    | ActionKeyWith::OnGround,
                 ..
             },
         )) => transition!(player, Player::UseKey(UseKey::from_key(key))),
@@ -477,7 +564,9 @@ fn update_from_action(player: &mut PlayerEntity, moving: Moving) {
             PlayerAction::Chat(_)
             | PlayerAction::Panic(_)
             | PlayerAction::FamiliarsSwap(_)
-            | PlayerAction::UseBooster(_),
+            | PlayerAction::UseBooster(_)
+            | PlayerAction::HoldPosition(_)
+            | PlayerAction::WaitForBuff(_),
         ) => {
             panic!("unhandled action {action:?}")
         }
@@ -521,14 +610,41 @@ pub fn find_intermediate_points(
     })
 }
 
+/// Breaks the horizontal distance from `cur_pos` to `dest` into points `range` apart.
+///
+/// The last point is `dest`.
+#[inline]
+fn find_teleport_hop_points(
+    cur_pos: Point,
+    dest: Point,
+    exact: bool,
+    range: i32,
+) -> MovingIntermediates {
+    let mut inner = Array::new();
+    let direction = (dest.x - cur_pos.x).signum();
+    let mut x = cur_pos.x + direction * range;
+    while (dest.x - x) * direction > 0 && inner.len() < MAX_TELEPORT_HOPS {
+        inner.push((Point::new(x, cur_pos.y), MovementHint::Infer, false));
+        x += direction * range;
+    }
+    inner.push((dest, MovementHint::Infer, exact));
+
+    MovingIntermediates { current: 0, inner }
+}
+
 #[cfg(test)]
 mod tests {
     use std::assert_matches::assert_matches;
 
-    use opencv::core::Point;
+    use opencv::core::{Point, Rect};
 
     use super::*;
-    use crate::ecs::Resources;
+    use crate::{
+        Position,
+        ecs::Resources,
+        minimap::MinimapIdle,
+        pathing::{Platform, find_neighbors},
+    };
 
     fn setup_player(pos: Point, state: Player) -> PlayerEntity {
         let mut player = PlayerEntity {
@@ -539,6 +655,40 @@ mod tests {
         player
     }
 
+    fn move_action(arrival_tolerance: i32) -> PlayerAction {
+        PlayerAction::Move(Move {
+            position: Position::default(),
+            wait_after_move_ticks: 0,
+            arrival_tolerance,
+        })
+    }
+
+    #[test]
+    fn update_moving_arrived_within_arrival_tolerance() {
+        let resources = Resources::new(None, None);
+        let dest = Point::new(3, 2);
+        let mut player = setup_player(Point::new(0, 0), Player::Moving(dest, true, None));
+        player.context.set_priority_action(None, move_action(3));
+
+        update_moving_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(player.state, Player::Idle);
+        assert!(!player.context.has_priority_action());
+    }
+
+    #[test]
+    fn update_moving_not_arrived_outside_arrival_tolerance() {
+        let resources = Resources::new(None, None);
+        let dest = Point::new(3, 2);
+        let mut player = setup_player(Point::new(0, 0), Player::Moving(dest, true, None));
+        player.context.set_priority_action(None, move_action(2));
+
+        update_moving_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(player.state, Player::Adjusting(_));
+        assert!(player.context.has_priority_action());
+    }
+
     #[test]
     fn update_moving_to_double_jump() {
         let resources = Resources::new(None, None);
@@ -550,6 +700,48 @@ mod tests {
         assert_matches!(player.state, Player::DoubleJumping(_));
     }
 
+    #[test]
+    fn update_moving_double_jump_at_configured_threshold() {
+        let resources = Resources::new(None, None);
+        let mut player = setup_player(
+            Point::new(0, 0),
+            Player::Moving(Point::new(39, 0), false, None),
+        );
+        player.context.config.double_jump_threshold = 40;
+
+        update_moving_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(player.state, Player::Adjusting(_));
+
+        player.state = Player::Moving(Point::new(40, 0), false, None);
+
+        update_moving_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(player.state, Player::DoubleJumping(_));
+    }
+
+    #[test]
+    fn update_moving_breaks_long_teleport_into_hops() {
+        let resources = Resources::new(None, None);
+        let dest = Point::new(100, 0);
+        let mut player = setup_player(Point::new(0, 0), Player::Moving(dest, true, None));
+        player.context.config.teleport_key = Some(KeyKind::Shift);
+        player.context.config.teleport_range = 30;
+
+        update_moving_state(&resources, &mut player, Minimap::Detecting);
+
+        let Player::Moving(Point { x: 30, y: 0 }, false, Some(intermediates)) = player.state else {
+            panic!("state is not moving to the first hop");
+        };
+        let points = intermediates
+            .inner()
+            .into_iter()
+            .map(|(point, _, exact)| (point.x, exact))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            points,
+            vec![(30, false), (60, false), (90, false), (100, true)]
+        );
+    }
+
     #[test]
     fn update_moving_to_adjusting() {
         let resources = Resources::new(None, None);
@@ -586,6 +778,76 @@ mod tests {
         assert_matches!(player.state, Player::UpJumping(_));
     }
 
+    #[test]
+    fn update_moving_to_climbing_with_nearby_rope() {
+        let resources = Resources::new(None, None);
+        let dest = Point::new(0, 20);
+        let mut player = setup_player(Point::new(0, 0), Player::Moving(dest, true, None));
+        let mut ropes = Array::new();
+        ropes.push(Rect::new(0, 2, 1, 20));
+        let mut idle = MinimapIdle::default();
+        idle.set_ropes(ropes);
+
+        update_moving_state(&resources, &mut player, Minimap::Idle(idle));
+
+        assert_matches!(player.state, Player::Climbing(_));
+    }
+
+    #[test]
+    fn update_moving_halts_at_platform_edge() {
+        let resources = Resources::new(None, None);
+        let mut idle = MinimapIdle::default();
+        idle.platforms = Array::from_iter(find_neighbors(
+            &[Platform::new(0..20, 5)],
+            DOUBLE_JUMP_THRESHOLD,
+            JUMP_THRESHOLD,
+            GRAPPLING_MAX_THRESHOLD,
+        ));
+        let dest = Point::new(40, 5); // Beyond the platform edge
+        let mut player = setup_player(Point::new(10, 5), Player::Moving(dest, false, None));
+        player.context.config.stop_at_platform_edge = true;
+
+        update_moving_state(&resources, &mut player, Minimap::Idle(idle));
+
+        assert_matches!(
+            player.state,
+            Player::Adjusting(Adjusting {
+                moving: Moving {
+                    dest: Point { x: 19, y: 5 },
+                    ..
+                },
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn update_moving_does_not_halt_at_platform_edge_when_disabled() {
+        let resources = Resources::new(None, None);
+        let mut idle = MinimapIdle::default();
+        idle.platforms = Array::from_iter(find_neighbors(
+            &[Platform::new(0..20, 5)],
+            DOUBLE_JUMP_THRESHOLD,
+            JUMP_THRESHOLD,
+            GRAPPLING_MAX_THRESHOLD,
+        ));
+        let dest = Point::new(40, 5);
+        let mut player = setup_player(Point::new(10, 5), Player::Moving(dest, false, None));
+
+        update_moving_state(&resources, &mut player, Minimap::Idle(idle));
+
+        assert_matches!(
+            player.state,
+            Player::DoubleJumping(DoubleJumping {
+                moving: Moving {
+                    dest: Point { x: 40, y: 5 },
+                    ..
+                },
+                ..
+            })
+        );
+    }
+
     #[test]
     fn update_moving_to_jumping() {
         let resources = Resources::new(None, None);
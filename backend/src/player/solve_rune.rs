@@ -1,3 +1,5 @@
+use log::debug;
+
 use super::{
     Player,
     actions::PlayerAction,
@@ -5,7 +7,7 @@ use super::{
 };
 use crate::{
     bridge::KeyKind,
-    detect::{ArrowsCalibrating, ArrowsState},
+    detect::{ArrowsCalibrating, ArrowsState, MIN_ARROW_CONFIDENCE},
     ecs::Resources,
     player::{PlayerContext, PlayerEntity, next_action, timeout::Timeout},
     transition, transition_from_action, transition_if, try_ok_transition,
@@ -13,6 +15,12 @@ use crate::{
 
 const MAX_RETRY_COUNT: u32 = 2;
 
+/// Default number of ticks to wait for rune cooldown before interacting with the rune again.
+pub const RUNE_COOLDOWN_TICKS: u32 = 125;
+
+/// Default number of ticks to wait between pressing each rune key.
+pub const RUNE_PRESS_KEY_INTERVAL_TICKS: u32 = 8;
+
 /// Representing the current state of rune solving.
 #[derive(Debug, Default, Clone, Copy)]
 pub enum State {
@@ -43,15 +51,24 @@ pub fn update_solving_rune_state(resources: &Resources, player: &mut PlayerEntit
         panic!("state is not solving rune");
     };
 
+    player.context.clear_retrying_rune();
+    #[cfg(debug_assertions)]
+    update_manual_keys(resources, &mut solving_rune);
+
     match solving_rune.state {
         State::Precondition => update_precondition(resources, &player.context, &mut solving_rune),
         State::FindRegion(_, _, _, _) => update_find_region(
             resources,
             &mut solving_rune,
             player.context.config.interact_key,
+            player.context.config.rune_cooldown_ticks,
         ),
         State::Solving(_, _) => update_solving(resources, &mut solving_rune),
-        State::PressKeys(_, _, _) => update_press_keys(resources, &mut solving_rune),
+        State::PressKeys(_, _, _) => update_press_keys(
+            resources,
+            &mut solving_rune,
+            player.context.config.rune_press_key_interval_ticks,
+        ),
         State::Completed => unreachable!(),
     }
 
@@ -74,6 +91,21 @@ pub fn update_solving_rune_state(resources: &Resources, player: &mut PlayerEntit
     }
 }
 
+/// Presses the manually specified keys instead if the rune arrows are currently visible.
+#[cfg(debug_assertions)]
+fn update_manual_keys(resources: &Resources, solving_rune: &mut SolvingRune) {
+    if !matches!(
+        solving_rune.state,
+        State::FindRegion(..) | State::Solving(..)
+    ) {
+        return;
+    }
+
+    if let Some(keys) = resources.debug.take_manual_rune_keys() {
+        solving_rune.state = State::PressKeys(Timeout::default(), keys, 0);
+    }
+}
+
 fn update_precondition(
     resources: &Resources,
     player_context: &PlayerContext,
@@ -81,18 +113,33 @@ fn update_precondition(
 ) {
     transition_if!(
         solving_rune,
-        State::FindRegion(ArrowsCalibrating::default(), Timeout::default(), None, 0),
+        State::FindRegion(
+            ArrowsCalibrating::new(player_context.config.rune_spin_arrows),
+            Timeout::default(),
+            None,
+            0
+        ),
         State::Precondition,
-        player_context.is_stationary && resources.input.all_keys_cleared()
+        player_context.is_stationary
+            && resources.input.all_keys_cleared()
+            && (!player_context.config.solve_rune_only_when_safe
+                || is_safe_to_solve(resources, player_context))
+            && resources.rune_solving.try_acquire()
     )
 }
 
+/// Whether the player is not being attacked.
+fn is_safe_to_solve(resources: &Resources, player_context: &PlayerContext) -> bool {
+    !player_context.is_taking_damage && !resources.detector().detect_elite_boss_bar()
+}
+
 fn update_find_region(
     resources: &Resources,
     solving_rune: &mut SolvingRune,
     interact_key: KeyKind,
+    cooldown_ticks: u32,
 ) {
-    const COOLDOWN_AND_SOLVE_TIMEOUT: u32 = 125;
+    const SOLVE_TIMEOUT: u32 = 125;
     const SOLVE_INTERVAL: u32 = 15;
 
     let State::FindRegion(calibrating, timeout, cooldown_timeout, retry_count) = solving_rune.state
@@ -100,10 +147,10 @@ fn update_find_region(
         panic!("solving rune state is not finding region")
     };
 
-    // cooldown_timeout is used to wait for rune cooldown around ~4 secs before hitting interact
-    // key again.
+    // cooldown_timeout is used to wait for rune cooldown (~4 secs by default) before hitting
+    // interact key again.
     if let Some(cooldown_timeout) = cooldown_timeout {
-        match next_timeout_lifecycle(cooldown_timeout, COOLDOWN_AND_SOLVE_TIMEOUT) {
+        match next_timeout_lifecycle(cooldown_timeout, cooldown_ticks) {
             Lifecycle::Updated(cooldown_timeout) | Lifecycle::Started(cooldown_timeout) => {
                 transition!(
                     solving_rune,
@@ -118,7 +165,7 @@ fn update_find_region(
     }
 
     debug_assert!(cooldown_timeout.is_none());
-    match next_timeout_lifecycle(timeout, COOLDOWN_AND_SOLVE_TIMEOUT) {
+    match next_timeout_lifecycle(timeout, SOLVE_TIMEOUT) {
         Lifecycle::Started(timeout) => transition!(
             solving_rune,
             State::FindRegion(calibrating, timeout, cooldown_timeout, retry_count),
@@ -129,7 +176,7 @@ fn update_find_region(
         Lifecycle::Ended => transition_if!(
             solving_rune,
             State::FindRegion(
-                ArrowsCalibrating::default(),
+                calibrating.restart(),
                 Timeout::default(),
                 Some(Timeout::default()),
                 retry_count + 1
@@ -142,7 +189,7 @@ fn update_find_region(
                 let arrows_state = try_ok_transition!(
                     solving_rune,
                     State::FindRegion(
-                        ArrowsCalibrating::default(),
+                        calibrating.restart(),
                         timeout,
                         cooldown_timeout,
                         retry_count
@@ -154,7 +201,7 @@ fn update_find_region(
                         solving_rune,
                         State::Solving(calibrating, Timeout::default())
                     ),
-                    ArrowsState::Complete(_) => unreachable!(),
+                    ArrowsState::Complete(_, _) => unreachable!(),
                 }
             }
 
@@ -186,7 +233,15 @@ fn update_solving(resources: &Resources, solving_rune: &mut SolvingRune) {
                 ArrowsState::Calibrating(calibrating) => {
                     transition!(solving_rune, State::Solving(calibrating, timeout))
                 }
-                ArrowsState::Complete(pairs) => transition!(
+                ArrowsState::Complete(pairs, confidences)
+                    if confidences
+                        .iter()
+                        .any(|confidence| *confidence < MIN_ARROW_CONFIDENCE) =>
+                {
+                    debug!(target: "rune", "retrying low confidence result {pairs:?} {confidences:?}");
+                    transition!(solving_rune, State::Solving(calibrating.restart(), timeout))
+                }
+                ArrowsState::Complete(pairs, _) => transition!(
                     solving_rune,
                     State::PressKeys(Timeout::default(), pairs.map(|(_, key)| key), 0),
                     {
@@ -201,14 +256,16 @@ fn update_solving(resources: &Resources, solving_rune: &mut SolvingRune) {
     }
 }
 
-fn update_press_keys(resources: &Resources, solving_rune: &mut SolvingRune) {
-    const PRESS_KEY_INTERVAL: u32 = 8;
-
+fn update_press_keys(
+    resources: &Resources,
+    solving_rune: &mut SolvingRune,
+    press_key_interval: u32,
+) {
     let State::PressKeys(timeout, keys, key_index) = solving_rune.state else {
         panic!("solving rune state is not pressing keys")
     };
 
-    match next_timeout_lifecycle(timeout, PRESS_KEY_INTERVAL) {
+    match next_timeout_lifecycle(timeout, press_key_interval) {
         Lifecycle::Started(timeout) => {
             transition!(solving_rune, State::PressKeys(timeout, keys, key_index), {
                 resources.input.send_key(keys[key_index]);
@@ -228,10 +285,14 @@ fn update_press_keys(resources: &Resources, solving_rune: &mut SolvingRune) {
 
 #[cfg(test)]
 mod tests {
-    use std::assert_matches::assert_matches;
+    use std::{
+        assert_matches::assert_matches,
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
 
     use anyhow::{Ok, anyhow};
-    use mockall::predicate::eq;
+    use mockall::{Sequence, predicate::eq};
     use opencv::core::Rect;
 
     use super::*;
@@ -265,6 +326,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_solving_rune_precondition_waits_for_other_instance() {
+        let mut keys = MockInput::default();
+        keys.expect_all_keys_cleared().once().returning(|| true);
+        let resources = Resources::new(Some(keys), None);
+        let other_instance = resources.rune_solving.share();
+        assert!(other_instance.try_acquire());
+
+        let mut player = PlayerEntity {
+            state: Player::SolvingRune(SolvingRune::default()),
+            context: PlayerContext::default(),
+        };
+        player.context.priority_action = Some(PlayerAction::SolveRune); // Avoid cancellation
+        player.context.is_stationary = true;
+
+        update_solving_rune_state(&resources, &mut player);
+
+        assert_matches!(
+            player.state,
+            Player::SolvingRune(SolvingRune {
+                state: State::Precondition
+            })
+        );
+        assert!(!resources.rune_solving.is_acquired());
+    }
+
+    #[test]
+    fn update_solving_rune_precondition_deferred_when_unsafe() {
+        let mut keys = MockInput::default();
+        keys.expect_all_keys_cleared().returning(|| true);
+        let mut detector = MockDetector::default();
+        detector.expect_detect_elite_boss_bar().returning(|| true);
+        let resources = Resources::new(Some(keys), Some(detector));
+
+        let mut player = PlayerEntity {
+            state: Player::SolvingRune(SolvingRune::default()),
+            context: PlayerContext::default(),
+        };
+        player.context.priority_action = Some(PlayerAction::SolveRune); // Avoid cancellation
+        player.context.is_stationary = true;
+        player.context.config.solve_rune_only_when_safe = true;
+
+        update_solving_rune_state(&resources, &mut player);
+
+        assert_matches!(
+            player.state,
+            Player::SolvingRune(SolvingRune {
+                state: State::Precondition
+            })
+        );
+        assert!(!resources.rune_solving.is_acquired());
+
+        player.context.is_taking_damage = true;
+        player.context.config.solve_rune_only_when_safe = false;
+        update_solving_rune_state(&resources, &mut player);
+
+        assert_matches!(
+            player.state,
+            Player::SolvingRune(SolvingRune {
+                state: State::FindRegion(_, _, None, 0)
+            })
+        );
+    }
+
     #[test]
     fn update_find_region_to_solving_on_calibrating() {
         let mut detector = MockDetector::default();
@@ -285,7 +410,7 @@ mod tests {
             ),
         };
 
-        update_find_region(&resources, &mut solving_rune, KeyKind::A);
+        update_find_region(&resources, &mut solving_rune, KeyKind::A, 125);
 
         assert_matches!(
             solving_rune.state,
@@ -320,7 +445,7 @@ mod tests {
             ),
         };
 
-        update_find_region(&resources, &mut solving_rune, KeyKind::A);
+        update_find_region(&resources, &mut solving_rune, KeyKind::A, 125);
 
         assert_matches!(
             solving_rune.state,
@@ -349,11 +474,38 @@ mod tests {
             ),
         };
 
-        update_find_region(&resources, &mut solving_rune, KeyKind::A);
+        update_find_region(&resources, &mut solving_rune, KeyKind::A, 125);
 
         assert_matches!(solving_rune.state, State::FindRegion(_, _, None, 1));
     }
 
+    #[test]
+    fn update_find_region_cooldown_lasts_configured_ticks() {
+        const COOLDOWN_TICKS: u32 = 200;
+
+        let resources = Resources::new(None, None);
+        let mut solving_rune = SolvingRune {
+            state: State::FindRegion(
+                ArrowsCalibrating::default(),
+                Timeout::default(),
+                Some(Timeout::default()),
+                1,
+            ),
+        };
+
+        let mut ticks = 0;
+        loop {
+            update_find_region(&resources, &mut solving_rune, KeyKind::A, COOLDOWN_TICKS);
+            if matches!(solving_rune.state, State::FindRegion(_, _, None, 1)) {
+                break;
+            }
+            ticks += 1;
+        }
+
+        // Includes the tick starting the cooldown
+        assert_eq!(ticks, COOLDOWN_TICKS + 1);
+    }
+
     #[test]
     fn update_solving_to_completed_on_error() {
         let mut detector = MockDetector::default();
@@ -413,7 +565,7 @@ mod tests {
         detector.expect_clone();
         detector
             .expect_detect_rune_arrows()
-            .return_once(move |_| Ok(ArrowsState::Complete(expected_keys)));
+            .return_once(move |_| Ok(ArrowsState::Complete(expected_keys, [1.0; 4])));
         let resources = Resources::new(None, Some(detector));
         let mut solving_rune = SolvingRune {
             state: State::Solving(
@@ -441,6 +593,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_solving_retry_on_low_confidence() {
+        let arrows = [
+            (Rect::default(), KeyKind::A),
+            (Rect::default(), KeyKind::S),
+            (Rect::default(), KeyKind::D),
+            (Rect::default(), KeyKind::F),
+        ];
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_rune_arrows()
+            .return_once(move |_| Ok(ArrowsState::Complete(arrows, [1.0, 0.85, 1.0, 1.0])));
+        let mut keys = MockInput::default();
+        keys.expect_send_key().never();
+        let resources = Resources::new(Some(keys), Some(detector));
+        let mut solving_rune = SolvingRune {
+            state: State::Solving(
+                ArrowsCalibrating::default(),
+                Timeout {
+                    started: true,
+                    ..Default::default()
+                },
+            ),
+        };
+
+        update_solving(&resources, &mut solving_rune);
+
+        assert_matches!(
+            solving_rune.state,
+            State::Solving(_, Timeout { started: true, .. })
+        );
+    }
+
+    #[test]
+    fn update_press_keys_interval_matches_configured_ticks() {
+        const PRESS_KEY_INTERVAL: u32 = 20;
+
+        let keys = [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F];
+        let tick = Rc::new(Cell::new(0));
+        let sent_ticks = Rc::new(RefCell::new(Vec::new()));
+        let mut input = MockInput::default();
+        input.expect_send_key().times(keys.len()).returning_st({
+            let tick = tick.clone();
+            let sent_ticks = sent_ticks.clone();
+            move |_| sent_ticks.borrow_mut().push(tick.get())
+        });
+        let resources = Resources::new(Some(input), None);
+        let mut solving_rune = SolvingRune {
+            state: State::PressKeys(Timeout::default(), keys, 0),
+        };
+
+        while !matches!(solving_rune.state, State::Completed) {
+            update_press_keys(&resources, &mut solving_rune, PRESS_KEY_INTERVAL);
+            tick.set(tick.get() + 1);
+        }
+
+        let sent_ticks = sent_ticks.borrow();
+        assert_eq!(sent_ticks.len(), keys.len());
+        for ticks in sent_ticks.windows(2) {
+            // Includes the ticks starting and ending each interval
+            assert_eq!(ticks[1] - ticks[0], PRESS_KEY_INTERVAL + 2);
+        }
+    }
+
     #[test]
     fn update_press_keys_to_completed_after_all_keys_sent() {
         let expected_keys = [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F];
@@ -454,7 +670,7 @@ mod tests {
             let resources = Resources::new(Some(keys), None);
 
             // Start key press
-            update_press_keys(&resources, &mut solving_rune);
+            update_press_keys(&resources, &mut solving_rune, 8);
 
             // Simulate timeout end (advance or complete)
             solving_rune.state = State::PressKeys(
@@ -466,9 +682,37 @@ mod tests {
                 expected_keys,
                 idx,
             );
-            update_press_keys(&resources, &mut solving_rune);
+            update_press_keys(&resources, &mut solving_rune, 8);
         }
 
         assert_matches!(solving_rune.state, State::Completed);
     }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn update_manual_keys_pressed_in_order() {
+        let manual_keys = [KeyKind::Up, KeyKind::Left, KeyKind::Down, KeyKind::Right];
+        let mut sequence = Sequence::new();
+        let mut keys = MockInput::default();
+        for key in manual_keys {
+            keys.expect_send_key()
+                .with(eq(key))
+                .once()
+                .in_sequence(&mut sequence)
+                .return_const(());
+        }
+        let resources = Resources::new(Some(keys), None);
+        resources.debug.set_manual_rune_keys(manual_keys);
+        let mut solving_rune = SolvingRune {
+            state: State::Solving(ArrowsCalibrating::default(), Timeout::default()),
+        };
+
+        update_manual_keys(&resources, &mut solving_rune);
+        assert_matches!(solving_rune.state, State::PressKeys(_, keys, 0) if keys == manual_keys);
+        assert!(resources.debug.take_manual_rune_keys().is_none());
+
+        while !matches!(solving_rune.state, State::Completed) {
+            update_press_keys(&resources, &mut solving_rune, 8);
+        }
+    }
 }
@@ -44,10 +44,87 @@ pub fn update_stalling_state(player: &mut PlayerEntity, timeout: Timeout, max_ti
 
             transition_from_action!(player, next_state, is_terminal);
         }
-        Some(PlayerAction::PingPong(_) | PlayerAction::Key(_) | PlayerAction::Move(_)) => {
+        Some(
+            PlayerAction::PingPong(_)
+            | PlayerAction::Key(_)
+            | PlayerAction::Move(_)
+            | PlayerAction::HoldPosition(_),
+        ) => {
             transition_from_action!(player, next_state, is_terminal);
         }
         Some(PlayerAction::SolveRune) | None => transition!(player, next_state),
         Some(_) => unreachable!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::{
+        ActionKeyDirection,
+        bridge::{KeyKind, MockInput},
+        ecs::Resources,
+        minimap::Minimap,
+        player::{HoldPosition, PlayerContext, idle::update_idle_state},
+    };
+
+    fn setup_player(hold: HoldPosition) -> PlayerEntity {
+        let mut context = PlayerContext::default();
+        context.set_priority_action(None, PlayerAction::HoldPosition(hold));
+        PlayerEntity {
+            state: Player::Idle,
+            context,
+        }
+    }
+
+    #[test]
+    fn update_stalling_state_hold_position_for_ticks() {
+        let mut keys = MockInput::default();
+        keys.expect_send_key_up();
+        keys.expect_send_key().never();
+        let resources = Resources::new(Some(keys), None);
+        let mut player = setup_player(HoldPosition {
+            ticks: 5,
+            direction: ActionKeyDirection::Any,
+        });
+
+        update_idle_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(player.state, Player::Stalling(_, 5));
+
+        let mut ticks = 0;
+        while let Player::Stalling(timeout, max_timeout) = player.state {
+            assert!(player.context.has_priority_action());
+            update_stalling_state(&mut player, timeout, max_timeout);
+            ticks += 1;
+        }
+
+        // One tick to start, five ticks to hold and one tick to end
+        assert_eq!(ticks, 7);
+        assert_matches!(player.state, Player::Idle);
+        assert!(!player.context.has_priority_action());
+    }
+
+    #[test]
+    fn update_idle_state_hold_position_changes_direction() {
+        let mut keys = MockInput::default();
+        keys.expect_send_key_up();
+        keys.expect_send_key().with(eq(KeyKind::Left)).once();
+        let resources = Resources::new(Some(keys), None);
+        let mut player = setup_player(HoldPosition {
+            ticks: 5,
+            direction: ActionKeyDirection::Left,
+        });
+
+        update_idle_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(player.state, Player::Stalling(_, 5));
+        assert_eq!(
+            player.context.last_known_direction,
+            ActionKeyDirection::Left
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, range::Range};
+use std::{cmp::Ordering, collections::HashMap, range::Range};
 
 use anyhow::Result;
 use log::debug;
@@ -8,13 +8,18 @@ use super::{
     DOUBLE_JUMP_THRESHOLD, JUMP_THRESHOLD, MOVE_TIMEOUT, Player, PlayerAction,
     double_jump::DOUBLE_JUMP_AUTO_MOB_THRESHOLD,
     fall::FALLING_THRESHOLD,
+    moving::Moving,
+    solve_rune::{RUNE_COOLDOWN_TICKS, RUNE_PRESS_KEY_INTERVAL_TICKS},
     timeout::{Lifecycle, Timeout, next_timeout_lifecycle},
+    up_jump::SPAM_DELAY,
+    use_key::CHANGE_DIRECTION_TIMEOUT,
 };
 use crate::{
     ActionKeyDirection, Class,
     array::Array,
     bridge::{KeyKind, MouseKind},
     buff::{Buff, BuffEntities, BuffKind},
+    detect::SpinArrowsConfig,
     ecs::Resources,
     minimap::Minimap,
     notification::NotificationKind,
@@ -22,6 +27,7 @@ use crate::{
     task::{Task, Update, update_detection_task},
 };
 
+/// Default number of ticks the player position must stay unchanged to be considered stationary.
 const STATIONARY_TIMEOUT: u32 = MOVE_TIMEOUT + 1;
 
 /// The maximum number of times rune solving can fail before transition to
@@ -32,6 +38,9 @@ const MAX_RUNE_FAILED_COUNT: u32 = 8;
 /// usable anymore (e.g. 10 times limit reached).
 const MAX_VIP_BOOSTER_FAILED_COUNT: u32 = 5;
 
+/// The maximum number of extra potions that can be used when health is below a percentage.
+pub const MAX_EXTRA_POTIONS: usize = 8;
+
 /// The maximum number of times horizontal movement can be repeated in non-auto-mobbing action.
 const HORIZONTAL_MOVEMENT_REPEAT_COUNT: u32 = 20;
 
@@ -50,6 +59,14 @@ const AUTO_MOB_REACHABLE_Y_SOLIDIFY_COUNT: u32 = 4;
 /// will be ignored.
 const AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT: u32 = 3;
 
+/// The count of an ignored x range populated from platform gaps.
+///
+/// These ranges are known to be unreachable so they never decay.
+const AUTO_MOB_IGNORE_XS_PLATFORM_COUNT: u32 = u32::MAX;
+
+/// Maximum number of double jumps that can be chained before using key in ping pong.
+const PING_PONG_MAX_CHAINED_DOUBLE_JUMPS: usize = 3;
+
 /// The range an ignored auto-mob x position spans.
 ///
 /// If an auto-mob x position is 5, then the range is [2, 8].
@@ -74,9 +91,37 @@ const UNSTUCK_COUNT_THRESHOLD: u32 = 6;
 /// The number of times [`Player::Unstucking`] can be transitioned to before entering GAMBA MODE.
 const UNSTUCK_GAMBA_MODE_COUNT: u32 = 3;
 
+/// The maximum number of consecutive times [`Player::UpJumping`] can time out without completing
+/// before transition to [`Player::Unstucking`].
+const MAX_UP_JUMP_FAILED_COUNT: u32 = 3;
+
 /// The number of samples to store for approximating velocity.
 const VELOCITY_SAMPLES: usize = MOVE_TIMEOUT as usize;
 
+/// Number of movement response samples to keep for adapting movement timeouts.
+const MOVE_RESPONSE_SAMPLES: usize = 10;
+
+/// Number of ticks a movement response can take on average before timeouts are extended.
+const MOVE_RESPONSE_MARGIN: u32 = MOVE_TIMEOUT - 2;
+
+/// Maximum number of ticks movement timeouts can be extended by.
+const MAX_MOVE_TIMEOUT_EXTENSION: u32 = MOVE_TIMEOUT;
+
+/// Number of walking speed samples required to calibrate movement on the current map.
+const WALK_SPEED_SAMPLES: usize = 10;
+
+/// Approximate walking `x` velocity the default movement thresholds are tuned for.
+const BASELINE_WALK_SPEED: f32 = 0.8;
+
+/// Minimum `x` velocity to be sampled as walking instead of starting or stopping to walk.
+const MIN_WALK_SPEED: f32 = 0.2;
+
+/// Minimum percentage the movement thresholds can be scaled to after calibration.
+const MIN_MOVEMENT_SCALE_PERCENT: u32 = 50;
+
+/// Maximum percentage the movement thresholds can be scaled to after calibration.
+const MAX_MOVEMENT_SCALE_PERCENT: u32 = 200;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Quadrant {
     TopLeft,
@@ -105,6 +150,7 @@ pub enum LastMovement {
     Grappling,
     UpJumping,
     Jumping,
+    Climbing,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -120,8 +166,31 @@ pub struct PlayerConfiguration {
     ///
     /// This also means the up jump can be performed mid-air.
     pub up_jump_specific_key_should_jump: bool,
+    /// Number of ticks to wait before re-sending the jump key in [`Player::UpJumping`].
+    pub up_jump_spam_delay: u32,
+    /// Number of ticks between each up key tap in [`Player::Climbing`].
+    ///
+    /// Zero means the up key is held until the destination is reached.
+    pub climb_up_interval_ticks: u32,
+    /// Number of ticks between each down key tap when climbing down a rope in
+    /// [`Player::Falling`].
+    ///
+    /// Zero means the down key is held.
+    pub climb_down_interval_ticks: u32,
+    /// Whether [`Player::DoubleJumping`] taps the direction key after each jump key.
+    ///
+    /// Some classes use flash jump (jump + direction) instead of double jump for fast horizontal
+    /// movement.
+    pub use_flash_jump: bool,
+    /// Whether [`Player::UseKey`] verifies the player facing direction against the detected
+    /// movement direction before using a directional key.
+    ///
+    /// Without verification, [`PlayerContext::last_known_direction`] is trusted.
+    pub verify_direction_by_detection: bool,
     /// Whether to disable [`Player::DoubleJumping`].
     pub disable_double_jumping: bool,
+    /// Minimum x distance to double jump instead of walking.
+    pub double_jump_threshold: i32,
     /// Whether to disable [`Player::Adjusting`].
     pub disable_adjusting: bool,
     /// Whether to disable teleportation in [`Player::Falling`].
@@ -142,6 +211,23 @@ pub struct PlayerConfiguration {
     pub auto_mob_platforms_bound: bool,
     pub auto_mob_use_key_when_pathing: bool,
     pub auto_mob_use_key_when_pathing_update_millis: u64,
+    /// Percentage of the auto mobbing bound half width the player can stray from the bound
+    /// center before moving back to the center, zero means disabled.
+    pub auto_mob_recenter_percent: u32,
+    /// Minimum milliseconds between each auto mobbing key use, zero means no limit.
+    pub auto_mob_attack_interval_millis: u64,
+    /// Prefers auto mobbing targets on higher platforms.
+    pub auto_mob_prefer_higher_platforms: bool,
+    /// Number of ticks for the abort count of an ignored auto mobbing x range to halve, zero
+    /// means ignored x ranges never decay.
+    pub auto_mob_ignore_xs_half_life_ticks: u32,
+    /// Number of pixels to inset the ping pong bound horizontal edges by.
+    pub ping_pong_bound_inset: u32,
+    /// Weights for chaining one to [`PING_PONG_MAX_CHAINED_DOUBLE_JUMPS`] double jumps before
+    /// turning around in ping pong.
+    ///
+    /// If all weights are zero, the player only turns around at the bound edges.
+    pub ping_pong_double_jump_weights: [u32; PING_PONG_MAX_CHAINED_DOUBLE_JUMPS],
 
     /// The interact key.
     pub interact_key: KeyKind,
@@ -149,6 +235,13 @@ pub struct PlayerConfiguration {
     pub grappling_key: Option<KeyKind>,
     /// The teleport key with [`None`] indicating double jump.
     pub teleport_key: Option<KeyKind>,
+    /// Horizontal distance of each teleport hop when [`Self::teleport_key`] is set.
+    ///
+    /// When non-zero, long horizontal movements are broken into intermediate points of this
+    /// distance apart.
+    pub teleport_range: i32,
+    /// Number of ticks the player position must stay unchanged to be considered stationary.
+    pub stationary_ticks: u32,
     /// The jump key.
     ///
     /// Replaces the previously default [`KeyKind::Space`] key.
@@ -167,10 +260,64 @@ pub struct PlayerConfiguration {
     pub potion_key: KeyKind,
     /// Uses potion when health is below a percentage.
     pub use_potion_below_percent: Option<f32>,
+    /// Extra potion keys each used when health is below its percentage.
+    pub extra_potions_below_percent: Array<(KeyKind, f32), MAX_EXTRA_POTIONS>,
     /// Milliseconds interval to update current health.
     pub update_health_millis: Option<u64>,
     /// VIP Booster key.
     pub vip_booster_key: KeyKind,
+
+    /// The spinning arrows calibration parameters when solving rune.
+    pub rune_spin_arrows: SpinArrowsConfig,
+    /// Number of ticks to wait for rune cooldown before interacting with the rune again.
+    pub rune_cooldown_ticks: u32,
+    /// Number of ticks to wait between pressing each rune key.
+    pub rune_press_key_interval_ticks: u32,
+    /// Whether to defer solving rune while the player is unsafe.
+    ///
+    /// The player is unsafe when an elite boss appears or the health is decreasing.
+    pub solve_rune_only_when_safe: bool,
+    /// Extra margin around each portal bounding box in which up jumping is aborted.
+    pub portal_avoidance_margin: u32,
+    /// Disables aborting up jump when the player is near a portal.
+    pub disable_portal_upjump_abort: bool,
+    /// Stops horizontal movement at the current platform edge when the destination is not on any
+    /// platform.
+    pub stop_at_platform_edge: bool,
+    /// Percentage of [`BASELINE_WALK_SPEED`] the player walks at on the current map.
+    ///
+    /// This is [`None`] until calibrated by [`PlayerContext::track_walk_speed`] or loaded from the
+    /// map.
+    pub movement_scale_percent: Option<u32>,
+    /// Percentage to scale movement timeouts derived from [`MOVE_TIMEOUT`] by.
+    ///
+    /// Higher values give more ticks for a movement to register on high-latency connections.
+    pub move_timeout_scale_percent: u32,
+    /// Number of ticks to wait for a direction change to register before using a key.
+    pub change_direction_timeout_ticks: u32,
+}
+
+impl PlayerConfiguration {
+    /// Scales a movement `timeout` by [`Self::move_timeout_scale_percent`].
+    ///
+    /// The scaled timeout is at least one tick.
+    #[inline]
+    pub fn scaled_move_timeout(&self, timeout: u32) -> u32 {
+        (timeout * self.move_timeout_scale_percent)
+            .div_ceil(100)
+            .max(1)
+    }
+
+    /// Sets [`Self::movement_scale_percent`] clamped between [`MIN_MOVEMENT_SCALE_PERCENT`] and
+    /// [`MAX_MOVEMENT_SCALE_PERCENT`].
+    ///
+    /// The percentage can come from the database, so it must never be zero as it is used as a
+    /// divisor.
+    #[inline]
+    pub fn set_movement_scale_percent(&mut self, percent: Option<u32>) {
+        self.movement_scale_percent = percent
+            .map(|percent| percent.clamp(MIN_MOVEMENT_SCALE_PERCENT, MAX_MOVEMENT_SCALE_PERCENT));
+    }
 }
 
 impl Default for PlayerConfiguration {
@@ -178,10 +325,16 @@ impl Default for PlayerConfiguration {
         Self {
             class: Class::default(),
             disable_double_jumping: false,
+            double_jump_threshold: DOUBLE_JUMP_THRESHOLD,
             disable_adjusting: false,
             disable_teleport_on_fall: false,
             up_jump_is_flight: false,
             up_jump_specific_key_should_jump: false,
+            up_jump_spam_delay: SPAM_DELAY,
+            climb_up_interval_ticks: 0,
+            climb_down_interval_ticks: 0,
+            use_flash_jump: false,
+            verify_direction_by_detection: false,
             rune_platforms_pathing: false,
             rune_platforms_pathing_up_jump_only: false,
             auto_mob_platforms_pathing: false,
@@ -189,9 +342,17 @@ impl Default for PlayerConfiguration {
             auto_mob_platforms_bound: false,
             auto_mob_use_key_when_pathing: false,
             auto_mob_use_key_when_pathing_update_millis: 0,
+            auto_mob_recenter_percent: 0,
+            auto_mob_attack_interval_millis: 0,
+            auto_mob_ignore_xs_half_life_ticks: 0,
+            ping_pong_bound_inset: 0,
+            ping_pong_double_jump_weights: [0; PING_PONG_MAX_CHAINED_DOUBLE_JUMPS],
+            auto_mob_prefer_higher_platforms: false,
             interact_key: KeyKind::A,
             grappling_key: None,
             teleport_key: None,
+            teleport_range: 0,
+            stationary_ticks: STATIONARY_TIMEOUT,
             jump_key: KeyKind::A,
             up_jump_key: None,
             cash_shop_key: None,
@@ -200,8 +361,19 @@ impl Default for PlayerConfiguration {
             change_channel_key: None,
             potion_key: KeyKind::A,
             use_potion_below_percent: None,
+            extra_potions_below_percent: Array::new(),
             update_health_millis: None,
             vip_booster_key: KeyKind::A,
+            rune_spin_arrows: SpinArrowsConfig::default(),
+            rune_cooldown_ticks: RUNE_COOLDOWN_TICKS,
+            rune_press_key_interval_ticks: RUNE_PRESS_KEY_INTERVAL_TICKS,
+            solve_rune_only_when_safe: false,
+            portal_avoidance_margin: 0,
+            disable_portal_upjump_abort: false,
+            stop_at_platform_edge: false,
+            movement_scale_percent: None,
+            move_timeout_scale_percent: 100,
+            change_direction_timeout_ticks: CHANGE_DIRECTION_TIMEOUT,
         }
     }
 }
@@ -228,6 +400,8 @@ pub struct PlayerContext {
 
     /// The player current health and max health.
     health: Option<(u32, u32)>,
+    /// Whether the current health decreased since the last health update.
+    pub(super) is_taking_damage: bool,
     /// The task to update health.
     health_task: Option<Task<Result<(u32, u32)>>>,
     /// The rectangular health bar region.
@@ -240,6 +414,13 @@ pub struct PlayerContext {
     is_stationary_timeout: Timeout,
     /// Whether the player is stationary.
     pub(super) is_stationary: bool,
+    /// Track if the player moved vertically within a specified ticks to determine if the player
+    /// is on the ground.
+    is_grounded_timeout: Timeout,
+    /// Whether the player is on the ground.
+    ///
+    /// Unlike [`Self::is_stationary`], the player can still be moving horizontally.
+    pub(super) is_grounded: bool,
 
     /// Whether the player is dead.
     is_dead: bool,
@@ -250,6 +431,10 @@ pub struct PlayerContext {
 
     /// Approximates the player direction for using key.
     pub(super) last_known_direction: ActionKeyDirection,
+    /// The horizontal direction of the latest detected position change.
+    ///
+    /// Resets to [`ActionKeyDirection::Any`] when [`Player::UseKey`] changes direction.
+    pub(super) last_movement_direction: ActionKeyDirection,
     /// Tracks last destination points for displaying to UI.
     ///
     /// Resets when all destinations are reached or in [`Player::Idle`].
@@ -288,6 +473,8 @@ pub struct PlayerContext {
     ///
     /// This will help auto-mobbing ignores positions that are known to be not reachable.
     auto_mob_ignore_xs_map: HashMap<i32, Vec<(Range<i32>, u32)>>,
+    /// The tick at which [`Self::auto_mob_ignore_xs_map`] last decayed.
+    auto_mob_ignore_xs_decay_tick: Option<u64>,
     /// The last auto-mobbing quadrant kind.
     auto_mob_last_quadrant: Option<Quadrant>,
     /// The last auto-mobbing bound's quadrant relative to bottom-left player coordinate.
@@ -296,6 +483,16 @@ pub struct PlayerContext {
     auto_mob_next_quadrant_bound: Option<Rect>,
     /// Task for detecting near and same direction mobs during pathing.
     auto_mob_pathing_task: Option<Task<Result<Vec<Point>>>>,
+    /// The tick at which the auto-mobbing key was last used.
+    pub(super) auto_mob_last_attack_tick: Option<u64>,
+
+    /// The number of chained double jumps and the sampled number of double jumps to chain in
+    /// ping pong.
+    ///
+    /// Resets when the ping pong action ends, turns around or is interrupted by vertical movement.
+    pub(super) ping_pong_double_jump_chain: Option<(u32, u32)>,
+    /// Whether the player was double jumped in the last ping pong double jump update.
+    ping_pong_double_jumped: bool,
 
     /// Tracks whether movement-related actions do not change the player position after a while.
     ///
@@ -315,6 +512,8 @@ pub struct PlayerContext {
     /// This is [`Some`] when [`Player::SolvingRune`] successfully detects the rune
     /// and sends all the keys.
     rune_validate_timeout: Option<Timeout>,
+    /// Whether to retry [`Player::SolvingRune`] immediately because the last validation failed.
+    rune_retrying: bool,
 
     /// A state to return to after stalling.
     ///
@@ -325,9 +524,30 @@ pub struct PlayerContext {
     velocity_samples: Array<(Point, u64), VELOCITY_SAMPLES>,
     /// Approximated player velocity.
     pub(super) velocity: (f32, f32),
+    /// Signed `y` change from the previous detected position.
+    ///
+    /// Positive when the player is ascending and negative when falling, unlike
+    /// [`Self::velocity`] which only holds the magnitude.
+    pub(super) y_delta: i32,
+
+    /// Stores a list of ticks movements took before the player position started changing.
+    ///
+    /// Resets on map change together with the rest of the context.
+    move_response_samples: Array<u32, MOVE_RESPONSE_SAMPLES>,
+    /// Number of ticks to extend movement timeouts by as learned from
+    /// [`Self::move_response_samples`].
+    move_timeout_extension: u32,
+
+    /// Stores a list of walking `x` velocity samples for calibrating movement.
+    ///
+    /// Resets on map change together with the rest of the context.
+    walk_speed_samples: Array<f32, WALK_SPEED_SAMPLES>,
 
     /// The number of times [`Player::UsingBooster`] for VIP Booster failed.
     vip_booster_failed_count: u32,
+
+    /// The number of consecutive times [`Player::UpJumping`] timed out without completing.
+    up_jump_failed_count: u32,
 }
 
 impl PlayerContext {
@@ -343,6 +563,73 @@ impl PlayerContext {
         };
     }
 
+    /// Returns the movement `timeout` after applying configured scale and adaptive extension.
+    ///
+    /// Movement timeouts are also extended when the player walks slower than
+    /// [`BASELINE_WALK_SPEED`] on the current map.
+    #[inline]
+    pub fn move_timeout(&self, timeout: u32) -> u32 {
+        let timeout = self.config.scaled_move_timeout(timeout) + self.move_timeout_extension;
+        match self.config.movement_scale_percent {
+            Some(percent) if percent < 100 => (timeout * 100).div_ceil(percent),
+            _ => timeout,
+        }
+    }
+
+    /// Clears the movement calibration so that it is calibrated again on the next walks.
+    #[inline]
+    pub fn clear_movement_calibration(&mut self) {
+        self.config.set_movement_scale_percent(None);
+        self.walk_speed_samples = Array::new();
+    }
+
+    /// Tracks the current walking speed to calibrate movement on the current map.
+    ///
+    /// Once [`WALK_SPEED_SAMPLES`] samples are collected, movement thresholds are scaled by the
+    /// average walking speed relative to [`BASELINE_WALK_SPEED`]. Calibration only happens once
+    /// per map and is persisted to the map afterward.
+    pub(super) fn track_walk_speed(&mut self) {
+        let speed = self.velocity.0;
+        if self.config.movement_scale_percent.is_some() || speed < MIN_WALK_SPEED {
+            return;
+        }
+        if self.walk_speed_samples.len() == WALK_SPEED_SAMPLES {
+            self.walk_speed_samples.remove(0);
+        }
+        self.walk_speed_samples.push(speed);
+
+        let samples = self.walk_speed_samples.as_slice();
+        if samples.len() < WALK_SPEED_SAMPLES {
+            return;
+        }
+        let average = samples.iter().sum::<f32>() / samples.len() as f32;
+        let percent = ((average / BASELINE_WALK_SPEED) * 100.0).round() as u32;
+        self.config.set_movement_scale_percent(Some(percent));
+    }
+
+    /// Tracks how long `moving` took to start changing the player position.
+    ///
+    /// A started and uncompleted `moving` without any response is tracked as having taken all of
+    /// its passed ticks. Movement timeouts are extended when the average response exceeds
+    /// [`MOVE_RESPONSE_MARGIN`], up to [`MAX_MOVE_TIMEOUT_EXTENSION`] ticks.
+    pub(super) fn track_move_response(&mut self, moving: &Moving) {
+        let ticks = match moving.response_ticks {
+            Some(ticks) => ticks,
+            None if moving.timeout.started && !moving.completed => moving.timeout.total + 1,
+            None => return,
+        };
+        if self.move_response_samples.len() == MOVE_RESPONSE_SAMPLES {
+            self.move_response_samples.remove(0);
+        }
+        self.move_response_samples.push(ticks);
+
+        let samples = self.move_response_samples.as_slice();
+        let average = samples.iter().sum::<u32>().div_ceil(samples.len() as u32);
+        self.move_timeout_extension = average
+            .saturating_sub(MOVE_RESPONSE_MARGIN)
+            .min(MAX_MOVE_TIMEOUT_EXTENSION);
+    }
+
     #[inline]
     pub fn health(&self) -> Option<(u32, u32)> {
         self.health
@@ -353,11 +640,18 @@ impl PlayerContext {
         self.is_dead
     }
 
-    #[cfg(test)]
+    /// The current normal action.
+    #[cfg(any(test, debug_assertions))]
+    #[inline]
     pub fn normal_action(&self) -> Option<PlayerAction> {
         self.normal_action.clone()
     }
 
+    #[cfg(test)]
+    pub fn priority_action(&self) -> Option<PlayerAction> {
+        self.priority_action.clone()
+    }
+
     /// The normal action name for displaying to UI.
     #[inline]
     pub fn normal_action_name(&self) -> Option<String> {
@@ -385,12 +679,14 @@ impl PlayerContext {
     pub fn set_normal_action(&mut self, id: Option<u32>, action: PlayerAction) {
         self.normal_action_id = id;
         self.normal_action = Some(action);
+        self.reset_ping_pong_double_jump_chain();
     }
 
     /// Removes the current normal action.
     #[inline]
     pub fn reset_normal_action(&mut self) {
         self.normal_action = None;
+        self.reset_ping_pong_double_jump_chain();
     }
 
     /// The priority action name for displaying to UI.
@@ -466,6 +762,19 @@ impl PlayerContext {
         self.rune_validate_timeout.is_some()
     }
 
+    /// Whether the rune should be solved again because the rune buff did not appear after the
+    /// last attempt.
+    #[inline]
+    pub fn is_retrying_rune(&self) -> bool {
+        self.rune_retrying
+    }
+
+    /// Clears the rune retry flag once a new [`Player::SolvingRune`] attempt starts.
+    #[inline]
+    pub(super) fn clear_retrying_rune(&mut self) {
+        self.rune_retrying = false;
+    }
+
     /// Whether there is a priority rune action.
     #[inline]
     fn has_rune_action(&self) -> bool {
@@ -484,6 +793,45 @@ impl PlayerContext {
         !self.has_priority_action() && matches!(self.normal_action, Some(PlayerAction::PingPong(_)))
     }
 
+    /// Tracks the chained double jumps in the current ping pong direction.
+    ///
+    /// The number of double jumps to chain is sampled from
+    /// [`PlayerConfiguration::ping_pong_double_jump_weights`] at the start of each chain. Returns
+    /// `true` if the player has chained enough double jumps and should turn around.
+    pub(super) fn ping_pong_update_double_jump_chain(
+        &mut self,
+        resources: &Resources,
+        double_jumped: bool,
+    ) -> bool {
+        let weights = self.config.ping_pong_double_jump_weights;
+        if weights.iter().all(|weight| *weight == 0) {
+            self.reset_ping_pong_double_jump_chain();
+            return false;
+        }
+
+        let (count, target) = self.ping_pong_double_jump_chain.get_or_insert_with(|| {
+            let index = resources.rng.random_weighted_index(&weights).unwrap_or(0);
+            (0, index as u32 + 1)
+        });
+        if double_jumped && !self.ping_pong_double_jumped {
+            *count += 1;
+        }
+        self.ping_pong_double_jumped = double_jumped;
+
+        if *count < *target {
+            return false;
+        }
+        self.reset_ping_pong_double_jump_chain();
+        true
+    }
+
+    /// Resets the chained double jumps during ping pong.
+    #[inline]
+    pub(super) fn reset_ping_pong_double_jump_chain(&mut self) {
+        self.ping_pong_double_jump_chain = None;
+        self.ping_pong_double_jumped = false;
+    }
+
     /// Clears both on-going normal and priority actions due to being aborted and whether to reset
     /// the player to [`Player::Idle`].
     #[inline]
@@ -491,6 +839,7 @@ impl PlayerContext {
         self.reset_to_idle_next_update = should_idle;
         self.priority_action = None;
         self.normal_action = None;
+        self.reset_ping_pong_double_jump_chain();
     }
 
     /// Clears either normal or priority due to completion.
@@ -501,6 +850,7 @@ impl PlayerContext {
             self.priority_action = None;
         } else {
             self.normal_action = None;
+            self.reset_ping_pong_double_jump_chain();
         }
     }
 
@@ -542,6 +892,25 @@ impl PlayerContext {
         self.vip_booster_failed_count = 0;
     }
 
+    /// Tracks whether [`Player::UpJumping`] has `completed` before timing out.
+    ///
+    /// Returns `true` when the player should transition to [`Player::Unstucking`].
+    #[inline]
+    pub(super) fn track_up_jump_failed(&mut self, completed: bool) -> bool {
+        if completed {
+            self.up_jump_failed_count = 0;
+            return false;
+        }
+
+        self.up_jump_failed_count += 1;
+        if self.up_jump_failed_count >= MAX_UP_JUMP_FAILED_COUNT {
+            self.up_jump_failed_count = 0;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Increments the rune validation fail count and sets [`PlayerState::rune_cash_shop`]
     /// if needed.
     #[inline]
@@ -600,7 +969,8 @@ impl PlayerContext {
             LastMovement::Falling
             | LastMovement::Grappling
             | LastMovement::UpJumping
-            | LastMovement::Jumping => {
+            | LastMovement::Jumping
+            | LastMovement::Climbing => {
                 if self.has_auto_mob_action_only() {
                     AUTO_MOB_VERTICAL_MOVEMENT_REPEAT_COUNT
                 } else {
@@ -639,17 +1009,23 @@ impl PlayerContext {
     /// Gets the double jump minimum `x` distance threshold.
     ///
     /// In auto mob and final destination, the threshold is relaxed for more
-    /// fluid movement. In ping pong, there is no threshold.
+    /// fluid movement. In ping pong, there is no threshold. The threshold is scaled by the
+    /// calibrated walking speed on the current map.
     #[inline]
     pub(super) fn double_jump_threshold(&self, is_intermediate: bool) -> i32 {
-        if self.has_auto_mob_action_only() && !is_intermediate {
+        let threshold = if self.has_auto_mob_action_only() && !is_intermediate {
             DOUBLE_JUMP_AUTO_MOB_THRESHOLD
         } else if self.has_ping_pong_action_only() {
             0 // Ping pong double jumps forever
         } else if self.config.teleport_key.is_some() {
-            DOUBLE_JUMP_THRESHOLD / 2 // Half the threshold for mage
+            self.config.double_jump_threshold / 2 // Half the threshold for mage
         } else {
-            DOUBLE_JUMP_THRESHOLD
+            self.config.double_jump_threshold
+        };
+
+        match self.config.movement_scale_percent {
+            Some(percent) => threshold * percent as i32 / 100,
+            None => threshold,
         }
     }
 
@@ -1038,7 +1414,10 @@ impl PlayerContext {
 
                     if overlapping && should_merge {
                         last_range.end = last_range.end.max(range.end);
-                        *last_count = AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT;
+                        // Keeps platform gap count so merged ranges do not decay
+                        *last_count = (*last_count)
+                            .max(count)
+                            .max(AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT);
                         continue;
                     }
                 }
@@ -1075,6 +1454,36 @@ impl PlayerContext {
         }
     }
 
+    /// Decays the abort counts of ignored x ranges learned from auto-mobbing.
+    ///
+    /// Each count halves every [`PlayerConfiguration::auto_mob_ignore_xs_half_life_ticks`] and
+    /// the range is removed once its count reaches zero so the player can revisit the area.
+    /// Ranges populated from platform gaps never decay.
+    pub(super) fn auto_mob_decay_ignore_xs(&mut self, tick: u64) {
+        let half_life = self.config.auto_mob_ignore_xs_half_life_ticks as u64;
+        if half_life == 0 {
+            return;
+        }
+
+        let last_tick = *self.auto_mob_ignore_xs_decay_tick.get_or_insert(tick);
+        if tick.saturating_sub(last_tick) < half_life {
+            return;
+        }
+        self.auto_mob_ignore_xs_decay_tick = Some(tick);
+
+        for ranges in self.auto_mob_ignore_xs_map.values_mut() {
+            ranges.retain_mut(|(_, count)| {
+                if *count != AUTO_MOB_IGNORE_XS_PLATFORM_COUNT {
+                    *count /= 2;
+                }
+                *count > 0
+            });
+        }
+        self.auto_mob_ignore_xs_map
+            .retain(|_, ranges| !ranges.is_empty());
+        debug!(target: "player", "auto mob decayed ignore xs {:?}", self.auto_mob_ignore_xs_map);
+    }
+
     pub(super) fn auto_mob_populate_ignore_xs(&mut self, minimap_state: Minimap) {
         let (platforms, minimap_width) = match minimap_state {
             Minimap::Idle(idle) => (idle.platforms, idle.bbox.width),
@@ -1099,19 +1508,19 @@ impl PlayerContext {
 
             let first_gap = 0..ranges[0].start;
             if !first_gap.is_empty() {
-                ignores.push((first_gap.into(), AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT));
+                ignores.push((first_gap.into(), AUTO_MOB_IGNORE_XS_PLATFORM_COUNT));
             }
 
             let last_gap = ranges.last().unwrap().end..minimap_width;
             if !last_gap.is_empty() {
-                ignores.push((last_gap.into(), AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT));
+                ignores.push((last_gap.into(), AUTO_MOB_IGNORE_XS_PLATFORM_COUNT));
             }
 
             for r in ranges.into_iter().skip(1) {
                 if r.start > last_end {
                     let gap = last_end..r.start;
                     if !gap.is_empty() {
-                        ignores.push((gap.into(), AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT));
+                        ignores.push((gap.into(), AUTO_MOB_IGNORE_XS_PLATFORM_COUNT));
                     }
                 }
                 last_end = last_end.max(r.end);
@@ -1133,6 +1542,11 @@ impl PlayerContext {
         minimap_state: Minimap,
         buffs: &BuffEntities,
     ) -> bool {
+        if matches!(minimap_state, Minimap::Detecting) {
+            // The rune being validated or retried belongs to the previous map
+            self.rune_validate_timeout = None;
+            self.rune_retrying = false;
+        }
         if self.update_position_state(resources, minimap_state) {
             self.update_health_state(resources, player_state);
             self.update_rune_validating_state(
@@ -1141,6 +1555,7 @@ impl PlayerContext {
                 buffs,
             );
             self.update_is_dead_state(resources);
+            self.auto_mob_decay_ignore_xs(resources.tick);
             true
         } else {
             false
@@ -1158,7 +1573,15 @@ impl PlayerContext {
             Minimap::Detecting => return false,
             Minimap::Idle(idle) => idle.bbox,
         };
-        let Ok(player_bbox) = resources.detector().detect_player(minimap_bbox) else {
+        let detector = resources.detector();
+        // Flips back to top-left coordinate relative to the minimap
+        let near = self
+            .last_known_pos
+            .map(|pos| Point::new(pos.x, minimap_bbox.height - pos.y));
+        let Ok(player_bbox) = detector
+            .detect_player(minimap_bbox)
+            .or_else(|_| detector.detect_player_by_color(minimap_bbox, near))
+        else {
             return false;
         };
         let tl = player_bbox.tl();
@@ -1177,16 +1600,35 @@ impl PlayerContext {
             self.unstuck_transitioned_count = 0;
             self.is_stationary_timeout = Timeout::default();
         }
+        if last_known_pos.y != pos.y {
+            self.is_grounded_timeout = Timeout::default();
+        }
+        self.y_delta = pos.y - last_known_pos.y;
+        match pos.x.cmp(&last_known_pos.x) {
+            Ordering::Less => self.last_movement_direction = ActionKeyDirection::Left,
+            Ordering::Greater => self.last_movement_direction = ActionKeyDirection::Right,
+            Ordering::Equal => (),
+        }
         self.update_velocity(pos, resources.tick);
 
+        let stationary_ticks = self.config.stationary_ticks.max(1);
         let (is_stationary, is_stationary_timeout) =
-            match next_timeout_lifecycle(self.is_stationary_timeout, STATIONARY_TIMEOUT) {
+            match next_timeout_lifecycle(self.is_stationary_timeout, stationary_ticks) {
                 Lifecycle::Started(timeout) => (false, timeout),
                 Lifecycle::Ended => (true, self.is_stationary_timeout),
                 Lifecycle::Updated(timeout) => (false, timeout),
             };
         self.is_stationary = is_stationary;
         self.is_stationary_timeout = is_stationary_timeout;
+
+        let (is_grounded, is_grounded_timeout) =
+            match next_timeout_lifecycle(self.is_grounded_timeout, stationary_ticks) {
+                Lifecycle::Started(timeout) => (false, timeout),
+                Lifecycle::Ended => (true, self.is_grounded_timeout),
+                Lifecycle::Updated(timeout) => (false, timeout),
+            };
+        self.is_grounded = is_grounded;
+        self.is_grounded_timeout = is_grounded_timeout;
         self.last_known_pos = Some(pos);
         true
     }
@@ -1236,9 +1678,10 @@ impl PlayerContext {
 
     /// Updates the rune validation [`Timeout`].
     ///
-    /// [`PlayerState::rune_validate_timeout`] is [`Some`] only when [`Player::SolvingRune`]
-    /// successfully detects and sends all the keys. After about 12 seconds, it
-    /// will check if the player has the rune buff.
+    /// [`Self::rune_validate_timeout`] is [`Some`] only when [`Player::SolvingRune`]
+    /// successfully detects and sends all the keys. The rune is validated as soon as the player has
+    /// the rune buff. If the rune buff does not appear after about 12 seconds, the rune is
+    /// retried through [`Self::rune_retrying`].
     #[inline]
     fn update_rune_validating_state(
         &mut self,
@@ -1249,16 +1692,23 @@ impl PlayerContext {
 
         debug_assert!(self.rune_failed_count < MAX_RUNE_FAILED_COUNT);
         debug_assert!(!self.rune_cash_shop);
+        let has_rune_buff = !matches!(buffs[BuffKind::Rune].state, Buff::No);
+        if has_rune_buff {
+            // The rune buff can appear late after validation timed out
+            self.rune_retrying = false;
+        }
         self.rune_validate_timeout = self.rune_validate_timeout.and_then(|timeout| {
+            if has_rune_buff {
+                self.rune_failed_count = 0;
+                #[cfg(debug_assertions)]
+                resources.debug.save_last_rune_result();
+                return None;
+            }
+
             match next_timeout_lifecycle(timeout, VALIDATE_TIMEOUT) {
                 Lifecycle::Ended => {
-                    if matches!(buffs[BuffKind::Rune].state, Buff::No) {
-                        self.track_rune_fail_count();
-                    } else {
-                        self.rune_failed_count = 0;
-                        #[cfg(debug_assertions)]
-                        resources.debug.save_last_rune_result();
-                    }
+                    self.track_rune_fail_count();
+                    self.rune_retrying = !self.rune_cash_shop;
                     None
                 }
                 Lifecycle::Started(timeout) | Lifecycle::Updated(timeout) => Some(timeout),
@@ -1271,17 +1721,24 @@ impl PlayerContext {
     /// The detection first detects the HP bar and caches the result. The HP bar is then used
     /// to crop into the game image and detects the current health bar and max health bar. These
     /// bars are then cached and used to extract the current health and max health.
+    ///
+    /// Health is also tracked when only solving rune when safe is enabled as it relies on
+    /// [`Self::is_taking_damage`].
     // TODO: This should be a PlayerAction?
     #[inline]
     fn update_health_state(&mut self, resources: &Resources, player_state: Player) {
         if matches!(player_state, Player::SolvingRune(_)) {
             return;
         }
-        if self.config.use_potion_below_percent.is_none() {
+        if self.config.use_potion_below_percent.is_none()
+            && self.config.extra_potions_below_percent.is_empty()
+            && !self.config.solve_rune_only_when_safe
+        {
             self.health = None;
             self.health_task = None;
             self.health_bar = None;
             self.health_bar_task = None;
+            self.is_taking_damage = false;
             return;
         }
 
@@ -1312,14 +1769,23 @@ impl PlayerContext {
             return;
         };
 
-        let percentage = self.config.use_potion_below_percent.unwrap();
         let (current, max) = health;
         let ratio = current as f32 / max as f32;
 
+        self.is_taking_damage = self
+            .health
+            .is_some_and(|(last_current, _)| current < last_current);
         self.health = Some(health);
-        if ratio <= percentage {
+        if let Some(percentage) = self.config.use_potion_below_percent
+            && ratio <= percentage
+        {
             resources.input.send_key(self.config.potion_key);
         }
+        for (key, percentage) in self.config.extra_potions_below_percent {
+            if ratio <= percentage {
+                resources.input.send_key(key);
+            }
+        }
     }
 
     /// Updates whether the player is dead.
@@ -1370,17 +1836,31 @@ fn auto_mob_ignore_xs_range_value(x: i32) -> (Range<i32>, u32) {
 
 #[cfg(test)]
 mod tests {
-    use std::{assert_matches::assert_matches, collections::HashMap};
+    use std::{assert_matches::assert_matches, collections::HashMap, time::Duration};
 
+    use mockall::predicate::eq;
     use opencv::core::{Point, Rect};
+    use strum::IntoEnumIterator;
+    use tokio::time::advance;
 
     use crate::{
         Position,
         array::Array,
+        bridge::{KeyKind, MockInput},
+        buff::{Buff, BuffContext, BuffEntities, BuffEntity, BuffKind},
+        detect::MockDetector,
         ecs::Resources,
         minimap::{Minimap, MinimapIdle},
         pathing::{Platform, find_neighbors},
-        player::{AutoMob, PlayerAction, PlayerContext, Quadrant},
+        player::{
+            AutoMob, Player, PlayerAction, PlayerContext, Quadrant,
+            moving::Moving,
+            state::{
+                BASELINE_WALK_SPEED, MAX_MOVE_TIMEOUT_EXTENSION, MOVE_RESPONSE_SAMPLES,
+                WALK_SPEED_SAMPLES,
+            },
+            timeout::Timeout,
+        },
         rng::Rng,
     };
 
@@ -1389,6 +1869,155 @@ mod tests {
         64, 44, 192, 172, 191, 191, 157, 107, 206, 193, 55, 115, 68,
     ];
 
+    #[test]
+    fn update_position_state_stationary_after_configured_ticks() {
+        let mut detector = MockDetector::new();
+        detector
+            .expect_detect_player()
+            .returning(|_| Ok(Rect::new(10, 10, 2, 2)));
+        let resources = Resources::new(None, Some(detector));
+        let minimap = Minimap::Idle(MinimapIdle::default());
+        let mut state = PlayerContext::default();
+        state.config.stationary_ticks = 10;
+
+        for _ in 0..=state.config.stationary_ticks {
+            assert!(state.update_position_state(&resources, minimap));
+            assert!(!state.is_stationary);
+        }
+
+        assert!(state.update_position_state(&resources, minimap));
+        assert!(state.is_stationary);
+    }
+
+    #[test]
+    fn update_position_state_falls_back_to_color_detection() {
+        let mut detector = MockDetector::new();
+        detector
+            .expect_detect_player()
+            .returning(|_| Err(anyhow::anyhow!("player not found")));
+        detector
+            .expect_detect_player_by_color()
+            .once()
+            .returning(|_, _| Ok(Rect::new(10, 10, 4, 4)));
+        let resources = Resources::new(None, Some(detector));
+        let mut idle = MinimapIdle::default();
+        idle.bbox = Rect::new(0, 0, 100, 50);
+        let minimap = Minimap::Idle(idle);
+        let mut state = PlayerContext::default();
+
+        assert!(state.update_position_state(&resources, minimap));
+        assert_eq!(state.last_known_pos, Some(Point::new(12, 36)));
+    }
+
+    #[test]
+    fn update_position_state_grounded_while_moving_horizontally() {
+        let mut detector = MockDetector::new();
+        let mut x = 10;
+        detector.expect_detect_player().returning(move |_| {
+            x += 1;
+            Ok(Rect::new(x, 10, 2, 2))
+        });
+        let resources = Resources::new(None, Some(detector));
+        let minimap = Minimap::Idle(MinimapIdle::default());
+        let mut state = PlayerContext::default();
+        state.config.stationary_ticks = 3;
+
+        for _ in 0..=state.config.stationary_ticks {
+            assert!(state.update_position_state(&resources, minimap));
+            assert!(!state.is_grounded);
+        }
+
+        assert!(state.update_position_state(&resources, minimap));
+        assert!(state.is_grounded);
+        assert!(!state.is_stationary);
+    }
+
+    #[test]
+    fn move_timeout_expands_within_bounds_after_slow_movements() {
+        let mut context = PlayerContext::default();
+        let fast = Moving {
+            response_ticks: Some(1),
+            ..Default::default()
+        };
+        let slow = Moving {
+            response_ticks: Some(7),
+            ..Default::default()
+        };
+        let stuck = Moving {
+            response_ticks: Some(100),
+            ..Default::default()
+        };
+
+        context.track_move_response(&fast);
+        assert_eq!(context.move_timeout(8), 8);
+
+        for _ in 0..MOVE_RESPONSE_SAMPLES {
+            context.track_move_response(&slow);
+        }
+        assert_eq!(context.move_timeout(8), 12);
+
+        for _ in 0..MOVE_RESPONSE_SAMPLES {
+            context.track_move_response(&stuck);
+        }
+        assert_eq!(context.move_timeout(8), 8 + MAX_MOVE_TIMEOUT_EXTENSION);
+    }
+
+    #[test]
+    fn move_timeout_expands_after_failed_movements() {
+        let mut context = PlayerContext::default();
+        let failed = Moving {
+            timeout: Timeout {
+                current: MOVE_TIMEOUT,
+                total: MOVE_TIMEOUT,
+                started: true,
+            },
+            ..Default::default()
+        };
+        context.track_move_response(&Moving::default());
+        assert_eq!(context.move_timeout(8), 8);
+
+        for _ in 0..MOVE_RESPONSE_SAMPLES {
+            context.track_move_response(&failed);
+        }
+        assert_eq!(
+            context.move_timeout(8),
+            8 + MOVE_TIMEOUT + 1 - MOVE_RESPONSE_MARGIN
+        );
+    }
+
+    #[test]
+    fn walk_speed_calibration_scales_movement_thresholds() {
+        let mut context = PlayerContext::default();
+        context.config.double_jump_threshold = 20;
+        assert_eq!(context.double_jump_threshold(false), 20);
+
+        context.velocity = (BASELINE_WALK_SPEED * 1.5, 0.0);
+        for _ in 0..WALK_SPEED_SAMPLES {
+            context.track_walk_speed();
+        }
+        assert_eq!(context.double_jump_threshold(false), 30);
+        assert_eq!(context.move_timeout(8), 8);
+
+        // Already calibrated on this map
+        context.velocity = (BASELINE_WALK_SPEED * 0.5, 0.0);
+        for _ in 0..WALK_SPEED_SAMPLES {
+            context.track_walk_speed();
+        }
+        assert_eq!(context.config.movement_scale_percent, Some(150));
+    }
+
+    #[test]
+    fn walk_speed_calibration_survives_reset() {
+        let mut context = PlayerContext::default();
+        context.config.double_jump_threshold = 20;
+        context.config.movement_scale_percent = Some(50);
+
+        context.reset();
+
+        assert_eq!(context.double_jump_threshold(false), 10);
+        assert_eq!(context.move_timeout(8), 16);
+    }
+
     #[test]
     fn auto_mob_pick_reachable_y_should_ignore_solidified_x_range() {
         let resources = Resources::new(None, None);
@@ -1514,6 +2143,47 @@ mod tests {
         assert_eq!(ranges, &vec![((55..65).into(), 2), ((63..75).into(), 1)])
     }
 
+    #[test]
+    fn auto_mob_decay_ignore_xs_expires_after_configured_ticks() {
+        let mut player = PlayerContext::default();
+        player.config.auto_mob_ignore_xs_half_life_ticks = 10;
+        player.auto_mob_ignore_xs_map = HashMap::from([(
+            50,
+            vec![
+                ((0..10).into(), AUTO_MOB_IGNORE_XS_PLATFORM_COUNT),
+                ((20..27).into(), AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT),
+            ],
+        )]);
+
+        // Starts tracking without decaying
+        player.auto_mob_decay_ignore_xs(100);
+        player.auto_mob_decay_ignore_xs(109);
+        assert_eq!(
+            player.auto_mob_ignore_xs_map.get(&50).unwrap(),
+            &vec![
+                ((0..10).into(), AUTO_MOB_IGNORE_XS_PLATFORM_COUNT),
+                ((20..27).into(), AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT),
+            ]
+        );
+
+        // Halves after one half-life
+        player.auto_mob_decay_ignore_xs(110);
+        assert_eq!(
+            player.auto_mob_ignore_xs_map.get(&50).unwrap(),
+            &vec![
+                ((0..10).into(), AUTO_MOB_IGNORE_XS_PLATFORM_COUNT),
+                ((20..27).into(), 1),
+            ]
+        );
+
+        // Expires after two half-lives but platform gaps remain
+        player.auto_mob_decay_ignore_xs(120);
+        assert_eq!(
+            player.auto_mob_ignore_xs_map.get(&50).unwrap(),
+            &vec![((0..10).into(), AUTO_MOB_IGNORE_XS_PLATFORM_COUNT)]
+        );
+    }
+
     #[test]
     fn auto_mob_populate_ignore_xs_detects_gaps_correctly() {
         let platforms = vec![
@@ -1596,4 +2266,191 @@ mod tests {
         assert_eq!(point.y, 20); // 100 - 80
         assert_matches!(state.auto_mob_last_quadrant, Some(Quadrant::BottomLeft));
     }
+
+    fn mock_buffs(rune: Buff) -> BuffEntities {
+        BuffKind::iter()
+            .map(|kind| BuffEntity {
+                state: if kind == BuffKind::Rune {
+                    rune
+                } else {
+                    Buff::No
+                },
+                context: BuffContext::new(kind),
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn update_rune_validating_state_retries_when_rune_buff_missing() {
+        let resources = Resources::new(None, None);
+        let mut state = PlayerContext {
+            rune_validate_timeout: Some(Timeout {
+                started: true,
+                current: 375,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        state.update_rune_validating_state(
+            #[cfg(debug_assertions)]
+            &resources,
+            &mock_buffs(Buff::No),
+        );
+
+        assert!(!state.is_validating_rune());
+        assert!(state.is_retrying_rune());
+        assert_eq!(state.rune_failed_count, 1);
+    }
+
+    #[test]
+    fn update_rune_validating_state_completes_early_when_rune_buff_appears() {
+        let resources = Resources::new(None, None);
+        let mut state = PlayerContext::default();
+        state.start_validating_rune();
+
+        state.update_rune_validating_state(
+            #[cfg(debug_assertions)]
+            &resources,
+            &mock_buffs(Buff::Yes),
+        );
+
+        assert!(!state.is_validating_rune());
+        assert!(!state.is_retrying_rune());
+        assert_eq!(state.rune_failed_count, 0);
+    }
+
+    #[test]
+    fn update_rune_validating_state_clears_retrying_when_rune_buff_appears_late() {
+        let resources = Resources::new(None, None);
+        let mut state = PlayerContext {
+            rune_retrying: true,
+            ..Default::default()
+        };
+
+        state.update_rune_validating_state(
+            #[cfg(debug_assertions)]
+            &resources,
+            &mock_buffs(Buff::Yes),
+        );
+
+        assert!(!state.is_retrying_rune());
+    }
+
+    #[test]
+    fn update_state_clears_rune_validating_and_retrying_on_minimap_detecting() {
+        let resources = Resources::new(None, None);
+        let mut state = PlayerContext {
+            rune_retrying: true,
+            ..Default::default()
+        };
+        state.start_validating_rune();
+
+        let did_update = state.update_state(
+            &resources,
+            Player::Idle,
+            Minimap::Detecting,
+            &mock_buffs(Buff::No),
+        );
+
+        assert!(!did_update);
+        assert!(!state.is_validating_rune());
+        assert!(!state.is_retrying_rune());
+    }
+
+    fn mock_health_detector(health: (u32, u32)) -> MockDetector {
+        let mut detector = MockDetector::new();
+        detector
+            .expect_clone()
+            .returning(move || mock_health_detector(health));
+        detector
+            .expect_detect_player_current_max_health_bars()
+            .returning(|bar| Ok((bar, bar)));
+        detector
+            .expect_detect_player_health()
+            .returning(move |_, _| Ok(health));
+        detector
+    }
+
+    async fn update_health_state_until_detected(resources: &Resources, state: &mut PlayerContext) {
+        while state.health.is_none() {
+            state.update_health_state(resources, Player::Idle);
+            advance(Duration::from_millis(1000)).await;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn update_health_state_uses_potion_below_percentage() {
+        let mut keys = MockInput::new();
+        keys.expect_send_key().with(eq(KeyKind::P)).once();
+        let resources = Resources::new(Some(keys), Some(mock_health_detector((40, 100))));
+        let mut state = PlayerContext {
+            health_bar: Some(Rect::new(0, 0, 100, 10)),
+            ..Default::default()
+        };
+        state.config.potion_key = KeyKind::P;
+        state.config.use_potion_below_percent = Some(0.5);
+
+        update_health_state_until_detected(&resources, &mut state).await;
+
+        assert_eq!(state.health, Some((40, 100)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn update_health_state_uses_extra_potions_below_percentage() {
+        let mut keys = MockInput::new();
+        keys.expect_send_key().with(eq(KeyKind::O)).once();
+        keys.expect_send_key().with(eq(KeyKind::I)).never();
+        let resources = Resources::new(Some(keys), Some(mock_health_detector((40, 100))));
+        let mut state = PlayerContext {
+            health_bar: Some(Rect::new(0, 0, 100, 10)),
+            ..Default::default()
+        };
+        state.config.extra_potions_below_percent =
+            Array::from_iter([(KeyKind::O, 0.5), (KeyKind::I, 0.3)]);
+
+        update_health_state_until_detected(&resources, &mut state).await;
+
+        assert_eq!(state.health, Some((40, 100)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn update_health_state_does_not_use_potion_above_percentage() {
+        let mut keys = MockInput::new();
+        keys.expect_send_key().never();
+        let resources = Resources::new(Some(keys), Some(mock_health_detector((60, 100))));
+        let mut state = PlayerContext {
+            health_bar: Some(Rect::new(0, 0, 100, 10)),
+            ..Default::default()
+        };
+        state.config.potion_key = KeyKind::P;
+        state.config.use_potion_below_percent = Some(0.5);
+
+        update_health_state_until_detected(&resources, &mut state).await;
+
+        assert_eq!(state.health, Some((60, 100)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn update_health_state_detects_damage_when_solving_rune_only_when_safe() {
+        let mut keys = MockInput::new();
+        keys.expect_send_key().never();
+        let resources = Resources::new(Some(keys), Some(mock_health_detector((40, 100))));
+        let mut state = PlayerContext {
+            health: Some((60, 100)),
+            health_bar: Some(Rect::new(0, 0, 100, 10)),
+            ..Default::default()
+        };
+        state.config.solve_rune_only_when_safe = true;
+
+        while state.health == Some((60, 100)) {
+            state.update_health_state(&resources, Player::Idle);
+            advance(Duration::from_millis(1000)).await;
+        }
+
+        assert_eq!(state.health, Some((40, 100)));
+        assert!(state.is_taking_damage);
+    }
 }
@@ -107,6 +107,9 @@ pub fn next_moving_lifecycle_with_axis(
         };
 
         if moved {
+            if moving.timeout.started && moving.response_ticks.is_none() {
+                moving.response_ticks = Some(moving.timeout.current + 1);
+            }
             moving.timeout.current = 0;
         }
     }
@@ -141,6 +144,7 @@ mod tests {
             exact: false,
             completed: false,
             intermediates: None,
+            response_ticks: None,
         }
     }
 
@@ -183,6 +187,7 @@ mod tests {
             MovingLifecycle::Updated(m) => {
                 assert_eq!(m.timeout.current, 1);
                 assert_eq!(m.pos, cur_pos);
+                assert_eq!(m.response_ticks, Some(4));
             }
             _ => panic!("Expected Started variant"),
         }
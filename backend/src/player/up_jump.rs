@@ -2,7 +2,7 @@ use super::{
     Key, Player, PlayerContext,
     actions::update_from_ping_pong_action,
     moving::Moving,
-    timeout::{MovingLifecycle, next_moving_lifecycle_with_axis},
+    timeout::{MovingLifecycle, Timeout, next_moving_lifecycle_with_axis},
     use_key::UseKey,
 };
 use crate::{
@@ -17,8 +17,8 @@ use crate::{
     transition, transition_if, transition_to_moving,
 };
 
-/// Number of ticks to wait before spamming jump key.
-const SPAM_DELAY: u32 = 7;
+/// Default number of ticks to wait before spamming jump key.
+pub(super) const SPAM_DELAY: u32 = 7;
 
 /// Number of ticks to wait before spamming jump key for lesser travel distance.
 const SOFT_SPAM_DELAY: u32 = 12;
@@ -81,7 +81,7 @@ impl UpJumping {
         {
             SOFT_SPAM_DELAY
         } else {
-            SPAM_DELAY
+            player_context.config.up_jump_spam_delay
         };
         let auto_mob_wait_completion =
             player_context.has_auto_mob_action_only() && resources.rng.random_bool(0.5);
@@ -110,8 +110,8 @@ impl UpJumping {
 /// This state can only be transitioned via [`Player::Moving`] when the
 /// player has reached the destination x-wise. Before performing an up jump, it will check for
 /// stationary state and whether the player is currently near a portal. If the player is near
-/// a portal, this action is aborted. The up jump action is made to be adapted for various classes
-/// that has different up jump key combination.
+/// a portal and aborting is not disabled, this action is aborted. The up jump action is made to
+/// be adapted for various classes that has different up jump key combination.
 pub fn update_up_jumping_state(
     resources: &Resources,
     player: &mut PlayerEntity,
@@ -124,6 +124,8 @@ pub fn update_up_jumping_state(
     let jump_key = player.context.config.jump_key;
     let should_jump = player.context.config.up_jump_specific_key_should_jump;
     let is_flight = player.context.config.up_jump_is_flight;
+    let portal_margin = player.context.config.portal_avoidance_margin as i32;
+    let disable_portal_abort = player.context.config.disable_portal_upjump_abort;
 
     match next_moving_lifecycle_with_axis(
         up_jumping.moving,
@@ -131,12 +133,23 @@ pub fn update_up_jumping_state(
             .context
             .last_known_pos
             .expect("in positional context"),
-        TIMEOUT,
+        player.context.move_timeout(TIMEOUT),
         ChangeAxis::Vertical,
     ) {
         MovingLifecycle::Started(moving) => {
-            // Stall until near stationary
             let (x_velocity, y_velocity) = player.context.velocity;
+            // Skip sending redundant jump key(s) when already ascending fast towards the
+            // destination as the velocity is used to determine whether the player has up jumped
+            transition_if!(
+                player,
+                Player::UpJumping(up_jumping.moving(moving.completed(true))),
+                is_already_ascending(&player.context, up_jumping.kind, moving),
+                {
+                    player.context.last_movement = Some(LastMovement::UpJumping);
+                }
+            );
+
+            // Stall until near stationary
             transition_if!(
                 player,
                 Player::UpJumping(up_jumping.moving(moving.timeout_started(false))),
@@ -145,7 +158,9 @@ pub fn update_up_jumping_state(
             );
 
             let is_inside_portal = match minimap_state {
-                Minimap::Idle(idle) => idle.is_position_inside_portal(moving.pos),
+                Minimap::Idle(idle) if !disable_portal_abort => {
+                    idle.is_position_inside_portal(moving.pos, portal_margin)
+                }
                 _ => false,
             };
             transition_if!(player, Player::Idle, is_inside_portal, {
@@ -189,9 +204,23 @@ pub fn update_up_jumping_state(
             }
             transition!(player, Player::UpJumping(up_jumping.moving(moving)));
         }
-        MovingLifecycle::Ended(moving) => transition_to_moving!(player, moving, {
-            resources.input.send_key_up(KeyKind::Up);
-        }),
+        MovingLifecycle::Ended(moving) => {
+            // Escalates to unstucking when up jump repeatedly fails to complete (e.g. blocked)
+            transition_if!(
+                player,
+                Player::Unstucking(
+                    Timeout::default(),
+                    player.context.track_unstucking_transitioned()
+                ),
+                player.context.track_up_jump_failed(moving.completed),
+                {
+                    resources.input.send_key_up(KeyKind::Up);
+                }
+            );
+            transition_to_moving!(player, moving, {
+                resources.input.send_key_up(KeyKind::Up);
+            })
+        }
         MovingLifecycle::Updated(mut moving) => {
             let cur_pos = moving.pos;
             let (y_distance, y_direction) = moving.y_distance_direction_from(true, moving.pos);
@@ -264,7 +293,11 @@ pub fn update_up_jumping_state(
                 None
                 | Some(
                     PlayerAction::Key(Key {
-                        with: ActionKeyWith::Stationary | ActionKeyWith::DoubleJump,
+                        with:
+                            ActionKeyWith::Stationary
+                            | ActionKeyWith::DoubleJump
+                            | ActionKeyWith::Falling
+                            | ActionKeyWith::OnGround,
                         ..
                     })
                     | PlayerAction::Move(_)
@@ -398,6 +431,22 @@ fn update_flying(resources: &Resources, moving: &mut Moving, y_direction: i32, k
     }
 }
 
+/// Whether the player is already ascending fast enough towards the destination to be considered
+/// as up jumped.
+///
+/// Only applicable to kinds that use velocity to determine up jump completion.
+#[inline]
+fn is_already_ascending(context: &PlayerContext, kind: UpJumpingKind, moving: Moving) -> bool {
+    let (x_velocity, y_velocity) = context.velocity;
+    let (_, y_direction) = moving.y_distance_direction_from(true, moving.pos);
+
+    matches!(kind, UpJumpingKind::UpArrow | UpJumpingKind::JumpKey)
+        && y_direction > 0
+        && context.y_delta > 0
+        && x_velocity <= X_NEAR_STATIONARY_THRESHOLD
+        && y_velocity > UP_JUMPED_Y_VELOCITY_THRESHOLD
+}
+
 #[inline]
 fn up_jumping_kind(up_jump_key: Option<KeyKind>, has_teleport_key: bool) -> UpJumpingKind {
     match (up_jump_key, has_teleport_key) {
@@ -414,11 +463,13 @@ fn up_jumping_kind(up_jump_key: Option<KeyKind>, has_teleport_key: bool) -> UpJu
 mod tests {
     use std::assert_matches::assert_matches;
 
-    use opencv::core::Point;
+    use opencv::core::{Point, Rect};
 
     use super::*;
+    use crate::array::Array;
     use crate::bridge::{KeyKind, MockInput};
     use crate::ecs::Resources;
+    use crate::minimap::MinimapIdle;
     use crate::player::{Player, PlayerEntity};
 
     fn setup_player(up_jumping: UpJumping) -> PlayerEntity {
@@ -431,6 +482,98 @@ mod tests {
         player
     }
 
+    fn minimap_with_portal(portal: Rect) -> Minimap {
+        let mut portals = Array::new();
+        portals.push(portal);
+        let mut idle = MinimapIdle::default();
+        idle.set_portals(portals);
+        Minimap::Idle(idle)
+    }
+
+    #[test]
+    fn update_up_jumping_state_started_aborts_within_portal_margin() {
+        let moving = Moving::new(Point::new(5, 0), Point::new(5, 20), true, None);
+        let mut player = setup_player(UpJumping {
+            moving,
+            kind: UpJumpingKind::JumpKey,
+            spam_delay: SPAM_DELAY,
+            auto_mob_wait_completion: false,
+        });
+        player.context.last_known_pos = Some(Point::new(5, 0));
+        player.context.config.portal_avoidance_margin = 10;
+        let mut keys = MockInput::new();
+        keys.expect_send_key_down().never();
+        keys.expect_send_key().never();
+        let resources = Resources::new(Some(keys), None);
+
+        update_up_jumping_state(
+            &resources,
+            &mut player,
+            minimap_with_portal(Rect::new(10, 0, 10, 10)),
+        );
+
+        assert_matches!(player.state, Player::Idle);
+    }
+
+    #[test]
+    fn update_up_jumping_state_started_within_portal_margin_abort_disabled() {
+        let moving = Moving::new(Point::new(5, 0), Point::new(5, 20), true, None);
+        let mut player = setup_player(UpJumping {
+            moving,
+            kind: UpJumpingKind::JumpKey,
+            spam_delay: SPAM_DELAY,
+            auto_mob_wait_completion: false,
+        });
+        player.context.last_known_pos = Some(Point::new(5, 0));
+        player.context.config.portal_avoidance_margin = 10;
+        player.context.config.disable_portal_upjump_abort = true;
+        let mut keys = MockInput::new();
+        keys.expect_send_key_down()
+            .withf(|k| *k == KeyKind::Up)
+            .once();
+        keys.expect_send_key()
+            .withf(|k| *k == KeyKind::Space)
+            .once();
+        let resources = Resources::new(Some(keys), None);
+
+        update_up_jumping_state(
+            &resources,
+            &mut player,
+            minimap_with_portal(Rect::new(10, 0, 10, 10)),
+        );
+
+        assert_matches!(player.state, Player::UpJumping(_));
+    }
+
+    #[test]
+    fn update_up_jumping_state_started_outside_portal_margin() {
+        let moving = Moving::new(Point::new(5, 0), Point::new(5, 20), true, None);
+        let mut player = setup_player(UpJumping {
+            moving,
+            kind: UpJumpingKind::JumpKey,
+            spam_delay: SPAM_DELAY,
+            auto_mob_wait_completion: false,
+        });
+        player.context.last_known_pos = Some(Point::new(5, 0));
+        player.context.config.portal_avoidance_margin = 2;
+        let mut keys = MockInput::new();
+        keys.expect_send_key_down()
+            .withf(|k| *k == KeyKind::Up)
+            .once();
+        keys.expect_send_key()
+            .withf(|k| *k == KeyKind::Space)
+            .once();
+        let resources = Resources::new(Some(keys), None);
+
+        update_up_jumping_state(
+            &resources,
+            &mut player,
+            minimap_with_portal(Rect::new(10, 0, 10, 10)),
+        );
+
+        assert_matches!(player.state, Player::UpJumping(_));
+    }
+
     #[test]
     fn update_up_jumping_state_started_jump_key_presses_up_and_jump() {
         let moving = Moving::new(Point::new(0, 0), Point::new(0, 20), true, None);
@@ -454,6 +597,69 @@ mod tests {
         assert_matches!(player.state, Player::UpJumping(_));
     }
 
+    #[test]
+    fn update_up_jumping_state_started_already_ascending_no_keys_sent() {
+        let moving = Moving::new(Point::new(0, 0), Point::new(0, 20), true, None);
+        let mut player = setup_player(UpJumping {
+            moving,
+            kind: UpJumpingKind::JumpKey,
+            spam_delay: SPAM_DELAY,
+            auto_mob_wait_completion: false,
+        });
+        player.context.velocity = (0.0, 2.0); // Y velocity above threshold
+        player.context.y_delta = 2;
+        let mut keys = MockInput::new();
+        keys.expect_send_key_down().never();
+        keys.expect_send_key().never();
+        let resources = Resources::new(Some(keys), None);
+
+        update_up_jumping_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(
+            player.state,
+            Player::UpJumping(UpJumping {
+                moving: Moving {
+                    completed: true,
+                    timeout: Timeout { started: true, .. },
+                    ..
+                },
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn update_up_jumping_state_started_falling_not_considered_ascending() {
+        let moving = Moving::new(Point::new(0, 0), Point::new(0, 20), true, None);
+        let mut player = setup_player(UpJumping {
+            moving,
+            kind: UpJumpingKind::JumpKey,
+            spam_delay: SPAM_DELAY,
+            auto_mob_wait_completion: false,
+        });
+        player.context.velocity = (0.0, 2.0); // Y velocity above threshold
+        player.context.y_delta = -2;
+        let mut keys = MockInput::new();
+        keys.expect_send_key_down().never();
+        keys.expect_send_key().never();
+        let resources = Resources::new(Some(keys), None);
+
+        update_up_jumping_state(&resources, &mut player, Minimap::Detecting);
+
+        // Stalls until near stationary instead of completing
+        assert_matches!(
+            player.state,
+            Player::UpJumping(UpJumping {
+                moving: Moving {
+                    completed: false,
+                    timeout: Timeout { started: false, .. },
+                    ..
+                },
+                ..
+            })
+        );
+    }
+
     #[test]
     fn update_up_jumping_state_started_up_arrow_presses_jump_only() {
         let moving = Moving::new(Point::new(0, 0), Point::new(0, 20), true, None);
@@ -593,6 +799,44 @@ mod tests {
         assert_matches!(player.state, Player::UpJumping(_));
     }
 
+    #[test]
+    fn update_up_jumping_state_updated_spam_jump_key_after_configured_delay() {
+        let mut moving = Moving::new(Point::new(0, 0), Point::new(0, 20), true, None);
+        moving.timeout.started = true;
+        moving.timeout.total = SPAM_DELAY;
+        let mut player = setup_player(UpJumping {
+            moving,
+            kind: UpJumpingKind::JumpKey,
+            spam_delay: SPAM_DELAY,
+            auto_mob_wait_completion: false,
+        });
+        player.context.config.up_jump_spam_delay = SPAM_DELAY + 3;
+        let resources = Resources::new(None, None);
+        player.state = Player::UpJumping(UpJumping::new(moving, &resources, &player.context));
+        let mut keys = MockInput::new();
+        keys.expect_send_key().never();
+        let resources = Resources::new(Some(keys), None);
+
+        // Not sent at the default delay
+        update_up_jumping_state(&resources, &mut player, Minimap::Detecting);
+
+        moving.timeout.total = SPAM_DELAY + 2;
+        let Player::UpJumping(up_jumping) = player.state else {
+            unreachable!();
+        };
+        player.state = Player::UpJumping(up_jumping.moving(moving));
+        let mut keys = MockInput::new();
+        keys.expect_send_key()
+            .withf(|k| *k == KeyKind::Space)
+            .once();
+        let resources = Resources::new(Some(keys), None);
+
+        // Sent at the configured delay
+        update_up_jumping_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(player.state, Player::UpJumping(_));
+    }
+
     #[test]
     fn update_up_jumping_state_updated_spam_specific_key_after_delay() {
         let mut moving = Moving::new(Point::new(0, 0), Point::new(0, 20), true, None);
@@ -640,6 +884,33 @@ mod tests {
         assert_matches!(player.state, Player::UpJumping(_));
     }
 
+    #[test]
+    fn update_up_jumping_state_ended_repeated_failures_escalate_to_unstucking() {
+        let mut moving = Moving::new(Point::new(0, 0), Point::new(0, 20), true, None);
+        moving.timeout.started = true;
+        moving.timeout.current = TIMEOUT;
+        let up_jumping = UpJumping {
+            moving,
+            kind: UpJumpingKind::JumpKey,
+            spam_delay: SPAM_DELAY,
+            auto_mob_wait_completion: false,
+        };
+        let mut player = setup_player(up_jumping);
+        let mut keys = MockInput::new();
+        keys.expect_send_key_up().withf(|k| *k == KeyKind::Up);
+        let resources = Resources::new(Some(keys), None);
+
+        for _ in 0..2 {
+            player.state = Player::UpJumping(up_jumping);
+            update_up_jumping_state(&resources, &mut player, Minimap::Detecting);
+            assert_matches!(player.state, Player::Moving(_, _, _));
+        }
+
+        player.state = Player::UpJumping(up_jumping);
+        update_up_jumping_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(player.state, Player::Unstucking(_, _));
+    }
+
     #[test]
     fn update_up_jumping_state_updated_completed_and_releases_up() {
         let mut moving = Moving::new(Point::new(0, 0), Point::new(0, 20), true, None);
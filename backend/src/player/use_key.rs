@@ -1,9 +1,12 @@
 use std::assert_matches::assert_matches;
 
+use opencv::core::Point;
+
 use super::{
     AutoMob, PingPongDirection, PlayerContext, Timeout,
     actions::{Key, PingPong, PlayerAction, update_from_ping_pong_action},
     double_jump::DoubleJumping,
+    fall::FALLING_THRESHOLD,
     timeout::{Lifecycle, next_timeout_lifecycle},
 };
 use crate::{
@@ -12,11 +15,19 @@ use crate::{
     ecs::Resources,
     minimap::Minimap,
     player::{LastMovement, MOVE_TIMEOUT, Moving, Player, PlayerEntity, next_action},
+    run::MS_PER_TICK,
     transition, transition_from_action, transition_if,
 };
 
 /// The total number of ticks for changing direction before timing out.
-const CHANGE_DIRECTION_TIMEOUT: u32 = 3;
+pub(super) const CHANGE_DIRECTION_TIMEOUT: u32 = 3;
+
+/// The maximum number of times to change direction again when the detected facing direction
+/// does not match.
+///
+/// Detection can keep disagreeing (e.g. a misread character sprite), so the key is used anyway
+/// after this many retries.
+const MAX_VERIFY_DIRECTION_RETRIES: u32 = 2;
 
 /// The tick to which the actual key will be pressed for [`LinkKeyBinding::Along`].
 const LINK_ALONG_PRESS_TICK: u32 = 2;
@@ -36,10 +47,10 @@ enum State {
     ///
     /// Returns to [`State::Precondition`] upon timeout.
     ChangingDirection(Timeout),
-    /// Ensures player double jumped or is stationary.
+    /// Ensures player double jumped, is falling, is on the ground or is stationary.
     ///
-    /// Returns to [`State::Precondition`] if player is stationary or
-    /// transfers to [`Player::DoubleJumping`].
+    /// Returns to [`State::Precondition`] if player is stationary, on the ground or
+    /// transfers to [`Player::DoubleJumping`] or [`Player::Falling`].
     EnsuringUseWith,
     /// Uses the actual key with optional [`LinkKeyBinding`] and stalls
     /// for [`UseKey::wait_after_use_ticks`].
@@ -54,6 +65,7 @@ enum PendingTransition {
     WaitBefore,
     WaitAfter,
     DoubleJump,
+    Fall,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -66,6 +78,11 @@ pub struct UseKey {
     with: ActionKeyWith,
     wait_before_use_ticks: u32,
     wait_after_use_ticks: u32,
+    /// Whether to skip satisfying [`Self::direction`] and [`Self::with`].
+    force: bool,
+    /// The number of times direction was changed again due to a mismatched facing direction
+    /// detection for the current use.
+    verify_direction_retries: u32,
     pending_transition: PendingTransition,
     action_info: Option<ActionInfo>,
     state: State,
@@ -83,6 +100,7 @@ impl UseKey {
             wait_before_use_ticks_random_range,
             wait_after_use_ticks,
             wait_after_use_ticks_random_range,
+            force,
             ..
         } = key;
         let wait_before =
@@ -98,6 +116,8 @@ impl UseKey {
             with,
             wait_before_use_ticks: wait_before,
             wait_after_use_ticks: wait_after,
+            force,
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
             action_info: None,
             state: State::Precondition,
@@ -122,6 +142,8 @@ impl UseKey {
             with: mob.with,
             wait_before_use_ticks: wait_before,
             wait_after_use_ticks: wait_after,
+            force: false,
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
             action_info: Some(ActionInfo::AutoMobbing { should_terminate }),
             state: State::Precondition,
@@ -152,6 +174,8 @@ impl UseKey {
             with: ping_pong.with,
             wait_before_use_ticks: wait_before,
             wait_after_use_ticks: wait_after,
+            force: false,
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
             action_info: None,
             state: State::Precondition,
@@ -177,7 +201,7 @@ pub fn update_use_key_state(
 
     match use_key.state {
         State::Precondition => {
-            update_precondition(&player.context, &mut use_key);
+            update_precondition(resources, &player.context, &mut use_key);
             transition_if!(
                 player,
                 Player::Stalling(Timeout::default(), use_key.wait_before_use_ticks),
@@ -211,8 +235,25 @@ pub fn update_use_key_state(
                     use_key.pending_transition = PendingTransition::None;
                 }
             );
+            if matches!(use_key.pending_transition, PendingTransition::Fall) {
+                let pos = player.context.last_known_pos.expect("in positional state");
+                let dest = Point::new(pos.x, pos.y - FALLING_THRESHOLD);
+                transition!(
+                    player,
+                    Player::Falling {
+                        moving: Moving::new(pos, dest, false, None),
+                        anchor: pos,
+                        timeout_on_complete: false,
+                    }
+                );
+            }
         }
         State::Using(timeout, completed) => {
+            if !timeout.started
+                && matches!(use_key.action_info, Some(ActionInfo::AutoMobbing { .. }))
+            {
+                player.context.auto_mob_last_attack_tick = Some(resources.tick);
+            }
             update_using(resources, &player.context, &mut use_key, timeout, completed);
             transition_if!(
                 player,
@@ -228,6 +269,7 @@ pub fn update_use_key_state(
         State::Postcondition => {
             use_key.current_count += 1;
             if use_key.current_count < use_key.count {
+                use_key.verify_direction_retries = 0;
                 use_key.state = State::Precondition;
             }
         }
@@ -289,17 +331,33 @@ pub fn update_use_key_state(
     }
 }
 
-fn update_precondition(context: &PlayerContext, use_key: &mut UseKey) {
+fn update_precondition(resources: &Resources, context: &PlayerContext, use_key: &mut UseKey) {
+    if matches!(use_key.action_info, Some(ActionInfo::AutoMobbing { .. }))
+        && !is_auto_mob_attack_interval_passed(resources, context)
+    {
+        return;
+    }
+
     transition_if!(
         use_key,
         State::ChangingDirection(Timeout::default()),
-        !ensure_direction(context, use_key.direction)
+        !use_key.force && !ensure_direction(context, use_key.direction)
+    );
+    transition_if!(
+        use_key,
+        State::ChangingDirection(Timeout::default()),
+        !use_key.force
+            && use_key.verify_direction_retries < MAX_VERIFY_DIRECTION_RETRIES
+            && !verify_direction(context, use_key.direction),
+        {
+            use_key.verify_direction_retries += 1;
+        }
     );
 
     transition_if!(
         use_key,
         State::EnsuringUseWith,
-        !ensure_use_with(context, use_key.with)
+        !use_key.force && !ensure_use_with(context, use_key.with)
     );
 
     transition_if!(
@@ -311,6 +369,16 @@ fn update_precondition(context: &PlayerContext, use_key: &mut UseKey) {
     use_key.pending_transition = PendingTransition::WaitBefore;
 }
 
+/// Checks whether the configured auto-mobbing attack interval has passed since the auto-mobbing
+/// key was last used.
+#[inline]
+fn is_auto_mob_attack_interval_passed(resources: &Resources, context: &PlayerContext) -> bool {
+    context.auto_mob_last_attack_tick.is_none_or(|tick| {
+        resources.tick.saturating_sub(tick) * MS_PER_TICK
+            >= context.config.auto_mob_attack_interval_millis
+    })
+}
+
 #[inline]
 fn ensure_direction(context: &PlayerContext, direction: ActionKeyDirection) -> bool {
     match direction {
@@ -321,6 +389,22 @@ fn ensure_direction(context: &PlayerContext, direction: ActionKeyDirection) -> b
     }
 }
 
+/// Verifies the player is facing `direction` by the detected movement direction if configured.
+///
+/// An unknown movement direction falls back to the already ensured
+/// [`PlayerContext::last_known_direction`].
+#[inline]
+fn verify_direction(context: &PlayerContext, direction: ActionKeyDirection) -> bool {
+    if !context.config.verify_direction_by_detection {
+        return true;
+    }
+
+    match (direction, context.last_movement_direction) {
+        (ActionKeyDirection::Any, _) | (_, ActionKeyDirection::Any) => true,
+        (direction, moved) => direction == moved,
+    }
+}
+
 #[inline]
 fn ensure_use_with(context: &PlayerContext, with: ActionKeyWith) -> bool {
     match with {
@@ -329,6 +413,10 @@ fn ensure_use_with(context: &PlayerContext, with: ActionKeyWith) -> bool {
         ActionKeyWith::DoubleJump => {
             matches!(context.last_movement, Some(LastMovement::DoubleJumping))
         }
+        ActionKeyWith::Falling => {
+            matches!(context.last_movement, Some(LastMovement::Falling)) && context.y_delta < 0
+        }
+        ActionKeyWith::OnGround => context.is_grounded,
     }
 }
 
@@ -407,6 +495,15 @@ fn update_ensuring_use_with(context: &PlayerContext, use_key: &mut UseKey) {
         ActionKeyWith::DoubleJump => {
             use_key.pending_transition = PendingTransition::DoubleJump;
         }
+        ActionKeyWith::Falling => {
+            use_key.pending_transition = PendingTransition::Fall;
+        }
+        ActionKeyWith::OnGround => transition_if!(
+            use_key,
+            State::Precondition,
+            State::EnsuringUseWith,
+            context.is_grounded
+        ),
     }
 }
 
@@ -424,7 +521,8 @@ fn update_changing_direction(
         ActionKeyDirection::Any => unreachable!(),
     };
 
-    match next_timeout_lifecycle(timeout, CHANGE_DIRECTION_TIMEOUT) {
+    let max_timeout = context.config.change_direction_timeout_ticks.max(1);
+    match next_timeout_lifecycle(timeout, max_timeout) {
         Lifecycle::Started(timeout) => {
             transition_if!(
                 use_key,
@@ -437,6 +535,7 @@ fn update_changing_direction(
         }
         Lifecycle::Ended => transition!(use_key, State::Precondition, {
             context.last_known_direction = use_key.direction;
+            context.last_movement_direction = ActionKeyDirection::Any;
         }),
         Lifecycle::Updated(timeout) => transition!(use_key, State::ChangingDirection(timeout)),
     }
@@ -522,10 +621,14 @@ mod tests {
         ecs::Resources,
         minimap::Minimap,
         player::{
-            Player, PlayerContext, PlayerEntity, Timeout,
+            LastMovement, Player, PlayerContext, PlayerEntity, Timeout,
             double_jump::DoubleJumping,
-            use_key::{PendingTransition, State, UseKey, update_use_key_state},
+            use_key::{
+                ActionInfo, CHANGE_DIRECTION_TIMEOUT, MAX_VERIFY_DIRECTION_RETRIES,
+                PendingTransition, State, UseKey, update_use_key_state,
+            },
         },
+        run::MS_PER_TICK,
     };
 
     fn make_player(use_key: UseKey) -> PlayerEntity {
@@ -547,8 +650,10 @@ mod tests {
             with: ActionKeyWith::Stationary,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            force: false,
             action_info: None,
             state: State::Precondition,
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
         });
 
@@ -574,6 +679,132 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_use_key_state_on_ground_allows_moving_unlike_stationary() {
+        let resources = Resources::new(None, None);
+        let use_key = UseKey {
+            key: KeyBinding::A,
+            link_key: None,
+            count: 1,
+            current_count: 0,
+            direction: ActionKeyDirection::Any,
+            with: ActionKeyWith::OnGround,
+            wait_before_use_ticks: 0,
+            wait_after_use_ticks: 0,
+            force: false,
+            action_info: None,
+            state: State::Precondition,
+            verify_direction_retries: 0,
+            pending_transition: PendingTransition::None,
+        };
+        let mut player = make_player(use_key);
+        player.context.is_grounded = true;
+        player.context.is_stationary = false;
+
+        // Grounded but moving satisfies on ground
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(
+            player.state,
+            Player::UseKey(UseKey {
+                state: State::Using(_, _),
+                ..
+            })
+        );
+
+        // Grounded but moving does not satisfy stationary
+        player.state = Player::UseKey(UseKey {
+            with: ActionKeyWith::Stationary,
+            ..use_key
+        });
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(
+            player.state,
+            Player::UseKey(UseKey {
+                state: State::EnsuringUseWith,
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn update_use_key_state_forced_skips_precondition() {
+        let resources = Resources::new(None, None);
+        let mut player = make_player(UseKey {
+            key: KeyBinding::A,
+            link_key: None,
+            count: 1,
+            current_count: 0,
+            direction: ActionKeyDirection::Right,
+            with: ActionKeyWith::Stationary,
+            wait_before_use_ticks: 0,
+            wait_after_use_ticks: 0,
+            force: true,
+            action_info: None,
+            state: State::Precondition,
+            verify_direction_retries: 0,
+            pending_transition: PendingTransition::None,
+        });
+        player.context.last_known_direction = ActionKeyDirection::Left;
+        player.context.is_stationary = false;
+
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+
+        assert_matches!(
+            player.state,
+            Player::UseKey(UseKey {
+                state: State::Using(_, false),
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn update_use_key_state_auto_mob_attack_interval() {
+        let mut resources = Resources::new(None, None);
+        let use_key = UseKey {
+            key: KeyBinding::A,
+            link_key: None,
+            count: 1,
+            current_count: 0,
+            direction: ActionKeyDirection::Any,
+            with: ActionKeyWith::Any,
+            wait_before_use_ticks: 0,
+            wait_after_use_ticks: 0,
+            force: false,
+            action_info: Some(ActionInfo::AutoMobbing {
+                should_terminate: true,
+            }),
+            state: State::Precondition,
+            verify_direction_retries: 0,
+            pending_transition: PendingTransition::None,
+        };
+        let mut player = make_player(use_key);
+        player.context.config.auto_mob_attack_interval_millis = 10 * MS_PER_TICK;
+        player.context.auto_mob_last_attack_tick = Some(5);
+
+        for tick in 5..15 {
+            resources.tick = tick;
+            update_use_key_state(&resources, &mut player, Minimap::Detecting);
+            assert_matches!(
+                player.state,
+                Player::UseKey(UseKey {
+                    state: State::Precondition,
+                    ..
+                })
+            );
+        }
+
+        resources.tick = 15;
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(
+            player.state,
+            Player::UseKey(UseKey {
+                state: State::Using(_, false),
+                ..
+            })
+        );
+    }
+
     #[test]
     fn update_use_key_state_ensuring_use_with_double_jump() {
         let resources = Resources::new(None, None);
@@ -586,8 +817,10 @@ mod tests {
             with: ActionKeyWith::DoubleJump,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            force: false,
             action_info: None,
             state: State::Precondition,
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
         });
         player.context.last_known_pos = Some(Point::default());
@@ -610,6 +843,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_use_key_state_ensuring_use_with_falling() {
+        let resources = Resources::new(None, None);
+        let use_key = UseKey {
+            key: KeyBinding::A,
+            link_key: None,
+            count: 1,
+            current_count: 0,
+            direction: ActionKeyDirection::Any,
+            with: ActionKeyWith::Falling,
+            wait_before_use_ticks: 0,
+            wait_after_use_ticks: 0,
+            force: false,
+            action_info: None,
+            state: State::Precondition,
+            verify_direction_retries: 0,
+            pending_transition: PendingTransition::None,
+        };
+        let mut player = make_player(use_key);
+        player.context.last_known_pos = Some(Point::new(10, 10));
+        player.context.is_stationary = true;
+
+        // Start EnsuringUseWith when not falling
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(
+            player.state,
+            Player::UseKey(UseKey {
+                state: State::EnsuringUseWith,
+                ..
+            })
+        );
+
+        // Transitions to falling
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(player.state, Player::Falling { anchor, .. } if anchor == Point::new(10, 10));
+
+        // Does not use the key when not moving downward
+        player.state = Player::UseKey(use_key);
+        player.context.is_stationary = false;
+        player.context.last_movement = Some(LastMovement::Falling);
+        player.context.y_delta = 0;
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(
+            player.state,
+            Player::UseKey(UseKey {
+                state: State::EnsuringUseWith,
+                ..
+            })
+        );
+
+        // Uses the key when falling
+        player.state = Player::UseKey(use_key);
+        player.context.y_delta = -2;
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(
+            player.state,
+            Player::UseKey(UseKey {
+                state: State::Using(_, _),
+                ..
+            })
+        );
+    }
+
     #[test]
     fn update_use_key_state_changing_direction() {
         let mut keys = MockInput::new();
@@ -629,8 +925,10 @@ mod tests {
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            force: false,
             action_info: None,
             state: State::Precondition,
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
         };
         let mut player = make_player(use_key);
@@ -676,6 +974,158 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_use_key_state_changing_direction_configured_timeout() {
+        let resources = Resources::new(None, None);
+        let mut use_key = UseKey {
+            key: KeyBinding::A,
+            link_key: None,
+            count: 1,
+            current_count: 0,
+            direction: ActionKeyDirection::Left,
+            with: ActionKeyWith::Any,
+            wait_before_use_ticks: 0,
+            wait_after_use_ticks: 0,
+            force: false,
+            action_info: None,
+            state: State::ChangingDirection(Timeout {
+                started: true,
+                total: CHANGE_DIRECTION_TIMEOUT,
+                current: CHANGE_DIRECTION_TIMEOUT,
+            }),
+            verify_direction_retries: 0,
+            pending_transition: PendingTransition::None,
+        };
+        let mut player = make_player(use_key);
+        player.context.config.change_direction_timeout_ticks = 5;
+
+        // Still changing direction past the default timeout
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(
+            player.state,
+            Player::UseKey(UseKey {
+                state: State::ChangingDirection(Timeout { current: 4, .. }),
+                ..
+            })
+        );
+
+        // Ends at the configured timeout
+        use_key.state = State::ChangingDirection(Timeout {
+            started: true,
+            total: 5,
+            current: 5,
+        });
+        player.state = Player::UseKey(use_key);
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(
+            player.context.last_known_direction,
+            ActionKeyDirection::Left
+        );
+        assert_matches!(
+            player.state,
+            Player::UseKey(UseKey {
+                state: State::Precondition,
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn update_use_key_state_verify_direction_defers_until_movement_confirmed() {
+        let mut keys = MockInput::new();
+        keys.expect_send_key()
+            .withf(|k| matches!(k, KeyKind::A))
+            .never();
+        let resources = Resources::new(Some(keys), None);
+        let use_key = UseKey {
+            key: KeyBinding::A,
+            link_key: None,
+            count: 1,
+            current_count: 0,
+            direction: ActionKeyDirection::Left,
+            with: ActionKeyWith::Any,
+            wait_before_use_ticks: 0,
+            wait_after_use_ticks: 0,
+            force: false,
+            action_info: None,
+            state: State::Precondition,
+            verify_direction_retries: 0,
+            pending_transition: PendingTransition::None,
+        };
+        let mut player = make_player(use_key);
+        player.context.config.verify_direction_by_detection = true;
+        player.context.last_known_direction = ActionKeyDirection::Left;
+        player.context.last_movement_direction = ActionKeyDirection::Right;
+
+        // Moved right so changes direction instead of using the key
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(
+            player.state,
+            Player::UseKey(UseKey {
+                state: State::ChangingDirection(_),
+                ..
+            })
+        );
+
+        // Moved left so uses the key
+        player.context.last_movement_direction = ActionKeyDirection::Left;
+        player.state = Player::UseKey(use_key);
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(
+            player.state,
+            Player::UseKey(UseKey {
+                state: State::Using(_, _),
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn update_use_key_state_verify_direction_uses_key_after_max_retries() {
+        let resources = Resources::new(None, None);
+        let use_key = UseKey {
+            key: KeyBinding::A,
+            link_key: None,
+            count: 1,
+            current_count: 0,
+            direction: ActionKeyDirection::Left,
+            with: ActionKeyWith::Any,
+            wait_before_use_ticks: 0,
+            wait_after_use_ticks: 0,
+            force: false,
+            action_info: None,
+            state: State::Precondition,
+            verify_direction_retries: 0,
+            pending_transition: PendingTransition::None,
+        };
+        let mut player = make_player(use_key);
+        player.context.config.verify_direction_by_detection = true;
+        player.context.last_known_direction = ActionKeyDirection::Left;
+        player.context.last_movement_direction = ActionKeyDirection::Right;
+
+        // Movement keeps disagreeing so changes direction up to the maximum retries
+        for retries in 1..=MAX_VERIFY_DIRECTION_RETRIES {
+            update_use_key_state(&resources, &mut player, Minimap::Detecting);
+            let Player::UseKey(mut use_key) = player.state else {
+                panic!("state is not using key");
+            };
+            assert_matches!(use_key.state, State::ChangingDirection(_));
+            assert_eq!(use_key.verify_direction_retries, retries);
+            use_key.state = State::Precondition;
+            player.state = Player::UseKey(use_key);
+        }
+
+        // Uses the key anyway
+        update_use_key_state(&resources, &mut player, Minimap::Detecting);
+        assert_matches!(
+            player.state,
+            Player::UseKey(UseKey {
+                state: State::Using(_, _),
+                ..
+            })
+        );
+    }
+
     #[test]
     fn update_use_key_state_repeats_until_count_reached() {
         let mut keys = MockInput::new();
@@ -692,8 +1142,10 @@ mod tests {
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            force: false,
             action_info: None,
             state: State::Precondition,
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
         };
         let mut player = make_player(use_key);
@@ -744,8 +1196,10 @@ mod tests {
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 5,
             wait_after_use_ticks: 0,
+            force: false,
             action_info: None,
             state: State::Precondition,
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
         };
         let mut player = make_player(use_key);
@@ -757,6 +1211,7 @@ mod tests {
             player.context.stalling_timeout_state,
             Some(Player::UseKey(UseKey {
                 state: State::Using(_, _),
+                verify_direction_retries: 0,
                 pending_transition: PendingTransition::None,
                 ..
             }))
@@ -779,8 +1234,10 @@ mod tests {
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 7,
+            force: false,
             action_info: None,
             state: State::Using(Timeout::default(), false),
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
         };
         let mut player = make_player(use_key);
@@ -792,6 +1249,7 @@ mod tests {
             player.context.stalling_timeout_state,
             Some(Player::UseKey(UseKey {
                 state: State::Postcondition,
+                verify_direction_retries: 0,
                 pending_transition: PendingTransition::None,
                 ..
             }))
@@ -824,8 +1282,10 @@ mod tests {
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            force: false,
             action_info: None,
             state: State::Using(Timeout::default(), false),
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
         };
         let mut player = make_player(use_key);
@@ -902,8 +1362,10 @@ mod tests {
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            force: false,
             action_info: None,
             state: State::Using(Timeout::default(), false),
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
         };
         let mut player = make_player(use_key);
@@ -947,8 +1409,10 @@ mod tests {
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            force: false,
             action_info: None,
             state: State::Using(Timeout::default(), false),
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
         };
         let mut player = make_player(use_key);
@@ -999,8 +1463,10 @@ mod tests {
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            force: false,
             action_info: None,
             state: State::Using(Timeout::default(), false),
+            verify_direction_retries: 0,
             pending_transition: PendingTransition::None,
         };
         let mut player = make_player(use_key);
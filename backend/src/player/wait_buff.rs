@@ -0,0 +1,119 @@
+use super::{
+    Player,
+    actions::next_action,
+    timeout::{Lifecycle, Timeout, next_timeout_lifecycle},
+};
+use crate::{
+    buff::{Buff, BuffEntities, BuffKind},
+    player::PlayerEntity,
+    transition, transition_from_action,
+};
+
+/// Maximum number of ticks to wait for the buff before giving up.
+const TIMEOUT: u32 = 150;
+
+/// Updates the [`Player::WaitingForBuff`] contextual state.
+///
+/// This state stalls until the player has the buff specified by `kind` or until timing out.
+/// Either way, the [`PlayerAction::WaitForBuff`] is considered complete.
+pub fn update_waiting_for_buff_state(
+    player: &mut PlayerEntity,
+    buffs: &BuffEntities,
+    timeout: Timeout,
+    kind: BuffKind,
+) {
+    let has_buff = matches!(buffs[kind].state, Buff::Yes);
+    if has_buff && next_action(&player.context).is_some() {
+        transition_from_action!(player, Player::Idle);
+    }
+
+    match next_timeout_lifecycle(timeout, TIMEOUT) {
+        Lifecycle::Started(timeout) | Lifecycle::Updated(timeout) => {
+            transition!(player, Player::WaitingForBuff(timeout, kind))
+        }
+        Lifecycle::Ended => {
+            if next_action(&player.context).is_some() {
+                transition_from_action!(player, Player::Idle);
+            }
+            transition!(player, Player::Idle)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::{
+        buff::{BuffContext, BuffEntity},
+        player::{PlayerAction, PlayerContext},
+    };
+
+    fn mock_buffs(kind: BuffKind, state: Buff) -> BuffEntities {
+        BuffKind::iter()
+            .map(|other| BuffEntity {
+                state: if other == kind { state } else { Buff::No },
+                context: BuffContext::new(other),
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn update_waiting_for_buff_state_stalls_until_buff_detected() {
+        let mut context = PlayerContext::default();
+        context.set_priority_action(None, PlayerAction::WaitForBuff(BuffKind::SayramElixir));
+        let mut player = PlayerEntity {
+            state: Player::WaitingForBuff(Timeout::default(), BuffKind::SayramElixir),
+            context,
+        };
+
+        // Stalls while the buff is missing
+        let buffs = mock_buffs(BuffKind::SayramElixir, Buff::No);
+        for _ in 0..3 {
+            let Player::WaitingForBuff(timeout, kind) = player.state else {
+                panic!("state is not waiting for buff");
+            };
+            update_waiting_for_buff_state(&mut player, &buffs, timeout, kind);
+            assert_matches!(
+                player.state,
+                Player::WaitingForBuff(_, BuffKind::SayramElixir)
+            );
+            assert!(player.context.has_priority_action());
+        }
+
+        // Continues once the buff is detected
+        let buffs = mock_buffs(BuffKind::SayramElixir, Buff::Yes);
+        let Player::WaitingForBuff(timeout, kind) = player.state else {
+            panic!("state is not waiting for buff");
+        };
+        update_waiting_for_buff_state(&mut player, &buffs, timeout, kind);
+        assert_matches!(player.state, Player::Idle);
+        assert!(!player.context.has_priority_action());
+    }
+
+    #[test]
+    fn update_waiting_for_buff_state_continues_on_timeout() {
+        let mut context = PlayerContext::default();
+        context.set_priority_action(None, PlayerAction::WaitForBuff(BuffKind::SayramElixir));
+        let mut player = PlayerEntity {
+            state: Player::Idle,
+            context,
+        };
+        let buffs = mock_buffs(BuffKind::SayramElixir, Buff::No);
+        let timeout = Timeout {
+            started: true,
+            current: TIMEOUT,
+            total: TIMEOUT,
+        };
+
+        update_waiting_for_buff_state(&mut player, &buffs, timeout, BuffKind::SayramElixir);
+
+        assert_matches!(player.state, Player::Idle);
+        assert!(!player.context.has_priority_action());
+    }
+}
@@ -0,0 +1,46 @@
+use std::collections::VecDeque;
+
+use crate::player::PlayerAction;
+
+/// Maximum number of actions kept in an [`ActionLog`].
+const MAX_ACTION_LOG_LEN: usize = 500;
+
+/// A bounded log of normal [`PlayerAction`]s in the order they were passed to the player.
+#[derive(Debug, Default)]
+pub struct ActionLog {
+    actions: VecDeque<PlayerAction>,
+}
+
+impl ActionLog {
+    /// Records `action`, dropping the oldest action if the log is full.
+    pub fn record(&mut self, action: PlayerAction) {
+        if self.actions.len() >= MAX_ACTION_LOG_LEN {
+            self.actions.pop_front();
+        }
+        self.actions.push_back(action);
+    }
+
+    /// Retrieves the recorded actions, oldest first.
+    pub fn actions(&self) -> Vec<PlayerAction> {
+        self.actions.iter().cloned().collect()
+    }
+}
+
+/// A source that feeds a previously recorded sequence of [`PlayerAction`]s back in order.
+#[derive(Debug, Default)]
+pub struct ActionReplay {
+    actions: VecDeque<PlayerAction>,
+}
+
+impl ActionReplay {
+    pub fn new(actions: Vec<PlayerAction>) -> Self {
+        Self {
+            actions: actions.into(),
+        }
+    }
+
+    /// Takes the next action to replay or [`None`] if the replay has finished.
+    pub fn next_action(&mut self) -> Option<PlayerAction> {
+        self.actions.pop_front()
+    }
+}
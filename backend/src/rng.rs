@@ -68,6 +68,27 @@ impl Rng {
         iter.choose(&mut self.rng.borrow_mut())
     }
 
+    /// Randomly picks an index of `weights` with probability proportional to its weight.
+    ///
+    /// Returns [`None`] if all weights are zero.
+    pub fn random_weighted_index(&self, weights: &[u32]) -> Option<usize> {
+        let total = weights.iter().map(|weight| *weight as u64).sum::<u64>();
+        if total == 0 {
+            return None;
+        }
+
+        let mut sample = self.random_range(0..total);
+        weights.iter().position(|weight| {
+            let weight = *weight as u64;
+            if sample < weight {
+                true
+            } else {
+                sample -= weight;
+                false
+            }
+        })
+    }
+
     /// Samples a random `(delay, tick count)` pair.
     ///
     /// The delay is sampled from a normal distribution with mean `mean_ms` and
@@ -141,6 +162,20 @@ mod tests {
         assert_eq!(count, 2);
     }
 
+    #[test]
+    fn random_weighted_index_seeded_matches_weights() {
+        let rng = Rng::new(SEED, 1337);
+        let mut counts = [0; 3];
+        for _ in 0..1000 {
+            counts[rng.random_weighted_index(&[1, 0, 3]).unwrap()] += 1;
+        }
+
+        assert_eq!(counts[1], 0);
+        assert!((200..300).contains(&counts[0]));
+        assert!((700..800).contains(&counts[2]));
+        assert_eq!(rng.random_weighted_index(&[0, 0, 0]), None);
+    }
+
     #[test]
     fn random_mu_std_pair_seeded() {
         let rng = Rng::new(SEED, 1337);
@@ -1,5 +1,6 @@
 use std::{
     assert_matches::debug_assert_matches,
+    cell::RefCell,
     collections::{HashSet, VecDeque},
     fmt::Debug,
     sync::atomic::{AtomicU32, Ordering},
@@ -13,19 +14,23 @@ use mockall::{automock, concretize};
 use opencv::core::{Point, Rect};
 use ordered_hash_map::OrderedHashMap;
 
+#[cfg(debug_assertions)]
+use crate::replay::{ActionLog, ActionReplay};
 use crate::{
     ActionKeyDirection, ActionKeyWith, Bound, FamiliarRarity, KeyBinding, MobbingKey, Position,
     SwappableFamiliars,
     array::Array,
     buff::{Buff, BuffKind},
-    database::{Action, ActionCondition, ActionKey, ActionMove, EliteBossBehavior},
+    database::{Action, ActionCondition, ActionKey, EliteBossBehavior},
     detect::{BoosterKind, BoosterState},
     ecs::{Resources, World},
-    minimap::Minimap,
+    minimap::{Minimap, MinimapIdle},
+    pathing::find_platforms_bound,
     player::{
         AutoMob, Booster, FamiliarsSwap, GRAPPLING_THRESHOLD, Key, Panic, PanicTo, PingPong,
         PingPongDirection, PlayerAction, PlayerContext, PlayerEntity, Quadrant, UseBooster,
     },
+    rng::Rng,
     run::MS_PER_TICK,
     skill::{Skill, SkillKind},
     task::{Task, Update, update_detection_task},
@@ -33,6 +38,16 @@ use crate::{
 
 const COOLDOWN_BETWEEN_QUEUE_MILLIS: u128 = 20_000;
 const AUTO_MOB_SAME_QUAD_THRESHOLD: u32 = 5;
+/// Number of consecutive mob detections inside the same corner without covering new ground
+/// before the player is forced to the opposite bound.
+const AUTO_MOB_CORNER_STUCK_THRESHOLD: u32 = 8;
+/// Maximum distance from the corner anchor position still considered as the same ground.
+const AUTO_MOB_CORNER_STUCK_DISTANCE: i32 = 5;
+/// Percentage of the bound width from either edge considered as a corner.
+const AUTO_MOB_CORNER_PERCENT: i32 = 20;
+/// Minimum milliseconds between two auto mobbing re-centers.
+const AUTO_MOB_RECENTER_COOLDOWN_MILLIS: u128 = 10_000;
+const FAMILIARS_SWAP_WINDOW_MILLIS: u128 = 3_600_000;
 
 /// [`Condition`] evaluation result.
 enum ConditionResult {
@@ -113,8 +128,12 @@ pub enum RotatorMode {
     StartToEnd,
     #[default]
     StartToEndThenReverse,
-    AutoMobbing(MobbingKey, Bound),
+    /// Auto mobbing inside the provided bounds, cycling to the next bound when no mob is found.
+    AutoMobbing(MobbingKey, Vec<Bound>),
     PingPong(MobbingKey, Bound),
+    /// Ping pong inside the provided bound while alternating between its upper and lower halves
+    /// on each turnaround to patrol the whole bound.
+    Patrol(MobbingKey, Bound),
 }
 
 #[derive(Debug)]
@@ -122,10 +141,15 @@ pub struct RotatorBuildArgs<'a> {
     pub mode: RotatorMode,
     pub actions: &'a [Action],
     pub buffs: &'a [(BuffKind, KeyBinding)],
+    /// Buffs from [`Self::buffs`] to also use while [`Operation`] is halting.
+    pub halting_buffs: &'a [BuffKind],
     pub familiar_essence_key: KeyBinding,
     pub familiar_swappable_slots: SwappableFamiliars,
     pub familiar_swappable_rarities: &'a HashSet<FamiliarRarity>,
     pub familiar_swap_check_millis: u64,
+    pub familiar_sort_by_level: bool,
+    /// Maximum number of familiars swaps within an hour, zero means no limit.
+    pub familiar_max_swaps_per_hour: u32,
     pub elite_boss_behavior: EliteBossBehavior,
     pub elite_boss_behavior_key: KeyBinding,
     pub enable_panic_mode: bool,
@@ -133,6 +157,12 @@ pub struct RotatorBuildArgs<'a> {
     pub enable_familiars_swapping: bool,
     pub enable_reset_normal_actions_on_erda: bool,
     pub enable_using_vip_booster: bool,
+    pub startup_delay_ticks: u32,
+    pub enable_buffs_burst_on_start: bool,
+    /// Inclusive range of ticks to idle for between normal actions rotations.
+    pub rotation_idle_ticks: (u32, u32),
+    /// The `(width, height)` of the minimap the bounds in [`Self::mode`] are relative to.
+    pub minimap_size: (i32, i32),
 }
 
 /// Handles rotating provided [`PlayerAction`]s.
@@ -158,6 +188,24 @@ pub trait Rotator: Debug + 'static {
     /// If [`Operation`] is currently halting, it does not rotate the built actions but only the
     /// side-loaded actions added by [`Self::inject_action`].
     fn rotate_action(&mut self, resources: &Resources, world: &mut World);
+
+    /// Retrieves the name and milliseconds until ready of each [`ActionCondition::EveryMillis`]
+    /// priority action in building order.
+    fn action_schedules(&self) -> Vec<(String, u64)>;
+
+    /// Retrieves the recently rotated normal actions, oldest first.
+    ///
+    /// Normal actions are only logged in debug builds.
+    #[cfg(debug_assertions)]
+    fn action_log(&self) -> Vec<PlayerAction>;
+
+    /// Replays `actions` in order in place of the normal actions built with
+    /// [`Self::build_actions`].
+    ///
+    /// Priority actions are still rotated as usual. The normal actions rotation resumes once all
+    /// `actions` have been replayed.
+    #[cfg(debug_assertions)]
+    fn replay_actions(&mut self, actions: Vec<PlayerAction>);
 }
 
 #[derive(Default, Debug)]
@@ -179,6 +227,26 @@ pub struct DefaultRotator {
     /// This limits the number of detections can be done inside the same quad as to help player
     /// advances to the next quad.
     auto_mob_quadrant_consecutive_count: Option<(Quadrant, u32)>,
+    /// Tracks the anchor position and number of times a mob detection has been completed while
+    /// the player stays around that position inside a corner of the bound.
+    ///
+    /// This helps the player escape a corner it keeps camping at.
+    auto_mob_corner_stuck_count: Option<(Point, u32)>,
+    /// The last [`Instant`] the player was re-centered inside the bound.
+    ///
+    /// This prevents re-centering from pulling the player back on every mob detection.
+    auto_mob_last_recenter_time: Option<Instant>,
+    /// Index of the currently used bound when [`Self::normal_rotate_mode`] is
+    /// [`RotatorMode::AutoMobbing`].
+    auto_mob_bound_index: usize,
+    /// Whether the next ping pong action uses the lower half of the bound when
+    /// [`Self::normal_rotate_mode`] is [`RotatorMode::Patrol`].
+    patrol_lower_half: bool,
+    /// The `(width, height)` of the minimap the bounds in [`Self::normal_rotate_mode`] are
+    /// relative to.
+    ///
+    /// Bounds are scaled to the currently detected minimap using this size.
+    normal_minimap_size: (i32, i32),
 
     priority_actions: OrderedHashMap<u32, PriorityAction>,
     /// The currently executing [`RotatorAction::Linked`] action
@@ -192,6 +260,30 @@ pub struct DefaultRotator {
     /// These are actions injected externally and to be executed as appropriate with the current
     /// [`Self::priority_actions_queue`]. These actions are run only once and do not have an ID.
     priority_actions_side_queue: VecDeque<RotatorAction>,
+
+    /// Number of ticks to wait after [`Operation`] starts running before rotating any action.
+    startup_delay_ticks: u32,
+    /// The tick at which [`Operation`] last started running.
+    running_since_tick: Option<u64>,
+    /// Buff keys to inject into [`Self::priority_actions_side_queue`] when [`Operation`] starts
+    /// running.
+    buffs_burst_keys: Vec<KeyBinding>,
+    /// [`PriorityAction`] ids of buffs to also use while [`Operation`] is halting.
+    halting_buff_ids: Vec<u32>,
+
+    /// Inclusive range of ticks to idle for after completing a normal actions rotation.
+    rotation_idle_ticks: (u32, u32),
+    /// Number of ticks to idle for before starting the next normal actions rotation.
+    normal_idle_ticks: u32,
+    /// The tick at which idling before the next normal actions rotation started.
+    normal_idle_since_tick: Option<u64>,
+
+    /// Log of normal actions passed to the player.
+    #[cfg(debug_assertions)]
+    action_log: ActionLog,
+    /// The replay source used in place of [`Self::normal_rotate_mode`] if any.
+    #[cfg(debug_assertions)]
+    action_replay: Option<ActionReplay>,
 }
 
 impl DefaultRotator {
@@ -199,6 +291,36 @@ impl DefaultRotator {
     fn reset_normal_actions_queue(&mut self) {
         self.normal_index = 0;
         self.normal_queuing_linked_action = None;
+        self.normal_idle_ticks = 0;
+        self.normal_idle_since_tick = None;
+    }
+
+    /// Samples the number of ticks to idle for before starting the next normal actions rotation.
+    #[inline]
+    fn queue_normal_idle(&mut self, rng: &Rng) {
+        let (min, max) = self.rotation_idle_ticks;
+        if max == 0 {
+            return;
+        }
+        self.normal_idle_ticks = rng.random_range(min.min(max)..=max);
+    }
+
+    /// Idles for [`Self::normal_idle_ticks`] before starting the next normal actions rotation.
+    ///
+    /// Returns `true` if the rotator is still idling.
+    #[inline]
+    fn rotate_normal_idle(&mut self, tick: u64) -> bool {
+        if self.normal_idle_ticks == 0 {
+            return false;
+        }
+
+        let since_tick = *self.normal_idle_since_tick.get_or_insert(tick);
+        if tick.saturating_sub(since_tick) < self.normal_idle_ticks as u64 {
+            return true;
+        }
+        self.normal_idle_ticks = 0;
+        self.normal_idle_since_tick = None;
+        false
     }
 
     /// Rotates the actions inside the [`Self::priority_actions`]
@@ -324,6 +446,25 @@ impl DefaultRotator {
         }
     }
 
+    /// Rotates the buffs in [`Self::halting_buff_ids`] while [`Operation`] is halting.
+    ///
+    /// Buffs that can be queued are pushed to [`Self::priority_actions_side_queue`] since
+    /// [`Self::priority_actions_queue`] is not rotated while halting.
+    fn rotate_halting_buffs(&mut self, resources: &Resources, world: &World) {
+        for id in self.halting_buff_ids.iter() {
+            let Some(action) = self.priority_actions.get_mut(id) else {
+                continue;
+            };
+            if let ConditionResult::Queue =
+                (action.condition.0)(resources, world, action.last_queued_time)
+            {
+                self.priority_actions_side_queue
+                    .push_back(action.inner.clone());
+                action.last_queued_time = Some(Instant::now());
+            }
+        }
+    }
+
     /// Rotates the actions inside the [`Self::priority_actions_queue`].
     ///
     /// If there is any on-going linked action:
@@ -437,6 +578,9 @@ impl DefaultRotator {
         }
     }
 
+    /// Rotates auto mobbing action inside `bound`.
+    ///
+    /// Returns `true` if no mob is found inside `bound`.
     fn rotate_auto_mobbing(
         &mut self,
         resources: &Resources,
@@ -444,29 +588,44 @@ impl DefaultRotator {
         minimap_state: Minimap,
         key: MobbingKey,
         bound: Bound,
-    ) {
+    ) -> bool {
         if player_context.has_normal_action() {
-            return;
+            return false;
         }
 
         let Minimap::Idle(idle) = minimap_state else {
-            return;
+            return false;
         };
         let Some(pos) = player_context.last_known_pos else {
-            return;
+            return false;
         };
+        let bound = scaled_bound(bound, self.normal_minimap_size, idle.bbox);
         let bound = if player_context.config.auto_mob_platforms_bound {
-            idle.platforms_bound.unwrap_or(bound.into())
+            scaled_platforms_bound(idle, self.normal_minimap_size).unwrap_or(bound)
         } else {
-            bound.into()
+            bound
         };
 
+        if let Some(x) = self.auto_mob_update_recenter(
+            pos,
+            bound,
+            player_context.config.auto_mob_recenter_percent,
+        ) {
+            let y = idle.bbox.height - (bound.y + bound.height / 2);
+            let point = player_context
+                .auto_mob_pick_reachable_y_position(resources, minimap_state, Point::new(x, y))
+                .unwrap_or(Point::new(x, pos.y));
+            debug!(target: "rotator", "auto mob re-centering to {point:?} from {pos:?}");
+            player_context.set_normal_action(None, auto_mob_action(key, point, true));
+            return false;
+        }
+
         let Update::Ok(points) =
             update_detection_task(resources, 0, &mut self.auto_mob_task, move |detector| {
                 detector.detect_mobs(idle.bbox, bound, pos)
             })
         else {
-            return;
+            return false;
         };
         // FIXME: Collect to a Vec first because `context.rng` needs to be borrowed again.
         let points = points
@@ -488,6 +647,16 @@ impl DefaultRotator {
                 })
             })
             .collect::<Vec<_>>();
+
+        if let Some(x) = self.auto_mob_update_corner_stuck(pos, bound) {
+            let point = player_context
+                .auto_mob_pick_reachable_y_position(resources, minimap_state, Point::new(x, pos.y))
+                .unwrap_or(Point::new(x, pos.y));
+            debug!(target: "rotator", "auto mob stuck in corner, repositioning to {point:?} from {pos:?}");
+            player_context.set_normal_action(None, auto_mob_action(key, point, true));
+            return false;
+        }
+
         let mut use_pathing_point = false;
 
         if let Some(last_quad) = player_context.auto_mob_last_quadrant()
@@ -512,45 +681,75 @@ impl DefaultRotator {
         }
 
         let mut is_pathing = use_pathing_point;
+        let mut has_no_mob = false;
         let point = if use_pathing_point {
             player_context.auto_mob_pathing_point(resources, minimap_state, bound)
         } else {
-            resources
-                .rng
-                .random_choose(points.into_iter())
-                .unwrap_or_else(|| {
-                    is_pathing = true;
-                    player_context.auto_mob_pathing_point(resources, minimap_state, bound)
-                })
-        };
-        let wait_before_ticks = (key.wait_before_millis / MS_PER_TICK) as u32;
-        let wait_before_ticks_random_range =
-            (key.wait_before_millis_random_range / MS_PER_TICK) as u32;
-        let wait_after_ticks = (key.wait_after_millis / MS_PER_TICK) as u32;
-        let wait_after_ticks_random_range =
-            (key.wait_after_millis_random_range / MS_PER_TICK) as u32;
-        let position = Position {
-            x: point.x,
-            x_random_range: 0,
-            y: point.y,
-            allow_adjusting: false,
+            auto_mob_pick_point(
+                &resources.rng,
+                points,
+                player_context.config.auto_mob_prefer_higher_platforms,
+            )
+            .unwrap_or_else(|| {
+                is_pathing = true;
+                has_no_mob = true;
+                player_context.auto_mob_pathing_point(resources, minimap_state, bound)
+            })
         };
 
-        player_context.set_normal_action(
-            None,
-            PlayerAction::AutoMob(AutoMob {
-                key: key.key,
-                link_key: key.link_key,
-                count: key.count.max(1),
-                with: key.with,
-                wait_before_ticks,
-                wait_before_ticks_random_range,
-                wait_after_ticks,
-                wait_after_ticks_random_range,
-                position,
-                is_pathing,
-            }),
-        );
+        player_context.set_normal_action(None, auto_mob_action(key, point, is_pathing));
+        has_no_mob
+    }
+
+    /// Retrieves the center x of `bound` to re-center the player at `pos` to.
+    ///
+    /// Returns [`None`] when the player has not strayed far enough or the last re-center was less
+    /// than [`AUTO_MOB_RECENTER_COOLDOWN_MILLIS`] ago.
+    fn auto_mob_update_recenter(&mut self, pos: Point, bound: Rect, percent: u32) -> Option<i32> {
+        if !at_least_millis_passed_since(
+            self.auto_mob_last_recenter_time,
+            AUTO_MOB_RECENTER_COOLDOWN_MILLIS,
+        ) {
+            return None;
+        }
+
+        let x = auto_mob_recenter_x(pos, bound, percent)?;
+        self.auto_mob_last_recenter_time = Some(Instant::now());
+        Some(x)
+    }
+
+    /// Updates the corner stuck count with the player current `pos` inside `bound`.
+    ///
+    /// Returns the x of the opposite side of `bound` when the player has not covered new ground
+    /// inside the same corner for [`AUTO_MOB_CORNER_STUCK_THRESHOLD`] mob detections.
+    fn auto_mob_update_corner_stuck(&mut self, pos: Point, bound: Rect) -> Option<i32> {
+        let corner_width = bound.width * AUTO_MOB_CORNER_PERCENT / 100;
+        let is_left_corner = pos.x <= bound.x + corner_width;
+        let is_right_corner = pos.x >= bound.x + bound.width - corner_width;
+        if !is_left_corner && !is_right_corner {
+            self.auto_mob_corner_stuck_count = None;
+            return None;
+        }
+
+        let (anchor, count) = self.auto_mob_corner_stuck_count.get_or_insert((pos, 0));
+        if (anchor.x - pos.x).abs() > AUTO_MOB_CORNER_STUCK_DISTANCE
+            || (anchor.y - pos.y).abs() > AUTO_MOB_CORNER_STUCK_DISTANCE
+        {
+            *anchor = pos;
+            *count = 0;
+        }
+
+        *count += 1;
+        if *count < AUTO_MOB_CORNER_STUCK_THRESHOLD {
+            return None;
+        }
+
+        self.auto_mob_corner_stuck_count = None;
+        if is_left_corner {
+            Some(bound.x + bound.width - corner_width)
+        } else {
+            Some(bound.x + corner_width)
+        }
     }
 
     fn rotate_ping_pong(
@@ -572,6 +771,7 @@ impl DefaultRotator {
         };
 
         let bbox = idle.bbox;
+        let bound = scaled_bound(bound, self.normal_minimap_size, bbox);
         let dist_left = pos.x - bbox.x;
         let dist_right = (bbox.x + bbox.width) - pos.x;
         let direction = if dist_left > dist_right {
@@ -605,17 +805,45 @@ impl DefaultRotator {
         );
     }
 
-    fn rotate_start_to_end(&mut self, player_context: &mut PlayerContext) {
+    /// Rotates ping pong action inside either the upper or lower half of `bound`.
+    ///
+    /// The half alternates each time a new ping pong action is rotated so that the player also
+    /// moves vertically when turning around.
+    fn rotate_patrol(
+        &mut self,
+        player_context: &mut PlayerContext,
+        minimap_state: Minimap,
+        key: MobbingKey,
+        bound: Bound,
+    ) {
+        if player_context.has_normal_action() {
+            return;
+        }
+
+        let half = patrol_half_bound(bound, self.patrol_lower_half);
+        self.rotate_ping_pong(player_context, minimap_state, key, half);
+        if player_context.has_normal_action() {
+            self.patrol_lower_half = !self.patrol_lower_half;
+        }
+    }
+
+    fn rotate_start_to_end(&mut self, resources: &Resources, player_context: &mut PlayerContext) {
         if player_context.has_normal_action() || self.normal_actions.is_empty() {
             return;
         }
         if self.rotate_queuing_linked_action(player_context, false) {
             return;
         }
+        if self.rotate_normal_idle(resources.tick) {
+            return;
+        }
 
         debug_assert!(self.normal_index < self.normal_actions.len());
         let (id, action) = self.normal_actions[self.normal_index].clone();
         self.normal_index = (self.normal_index + 1) % self.normal_actions.len();
+        if self.normal_index == 0 {
+            self.queue_normal_idle(&resources.rng);
+        }
         match action {
             RotatorAction::Single(action) => {
                 player_context.set_normal_action(Some(id), action);
@@ -627,13 +855,20 @@ impl DefaultRotator {
         }
     }
 
-    fn rotate_start_to_end_then_reverse(&mut self, player_context: &mut PlayerContext) {
+    fn rotate_start_to_end_then_reverse(
+        &mut self,
+        resources: &Resources,
+        player_context: &mut PlayerContext,
+    ) {
         if player_context.has_normal_action() || self.normal_actions.is_empty() {
             return;
         }
         if self.rotate_queuing_linked_action(player_context, false) {
             return;
         }
+        if self.rotate_normal_idle(resources.tick) {
+            return;
+        }
 
         let len = self.normal_actions.len();
         if (self.normal_index + 1) == len {
@@ -651,6 +886,9 @@ impl DefaultRotator {
         let (id, action) = self.normal_actions[i].clone();
 
         self.normal_index = (self.normal_index + 1) % len;
+        if (self.normal_index + 1) == len {
+            self.queue_normal_idle(&resources.rng);
+        }
         match action {
             RotatorAction::Single(action) => {
                 player_context.set_normal_action(Some(id), action);
@@ -686,6 +924,69 @@ impl DefaultRotator {
         true
     }
 
+    /// Rotates normal actions using the replay source if any or [`Self::normal_rotate_mode`].
+    fn rotate_normal_actions(&mut self, resources: &Resources, world: &mut World) {
+        #[cfg(debug_assertions)]
+        if self.rotate_replay(&mut world.player.context) {
+            return;
+        }
+
+        match self.normal_rotate_mode {
+            RotatorMode::StartToEnd => {
+                self.rotate_start_to_end(resources, &mut world.player.context)
+            }
+            RotatorMode::StartToEndThenReverse => {
+                self.rotate_start_to_end_then_reverse(resources, &mut world.player.context)
+            }
+            RotatorMode::AutoMobbing(key, ref bounds) => {
+                let Some(bound) = bounds.get(self.auto_mob_bound_index).copied() else {
+                    return;
+                };
+                let len = bounds.len();
+                if self.rotate_auto_mobbing(
+                    resources,
+                    &mut world.player.context,
+                    world.minimap.state,
+                    key,
+                    bound,
+                ) {
+                    self.auto_mob_bound_index = (self.auto_mob_bound_index + 1) % len;
+                }
+            }
+            RotatorMode::PingPong(key, bound) => {
+                self.rotate_ping_pong(&mut world.player.context, world.minimap.state, key, bound)
+            }
+            RotatorMode::Patrol(key, bound) => {
+                self.rotate_patrol(&mut world.player.context, world.minimap.state, key, bound)
+            }
+        }
+    }
+
+    /// Passes the next replayed action to the player once the current normal action completes.
+    ///
+    /// Returns `false` if there is no replay in progress.
+    #[cfg(debug_assertions)]
+    fn rotate_replay(&mut self, player_context: &mut PlayerContext) -> bool {
+        let Some(replay) = self.action_replay.as_mut() else {
+            return false;
+        };
+        if player_context.has_normal_action() {
+            return true;
+        }
+
+        match replay.next_action() {
+            Some(action) => {
+                player_context.set_normal_action(None, action);
+                true
+            }
+            None => {
+                info!(target: "rotator", "action replay finished");
+                self.action_replay = None;
+                false
+            }
+        }
+    }
+
     #[inline]
     fn rotate_side_priority_action(&mut self, player_context: &mut PlayerContext) -> bool {
         if let Some(action) = self.priority_actions_side_queue.pop_front() {
@@ -711,10 +1012,13 @@ impl Rotator for DefaultRotator {
             mode,
             actions,
             buffs,
+            halting_buffs,
             familiar_essence_key,
             familiar_swappable_slots,
             familiar_swappable_rarities,
             familiar_swap_check_millis,
+            familiar_sort_by_level,
+            familiar_max_swaps_per_hour,
             elite_boss_behavior,
             elite_boss_behavior_key,
             enable_panic_mode,
@@ -722,19 +1026,32 @@ impl Rotator for DefaultRotator {
             enable_familiars_swapping,
             enable_reset_normal_actions_on_erda,
             enable_using_vip_booster,
+            startup_delay_ticks,
+            enable_buffs_burst_on_start,
+            rotation_idle_ticks,
+            minimap_size,
         } = args;
         self.reset_queue();
+        self.startup_delay_ticks = startup_delay_ticks;
+        self.rotation_idle_ticks = rotation_idle_ticks;
+        self.buffs_burst_keys = if enable_buffs_burst_on_start {
+            buffs.iter().map(|(_, key)| *key).collect()
+        } else {
+            vec![]
+        };
         self.normal_actions.clear();
         self.normal_rotate_mode = mode;
+        self.normal_minimap_size = minimap_size;
         self.normal_actions_reset_on_erda = enable_reset_normal_actions_on_erda;
         self.priority_actions.clear();
+        self.halting_buff_ids.clear();
 
         let mut i = 0;
         while i < actions.len() {
             let action = actions[i];
             let condition = action.condition();
             let queue_to_front = match action {
-                Action::Move(_) => false,
+                Action::Move(_) | Action::HoldPosition(_) | Action::WaitForBuff(_) => false,
                 Action::Key(ActionKey { queue_to_front, .. }) => queue_to_front.unwrap_or_default(),
             };
             let (action, offset) = rotator_action(action, i, actions);
@@ -793,13 +1110,14 @@ impl Rotator for DefaultRotator {
         if enable_familiars_swapping {
             self.priority_actions.insert(
                 self.id_counter.fetch_add(1, Ordering::Relaxed),
-                priority_action(
-                    RotatorAction::Single(PlayerAction::FamiliarsSwap(FamiliarsSwap {
+                familiars_swap_priority_action(
+                    FamiliarsSwap {
                         swappable_slots: familiar_swappable_slots,
                         swappable_rarities: Array::from_iter(familiar_swappable_rarities.clone()),
-                    })),
-                    ActionCondition::EveryMillis(familiar_swap_check_millis),
-                    true,
+                        sort_by_level: familiar_sort_by_level,
+                    },
+                    familiar_swap_check_millis,
+                    familiar_max_swaps_per_hour,
                 ),
             );
         }
@@ -815,11 +1133,15 @@ impl Rotator for DefaultRotator {
                 use_booster_priority_action(BoosterKind::Vip),
             );
         }
-        for (i, key) in buffs.iter().copied() {
-            self.priority_actions.insert(
-                self.id_counter.fetch_add(1, Ordering::Relaxed),
-                buff_priority_action(i, key),
-            );
+        // Buff actions are queued to front so the last inserted buff is used first. Inserts in
+        // reverse to have the first buff in `buffs` used first.
+        for (i, key) in buffs.iter().rev().copied() {
+            let id = self.id_counter.fetch_add(1, Ordering::Relaxed);
+            if halting_buffs.contains(&i) {
+                self.halting_buff_ids.push(id);
+            }
+            self.priority_actions
+                .insert(id, buff_priority_action(i, key));
         }
     }
 
@@ -831,6 +1153,10 @@ impl Rotator for DefaultRotator {
         self.priority_queuing_linked_action = None;
         self.auto_mob_task = None;
         self.auto_mob_quadrant_consecutive_count = None;
+        self.auto_mob_corner_stuck_count = None;
+        self.auto_mob_last_recenter_time = None;
+        self.auto_mob_bound_index = 0;
+        self.patrol_lower_half = false;
     }
 
     #[inline]
@@ -842,32 +1168,81 @@ impl Rotator for DefaultRotator {
     #[inline]
     fn rotate_action(&mut self, resources: &Resources, world: &mut World) {
         if resources.operation.halting() {
+            self.running_since_tick = None;
+            self.rotate_halting_buffs(resources, world);
             if !has_side_loaded_action_executing(&world.player.context) {
                 self.rotate_side_priority_action(&mut world.player.context);
             }
             return;
         }
 
+        if self.running_since_tick.is_none() {
+            for key in self.buffs_burst_keys.iter().copied() {
+                self.priority_actions_side_queue
+                    .push_back(RotatorAction::Single(buff_key_action(key)));
+            }
+        }
+
+        let running_since_tick = *self.running_since_tick.get_or_insert(resources.tick);
+        if resources.tick.saturating_sub(running_since_tick) < self.startup_delay_ticks as u64 {
+            return;
+        }
+
         self.rotate_priority_actions(resources, world);
         self.rotate_priority_actions_queue(&mut world.player);
 
-        match self.normal_rotate_mode {
-            RotatorMode::StartToEnd => self.rotate_start_to_end(&mut world.player.context),
-            RotatorMode::StartToEndThenReverse => {
-                self.rotate_start_to_end_then_reverse(&mut world.player.context)
-            }
-            RotatorMode::AutoMobbing(key, bound) => self.rotate_auto_mobbing(
-                resources,
-                &mut world.player.context,
-                world.minimap.state,
-                key,
-                bound,
-            ),
-            RotatorMode::PingPong(key, bound) => {
-                self.rotate_ping_pong(&mut world.player.context, world.minimap.state, key, bound)
-            }
+        #[cfg(debug_assertions)]
+        let had_normal_action = world.player.context.has_normal_action();
+        self.rotate_normal_actions(resources, world);
+        #[cfg(debug_assertions)]
+        if !had_normal_action && let Some(action) = world.player.context.normal_action() {
+            self.action_log.record(action);
         }
     }
+
+    fn action_schedules(&self) -> Vec<(String, u64)> {
+        self.priority_actions
+            .values()
+            .filter_map(|action| {
+                let Some(ActionCondition::EveryMillis(millis)) = action.condition_kind else {
+                    return None;
+                };
+                let elapsed_millis = action
+                    .last_queued_time
+                    .map(|time| time.elapsed().as_millis() as u64)
+                    .unwrap_or(millis);
+
+                Some((
+                    rotator_action_name(&action.inner),
+                    millis.saturating_sub(elapsed_millis),
+                ))
+            })
+            .collect()
+    }
+
+    #[cfg(debug_assertions)]
+    fn action_log(&self) -> Vec<PlayerAction> {
+        self.action_log.actions()
+    }
+
+    #[cfg(debug_assertions)]
+    fn replay_actions(&mut self, actions: Vec<PlayerAction>) {
+        info!(target: "rotator", "replaying {} actions", actions.len());
+        self.action_replay = Some(ActionReplay::new(actions));
+    }
+}
+
+/// Retrieves the name of the first action in `action` for displaying to the user.
+#[inline]
+fn rotator_action_name(action: &RotatorAction) -> String {
+    let inner = match action {
+        RotatorAction::Single(inner) => inner,
+        RotatorAction::Linked(linked) => &linked.inner,
+    };
+    match inner {
+        PlayerAction::Key(Key { key, .. }) => format!("{inner} {key}"),
+        _ => inner.to_string(),
+    }
 }
 
 #[inline]
@@ -875,6 +1250,133 @@ fn has_side_loaded_action_executing(player_context: &PlayerContext) -> bool {
     player_context.has_priority_action() && player_context.priority_action_id().is_none()
 }
 
+/// Creates a [`PlayerAction::AutoMob`] that moves to `point` and uses the mobbing `key`.
+#[inline]
+fn auto_mob_action(key: MobbingKey, point: Point, is_pathing: bool) -> PlayerAction {
+    let wait_before_ticks = (key.wait_before_millis / MS_PER_TICK) as u32;
+    let wait_before_ticks_random_range = (key.wait_before_millis_random_range / MS_PER_TICK) as u32;
+    let wait_after_ticks = (key.wait_after_millis / MS_PER_TICK) as u32;
+    let wait_after_ticks_random_range = (key.wait_after_millis_random_range / MS_PER_TICK) as u32;
+    let position = Position {
+        x: point.x,
+        x_random_range: 0,
+        y: point.y,
+        allow_adjusting: false,
+    };
+
+    PlayerAction::AutoMob(AutoMob {
+        key: key.key,
+        link_key: key.link_key,
+        count: key.count.max(1),
+        with: key.with,
+        wait_before_ticks,
+        wait_before_ticks_random_range,
+        wait_after_ticks,
+        wait_after_ticks_random_range,
+        position,
+        is_pathing,
+    })
+}
+
+/// Scales `bound` relative to a minimap of `minimap_size` to the currently detected minimap
+/// `bbox`.
+///
+/// This keeps bounds valid after the minimap size changes (e.g. game resolution changes). If
+/// `minimap_size` is empty, `bound` is returned as is.
+#[inline]
+fn scaled_bound(bound: Bound, minimap_size: (i32, i32), bbox: Rect) -> Rect {
+    scaled_rect(bound.into(), minimap_size, bbox)
+}
+
+/// Scales the platforms bound of `idle` the same way as [`scaled_bound`].
+///
+/// Platforms are relative to a minimap of `minimap_size` like the bounds so the platforms bound
+/// is computed against that size before scaling instead of using
+/// [`MinimapIdle::platforms_bound`], which is computed against the detected minimap.
+#[inline]
+fn scaled_platforms_bound(idle: MinimapIdle, minimap_size: (i32, i32)) -> Option<Rect> {
+    let (width, height) = minimap_size;
+    if width <= 0 || height <= 0 || (width == idle.bbox.width && height == idle.bbox.height) {
+        return idle.platforms_bound;
+    }
+
+    find_platforms_bound(Rect::new(0, 0, width, height), &idle.platforms)
+        .map(|bound| scaled_rect(bound, minimap_size, idle.bbox))
+}
+
+#[inline]
+fn scaled_rect(rect: Rect, minimap_size: (i32, i32), bbox: Rect) -> Rect {
+    let (width, height) = minimap_size;
+    if width <= 0 || height <= 0 || (width == bbox.width && height == bbox.height) {
+        return rect;
+    }
+
+    let scale_x = bbox.width as f32 / width as f32;
+    let scale_y = bbox.height as f32 / height as f32;
+    Rect::new(
+        (rect.x as f32 * scale_x).round() as i32,
+        (rect.y as f32 * scale_y).round() as i32,
+        (rect.width as f32 * scale_x).round() as i32,
+        (rect.height as f32 * scale_y).round() as i32,
+    )
+}
+
+/// Retrieves the upper or lower half of `bound` in minimap coordinate.
+#[inline]
+fn patrol_half_bound(bound: Bound, lower: bool) -> Bound {
+    let upper_height = bound.height / 2;
+    if lower {
+        Bound {
+            y: bound.y + upper_height,
+            height: bound.height - upper_height,
+            ..bound
+        }
+    } else {
+        Bound {
+            height: upper_height,
+            ..bound
+        }
+    }
+}
+
+/// Randomly picks an auto mobbing target from `points`.
+///
+/// When `prefer_higher` is true, points are weighted by the rank of their height so that higher
+/// points are more likely to be picked while lower points can still be picked.
+#[inline]
+fn auto_mob_pick_point(rng: &Rng, points: Vec<Point>, prefer_higher: bool) -> Option<Point> {
+    if !prefer_higher {
+        return rng.random_choose(points.into_iter());
+    }
+
+    let mut heights = points.iter().map(|point| point.y).collect::<Vec<_>>();
+    heights.sort_unstable();
+    heights.dedup();
+    let weights = points
+        .iter()
+        .map(|point| heights.partition_point(|y| *y < point.y) as u32 + 1)
+        .collect::<Vec<_>>();
+
+    rng.random_weighted_index(&weights)
+        .map(|index| points[index])
+}
+
+/// Retrieves the center x of the auto mobbing `bound` if `pos` has strayed from the center
+/// by more than `percent` of the bound half width.
+///
+/// Returns [`None`] when `percent` is zero.
+#[inline]
+fn auto_mob_recenter_x(pos: Point, bound: Rect, percent: u32) -> Option<i32> {
+    if percent == 0 {
+        return None;
+    }
+
+    let half_width = bound.width / 2;
+    let center_x = bound.x + half_width;
+    let threshold = half_width * percent as i32 / 100;
+    ((pos.x - center_x).abs() > threshold).then_some(center_x)
+}
+
 /// Creates a [`RotatorAction`] with `start_action` as the initial action
 ///
 /// If `start_action` is linked, this function returns [`RotatorAction::Linked`] with [`usize`] as
@@ -890,18 +1392,13 @@ fn rotator_action(
         // Last action cannot be a linked action
         return (RotatorAction::Single(start_action.into()), 1);
     }
-    if start_index + 1 < actions.len() {
-        match actions[start_index + 1] {
-            Action::Move(ActionMove {
-                condition: ActionCondition::Linked,
-                ..
-            })
-            | Action::Key(ActionKey {
-                condition: ActionCondition::Linked,
-                ..
-            }) => (),
-            _ => return (RotatorAction::Single(start_action.into()), 1),
-        }
+    if start_index + 1 < actions.len()
+        && !matches!(
+            actions[start_index + 1].condition(),
+            ActionCondition::Linked
+        )
+    {
+        return (RotatorAction::Single(start_action.into()), 1);
     }
     let mut head = LinkedAction {
         inner: start_action.into(),
@@ -910,25 +1407,16 @@ fn rotator_action(
     let mut current = &mut head;
     let mut offset = 1;
     for action in actions.iter().skip(start_index + 1) {
-        match action {
-            Action::Move(ActionMove {
-                condition: ActionCondition::Linked,
-                ..
-            })
-            | Action::Key(ActionKey {
-                condition: ActionCondition::Linked,
-                ..
-            }) => {
-                let action = LinkedAction {
-                    inner: (*action).into(),
-                    next: None,
-                };
-                current.next = Some(Box::new(action));
-                current = current.next.as_mut().unwrap();
-                offset += 1;
-            }
-            _ => break,
+        if !matches!(action.condition(), ActionCondition::Linked) {
+            break;
         }
+        let action = LinkedAction {
+            inner: (*action).into(),
+            next: None,
+        };
+        current.next = Some(Box::new(action));
+        current = current.next.as_mut().unwrap();
+        offset += 1;
     }
     (RotatorAction::Linked(head), offset)
 }
@@ -959,6 +1447,43 @@ fn priority_action(
     }
 }
 
+/// Creates a [`PlayerAction::FamiliarsSwap`] priority action that queues every `check_millis`.
+///
+/// When `max_swaps_per_hour` is non-zero, the action is ignored once it has been queued
+/// `max_swaps_per_hour` times within the last hour.
+#[inline]
+fn familiars_swap_priority_action(
+    swap: FamiliarsSwap,
+    check_millis: u64,
+    max_swaps_per_hour: u32,
+) -> PriorityAction {
+    let condition = ActionCondition::EveryMillis(check_millis);
+    let queued_times = RefCell::new(VecDeque::<Instant>::new());
+
+    PriorityAction {
+        inner: RotatorAction::Single(PlayerAction::FamiliarsSwap(swap)),
+        condition: Condition(Box::new(move |_, world, last_queued_time| {
+            if !should_queue_fixed_action(world, last_queued_time, condition) {
+                return ConditionResult::Skip;
+            }
+            if max_swaps_per_hour > 0 {
+                let mut queued_times = queued_times.borrow_mut();
+                queued_times
+                    .retain(|time| time.elapsed().as_millis() < FAMILIARS_SWAP_WINDOW_MILLIS);
+                if queued_times.len() >= max_swaps_per_hour as usize {
+                    return ConditionResult::Ignore;
+                }
+                queued_times.push_back(Instant::now());
+            }
+            ConditionResult::Queue
+        })),
+        condition_kind: Some(condition),
+        queue_to_front: true,
+        ignoring: false,
+        last_queued_time: None,
+    }
+}
+
 /// Creates a [`PlayerAction::Key`] priority action to replenish familiar essence
 /// when it is detected as depleted.
 ///
@@ -997,6 +1522,7 @@ fn familiar_essence_replenish_priority_action(key: KeyBinding) -> PriorityAction
             wait_before_use_ticks_random_range: 0,
             wait_after_use_ticks: 0,
             wait_after_use_ticks_random_range: 0,
+            force: false,
         })),
         queue_to_front: true,
         ignoring: false,
@@ -1008,7 +1534,7 @@ fn familiar_essence_replenish_priority_action(key: KeyBinding) -> PriorityAction
 ///
 /// This action queues if all the following conditions are met:
 /// - The player is not currently validating a rune.
-/// - Enough time has passed since the last queue attempt.
+/// - Enough time has passed since the last queue attempt or the last attempt failed validation.
 /// - The minimap is in the [`Minimap::Idle`] state.
 /// - A rune is present on the minimap.
 /// - The player currently has no rune buff.
@@ -1019,7 +1545,9 @@ fn solve_rune_priority_action() -> PriorityAction {
             if world.player.context.is_validating_rune() {
                 return ConditionResult::Skip;
             }
-            if !at_least_millis_passed_since(last_queued_time, COOLDOWN_BETWEEN_QUEUE_MILLIS) {
+            if !world.player.context.is_retrying_rune()
+                && !at_least_millis_passed_since(last_queued_time, COOLDOWN_BETWEEN_QUEUE_MILLIS)
+            {
                 return ConditionResult::Skip;
             }
             if let Minimap::Idle(idle) = world.minimap.state
@@ -1092,24 +1620,30 @@ fn buff_priority_action(buff: BuffKind, key: KeyBinding) -> PriorityAction {
             }
         })),
         condition_kind: None,
-        inner: RotatorAction::Single(PlayerAction::Key(Key {
-            key,
-            link_key: None,
-            count: 1,
-            position: None,
-            direction: ActionKeyDirection::Any,
-            with: ActionKeyWith::Stationary,
-            wait_before_use_ticks: 10,
-            wait_before_use_ticks_random_range: 0,
-            wait_after_use_ticks: 10,
-            wait_after_use_ticks_random_range: 0,
-        })),
+        inner: RotatorAction::Single(buff_key_action(key)),
         queue_to_front: true,
         ignoring: false,
         last_queued_time: None,
     }
 }
 
+#[inline]
+fn buff_key_action(key: KeyBinding) -> PlayerAction {
+    PlayerAction::Key(Key {
+        key,
+        link_key: None,
+        count: 1,
+        position: None,
+        direction: ActionKeyDirection::Any,
+        with: ActionKeyWith::Stationary,
+        wait_before_use_ticks: 10,
+        wait_before_use_ticks_random_range: 0,
+        wait_after_use_ticks: 10,
+        wait_after_use_ticks_random_range: 0,
+        force: false,
+    })
+}
+
 #[inline]
 fn panic_priority_action() -> PriorityAction {
     PriorityAction {
@@ -1190,6 +1724,7 @@ fn elite_boss_use_key_priority_action(key: KeyBinding) -> PriorityAction {
             wait_before_use_ticks_random_range: 0,
             wait_after_use_ticks: 10,
             wait_after_use_ticks_random_range: 0,
+            force: false,
         })),
         queue_to_front: true,
         ignoring: false,
@@ -1269,6 +1804,7 @@ fn should_queue_fixed_action(
 mod tests {
     use std::{
         assert_matches::assert_matches,
+        sync::LazyLock,
         time::{Duration, Instant},
     };
 
@@ -1277,10 +1813,13 @@ mod tests {
 
     use super::*;
     use crate::{
-        Position,
+        ActionHoldPosition, ActionMove, ActionWaitForBuff, CharacterBuffKind, Position,
         buff::{BuffContext, BuffEntity, BuffKind},
-        minimap::{MinimapContext, MinimapEntity, MinimapIdle},
-        player::Player,
+        detect::MockDetector,
+        minimap::{MinimapContext, MinimapEntity},
+        operation::Operation,
+        pathing::{Platform, find_neighbors},
+        player::{HoldPosition, Player},
         skill::{SkillContext, SkillEntity, SkillKind},
     };
 
@@ -1293,6 +1832,7 @@ mod tests {
         },
         condition: ActionCondition::Any,
         wait_after_move_millis: 0,
+        arrival_tolerance: 0,
     });
     const PRIORITY_ACTION: Action = Action::Move(ActionMove {
         position: Position {
@@ -1303,6 +1843,7 @@ mod tests {
         },
         condition: ActionCondition::ErdaShowerOffCooldown,
         wait_after_move_millis: 0,
+        arrival_tolerance: 0,
     });
 
     fn mock_world() -> World {
@@ -1334,6 +1875,169 @@ mod tests {
         }
     }
 
+    #[test]
+    fn rotator_action_schedules_matches_every_millis() {
+        let mut rotator = DefaultRotator::default();
+        let mut queued = priority_action(
+            RotatorAction::Single(NORMAL_ACTION.into()),
+            ActionCondition::EveryMillis(5000),
+            false,
+        );
+        queued.last_queued_time = Some(Instant::now() - Duration::from_millis(2000));
+        let never_queued = priority_action(
+            RotatorAction::Single(NORMAL_ACTION.into()),
+            ActionCondition::EveryMillis(3000),
+            false,
+        );
+        let erda = priority_action(
+            RotatorAction::Single(PRIORITY_ACTION.into()),
+            ActionCondition::ErdaShowerOffCooldown,
+            false,
+        );
+        rotator.priority_actions.insert(0, queued);
+        rotator.priority_actions.insert(1, never_queued);
+        rotator.priority_actions.insert(2, erda);
+
+        let schedules = rotator.action_schedules();
+
+        assert_eq!(schedules.len(), 2);
+        assert_eq!(schedules[0].0, "Move");
+        assert!((2900..=3000).contains(&schedules[0].1));
+        assert_eq!(schedules[1], ("Move".to_string(), 0));
+    }
+
+    #[test]
+    fn rotator_scaled_bound_after_minimap_size_change() {
+        let bound = Bound {
+            x: 10,
+            y: 20,
+            width: 30,
+            height: 40,
+        };
+
+        assert_eq!(
+            scaled_bound(bound, (100, 100), Rect::new(5, 5, 200, 150)),
+            Rect::new(20, 30, 60, 60)
+        );
+        assert_eq!(
+            scaled_bound(bound, (100, 100), Rect::new(5, 5, 100, 100)),
+            Rect::new(10, 20, 30, 40)
+        );
+        assert_eq!(
+            scaled_bound(bound, (0, 0), Rect::new(5, 5, 200, 150)),
+            Rect::new(10, 20, 30, 40)
+        );
+    }
+
+    #[test]
+    fn rotator_scaled_platforms_bound_after_minimap_size_change() {
+        let platforms = [Platform::new(10..40, 50), Platform::new(20..30, 20)];
+        let idle = MinimapIdle {
+            bbox: Rect::new(5, 5, 200, 200),
+            platforms: Array::from_iter(find_neighbors(&platforms, 25, 7, 41)),
+            ..Default::default()
+        };
+
+        // Computed against the saved minimap height before scaling
+        assert_eq!(
+            scaled_platforms_bound(idle, (100, 100)),
+            Some(Rect::new(20, 94, 60, 68))
+        );
+    }
+
+    #[test]
+    fn rotator_auto_mob_pick_point_prefer_higher() {
+        let rng = Rng::new([3; 32], 1337);
+        let points = vec![Point::new(10, 5), Point::new(20, 30), Point::new(30, 30)];
+
+        // Each higher point has twice the weight of the lower point
+        let mut higher_count = 0;
+        let mut lower_count = 0;
+        for _ in 0..1000 {
+            let point = auto_mob_pick_point(&rng, points.clone(), true).unwrap();
+            if point.y == 30 {
+                higher_count += 1;
+            } else {
+                lower_count += 1;
+            }
+        }
+        assert!(lower_count > 0);
+        assert!(higher_count > lower_count * 3);
+        assert!(auto_mob_pick_point(&rng, vec![], true).is_none());
+    }
+
+    #[test]
+    fn rotator_auto_mob_recenter_x_past_fraction() {
+        let bound = Rect::new(0, 0, 100, 50);
+
+        assert_eq!(auto_mob_recenter_x(Point::new(95, 10), bound, 0), None);
+        assert_eq!(auto_mob_recenter_x(Point::new(70, 10), bound, 50), None);
+        assert_eq!(auto_mob_recenter_x(Point::new(75, 10), bound, 50), None);
+        assert_eq!(auto_mob_recenter_x(Point::new(76, 10), bound, 50), Some(50));
+        assert_eq!(auto_mob_recenter_x(Point::new(24, 10), bound, 50), Some(50));
+    }
+
+    #[test]
+    fn rotator_auto_mob_recenter_throttled_by_cooldown() {
+        let mut rotator = DefaultRotator::default();
+        let bound = Rect::new(0, 0, 100, 50);
+        let pos = Point::new(95, 10);
+
+        assert_eq!(rotator.auto_mob_update_recenter(pos, bound, 50), Some(50));
+        assert_eq!(rotator.auto_mob_update_recenter(pos, bound, 50), None);
+
+        rotator.auto_mob_last_recenter_time =
+            Some(Instant::now() - Duration::from_millis(AUTO_MOB_RECENTER_COOLDOWN_MILLIS as u64));
+        assert_eq!(rotator.auto_mob_update_recenter(pos, bound, 50), Some(50));
+    }
+
+    #[test]
+    fn rotator_auto_mob_corner_stuck_repositions_to_opposite_bound() {
+        let mut rotator = DefaultRotator::default();
+        let bound = Rect::new(0, 0, 100, 50);
+
+        // Moving around outside of corners never triggers
+        for x in 30..70 {
+            assert_eq!(
+                rotator.auto_mob_update_corner_stuck(Point::new(x, 10), bound),
+                None
+            );
+        }
+
+        // Camping at the left corner triggers after threshold
+        for i in 1..AUTO_MOB_CORNER_STUCK_THRESHOLD {
+            let pos = Point::new(5 + (i as i32 % 2), 10);
+            assert_eq!(rotator.auto_mob_update_corner_stuck(pos, bound), None);
+        }
+        assert_eq!(
+            rotator.auto_mob_update_corner_stuck(Point::new(5, 10), bound),
+            Some(80)
+        );
+        assert!(rotator.auto_mob_corner_stuck_count.is_none());
+
+        // Covering new ground inside the right corner resets the count
+        for _ in 1..AUTO_MOB_CORNER_STUCK_THRESHOLD {
+            assert_eq!(
+                rotator.auto_mob_update_corner_stuck(Point::new(98, 10), bound),
+                None
+            );
+        }
+        assert_eq!(
+            rotator.auto_mob_update_corner_stuck(Point::new(85, 10), bound),
+            None
+        );
+        for _ in 1..AUTO_MOB_CORNER_STUCK_THRESHOLD - 1 {
+            assert_eq!(
+                rotator.auto_mob_update_corner_stuck(Point::new(85, 10), bound),
+                None
+            );
+        }
+        assert_eq!(
+            rotator.auto_mob_update_corner_stuck(Point::new(85, 10), bound),
+            Some(20)
+        );
+    }
+
     #[test]
     fn rotator_at_least_millis_passed_since() {
         let now = Instant::now();
@@ -1390,6 +2094,79 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn rotator_familiars_swap_priority_action_max_swaps_per_hour() {
+        let resources = Resources::new(None, None);
+        let world = mock_world();
+        let swap = FamiliarsSwap {
+            swappable_slots: SwappableFamiliars::All,
+            swappable_rarities: Array::new(),
+            sort_by_level: true,
+        };
+        let action = familiars_swap_priority_action(swap, 0, 2);
+
+        assert!(matches!(
+            (action.condition.0)(&resources, &world, None),
+            ConditionResult::Queue
+        ));
+        assert!(matches!(
+            (action.condition.0)(&resources, &world, None),
+            ConditionResult::Queue
+        ));
+        assert!(matches!(
+            (action.condition.0)(&resources, &world, None),
+            ConditionResult::Ignore
+        ));
+    }
+
+    #[test]
+    fn rotator_familiars_swap_priority_action_no_limit() {
+        let resources = Resources::new(None, None);
+        let world = mock_world();
+        let swap = FamiliarsSwap {
+            swappable_slots: SwappableFamiliars::All,
+            swappable_rarities: Array::new(),
+            sort_by_level: true,
+        };
+        let action = familiars_swap_priority_action(swap, 0, 0);
+
+        for _ in 0..10 {
+            assert!(matches!(
+                (action.condition.0)(&resources, &world, None),
+                ConditionResult::Queue
+            ));
+        }
+    }
+
+    fn mock_build_args() -> RotatorBuildArgs<'static> {
+        static FAMILIAR_SWAPPABLE_RARITIES: LazyLock<HashSet<FamiliarRarity>> =
+            LazyLock::new(HashSet::default);
+
+        RotatorBuildArgs {
+            mode: RotatorMode::StartToEnd,
+            actions: &[],
+            buffs: &[],
+            halting_buffs: &[],
+            familiar_essence_key: KeyBinding::default(),
+            familiar_swappable_slots: SwappableFamiliars::default(),
+            familiar_swappable_rarities: &FAMILIAR_SWAPPABLE_RARITIES,
+            familiar_swap_check_millis: 0,
+            familiar_sort_by_level: true,
+            familiar_max_swaps_per_hour: 0,
+            elite_boss_behavior: EliteBossBehavior::None,
+            elite_boss_behavior_key: KeyBinding::default(),
+            enable_panic_mode: false,
+            enable_rune_solving: false,
+            enable_familiars_swapping: false,
+            enable_reset_normal_actions_on_erda: false,
+            enable_using_vip_booster: false,
+            startup_delay_ticks: 0,
+            enable_buffs_burst_on_start: false,
+            rotation_idle_ticks: (0, 0),
+            minimap_size: (0, 0),
+        }
+    }
+
     #[test]
     fn rotator_build_actions() {
         let mut rotator = DefaultRotator::default();
@@ -1399,17 +2176,10 @@ mod tests {
             mode: RotatorMode::default(),
             actions: &actions,
             buffs: &buffs,
-            familiar_essence_key: KeyBinding::default(),
-            familiar_swappable_slots: SwappableFamiliars::default(),
-            familiar_swappable_rarities: &HashSet::default(),
-            familiar_swap_check_millis: 0,
             elite_boss_behavior: EliteBossBehavior::CycleChannel,
-            elite_boss_behavior_key: KeyBinding::default(),
             enable_panic_mode: true,
             enable_rune_solving: true,
-            enable_familiars_swapping: false,
-            enable_reset_normal_actions_on_erda: false,
-            enable_using_vip_booster: false,
+            ..mock_build_args()
         };
 
         rotator.build_actions(args);
@@ -1417,6 +2187,87 @@ mod tests {
         assert_eq!(rotator.normal_actions.len(), 2);
     }
 
+    #[test]
+    fn rotator_action_links_hold_position() {
+        let hold = Action::HoldPosition(ActionHoldPosition {
+            hold_millis: 1000,
+            direction: ActionKeyDirection::Left,
+            condition: ActionCondition::Linked,
+        });
+        let actions = vec![NORMAL_ACTION, hold];
+
+        let (action, offset) = rotator_action(NORMAL_ACTION, 0, &actions);
+
+        assert_eq!(offset, 2);
+        let RotatorAction::Linked(LinkedAction {
+            inner: PlayerAction::Move(_),
+            next: Some(next),
+        }) = action
+        else {
+            panic!("action is not linked");
+        };
+        assert_matches!(
+            next.inner,
+            PlayerAction::HoldPosition(HoldPosition {
+                ticks: 30,
+                direction: ActionKeyDirection::Left
+            })
+        );
+    }
+
+    #[test]
+    fn rotator_action_converts_wait_for_buff() {
+        let wait = Action::WaitForBuff(ActionWaitForBuff {
+            buff: CharacterBuffKind::SayramElixir,
+            condition: ActionCondition::Any,
+        });
+
+        let (action, offset) = rotator_action(wait, 0, &[wait, NORMAL_ACTION]);
+
+        assert_eq!(offset, 1);
+        assert_matches!(
+            action,
+            RotatorAction::Single(PlayerAction::WaitForBuff(BuffKind::SayramElixir))
+        );
+    }
+
+    #[test]
+    fn rotator_build_actions_auto_mobbing_bounds() {
+        let mut rotator = DefaultRotator {
+            auto_mob_bound_index: 1,
+            ..Default::default()
+        };
+        let bounds = vec![
+            Bound {
+                x: 0,
+                y: 0,
+                width: 10,
+                height: 10,
+            },
+            Bound {
+                x: 20,
+                y: 20,
+                width: 10,
+                height: 10,
+            },
+        ];
+        let args = RotatorBuildArgs {
+            mode: RotatorMode::AutoMobbing(MobbingKey::default(), bounds.clone()),
+            actions: &[NORMAL_ACTION],
+            familiar_sort_by_level: false,
+            ..mock_build_args()
+        };
+
+        rotator.build_actions(args);
+
+        assert_matches!(
+            &rotator.normal_rotate_mode,
+            RotatorMode::AutoMobbing(_, mode_bounds) if *mode_bounds == bounds
+        );
+        assert_eq!(rotator.auto_mob_bound_index, 0);
+        assert!(rotator.normal_actions.is_empty());
+    }
+
     #[test]
     fn rotator_rotate_action_start_to_end_then_reverse() {
         let mut rotator = DefaultRotator::default();
@@ -1459,6 +2310,195 @@ mod tests {
         assert_eq!(rotator.normal_index, 1);
     }
 
+    #[test]
+    fn rotator_rotate_action_startup_delay() {
+        let mut rotator = DefaultRotator {
+            startup_delay_ticks: 3,
+            ..Default::default()
+        };
+        let mut world = mock_world();
+        let mut resources = Resources::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::StartToEnd;
+        rotator
+            .normal_actions
+            .push((0, RotatorAction::Single(NORMAL_ACTION.into())));
+
+        for tick in 0..3 {
+            resources.tick = tick;
+            rotator.rotate_action(&resources, &mut world);
+            assert!(!world.player.context.has_normal_action());
+        }
+
+        resources.tick = 3;
+        rotator.rotate_action(&resources, &mut world);
+        assert_eq!(world.player.context.normal_action_id(), Some(0));
+
+        // Delay starts over after halting
+        world.player.context.clear_actions_aborted(true);
+        resources.operation = Operation::Halting;
+        rotator.rotate_action(&resources, &mut world);
+        resources.operation = Operation::Running;
+        resources.tick = 4;
+        rotator.rotate_action(&resources, &mut world);
+        assert!(!world.player.context.has_normal_action());
+    }
+
+    #[test]
+    fn rotator_rotate_action_buffs_burst_on_start() {
+        let mut rotator = DefaultRotator::default();
+        let mut world = mock_world();
+        let mut resources = Resources::new(None, None);
+        let buffs = vec![
+            (BuffKind::SayramElixir, KeyBinding::A),
+            (BuffKind::ExpCouponX2, KeyBinding::B),
+        ];
+        rotator.build_actions(RotatorBuildArgs {
+            buffs: &buffs,
+            enable_buffs_burst_on_start: true,
+            ..mock_build_args()
+        });
+
+        rotator.rotate_action(&resources, &mut world);
+        assert!(world.player.context.has_priority_action());
+        assert_eq!(world.player.context.priority_action_id(), None);
+        assert_matches!(
+            rotator.priority_actions_side_queue.as_slices(),
+            (
+                [RotatorAction::Single(PlayerAction::Key(Key {
+                    key: KeyBinding::B,
+                    ..
+                }))],
+                []
+            )
+        );
+
+        // Does not inject again while still running
+        resources.tick = 1;
+        rotator.rotate_action(&resources, &mut world);
+        assert_eq!(rotator.priority_actions_side_queue.len(), 1);
+    }
+
+    #[test]
+    fn rotator_rotate_priority_actions_buffs_in_order() {
+        let mut rotator = DefaultRotator::default();
+        let mut world = mock_world();
+        let resources = Resources::new(None, None);
+        let buffs = vec![
+            (BuffKind::ExtremeRedPotion, KeyBinding::A),
+            (BuffKind::SayramElixir, KeyBinding::B),
+        ];
+        rotator.build_actions(RotatorBuildArgs {
+            buffs: &buffs,
+            ..mock_build_args()
+        });
+        world.minimap.state = Minimap::Idle(MinimapIdle::default());
+
+        rotator.rotate_priority_actions(&resources, &mut world);
+
+        assert_eq!(queued_keys(&rotator), vec![KeyBinding::A, KeyBinding::B]);
+    }
+
+    #[test]
+    fn rotator_rotate_action_halting_buffs() {
+        let mut rotator = DefaultRotator::default();
+        let mut world = mock_world();
+        let mut resources = Resources::new(None, None);
+        let buffs = vec![
+            (BuffKind::SayramElixir, KeyBinding::A),
+            (BuffKind::ExpCouponX2, KeyBinding::B),
+        ];
+        rotator.build_actions(RotatorBuildArgs {
+            buffs: &buffs,
+            halting_buffs: &[BuffKind::ExpCouponX2],
+            ..mock_build_args()
+        });
+        world.minimap.state = Minimap::Idle(MinimapIdle::default());
+        resources.operation = Operation::Halting;
+
+        rotator.rotate_action(&resources, &mut world);
+        assert!(world.player.context.has_priority_action());
+        assert_eq!(world.player.context.priority_action_id(), None);
+        assert!(rotator.priority_actions_side_queue.is_empty());
+
+        // Does not queue again while on cooldown
+        world.player.context.take_priority_action();
+        rotator.rotate_action(&resources, &mut world);
+        assert!(!world.player.context.has_priority_action());
+    }
+
+    #[test]
+    fn rotator_rotate_action_halting_without_halting_buffs() {
+        let mut rotator = DefaultRotator::default();
+        let mut world = mock_world();
+        let mut resources = Resources::new(None, None);
+        let buffs = vec![(BuffKind::ExpCouponX2, KeyBinding::B)];
+        rotator.build_actions(RotatorBuildArgs {
+            buffs: &buffs,
+            ..mock_build_args()
+        });
+        world.minimap.state = Minimap::Idle(MinimapIdle::default());
+        resources.operation = Operation::Halting;
+
+        rotator.rotate_action(&resources, &mut world);
+        assert!(!world.player.context.has_priority_action());
+    }
+
+    fn familiar_essence_rotator(familiar_essence_key: KeyBinding) -> DefaultRotator {
+        let mut rotator = DefaultRotator::default();
+        let buffs = vec![(BuffKind::Familiar, KeyBinding::A)];
+        rotator.build_actions(RotatorBuildArgs {
+            buffs: &buffs,
+            familiar_essence_key,
+            ..mock_build_args()
+        });
+        rotator
+    }
+
+    fn queued_keys(rotator: &DefaultRotator) -> Vec<KeyBinding> {
+        rotator
+            .priority_actions_queue
+            .iter()
+            .filter_map(|id| match rotator.priority_actions.get(id).unwrap().inner {
+                RotatorAction::Single(PlayerAction::Key(Key { key, .. })) => Some(key),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rotator_rotate_priority_actions_familiar_essence_depleted() {
+        let mut detector = MockDetector::new();
+        detector
+            .expect_detect_familiar_essence_depleted()
+            .once()
+            .return_const(true);
+        let resources = Resources::new(None, Some(detector));
+        let mut rotator = familiar_essence_rotator(KeyBinding::Z);
+        let mut world = mock_world();
+        world.buffs[BuffKind::Familiar].state = Buff::Yes;
+
+        rotator.rotate_priority_actions(&resources, &mut world);
+
+        assert_eq!(queued_keys(&rotator), vec![KeyBinding::Z]);
+    }
+
+    #[test]
+    fn rotator_rotate_priority_actions_familiar_essence_not_depleted() {
+        let mut detector = MockDetector::new();
+        detector
+            .expect_detect_familiar_essence_depleted()
+            .once()
+            .return_const(false);
+        let resources = Resources::new(None, Some(detector));
+        let mut rotator = familiar_essence_rotator(KeyBinding::Z);
+        let mut world = mock_world();
+        world.buffs[BuffKind::Familiar].state = Buff::Yes;
+
+        rotator.rotate_priority_actions(&resources, &mut world);
+
+        assert!(queued_keys(&rotator).is_empty());
+    }
+
     #[test]
     fn rotator_rotate_action_start_to_end() {
         let mut world = mock_world();
@@ -1484,6 +2524,100 @@ mod tests {
         assert_eq!(rotator.normal_index, 0);
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    fn rotator_replay_actions_produces_same_key_sequence() {
+        fn rotate_keys(rotator: &mut DefaultRotator, count: usize) -> Vec<KeyBinding> {
+            let mut world = mock_world();
+            let resources = Resources::new(None, None);
+            let mut keys = vec![];
+            for _ in 0..count {
+                rotator.rotate_action(&resources, &mut world);
+                if let Some(PlayerAction::Key(Key { key, .. })) =
+                    world.player.context.normal_action()
+                {
+                    keys.push(key);
+                }
+                world.player.context.clear_actions_aborted(true);
+            }
+            keys
+        }
+
+        let mut rotator = DefaultRotator {
+            normal_rotate_mode: RotatorMode::StartToEnd,
+            ..Default::default()
+        };
+        for (i, key) in [KeyBinding::A, KeyBinding::B, KeyBinding::C]
+            .into_iter()
+            .enumerate()
+        {
+            let action = Action::Key(ActionKey {
+                key,
+                ..Default::default()
+            });
+            rotator
+                .normal_actions
+                .push((i as u32, RotatorAction::Single(action.into())));
+        }
+        let recorded_keys = rotate_keys(&mut rotator, 5);
+
+        let mut replay_rotator = DefaultRotator::default();
+        replay_rotator.replay_actions(rotator.action_log());
+        let replayed_keys = rotate_keys(&mut replay_rotator, 5);
+
+        assert_eq!(
+            recorded_keys,
+            vec![
+                KeyBinding::A,
+                KeyBinding::B,
+                KeyBinding::C,
+                KeyBinding::A,
+                KeyBinding::B
+            ]
+        );
+        assert_eq!(replayed_keys, recorded_keys);
+        // Finishes once all actions are replayed
+        assert!(rotate_keys(&mut replay_rotator, 1).is_empty());
+        assert!(replay_rotator.action_replay.is_none());
+    }
+
+    #[test]
+    fn rotator_rotate_action_idle_between_rotations() {
+        let mut world = mock_world();
+        let mut rotator = DefaultRotator {
+            rotation_idle_ticks: (3, 6),
+            ..Default::default()
+        };
+        let mut resources = Resources::new(None, None);
+        resources.rng = Rng::new([7; 32], 1337);
+        rotator.normal_rotate_mode = RotatorMode::StartToEnd;
+        for i in 0..2 {
+            rotator
+                .normal_actions
+                .push((i, RotatorAction::Single(NORMAL_ACTION.into())));
+        }
+
+        rotator.rotate_action(&resources, &mut world);
+        assert_eq!(rotator.normal_idle_ticks, 0);
+        world.player.context.clear_actions_aborted(true);
+        rotator.rotate_action(&resources, &mut world);
+        world.player.context.clear_actions_aborted(true);
+
+        let idle_ticks = rotator.normal_idle_ticks;
+        assert!((3..=6).contains(&idle_ticks));
+
+        for tick in 0..idle_ticks as u64 {
+            resources.tick = tick;
+            rotator.rotate_action(&resources, &mut world);
+            assert!(!world.player.context.has_normal_action());
+        }
+
+        resources.tick = idle_ticks as u64;
+        rotator.rotate_action(&resources, &mut world);
+        assert_eq!(world.player.context.normal_action_id(), Some(0));
+        assert_eq!(rotator.normal_idle_ticks, 0);
+    }
+
     #[test]
     fn rotator_priority_action_queue() {
         let mut rotator = DefaultRotator::default();
@@ -1693,6 +2827,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rotate_patrol_alternates_bound_halves() {
+        let mut player = PlayerContext::default();
+        let mut rotator = DefaultRotator::default();
+        let mut idle = MinimapIdle::default();
+        idle.bbox = Rect::new(0, 0, 100, 100);
+        player.last_known_pos = Some(Point::new(80, 50));
+        let bound = Bound {
+            x: 20,
+            y: 20,
+            width: 60,
+            height: 60,
+        };
+
+        // Upper half in player relative coordinate
+        rotator.rotate_patrol(
+            &mut player,
+            Minimap::Idle(idle),
+            MobbingKey::default(),
+            bound,
+        );
+        let Some(PlayerAction::PingPong(ping_pong)) = player.normal_action() else {
+            panic!("normal action is not ping pong");
+        };
+        assert_eq!(ping_pong.bound, Rect::new(20, 50, 60, 30));
+
+        // Does not alternate while the action is still executing
+        rotator.rotate_patrol(
+            &mut player,
+            Minimap::Idle(idle),
+            MobbingKey::default(),
+            bound,
+        );
+        assert!(rotator.patrol_lower_half);
+
+        // Lower half in player relative coordinate
+        player.clear_actions_aborted(true);
+        rotator.rotate_patrol(
+            &mut player,
+            Minimap::Idle(idle),
+            MobbingKey::default(),
+            bound,
+        );
+        let Some(PlayerAction::PingPong(ping_pong)) = player.normal_action() else {
+            panic!("normal action is not ping pong");
+        };
+        assert_eq!(ping_pong.bound, Rect::new(20, 20, 60, 30));
+        assert!(!rotator.patrol_lower_half);
+    }
+
     #[test]
     fn rotator_priority_action_is_ignored_when_executing() {
         let mut rotator = DefaultRotator::default();
@@ -1,6 +1,9 @@
 use std::{
     cell::RefCell,
     env,
+    hash::{DefaultHasher, Hasher},
+    io::stdout,
+    path::PathBuf,
     rc::Rc,
     sync::{
         Arc,
@@ -10,27 +13,31 @@ use std::{
     time::{Duration, Instant},
 };
 
-use platforms::{Error, input::InputKind};
+use fern::Dispatch;
+use log::{LevelFilter, error};
+use platforms::Error;
 use strum::IntoEnumIterator;
-use tokio::sync::broadcast::channel;
+use tokio::sync::broadcast::{Sender, channel};
 
 #[cfg(debug_assertions)]
 use crate::ecs::Debug;
 use crate::{
+    ColorThresholds, DetectionRegion, GameState, KeyBinding, Localization, LogLevel, Settings,
     bridge::{Capture, DefaultCapture, DefaultInput, InputMethod},
     buff::{self, Buff, BuffContext, BuffEntity, BuffKind},
     database::{query_and_upsert_seeds, query_or_upsert_localization, query_settings},
-    detect::DefaultDetector,
-    ecs::{Resources, World, WorldEvent},
-    mat::OwnedMat,
+    detect::{DefaultDetector, Detector},
+    ecs::{Resources, RuneSolvingToken, World, WorldEvent},
+    mat::{FramePool, OwnedMat},
     minimap::{self, Minimap, MinimapContext, MinimapEntity},
     navigator::{DefaultNavigator, Navigator},
     notification::DiscordNotification,
     operation::Operation,
+    pipeline::Pipeline,
     player::{self, Player, PlayerContext, PlayerEntity},
     rng::Rng,
     rotator::{DefaultRotator, Rotator},
-    services::DefaultService,
+    services::{ControlService, DefaultService, InstanceControl, send_status, send_where},
     skill::{self, Skill, SkillContext, SkillEntity, SkillKind},
 };
 
@@ -45,6 +52,20 @@ pub const MS_PER_TICK: u64 = MS_PER_TICK_F32 as u64;
 /// Milliseconds per tick as an [`f32`].
 pub const MS_PER_TICK_F32: f32 = 1000.0 / FPS as f32;
 
+/// Number of ticks between each attempt to recover the selected window when it is not found.
+const WINDOW_RECOVERY_INTERVAL_TICKS: u64 = FPS as u64 * 2;
+
+/// Maximum number of ticks to skip grabbing for after consecutive capture failures.
+const MAX_CAPTURE_BACKOFF_TICKS: u32 = FPS;
+
+/// Number of consecutive capture failures before falling back to the next capture mode.
+const CAPTURE_FALLBACK_FAILURES: u32 = 5;
+
+/// Number of bytes between each sampled byte when hashing a frame.
+///
+/// Odd so that samples do not always land on the same color channel.
+const FRAME_HASH_STRIDE: usize = 61;
+
 pub fn init() {
     static LOOPING: AtomicBool = AtomicBool::new(false);
 
@@ -52,6 +73,8 @@ pub fn init() {
         .compare_exchange(false, true, Ordering::SeqCst, Ordering::Acquire)
         .is_ok()
     {
+        init_logger(&query_settings());
+
         let dll = env::current_exe()
             .unwrap()
             .parent()
@@ -73,112 +96,471 @@ pub fn init() {
     }
 }
 
-fn systems_loop() {
-    let settings = Rc::new(RefCell::new(query_settings()));
-    let localization = Rc::new(RefCell::new(Arc::new(query_or_upsert_localization())));
-    let seeds = query_and_upsert_seeds();
-    let rng = Rng::new(seeds.rng_seed, seeds.perlin_seed);
-    let (event_tx, event_rx) = channel::<WorldEvent>(5);
-
-    let mut service =
-        DefaultService::new(settings.clone(), localization.clone(), event_tx.subscribe());
-    let window = service.selected_window();
-    let mut input = DefaultInput::new(
-        InputMethod::Default(window, InputKind::Focused),
-        rng.clone(),
-    );
-    let mut capture = DefaultCapture::new(window);
-    service.update_input_and_capture(&mut input, &mut capture);
-
-    let mut rotator = DefaultRotator::default();
-    let mut navigator = DefaultNavigator::new(event_rx);
-    let notification = DiscordNotification::new(settings.clone());
-    let mut resources = Resources {
-        #[cfg(debug_assertions)]
-        debug: Debug::default(),
-        input: Box::new(input),
-        rng,
-        notification,
-        detector: None,
-        operation: Operation::Halting,
-        tick: 0,
-    };
+/// Initializes the logger with the level and file from `settings`.
+fn init_logger(settings: &Settings) {
+    let mut level = level_filter_from(settings.log_level);
+    if cfg!(debug_assertions) {
+        level = level.max(LevelFilter::Debug);
+    }
 
-    let minimap = MinimapEntity {
-        state: Minimap::Detecting,
-        context: MinimapContext::default(),
-    };
-    let player = PlayerEntity {
-        state: Player::Idle,
-        context: PlayerContext::default(),
+    let path = settings.log_file_path.trim();
+    let path = if path.is_empty() {
+        env::current_exe()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join("log.txt")
+    } else {
+        PathBuf::from(path)
     };
-    let skills = SkillKind::iter()
-        .map(SkillContext::new)
-        .map(|context| SkillEntity {
-            state: Skill::Detecting,
-            context,
-        })
-        .collect::<Vec<_>>()
-        .try_into()
-        .expect("matching size");
-
-    let buffs = BuffKind::iter()
-        .map(BuffContext::new)
-        .map(|context| BuffEntity {
-            state: Buff::No,
-            context,
+    let dispatch = log_dispatch(level).chain(stdout());
+    match fern::log_file(&path) {
+        Ok(file) => dispatch.chain(file).apply().unwrap(),
+        Err(err) => {
+            dispatch.apply().unwrap();
+            error!(target: "run", "failed to open log file {path:?} {err}");
+        }
+    }
+}
+
+fn log_dispatch(level: LevelFilter) -> Dispatch {
+    Dispatch::new()
+        .format(|out, message, record| {
+            out.finish(format_args!(
+                "[{} {} {}] {}",
+                humantime::format_rfc3339(std::time::SystemTime::now()),
+                record.level(),
+                record.target(),
+                message
+            ))
         })
-        .collect::<Vec<_>>()
-        .try_into()
-        .expect("matching size");
-    let mut world = World {
-        minimap,
-        player,
-        skills,
-        buffs,
-    };
-    let mut is_capturing_normally = false;
+        .level(level)
+}
 
-    loop_with_fps(FPS, || {
-        let detector = capture
-            .grab()
-            .map(OwnedMat::new_from_frame)
-            .map(|mat| DefaultDetector::new(mat, localization.borrow().clone()));
-        let was_capturing_normally = is_capturing_normally;
+#[inline]
+fn level_filter_from(level: LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Error => LevelFilter::Error,
+        LogLevel::Warn => LevelFilter::Warn,
+        LogLevel::Info => LevelFilter::Info,
+        LogLevel::Debug => LevelFilter::Debug,
+        LogLevel::Trace => LevelFilter::Trace,
+    }
+}
+
+type DetectionPipeline = Pipeline<(OwnedMat, Arc<Localization>, DetectionOptions), DefaultDetector>;
+
+fn detection_pipeline() -> DetectionPipeline {
+    Pipeline::new(
+        "detection",
+        |(mat, localization, options): (_, _, DetectionOptions)| {
+            let detector = options.detector(mat, localization);
+            // Eagerly transforms the frame on the worker thread
+            detector.grayscale_mat();
+            detector
+        },
+    )
+}
+
+/// Submits `mat` to `pipeline` and returns the detector of the previously submitted frame.
+///
+/// The previous detector is received before submitting so that the pipeline is free to accept
+/// the new frame. As long as the worker keeps up, systems run on every tick with a detector that
+/// is one frame behind. Frames are only dropped when the worker is slower than a tick.
+fn detect_pipelined(
+    pipeline: &mut DetectionPipeline,
+    mat: OwnedMat,
+    localization: Arc<Localization>,
+    options: DetectionOptions,
+) -> Option<DefaultDetector> {
+    let detector = pipeline.try_recv();
+    pipeline.submit((mat, localization, options));
+    detector
+}
+
+/// Detection options read from [`Settings`] on each tick.
+#[derive(Clone, Copy, Debug)]
+struct DetectionOptions {
+    parallel: bool,
+    buff_scale_percent: u32,
+    buffs_region: DetectionRegion,
+    popups_region: DetectionRegion,
+    player_dot_color: ColorThresholds,
+    pipelined: bool,
+    /// Duration without a changed frame before the game is considered frozen.
+    frozen_threshold: Duration,
+    /// Duration not capturing normally before capture is considered failed.
+    capture_failed_threshold: Duration,
+}
+
+impl DetectionOptions {
+    fn detector(self, mat: OwnedMat, localization: Arc<Localization>) -> DefaultDetector {
+        DefaultDetector::new(
+            mat,
+            localization,
+            self.parallel,
+            self.buff_scale_percent,
+            self.buffs_region,
+            self.popups_region,
+            self.player_dot_color,
+        )
+    }
+}
+
+/// Exponential backoff for grabbing frames while capture keeps failing.
+///
+/// Each consecutive failure doubles the number of ticks to skip grabbing for up to
+/// [`MAX_CAPTURE_BACKOFF_TICKS`]. A successful grab resumes grabbing every tick.
+#[derive(Debug, Default)]
+struct CaptureBackoff {
+    failures: u32,
+    skip_ticks: u32,
+}
+
+impl CaptureBackoff {
+    /// Gets the number of consecutive failed grabs.
+    fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Returns whether to grab on the current tick.
+    fn should_grab(&mut self) -> bool {
+        if self.skip_ticks > 0 {
+            self.skip_ticks -= 1;
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Updates the backoff with whether the last grab succeeded.
+    fn update(&mut self, success: bool) {
+        if success {
+            *self = CaptureBackoff::default();
+            return;
+        }
+
+        self.skip_ticks = 2u32
+            .saturating_pow(self.failures)
+            .min(MAX_CAPTURE_BACKOFF_TICKS);
+        self.failures = self.failures.saturating_add(1);
+    }
+}
+
+/// Tracks the time since the last changed frame to detect a frozen game.
+///
+/// Depending on the capture mode, a frozen game either keeps producing identical frames or stops
+/// producing frames at all. Both are handled by measuring time instead of counting frames.
+#[derive(Debug, Default)]
+struct FrozenFrames {
+    last_hash: Option<u64>,
+    last_changed: Option<Instant>,
+    detected: bool,
+}
+
+impl FrozenFrames {
+    /// Updates with the `hash` of the last grabbed frame at `now`.
+    ///
+    /// `hash` is [`None`] when no new frame arrived. Returns `true` once when no frame has changed
+    /// for `threshold`. A zero `threshold` disables the detection.
+    fn update(&mut self, hash: Option<u64>, now: Instant, threshold: Duration) -> bool {
+        if hash.is_some() && hash != self.last_hash {
+            self.last_hash = hash;
+            self.last_changed = Some(now);
+            self.detected = false;
+            return false;
+        }
+
+        let last_changed = *self.last_changed.get_or_insert(now);
+        if threshold.is_zero() || self.detected || now - last_changed < threshold {
+            return false;
+        }
+        self.detected = true;
+        true
+    }
+}
+
+/// Tracks how long capture has been failing for.
+///
+/// Measured in time instead of ticks because failed captures are retried with a backoff.
+#[derive(Debug, Default)]
+struct CaptureFailures {
+    failed_since: Option<Instant>,
+    detected: bool,
+}
+
+impl CaptureFailures {
+    /// Updates with whether the last tick is `capturing_normally` at `now`.
+    ///
+    /// Returns `true` once when capture has kept failing for `threshold`.
+    fn update(&mut self, capturing_normally: bool, now: Instant, threshold: Duration) -> bool {
+        if capturing_normally {
+            self.failed_since = None;
+            self.detected = false;
+            return false;
+        }
+
+        let failed_since = *self.failed_since.get_or_insert(now);
+        if self.detected || now - failed_since < threshold {
+            return false;
+        }
+        self.detected = true;
+        true
+    }
+}
+
+/// Hashes a sample of `data` bytes of a frame.
+///
+/// Sampling is enough to tell apart frames of a running game while being cheap to run every tick.
+fn frame_hash(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write_usize(data.len());
+    for byte in data.iter().step_by(FRAME_HASH_STRIDE) {
+        hasher.write_u8(*byte);
+    }
+    hasher.finish()
+}
+
+/// States shared between all [`Instance`]s.
+struct SharedStates {
+    settings: Rc<RefCell<Settings>>,
+    localization: Rc<RefCell<Arc<Localization>>>,
+    rng: Rng,
+    rune_solving: RuneSolvingToken,
+    bot: Rc<RefCell<ControlService>>,
+    key_tx: Sender<KeyBinding>,
+    game_state_tx: Sender<GameState>,
+}
+
+/// An independent bot instance controlling a single game window.
+struct Instance {
+    resources: Resources,
+    world: World,
+    rotator: DefaultRotator,
+    navigator: DefaultNavigator,
+    capture: DefaultCapture,
+    service: DefaultService,
+    event_tx: Sender<WorldEvent>,
+    is_capturing_normally: bool,
+    capture_backoff: CaptureBackoff,
+    capture_failures: CaptureFailures,
+    frozen_frames: FrozenFrames,
+    /// The tick at which to next attempt recovering the selected window.
+    window_recovery_tick: u64,
+    /// Pipeline for detecting on a worker thread when [`DetectionOptions::pipelined`] is set.
+    detection_pipeline: Option<DetectionPipeline>,
+    frame_pool: FramePool,
+}
+
+impl Instance {
+    fn new(shared: &SharedStates) -> Self {
+        let (event_tx, event_rx) = channel::<WorldEvent>(5);
+
+        let mut service = DefaultService::new(
+            shared.settings.clone(),
+            shared.localization.clone(),
+            shared.bot.clone(),
+            shared.key_tx.clone(),
+            shared.game_state_tx.clone(),
+            event_tx.subscribe(),
+        );
+        let window = service.selected_window();
+        let mut input = DefaultInput::new(
+            InputMethod::Default(window, service.selected_input_kind()),
+            shared.rng.clone(),
+        );
+        let mut capture = DefaultCapture::new(window);
+        service.update_input_and_capture(&mut input, &mut capture);
+
+        let rotator = DefaultRotator::default();
+        let navigator = DefaultNavigator::new(event_rx);
+        let notification = DiscordNotification::new(shared.settings.clone());
+        let mut resources = Resources {
+            #[cfg(debug_assertions)]
+            debug: Debug::default(),
+            input: Box::new(input),
+            rng: shared.rng.clone(),
+            notification,
+            detector: None,
+            operation: Operation::Halting,
+            rune_solving: shared.rune_solving.share(),
+            tick: 0,
+        };
+        service.apply_settings(&mut resources, &mut capture);
+        #[cfg(debug_assertions)]
+        service.apply_debug_settings(&resources);
+
+        let minimap = MinimapEntity {
+            state: Minimap::Detecting,
+            context: MinimapContext::default(),
+        };
+        let player = PlayerEntity {
+            state: Player::Idle,
+            context: PlayerContext::default(),
+        };
+        let skills = SkillKind::iter()
+            .map(SkillContext::new)
+            .map(|context| SkillEntity {
+                state: Skill::Detecting,
+                context,
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .expect("matching size");
+
+        let buffs = BuffKind::iter()
+            .map(BuffContext::new)
+            .map(|context| BuffEntity {
+                state: Buff::No,
+                context,
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .expect("matching size");
+        let mut world = World {
+            minimap,
+            player,
+            skills,
+            buffs,
+        };
+        service.apply_player_settings(&mut world.player.context);
+
+        Self {
+            resources,
+            world,
+            rotator,
+            navigator,
+            capture,
+            service,
+            event_tx,
+            is_capturing_normally: false,
+            capture_backoff: CaptureBackoff::default(),
+            capture_failures: CaptureFailures::default(),
+            frozen_frames: FrozenFrames::default(),
+            window_recovery_tick: 0,
+            detection_pipeline: None,
+            frame_pool: FramePool::default(),
+        }
+    }
+
+    fn run_systems(&mut self, localization: Arc<Localization>, options: DetectionOptions) {
+        if options.pipelined != self.detection_pipeline.is_some() {
+            self.detection_pipeline = options.pipelined.then(detection_pipeline);
+        }
+
+        let Instance {
+            resources,
+            world,
+            rotator,
+            navigator,
+            capture,
+            service,
+            event_tx,
+            is_capturing_normally,
+            capture_backoff,
+            capture_failures,
+            frozen_frames,
+            window_recovery_tick,
+            detection_pipeline,
+            frame_pool,
+            ..
+        } = self;
+        if !capture_backoff.should_grab() {
+            // Keeps updating input so that pending key presses are not stalled
+            resources.tick += 1;
+            resources.input.update(resources.tick);
+            return;
+        }
+        let mut buffer = frame_pool.take();
+        let frame = capture.grab(&mut buffer);
+        if frame.is_err() {
+            frame_pool.put(buffer);
+        }
+        let frozen_hash = match &frame {
+            Ok(frame) => Some(Some(frame_hash(&frame.data))),
+            Err(Error::FrameNotAvailable) => Some(None),
+            Err(_) => None,
+        };
+        let game_frozen = frozen_hash.is_some_and(|hash| {
+            frozen_frames.update(hash, Instant::now(), options.frozen_threshold)
+        });
+        let frame = frame.map(|frame| OwnedMat::new_from_frame(frame, frame_pool));
+        // No new frame arriving is not a capture failure
+        if !matches!(frame, Err(Error::FrameNotAvailable)) {
+            capture_backoff.update(frame.is_ok());
+        }
+        // Sends before the pipelined detection can return early without a detector
+        if game_frozen {
+            let _ = event_tx.send(WorldEvent::GameFrozen);
+        }
+        // Window errors are handled by window recovery instead and are expected in cash shop
+        let can_fall_back = matches!(
+            frame,
+            Err(ref err) if !matches!(
+                err,
+                Error::WindowNotFound | Error::WindowInvalidSize | Error::FrameNotAvailable
+            )
+        ) && !matches!(world.player.state, Player::CashShopThenExit(_));
+        if can_fall_back
+            && capture_backoff.failures() >= CAPTURE_FALLBACK_FAILURES
+            && service.fallback_capture(resources.input.as_mut(), capture)
+        {
+            *capture_backoff = CaptureBackoff::default();
+        }
+        let detector = match (detection_pipeline.as_mut(), frame) {
+            (Some(pipeline), Ok(mat)) => {
+                let Some(detector) = detect_pipelined(pipeline, mat, localization, options) else {
+                    // Keeps updating input so that pending key presses are not stalled
+                    resources.tick += 1;
+                    resources.input.update(resources.tick);
+                    return;
+                };
+                Ok(detector)
+            }
+            (_, frame) => frame.map(|mat| options.detector(mat, localization)),
+        };
         let player_in_cash_shop = matches!(world.player.state, Player::CashShopThenExit(_));
 
-        is_capturing_normally = detector.is_ok()
+        *is_capturing_normally = detector.is_ok()
             || (!player_in_cash_shop
                 && !matches!(
                     detector,
                     Err(Error::WindowNotFound | Error::WindowInvalidSize)
                 ));
+        let window_not_found = matches!(detector, Err(Error::WindowNotFound));
         resources.tick += 1;
         if let Ok(detector) = detector {
             let was_running_cycle = matches!(resources.operation, Operation::RunUntil { .. });
             let was_player_alive = !world.player.context.is_dead();
             let was_minimap_idle = matches!(world.minimap.state, Minimap::Idle(_));
+            let (had_stranger_player, had_guildie_player) = other_players_from(world.minimap.state);
+            let was_movement_calibrated =
+                world.player.context.config.movement_scale_percent.is_some();
 
             resources.detector = Some(Box::new(detector));
             resources.operation = resources.operation.update_tick();
 
-            minimap::run_system(&resources, &mut world.minimap, world.player.state);
-            player::run_system(&resources, &mut world.player, &world.minimap, &world.buffs);
+            minimap::run_system(resources, &mut world.minimap, world.player.state);
+            player::run_system(resources, &mut world.player, &world.minimap, &world.buffs);
             for skill in world.skills.iter_mut() {
-                skill::run_system(&resources, skill, world.player.state);
+                skill::run_system(resources, skill, world.player.state);
             }
             for buff in world.buffs.iter_mut() {
-                buff::run_system(&resources, buff, world.player.state);
+                buff::run_system(resources, buff, world.player.state);
             }
 
-            if navigator.navigate_player(&resources, &mut world.player.context, world.minimap.state)
+            if navigator.navigate_player(resources, &mut world.player.context, world.minimap.state)
             {
-                rotator.rotate_action(&resources, &mut world);
+                rotator.rotate_action(resources, world);
+            }
+            if navigator.take_hop_failed() {
+                let _ = event_tx.send(WorldEvent::NavigationFailed);
             }
 
             let did_cycled_to_stop = resources.operation.halting();
             // Go to town on stop cycle
             if was_running_cycle && did_cycled_to_stop {
+                resources.input.clear_pending_keys();
                 let _ = event_tx.send(WorldEvent::CycledToHalt);
             }
 
@@ -191,26 +573,153 @@ fn systems_loop() {
             if was_minimap_idle && minimap_detecting {
                 let _ = event_tx.send(WorldEvent::MinimapChanged);
             }
+
+            let (has_stranger_player, has_guildie_player) = other_players_from(world.minimap.state);
+            if !had_stranger_player && has_stranger_player {
+                let _ = event_tx.send(WorldEvent::PlayerStrangerAppeared);
+            }
+            if !had_guildie_player && has_guildie_player {
+                let _ = event_tx.send(WorldEvent::PlayerGuildieAppeared);
+            }
+
+            if !was_movement_calibrated
+                && let Some(percent) = world.player.context.config.movement_scale_percent
+            {
+                let _ = event_tx.send(WorldEvent::MovementCalibrated(percent));
+            }
         }
 
-        if was_capturing_normally && !is_capturing_normally {
+        // Only sends once per consecutive failures to not flood the event channel
+        if capture_failures.update(
+            *is_capturing_normally,
+            Instant::now(),
+            options.capture_failed_threshold,
+        ) {
             let _ = event_tx.send(WorldEvent::CaptureFailed);
         }
+        if window_not_found && resources.tick >= *window_recovery_tick {
+            *window_recovery_tick = resources.tick + WINDOW_RECOVERY_INTERVAL_TICKS;
+            let _ = event_tx.send(WorldEvent::CaptureWindowNotFound);
+        }
 
         resources.input.update(resources.tick);
         resources
             .notification
             .update(resources.detector.as_ref().map(|detector| detector.mat()));
-        service.poll(
-            &mut resources,
-            &mut world,
-            &mut rotator,
-            &mut navigator,
-            &mut capture,
+    }
+
+    fn poll_service(&mut self, control: Option<&mut InstanceControl>) {
+        let was_halting = self.resources.operation.halting();
+        self.service.poll(
+            &mut self.resources,
+            &mut self.world,
+            &mut self.rotator,
+            &mut self.navigator,
+            &mut self.capture,
+            control,
         );
+        // Key strokes postponed before halting must not be sent after
+        if !was_halting && self.resources.operation.halting() {
+            self.resources.input.clear_pending_keys();
+        }
+    }
+}
+
+fn systems_loop() {
+    let settings = Rc::new(RefCell::new(query_settings()));
+    let localization = Rc::new(RefCell::new(Arc::new(query_or_upsert_localization())));
+    let seeds = query_and_upsert_seeds();
+    let rng = Rng::new(seeds.rng_seed, seeds.perlin_seed);
+    let bot = Rc::new(RefCell::new(ControlService::default()));
+    bot.borrow_mut().update(&settings.borrow());
+
+    let shared = SharedStates {
+        settings,
+        localization,
+        rng,
+        rune_solving: RuneSolvingToken::default(),
+        bot,
+        key_tx: channel(1).0,
+        game_state_tx: channel(1).0,
+    };
+    let mut instances = vec![Instance::new(&shared)];
+    let mut control = InstanceControl {
+        count: 1,
+        ..InstanceControl::default()
+    };
+
+    loop_with_fps(FPS, || {
+        let localization = shared.localization.borrow().clone();
+        let options = {
+            let settings = shared.settings.borrow();
+            DetectionOptions {
+                parallel: settings.enable_parallel_detection,
+                buff_scale_percent: settings.buff_detection_scale_percent,
+                buffs_region: settings.buffs_detection_region,
+                popups_region: settings.popups_detection_region,
+                player_dot_color: settings.player_dot_color_thresholds,
+                pipelined: settings.enable_detection_pipeline,
+                frozen_threshold: Duration::from_millis(settings.game_frozen_threshold_millis),
+                capture_failed_threshold: Duration::from_millis(
+                    settings.capture_failed_threshold_millis,
+                ),
+            }
+        };
+        for (index, instance) in instances.iter_mut().enumerate() {
+            instance.run_systems(localization.clone(), options);
+            if index == control.selected {
+                instance.poll_service(Some(&mut control));
+            } else {
+                instance.poll_service(None);
+            }
+        }
+
+        if let Some((index, sender)) = control.pending_status.take()
+            && let Some(instance) = instances.get(index)
+        {
+            send_status(
+                &instance.resources,
+                &instance.world,
+                instance.navigator.navigation_progress(),
+                instance.rotator.action_schedules(),
+                index,
+                sender,
+            );
+        }
+        if let Some((index, sender)) = control.pending_where.take()
+            && let Some(instance) = instances.get(index)
+        {
+            send_where(
+                &instance.resources,
+                &instance.world,
+                instance.service.minimap_name(),
+                sender,
+            );
+        }
+        if control.pending_create {
+            control.pending_create = false;
+            instances.push(Instance::new(&shared));
+        }
+        if let Some(index) = control.pending_remove.take()
+            && index > 0
+            && index < instances.len()
+        {
+            instances.remove(index);
+        }
+        control.count = instances.len();
+        control.selected = control.selected.min(control.count - 1);
     });
 }
 
+/// Returns whether a stranger and a guildie player are detected respectively.
+#[inline]
+fn other_players_from(minimap: Minimap) -> (bool, bool) {
+    match minimap {
+        Minimap::Detecting => (false, false),
+        Minimap::Idle(idle) => (idle.has_stranger_player(), idle.has_guildie_player()),
+    }
+}
+
 #[inline]
 fn loop_with_fps(fps: u32, mut on_tick: impl FnMut()) {
     #[cfg(debug_assertions)]
@@ -241,3 +750,141 @@ fn loop_with_fps(fps: u32, mut on_tick: impl FnMut()) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use log::{Level, Record};
+    use opencv::core::{CV_8UC4, Mat, MatExprTraitConst};
+
+    use super::*;
+
+    #[test]
+    fn capture_backoff_doubles_skipped_ticks_until_success() {
+        let mut backoff = CaptureBackoff::default();
+        let mut grabbed_ticks = vec![];
+
+        for tick in 0..100 {
+            if backoff.should_grab() {
+                grabbed_ticks.push(tick);
+                backoff.update(false);
+            }
+        }
+        assert_eq!(grabbed_ticks, vec![0, 2, 5, 10, 19, 36, 67, 98]);
+
+        // Resumes grabbing every tick after a successful grab
+        while !backoff.should_grab() {}
+        backoff.update(true);
+        assert!(backoff.should_grab());
+        assert!(backoff.should_grab());
+    }
+
+    #[test]
+    fn capture_failures_detected_once_after_failing_for_threshold() {
+        let threshold = Duration::from_millis(100);
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let mut failures = CaptureFailures::default();
+
+        // A transient failure followed by recovery resets the timer
+        assert!(!failures.update(false, at(0), threshold));
+        assert!(!failures.update(true, at(50), threshold));
+
+        assert!(!failures.update(false, at(100), threshold));
+        assert!(!failures.update(false, at(150), threshold));
+        assert!(failures.update(false, at(200), threshold));
+        // Only detected once while still failing
+        assert!(!failures.update(false, at(250), threshold));
+
+        // Zero threshold detects on the first failure
+        let mut failures = CaptureFailures::default();
+        assert!(failures.update(false, at(0), Duration::ZERO));
+    }
+
+    #[test]
+    fn frozen_frames_detected_after_no_changed_frame_for_threshold() {
+        let threshold = Duration::from_millis(100);
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let mut frozen = FrozenFrames::default();
+        let frame = vec![1u8; 4 * 100 * 100];
+        let hash = frame_hash(&frame);
+
+        assert!(!frozen.update(Some(hash), at(0), threshold));
+        assert!(!frozen.update(Some(hash), at(50), threshold));
+        assert!(frozen.update(Some(hash), at(100), threshold));
+        // Only detected once while staying frozen
+        assert!(!frozen.update(Some(hash), at(150), threshold));
+
+        // A changed frame resets the timer
+        let mut changed = frame.clone();
+        changed[0] = 2;
+        assert!(!frozen.update(Some(frame_hash(&changed)), at(200), threshold));
+        assert!(!frozen.update(Some(frame_hash(&changed)), at(250), threshold));
+
+        // No new frame arriving counts as frozen as well
+        assert!(!frozen.update(None, at(280), threshold));
+        assert!(frozen.update(None, at(300), threshold));
+
+        // Disabled with zero threshold
+        let mut frozen = FrozenFrames::default();
+        for millis in 0..10 {
+            assert!(!frozen.update(Some(hash), at(millis * 100), Duration::ZERO));
+        }
+    }
+
+    #[test]
+    fn detect_pipelined_yields_detector_every_tick() {
+        let mut pipeline = detection_pipeline();
+        let localization = Arc::new(Localization::default());
+        let options = DetectionOptions {
+            parallel: false,
+            buff_scale_percent: 100,
+            buffs_region: DetectionRegion::default(),
+            popups_region: DetectionRegion::default(),
+            player_dot_color: ColorThresholds::default(),
+            pipelined: true,
+            frozen_threshold: Duration::ZERO,
+            capture_failed_threshold: Duration::ZERO,
+        };
+        let mat = || OwnedMat::from(Mat::zeros(10, 10, CV_8UC4).unwrap().to_mat().unwrap());
+
+        // No detector yet for the first frame
+        assert!(detect_pipelined(&mut pipeline, mat(), localization.clone(), options).is_none());
+        for _ in 0..5 {
+            // Simulates a tick that is longer than the worker takes
+            thread::sleep(Duration::from_millis(MS_PER_TICK));
+            assert!(
+                detect_pipelined(&mut pipeline, mat(), localization.clone(), options).is_some()
+            );
+        }
+    }
+
+    #[test]
+    fn log_dispatch_filters_lower_severity() {
+        let (tx, rx) = mpsc::channel::<String>();
+        let (_, logger) = log_dispatch(level_filter_from(LogLevel::Warn))
+            .chain(tx)
+            .into_log();
+        let log = |level| {
+            logger.log(
+                &Record::builder()
+                    .level(level)
+                    .target("test")
+                    .args(format_args!("{level}"))
+                    .build(),
+            )
+        };
+
+        log(Level::Error);
+        log(Level::Warn);
+        log(Level::Info);
+        log(Level::Debug);
+
+        let messages = rx.try_iter().collect::<Vec<_>>();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].trim_end().ends_with("ERROR"));
+        assert!(messages[1].trim_end().ends_with("WARN"));
+    }
+}
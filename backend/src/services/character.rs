@@ -1,9 +1,13 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, ops::Range};
 
 #[cfg(test)]
 use mockall::automock;
 
-use crate::{Character, PotionMode, player::PlayerContext};
+use crate::{
+    Action, ActionCondition, ActionConfiguration, ActionConfigurationCondition, ActionKey,
+    Character, KeyBinding, PotionMode,
+    player::{MAX_EXTRA_POTIONS, PlayerContext},
+};
 
 /// A service to handle character-related incoming requests.
 #[cfg_attr(test, automock)]
@@ -15,6 +19,13 @@ pub trait CharacterService: Debug {
     /// Sets a new `character` to be used.
     fn update_character(&mut self, character: Option<Character>);
 
+    /// Appends `actions` to the actions of a copy of the currently in use [`Character`].
+    ///
+    /// Only key actions without a position and with a fixed interval or linked condition can be
+    /// character actions so other actions are skipped. Returns the updated copy or [`None`] if
+    /// there is no character in use.
+    fn insert_actions(&self, actions: &[Action]) -> Option<Character>;
+
     /// Updates `player_context` with information from the currently in use `[Character]`.
     fn apply_character(&self, player_context: &mut PlayerContext);
 }
@@ -33,20 +44,41 @@ impl CharacterService for DefaultCharacterService {
         self.character = character;
     }
 
+    fn insert_actions(&self, actions: &[Action]) -> Option<Character> {
+        let mut character = self.character.clone()?;
+
+        character.actions.extend(
+            actions
+                .iter()
+                .copied()
+                .filter_map(action_configuration_from),
+        );
+        Some(character)
+    }
+
     /// Updates `state` from currently using `[Character]`.
     fn apply_character(&self, player_context: &mut PlayerContext) {
         player_context.reset();
         if let Some(character) = self.character.as_ref() {
             player_context.config.class = character.class;
             player_context.config.disable_double_jumping = character.disable_double_jumping;
+            player_context.config.double_jump_threshold = character.double_jump_threshold as i32;
             player_context.config.disable_adjusting = character.disable_adjusting;
             player_context.config.disable_teleport_on_fall = character.disable_teleport_on_fall;
             player_context.config.up_jump_is_flight = character.up_jump_is_flight;
             player_context.config.up_jump_specific_key_should_jump =
                 character.up_jump_specific_key_should_jump;
+            player_context.config.up_jump_spam_delay = character.up_jump_spam_delay;
+            player_context.config.climb_up_interval_ticks = character.climb_up_interval_ticks;
+            player_context.config.climb_down_interval_ticks = character.climb_down_interval_ticks;
+            player_context.config.use_flash_jump = character.use_flash_jump;
+            player_context.config.verify_direction_by_detection =
+                character.verify_direction_by_detection;
             player_context.config.interact_key = character.interact_key.key.into();
             player_context.config.grappling_key = character.ropelift_key.map(|key| key.key.into());
             player_context.config.teleport_key = character.teleport_key.map(|key| key.key.into());
+            player_context.config.teleport_range = character.teleport_range as i32;
+            player_context.config.stationary_ticks = character.stationary_ticks;
             player_context.config.jump_key = character.jump_key.key.into();
             player_context.config.up_jump_key = character.up_jump_key.map(|key| key.key.into());
             player_context.config.cash_shop_key = character.cash_shop_key.map(|key| key.key.into());
@@ -61,27 +93,166 @@ impl CharacterService for DefaultCharacterService {
                     (false, _) | (_, PotionMode::EveryMillis(_)) => None,
                     (_, PotionMode::Percentage(percent)) => Some(percent / 100.0),
                 };
+            player_context.config.extra_potions_below_percent = character
+                .extra_potions
+                .iter()
+                .filter(|potion| potion.key.enabled)
+                .filter_map(|potion| match potion.mode {
+                    PotionMode::EveryMillis(_) => None,
+                    PotionMode::Percentage(percent) => {
+                        Some((potion.key.key.into(), percent / 100.0))
+                    }
+                })
+                .take(MAX_EXTRA_POTIONS)
+                .collect();
             player_context.config.update_health_millis = Some(character.health_update_millis);
             player_context.config.vip_booster_key = character.vip_booster_key.key.into();
         }
     }
 }
 
+/// Converts `action` to an enabled [`ActionConfiguration`] if possible.
+fn action_configuration_from(action: Action) -> Option<ActionConfiguration> {
+    let Action::Key(ActionKey {
+        key,
+        link_key,
+        count,
+        position: None,
+        condition,
+        with,
+        wait_before_use_millis,
+        wait_before_use_millis_random_range,
+        wait_after_use_millis,
+        wait_after_use_millis_random_range,
+        ..
+    }) = action
+    else {
+        return None;
+    };
+    let condition = match condition {
+        ActionCondition::EveryMillis(millis) => ActionConfigurationCondition::EveryMillis(millis),
+        ActionCondition::Linked => ActionConfigurationCondition::Linked,
+        ActionCondition::Any | ActionCondition::ErdaShowerOffCooldown => return None,
+    };
+
+    Some(ActionConfiguration {
+        key,
+        link_key,
+        count,
+        condition,
+        with,
+        wait_before_millis: wait_before_use_millis,
+        wait_before_millis_random_range: wait_before_use_millis_random_range,
+        wait_after_millis: wait_after_use_millis,
+        wait_after_millis_random_range: wait_after_use_millis_random_range,
+        enabled: true,
+    })
+}
+
+/// Sets `enabled` for all `character` actions or only those within `range` if provided.
+///
+/// The range is clamped to the number of actions and an inverted range is treated as empty.
+pub fn set_character_actions_enabled(
+    character: &mut Character,
+    range: Option<Range<usize>>,
+    enabled: bool,
+) {
+    let len = character.actions.len();
+    let range = range.unwrap_or(0..len);
+    let end = range.end.min(len);
+    let range = range.start.min(end)..end;
+    for action in &mut character.actions[range] {
+        action.enabled = enabled;
+    }
+}
+
+/// Validates `character` movement and action key bindings for collisions.
+///
+/// Returns a warning message for each key bound to more than one of teleport, jump, up jump,
+/// rope lift, interact and enabled action keys.
+pub fn validate_character_keys(character: &Character) -> Vec<String> {
+    find_key_collisions(&character_key_bindings(character))
+}
+
+/// Collects `character` movement and enabled action key bindings along with their names.
+pub fn character_key_bindings(character: &Character) -> Vec<(KeyBinding, String)> {
+    let mut keys = Vec::<(KeyBinding, String)>::new();
+    keys.extend(
+        character
+            .teleport_key
+            .map(|key| (key.key, "Teleport".to_string())),
+    );
+    keys.push((character.jump_key.key, "Jump".to_string()));
+    keys.extend(
+        character
+            .up_jump_key
+            .map(|key| (key.key, "Up jump".to_string())),
+    );
+    keys.extend(
+        character
+            .ropelift_key
+            .map(|key| (key.key, "Rope lift".to_string())),
+    );
+    keys.push((character.interact_key.key, "Interact".to_string()));
+    keys.extend(
+        character
+            .actions
+            .iter()
+            .enumerate()
+            .filter(|(_, action)| action.enabled)
+            .map(|(i, action)| (action.key, format!("Action #{}", i + 1))),
+    );
+
+    keys
+}
+
+/// Returns a warning message for each key in `keys` used by more than one name.
+pub fn find_key_collisions(keys: &[(KeyBinding, String)]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let mut checked = Vec::<KeyBinding>::new();
+    for (key, _) in keys.iter() {
+        if checked.contains(key) {
+            continue;
+        }
+        checked.push(*key);
+
+        let names = keys
+            .iter()
+            .filter(|(other, _)| other == key)
+            .map(|(_, name)| name.as_str())
+            .collect::<Vec<_>>();
+        if names.len() > 1 {
+            warnings.push(format!("Key {key} is used by {}", names.join(", ")));
+        }
+    }
+
+    warnings
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        Class, KeyBinding, KeyBindingConfiguration, bridge::KeyKind, player::PlayerContext,
+        ActionMove, Class, KeyBindingConfiguration, Position, PotionConfiguration, bridge::KeyKind,
+        player::PlayerContext,
     };
 
     fn mock_character() -> Character {
         Character {
             class: Class::Cadena,
             disable_double_jumping: true,
+            double_jump_threshold: 40,
+            teleport_range: 30,
+            stationary_ticks: 10,
             disable_adjusting: true,
             disable_teleport_on_fall: true,
             up_jump_is_flight: true,
             up_jump_specific_key_should_jump: true,
+            up_jump_spam_delay: 4,
+            climb_up_interval_ticks: 2,
+            climb_down_interval_ticks: 3,
+            use_flash_jump: true,
+            verify_direction_by_detection: true,
             interact_key: KeyBindingConfiguration {
                 key: KeyBinding::Z,
                 ..Default::default()
@@ -123,11 +294,63 @@ mod tests {
                 enabled: true,
             },
             potion_mode: PotionMode::Percentage(50.0),
+            extra_potions: vec![
+                PotionConfiguration {
+                    key: KeyBindingConfiguration {
+                        key: KeyBinding::O,
+                        enabled: true,
+                    },
+                    mode: PotionMode::Percentage(30.0),
+                },
+                PotionConfiguration {
+                    key: KeyBindingConfiguration {
+                        key: KeyBinding::I,
+                        enabled: true,
+                    },
+                    mode: PotionMode::EveryMillis(1000),
+                },
+                PotionConfiguration {
+                    key: KeyBindingConfiguration {
+                        key: KeyBinding::U,
+                        enabled: false,
+                    },
+                    mode: PotionMode::Percentage(20.0),
+                },
+            ],
             health_update_millis: 3000,
             ..Default::default()
         }
     }
 
+    #[test]
+    fn validate_character_keys_colliding() {
+        let mut character = mock_character();
+        assert!(validate_character_keys(&character).is_empty());
+
+        character.teleport_key = Some(KeyBindingConfiguration {
+            key: KeyBinding::C,
+            ..Default::default()
+        });
+        character.actions.push(ActionConfiguration {
+            key: KeyBinding::A,
+            enabled: true,
+            ..Default::default()
+        });
+        character.actions.push(ActionConfiguration {
+            key: KeyBinding::Z,
+            enabled: false,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            validate_character_keys(&character),
+            vec![
+                "Key C is used by Teleport, Jump".to_string(),
+                "Key A is used by Up jump, Action #1".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn update_and_current() {
         let mut service = DefaultCharacterService::default();
@@ -140,6 +363,54 @@ mod tests {
         assert_eq!(current, &mock_character());
     }
 
+    #[test]
+    fn insert_actions_appends_convertible_actions() {
+        let mut service = DefaultCharacterService::default();
+        let actions = [
+            Action::Key(ActionKey {
+                key: KeyBinding::B,
+                condition: ActionCondition::EveryMillis(1000),
+                wait_after_use_millis: 300,
+                ..Default::default()
+            }),
+            Action::Key(ActionKey {
+                key: KeyBinding::C,
+                condition: ActionCondition::Linked,
+                ..Default::default()
+            }),
+            // Skipped
+            Action::Key(ActionKey {
+                key: KeyBinding::D,
+                condition: ActionCondition::Any,
+                ..Default::default()
+            }),
+            Action::Key(ActionKey {
+                key: KeyBinding::E,
+                position: Some(Position::default()),
+                condition: ActionCondition::Linked,
+                ..Default::default()
+            }),
+            Action::Move(ActionMove::default()),
+        ];
+        assert!(service.insert_actions(&actions).is_none());
+
+        service.update_character(Some(mock_character()));
+        let character = service.insert_actions(&actions).unwrap();
+        let existing = mock_character().actions.len();
+
+        assert_eq!(character.actions.len(), existing + 2);
+        assert_eq!(character.actions[existing].key, KeyBinding::B);
+        assert_eq!(
+            character.actions[existing].condition,
+            ActionConfigurationCondition::EveryMillis(1000)
+        );
+        assert_eq!(character.actions[existing].wait_after_millis, 300);
+        assert!(character.actions[existing].enabled);
+        assert_eq!(character.actions[existing + 1].key, KeyBinding::C);
+        // Not used until updated
+        assert_eq!(service.character(), Some(&mock_character()));
+    }
+
     #[test]
     fn update_from_character_none() {
         let service = DefaultCharacterService::default();
@@ -164,6 +435,10 @@ mod tests {
             state.config.disable_double_jumping,
             character.disable_double_jumping
         );
+        assert_eq!(
+            state.config.double_jump_threshold,
+            character.double_jump_threshold as i32
+        );
         assert_eq!(state.config.disable_adjusting, character.disable_adjusting);
         assert_eq!(
             state.config.disable_teleport_on_fall,
@@ -174,9 +449,19 @@ mod tests {
             state.config.up_jump_specific_key_should_jump,
             character.up_jump_specific_key_should_jump
         );
+        assert_eq!(state.config.up_jump_spam_delay, 4);
+        assert_eq!(state.config.climb_up_interval_ticks, 2);
+        assert_eq!(state.config.climb_down_interval_ticks, 3);
+        assert_eq!(state.config.use_flash_jump, character.use_flash_jump);
+        assert_eq!(
+            state.config.verify_direction_by_detection,
+            character.verify_direction_by_detection
+        );
         assert_eq!(state.config.interact_key, KeyKind::Z);
         assert_eq!(state.config.grappling_key, Some(KeyKind::V));
         assert_eq!(state.config.teleport_key, Some(KeyKind::X));
+        assert_eq!(state.config.teleport_range, 30);
+        assert_eq!(state.config.stationary_ticks, 10);
         assert_eq!(state.config.jump_key, KeyKind::C);
         assert_eq!(state.config.up_jump_key, Some(KeyKind::A));
         assert_eq!(state.config.cash_shop_key, Some(KeyKind::B));
@@ -185,6 +470,10 @@ mod tests {
         assert_eq!(state.config.change_channel_key, Some(KeyKind::L));
         assert_eq!(state.config.potion_key, KeyKind::P);
         assert_eq!(state.config.use_potion_below_percent, Some(0.5));
+        assert_eq!(
+            state.config.extra_potions_below_percent.as_slice(),
+            &[(KeyKind::O, 0.3)]
+        );
         assert_eq!(state.config.update_health_millis, Some(3000));
     }
 }
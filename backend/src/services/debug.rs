@@ -1,4 +1,5 @@
 use std::{
+    collections::VecDeque,
     sync::{Arc, LazyLock},
     time::Instant,
 };
@@ -7,29 +8,80 @@ use include_dir::{Dir, include_dir};
 use log::debug;
 use opencv::{
     core::{Mat, ModifyInplace, Vector},
-    imgcodecs::{IMREAD_COLOR, imdecode},
+    imgcodecs::{IMREAD_COLOR, imdecode, imencode_def},
     imgproc::{COLOR_BGR2BGRA, cvt_color_def},
 };
 use rand::distr::SampleString;
 use rand_distr::Alphanumeric;
+use strum::IntoEnumIterator;
 use tokio::sync::broadcast::{self, Receiver, Sender};
 
 use crate::{
-    DebugState,
-    debug::{save_image_for_training, save_image_for_training_to, save_minimap_for_training},
-    detect::{ArrowsCalibrating, ArrowsState, DefaultDetector, Detector},
+    ColorThresholds, DebugState, DetectionRegion, GameTemplate, Settings, TemplateSelfTest,
+    bridge::KeyKind,
+    debug::{
+        save_encoded_frames, save_image_for_training, save_image_for_training_to,
+        save_minimap_for_training,
+    },
+    detect::{ArrowsCalibrating, ArrowsState, DefaultDetector, Detector, to_base64_from_mat},
     ecs::Resources,
     mat::OwnedMat,
+    minimap::Minimap,
     models::Localization,
 };
 
 const SOLVE_RUNE_TIMEOUT_SECS: u64 = 10;
 
+/// Number of recent frames kept for dumping, roughly 3 seconds at the sampling interval.
+const RECENT_FRAMES_CAPACITY: usize = 30;
+
+/// Number of ticks between two sampled recent frames.
+///
+/// Only sampled frames are encoded so that debug builds do not encode every tick.
+const RECENT_FRAMES_SAMPLE_INTERVAL_TICKS: u64 = 3;
+
+/// Extension used to encode recent frames.
+///
+/// JPEG is used over PNG because encoding happens while the bot is running.
+const RECENT_FRAMES_EXTENSION: &str = ".jpg";
+
+/// A fixed-capacity ring buffer of encoded frames.
+#[derive(Debug)]
+struct RecentFrames {
+    frames: VecDeque<Vec<u8>>,
+    capacity: usize,
+}
+
+impl RecentFrames {
+    fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Pushes `frame` evicting the oldest frame if the buffer is full.
+    fn push(&mut self, frame: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.frames.iter().map(Vec::as_slice)
+    }
+}
+
 #[derive(Debug)]
 pub struct DebugService {
     state: Sender<DebugState>,
     recording_id: Option<String>,
     infering_rune: Option<(ArrowsCalibrating, Instant)>,
+    recent_frames: RecentFrames,
 }
 
 impl Default for DebugService {
@@ -38,6 +90,7 @@ impl Default for DebugService {
             state: broadcast::channel(1).0,
             recording_id: None,
             infering_rune: None,
+            recent_frames: RecentFrames::new(RECENT_FRAMES_CAPACITY),
         }
     }
 }
@@ -48,6 +101,22 @@ impl DebugService {
             save_image_for_training_to(resources.detector().mat(), Some(id), false, false);
         }
 
+        if resources
+            .tick
+            .is_multiple_of(RECENT_FRAMES_SAMPLE_INTERVAL_TICKS)
+        {
+            let mut frame = Vector::<u8>::new();
+            if imencode_def(
+                RECENT_FRAMES_EXTENSION,
+                resources.detector().mat(),
+                &mut frame,
+            )
+            .unwrap_or_default()
+            {
+                self.recent_frames.push(frame.to_vec());
+            }
+        }
+
         if let Some((calibrating, instant)) = self.infering_rune.as_ref().copied() {
             if instant.elapsed().as_secs() >= SOLVE_RUNE_TIMEOUT_SECS {
                 self.infering_rune = None;
@@ -56,10 +125,10 @@ impl DebugService {
             }
 
             match resources.detector().detect_rune_arrows(calibrating) {
-                Ok(ArrowsState::Complete(arrows)) => {
+                Ok(ArrowsState::Complete(arrows, confidences)) => {
                     // TODO: Save
                     self.infering_rune = None;
-                    debug!(target: "debug", "infer rune result {arrows:?}");
+                    debug!(target: "debug", "infer rune result {arrows:?} {confidences:?}");
                 }
                 Ok(ArrowsState::Calibrating(calibrating)) => {
                     self.infering_rune = Some((calibrating, instant));
@@ -83,8 +152,9 @@ impl DebugService {
         self.state.subscribe()
     }
 
-    pub fn set_auto_save_rune(&self, resources: &Resources, auto_save: bool) {
-        resources.debug.set_auto_save_rune(auto_save);
+    /// Applies debug-related `settings` to `resources`.
+    pub fn apply_settings(&self, resources: &Resources, settings: &Settings) {
+        resources.debug.set_auto_save_rune(settings.auto_save_rune);
     }
 
     pub fn capture_image(&self, resources: &Resources, is_grayscale: bool) {
@@ -101,6 +171,15 @@ impl DebugService {
         };
     }
 
+    pub fn dump_recent_frames(&self) {
+        save_encoded_frames(self.recent_frames.iter(), RECENT_FRAMES_EXTENSION);
+        debug!(target: "debug", "dumped {} recent frames", self.recent_frames.frames.len());
+    }
+
+    pub fn manual_rune_keys(&self, resources: &Resources, keys: [KeyKind; 4]) {
+        resources.debug.set_manual_rune_keys(keys);
+    }
+
     pub fn infer_rune(&mut self) {
         self.infering_rune = Some((ArrowsCalibrating::default(), Instant::now()));
     }
@@ -113,6 +192,45 @@ impl DebugService {
         }
     }
 
+    /// Matches every [`GameTemplate`] against the current frame.
+    ///
+    /// Returns an empty list if no frame has been captured.
+    pub fn detector_self_test(&self, resources: &Resources) -> Vec<TemplateSelfTest> {
+        let Some(detector) = resources.detector.as_ref() else {
+            return vec![];
+        };
+
+        GameTemplate::iter()
+            .map(|template| {
+                let result = detector.detect_template_confidence(template);
+                debug!(target: "debug", "self test template {template:?} result {result:?}");
+                TemplateSelfTest {
+                    template,
+                    detected: result.as_ref().is_ok_and(|(_, detected)| *detected),
+                    confidence: result.ok().map(|(confidence, _)| confidence as f32),
+                }
+            })
+            .collect()
+    }
+
+    /// Encodes the mask of the current `minimap` for `thresholds` as base64 PNG.
+    pub fn minimap_color_mask(
+        &self,
+        resources: &Resources,
+        minimap: Minimap,
+        thresholds: ColorThresholds,
+    ) -> Option<String> {
+        let Minimap::Idle(idle) = minimap else {
+            return None;
+        };
+        let detector = resources.detector.as_ref()?;
+        let mask = detector
+            .detect_minimap_color_mask(idle.bbox, thresholds.lower, thresholds.upper)
+            .ok()?;
+
+        to_base64_from_mat(&mask).ok()
+    }
+
     pub fn test_spin_rune(&self) {
         static SPIN_TEST_DIR: Dir<'static> = include_dir!("$SPIN_TEST_DIR");
         static SPIN_TEST_IMAGES: LazyLock<Vec<Mat>> = LazyLock::new(|| {
@@ -138,11 +256,19 @@ impl DebugService {
         calibrating.enable_spin_test();
 
         for mat in &*SPIN_TEST_IMAGES {
-            match DefaultDetector::new(OwnedMat::from(mat.clone()), localization.clone())
-                .detect_rune_arrows(calibrating)
+            match DefaultDetector::new(
+                OwnedMat::from(mat.clone()),
+                localization.clone(),
+                false,
+                100,
+                DetectionRegion::default(),
+                DetectionRegion::default(),
+                ColorThresholds::default(),
+            )
+            .detect_rune_arrows(calibrating)
             {
-                Ok(ArrowsState::Complete(arrows)) => {
-                    debug!(target: "test", "spin test completed {arrows:?}");
+                Ok(ArrowsState::Complete(arrows, confidences)) => {
+                    debug!(target: "test", "spin test completed {arrows:?} {confidences:?}");
                     break;
                 }
                 Ok(ArrowsState::Calibrating(new_calibrating)) => {
@@ -156,3 +282,91 @@ impl DebugService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use opencv::core::{CV_8UC1, MatExprTraitConst, Rect};
+
+    use super::*;
+    use crate::{detect::MockDetector, minimap::MinimapIdle};
+
+    #[test]
+    fn recent_frames_retains_only_capacity() {
+        let mut frames = RecentFrames::new(3);
+
+        for i in 0..5u8 {
+            frames.push(vec![i]);
+        }
+
+        assert_eq!(
+            frames.iter().collect::<Vec<_>>(),
+            vec![&[2u8][..], &[3u8][..], &[4u8][..]]
+        );
+    }
+
+    #[test]
+    fn recent_frames_zero_capacity_retains_nothing() {
+        let mut frames = RecentFrames::new(0);
+
+        frames.push(vec![0]);
+
+        assert_eq!(frames.iter().count(), 0);
+    }
+
+    #[test]
+    fn detector_self_test_aggregates_per_template() {
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_template_confidence()
+            .returning(|template| match template {
+                GameTemplate::CashShop => Ok((0.9, true)),
+                GameTemplate::PopupConfirm => Err(anyhow!("template larger than image")),
+                _ => Ok((0.25, false)),
+            });
+        let resources = Resources::new(None, Some(detector));
+
+        let results = DebugService::default().detector_self_test(&resources);
+
+        assert_eq!(results.len(), GameTemplate::iter().count());
+        for result in results {
+            let expected = match result.template {
+                GameTemplate::CashShop => (true, Some(0.9)),
+                GameTemplate::PopupConfirm => (false, None),
+                _ => (false, Some(0.25)),
+            };
+            assert_eq!((result.detected, result.confidence), expected);
+        }
+    }
+
+    #[test]
+    fn minimap_color_mask_encodes_mask_only_when_minimap_idle() {
+        let thresholds = ColorThresholds {
+            lower: [1, 2, 3],
+            upper: [4, 5, 6],
+        };
+        let bbox = Rect::new(0, 0, 2, 2);
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_minimap_color_mask()
+            .withf(move |rect, lower, upper| {
+                *rect == bbox && *lower == thresholds.lower && *upper == thresholds.upper
+            })
+            .returning(|_, _, _| Ok(Mat::zeros(2, 2, CV_8UC1).unwrap().to_mat().unwrap()));
+        let resources = Resources::new(None, Some(detector));
+        let mut idle = MinimapIdle::default();
+        idle.bbox = bbox;
+        let service = DebugService::default();
+
+        assert!(
+            service
+                .minimap_color_mask(&resources, Minimap::Detecting, thresholds)
+                .is_none()
+        );
+        assert!(
+            service
+                .minimap_color_mask(&resources, Minimap::Idle(idle), thresholds)
+                .is_some()
+        );
+    }
+}
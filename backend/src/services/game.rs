@@ -6,12 +6,12 @@ use mockall::{automock, concretize};
 use opencv::core::{MatTraitConst, MatTraitConstManual, Rect, Vec4b};
 use tokio::{
     spawn,
-    sync::broadcast::{self, Receiver, Sender},
+    sync::broadcast::{Receiver, Sender},
 };
 
 use crate::{
     BoundQuadrant, Character, DatabaseEvent, GameOperation, GameState, KeyBinding,
-    KeyBindingConfiguration, Localization, Minimap, Settings,
+    KeyBindingConfiguration, Localization, Minimap, RotationMode, Settings,
     bridge::InputReceiver,
     database_event_receiver,
     ecs::{Resources, World},
@@ -46,7 +46,13 @@ pub trait GameService: Debug {
 
     /// Broadcasts game state to listeners.
     #[cfg_attr(test, concretize)]
-    fn broadcast_state(&self, resources: &Resources, world: &World, minimap: Option<&Minimap>);
+    fn broadcast_state(
+        &self,
+        resources: &Resources,
+        world: &World,
+        minimap: Option<&Minimap>,
+        navigation_progress: Option<(usize, usize)>,
+    );
 
     /// Subscribes to game state.
     fn subscribe_state(&self) -> Receiver<GameState>;
@@ -64,12 +70,16 @@ pub struct DefaultGameService {
 }
 
 impl DefaultGameService {
-    pub fn new(input_rx: impl InputReceiver) -> Self {
+    pub fn new(
+        input_rx: impl InputReceiver,
+        key_tx: Sender<KeyBinding>,
+        game_state_tx: Sender<GameState>,
+    ) -> Self {
         Self {
             input_rx: Box::new(input_rx),
-            key_tx: broadcast::channel(1).0,
+            key_tx,
             database_event_rx: database_event_receiver(),
-            game_state_tx: broadcast::channel(1).0,
+            game_state_tx,
         }
     }
 }
@@ -103,6 +113,7 @@ impl GameService for DefaultGameService {
         resources: &Resources,
         world: &World,
         minimap_data: Option<&Minimap>,
+        navigation_progress: Option<(usize, usize)>,
     ) {
         if self.game_state_tx.is_empty() {
             let position = world
@@ -148,6 +159,15 @@ impl GameService for DefaultGameService {
             } else {
                 None
             };
+            let rotation_bound = minimap_data.and_then(|data| match data.rotation_mode {
+                RotationMode::StartToEnd | RotationMode::StartToEndThenReverse => None,
+                RotationMode::AutoMobbing => {
+                    Some(platforms_bound.unwrap_or(data.rotation_auto_mob_bound))
+                }
+                RotationMode::PingPong | RotationMode::Patrol => {
+                    Some(data.rotation_ping_pong_bound)
+                }
+            });
             let portals = if let Some(idle) = idle {
                 idle.portals()
                     .into_iter()
@@ -190,8 +210,10 @@ impl GameService for DefaultGameService {
                     operation,
                     frame,
                     platforms_bound,
+                    rotation_bound,
                     portals,
                     auto_mob_quadrant,
+                    navigation_progress,
                 };
                 let _ = sender.send(game_state);
             });
@@ -287,3 +309,76 @@ fn poll_database(
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use opencv::core::Point;
+    use strum::IntoEnumIterator;
+    use tokio::sync::broadcast::channel;
+
+    use super::*;
+    use crate::{
+        Bound,
+        bridge::MockInputReceiver,
+        buff::{Buff, BuffContext, BuffEntity, BuffKind},
+        minimap::{MinimapContext, MinimapEntity},
+        player::{Player, PlayerContext, PlayerEntity},
+        skill::{Skill, SkillContext, SkillEntity},
+    };
+
+    fn mock_world() -> World {
+        World {
+            minimap: MinimapEntity {
+                state: minimap::Minimap::Detecting,
+                context: MinimapContext::default(),
+            },
+            player: PlayerEntity {
+                state: Player::Idle,
+                context: PlayerContext::default(),
+            },
+            skills: SkillKind::iter()
+                .map(|kind| SkillEntity {
+                    state: Skill::Detecting,
+                    context: SkillContext::new(kind),
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+            buffs: BuffKind::iter()
+                .map(|kind| BuffEntity {
+                    state: Buff::No,
+                    context: BuffContext::new(kind),
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn broadcast_state_includes_rotation_bound() {
+        let (game_state_tx, mut game_state_rx) = channel(1);
+        let service =
+            DefaultGameService::new(MockInputReceiver::new(), channel(1).0, game_state_tx);
+        let resources = Resources::new(None, None);
+        let mut world = mock_world();
+        world.player.context.last_known_pos = Some(Point::new(10, 20));
+        let bound = Bound {
+            x: 5,
+            y: 6,
+            width: 30,
+            height: 20,
+        };
+        let minimap = Minimap {
+            rotation_mode: RotationMode::PingPong,
+            rotation_ping_pong_bound: bound,
+            ..Default::default()
+        };
+
+        service.broadcast_state(&resources, &world, Some(&minimap), None);
+        let state = game_state_rx.recv().await.unwrap();
+
+        assert_eq!(state.rotation_bound, Some(bound));
+        assert_eq!(state.position, Some((10, 20)));
+    }
+}
@@ -4,12 +4,16 @@ use std::fmt::Debug;
 use mockall::automock;
 
 use crate::{
+    Action, ActionKey, ActionMove, Bound, Position, PresetMetadata,
     database::Minimap as MinimapData,
     minimap::{Minimap, MinimapContext, MinimapEntity},
     pathing::Platform,
     player::PlayerContext,
 };
 
+/// Maximum number of actions edits that can be undone.
+const MAX_ACTIONS_HISTORY: usize = 50;
+
 /// A service to handle minimap-related incoming requests.
 #[cfg_attr(test, automock)]
 pub trait MinimapService: Debug {
@@ -24,8 +28,48 @@ pub trait MinimapService: Debug {
     fn preset(&self) -> Option<String>;
 
     /// Sets new `minimap` and `preset` to be used.
+    ///
+    /// If `minimap` and `preset` are the same as the currently in use ones but with different
+    /// actions, the previous actions are recorded for [`Self::undo_actions`]. Otherwise, the
+    /// actions edit history is cleared.
     fn update_minimap_preset(&mut self, minimap: Option<MinimapData>, preset: Option<String>);
 
+    /// Restores the actions of the currently in use preset to before the last edit.
+    ///
+    /// Returns the updated [`MinimapData`] or [`None`] if there is nothing to undo.
+    fn undo_actions(&mut self) -> Option<MinimapData>;
+
+    /// Re-applies the actions of the currently in use preset undone by [`Self::undo_actions`].
+    ///
+    /// Returns the updated [`MinimapData`] or [`None`] if there is nothing to redo.
+    fn redo_actions(&mut self) -> Option<MinimapData>;
+
+    /// Returns a copy of the currently in use [`MinimapData`] with the actions of preset `src`
+    /// duplicated to a new preset `dst`.
+    ///
+    /// Returns [`None`] if `src` does not exist or `dst` already exists.
+    fn duplicate_preset(&self, src: String, dst: String) -> Option<MinimapData>;
+
+    /// Returns a copy of the currently in use [`MinimapData`] with preset `old` renamed to `new`.
+    ///
+    /// Returns [`None`] if `old` does not exist or `new` already exists.
+    fn rename_preset(&self, old: String, new: String) -> Option<MinimapData>;
+
+    /// Returns a copy of the currently in use [`MinimapData`] with the actions of `preset` from
+    /// `source` appended to the currently in use preset.
+    ///
+    /// Positions are scaled to the current [`MinimapData`] if `source` has a different size.
+    /// Returns [`None`] if there is no preset in use or `preset` does not exist in `source`.
+    fn import_actions(&self, source: &MinimapData, preset: String) -> Option<MinimapData>;
+
+    /// Appends `actions` to the currently in use preset of a copy of the currently in use
+    /// [`MinimapData`].
+    ///
+    /// The copy is not used until passed to [`Self::update_minimap_preset`] so that the edit is
+    /// recorded for [`Self::undo_actions`]. Returns the updated copy or [`None`] if there is no
+    /// preset in use.
+    fn insert_actions(&self, actions: &[Action]) -> Option<MinimapData>;
+
     /// Updates `minimap_context` and `player_context` with information from the currently in use
     /// [`MinimapData`] and preset.
     fn apply(&self, minimap_context: &mut MinimapContext, player_context: &mut PlayerContext);
@@ -38,6 +82,30 @@ pub trait MinimapService: Debug {
 pub struct DefaultMinimapService {
     minimap: Option<MinimapData>,
     preset: Option<String>,
+    /// Previous actions of the currently in use preset, most recent last.
+    actions_undo: Vec<Vec<Action>>,
+    /// Actions of the currently in use preset undone by [`MinimapService::undo_actions`], most
+    /// recent last.
+    actions_redo: Vec<Vec<Action>>,
+}
+
+impl DefaultMinimapService {
+    /// Gets the actions of the currently in use preset.
+    fn preset_actions(&self) -> Option<Vec<Action>> {
+        let preset = self.preset.as_ref()?;
+        let minimap = self.minimap.as_ref()?;
+
+        Some(minimap.actions.get(preset).cloned().unwrap_or_default())
+    }
+
+    /// Replaces the actions of the currently in use preset with `actions`.
+    fn set_preset_actions(&mut self, actions: Vec<Action>) -> Option<MinimapData> {
+        let preset = self.preset.clone()?;
+        let minimap = self.minimap.as_mut()?;
+        minimap.actions.insert(preset, actions);
+
+        Some(minimap.clone())
+    }
 }
 
 impl MinimapService for DefaultMinimapService {
@@ -63,8 +131,117 @@ impl MinimapService for DefaultMinimapService {
     }
 
     fn update_minimap_preset(&mut self, minimap: Option<MinimapData>, preset: Option<String>) {
+        let is_same_preset = preset.is_some()
+            && self.preset == preset
+            && self
+                .minimap
+                .as_ref()
+                .zip(minimap.as_ref())
+                .is_some_and(|(current, new)| current.id == new.id);
+        let previous_actions = self.preset_actions();
+
         self.minimap = minimap;
         self.preset = preset;
+        if !is_same_preset {
+            self.actions_undo.clear();
+            self.actions_redo.clear();
+            return;
+        }
+
+        let previous_actions = previous_actions.expect("has preset");
+        if Some(&previous_actions) != self.preset_actions().as_ref() {
+            if self.actions_undo.len() >= MAX_ACTIONS_HISTORY {
+                self.actions_undo.remove(0);
+            }
+            self.actions_undo.push(previous_actions);
+            self.actions_redo.clear();
+        }
+    }
+
+    fn undo_actions(&mut self) -> Option<MinimapData> {
+        let current = self.preset_actions()?;
+        let actions = self.actions_undo.pop()?;
+        self.actions_redo.push(current);
+        self.set_preset_actions(actions)
+    }
+
+    fn redo_actions(&mut self) -> Option<MinimapData> {
+        let current = self.preset_actions()?;
+        let actions = self.actions_redo.pop()?;
+        self.actions_undo.push(current);
+        self.set_preset_actions(actions)
+    }
+
+    fn duplicate_preset(&self, src: String, dst: String) -> Option<MinimapData> {
+        let mut minimap = self.minimap.clone()?;
+        if minimap.actions.contains_key(&dst) {
+            return None;
+        }
+
+        let actions = minimap.actions.get(&src)?.clone();
+        let description = minimap
+            .preset_metadata
+            .get(&src)
+            .map(|metadata| metadata.description.clone())
+            .unwrap_or_default();
+        minimap
+            .preset_metadata
+            .insert(dst.clone(), PresetMetadata::new(description));
+        minimap.actions.insert(dst, actions);
+        Some(minimap)
+    }
+
+    fn rename_preset(&self, old: String, new: String) -> Option<MinimapData> {
+        let mut minimap = self.minimap.clone()?;
+        if minimap.actions.contains_key(&new) {
+            return None;
+        }
+
+        let actions = minimap.actions.remove(&old)?;
+        minimap.actions.insert(new.clone(), actions);
+        if let Some(metadata) = minimap.preset_metadata.remove(&old) {
+            minimap.preset_metadata.insert(new, metadata);
+        }
+        Some(minimap)
+    }
+
+    fn import_actions(&self, source: &MinimapData, preset: String) -> Option<MinimapData> {
+        let current_preset = self.preset.clone()?;
+        let mut minimap = self.minimap.clone()?;
+        let actions = source.actions.get(&preset)?;
+        let scale = if source.width > 0
+            && source.height > 0
+            && (source.width != minimap.width || source.height != minimap.height)
+        {
+            Some((
+                minimap.width as f32 / source.width as f32,
+                minimap.height as f32 / source.height as f32,
+            ))
+        } else {
+            None
+        };
+
+        minimap
+            .actions
+            .entry(current_preset)
+            .or_default()
+            .extend(actions.iter().copied().map(|action| match scale {
+                Some(scale) => scaled_action(action, scale),
+                None => action,
+            }));
+        Some(minimap)
+    }
+
+    fn insert_actions(&self, actions: &[Action]) -> Option<MinimapData> {
+        let preset = self.preset.clone()?;
+        let mut minimap = self.minimap.clone()?;
+
+        minimap
+            .actions
+            .entry(preset)
+            .or_default()
+            .extend_from_slice(actions);
+        Some(minimap)
     }
 
     fn apply(&self, minimap_context: &mut MinimapContext, player_context: &mut PlayerContext) {
@@ -97,6 +274,21 @@ impl MinimapService for DefaultMinimapService {
                 .config
                 .auto_mob_use_key_when_pathing_update_millis =
                 minimap.auto_mob_use_key_when_pathing_update_millis;
+            player_context.config.auto_mob_recenter_percent = minimap.auto_mob_recenter_percent;
+            player_context.config.auto_mob_attack_interval_millis =
+                minimap.auto_mob_attack_interval_millis;
+            player_context.config.auto_mob_prefer_higher_platforms =
+                minimap.auto_mob_prefer_higher_platforms;
+            player_context.config.auto_mob_ignore_xs_half_life_ticks =
+                minimap.auto_mob_ignore_xs_half_life_ticks;
+            player_context.config.ping_pong_bound_inset = minimap.rotation_ping_pong_bound_inset;
+            player_context.config.ping_pong_double_jump_weights =
+                minimap.rotation_ping_pong_double_jump_weights;
+            player_context.config.disable_portal_upjump_abort = minimap.disable_portal_upjump_abort;
+            player_context.config.stop_at_platform_edge = minimap.stop_at_platform_edge;
+            player_context
+                .config
+                .set_movement_scale_percent(minimap.movement_scale_percent);
         }
     }
 
@@ -105,6 +297,117 @@ impl MinimapService for DefaultMinimapService {
     }
 }
 
+/// Scales the position of `action` by `(scale_x, scale_y)`.
+fn scaled_action(action: Action, (scale_x, scale_y): (f32, f32)) -> Action {
+    let scale = |position: Position| Position {
+        x: (position.x as f32 * scale_x).round() as i32,
+        x_random_range: (position.x_random_range as f32 * scale_x).round() as i32,
+        y: (position.y as f32 * scale_y).round() as i32,
+        ..position
+    };
+
+    match action {
+        Action::Move(action) => Action::Move(ActionMove {
+            position: scale(action.position),
+            ..action
+        }),
+        Action::Key(action) => Action::Key(ActionKey {
+            position: action.position.map(scale),
+            ..action
+        }),
+        Action::HoldPosition(_) | Action::WaitForBuff(_) => action,
+    }
+}
+
+/// Clamps the rotation bounds of `minimap` to be within the minimap.
+///
+/// All bounds are validated regardless of the current rotation mode so that switching mode
+/// never uses an invalid bound. If a main bound has zero size or lies outside of the minimap, it
+/// is replaced with the whole minimap. Such extra auto mobbing bounds are removed instead. Returns
+/// the issues found with the bounds, if any.
+pub fn validate_rotation_bounds(minimap: &mut MinimapData) -> Vec<String> {
+    let (width, height) = (minimap.width, minimap.height);
+    if width <= 0 || height <= 0 {
+        return vec![];
+    }
+
+    let mut issues = Vec::new();
+    issues.extend(validate_bound(
+        &mut minimap.rotation_ping_pong_bound,
+        "Ping pong",
+        width,
+        height,
+    ));
+    issues.extend(validate_bound(
+        &mut minimap.rotation_auto_mob_bound,
+        "Auto mobbing",
+        width,
+        height,
+    ));
+    minimap
+        .rotation_auto_mob_extra_bounds
+        .retain_mut(|bound| match clamped_bound(*bound, width, height) {
+            Some(clamped) => {
+                if clamped != *bound {
+                    issues.push(format!(
+                        "Auto mobbing extra bound {bound:?} is clamped to within the minimap"
+                    ));
+                    *bound = clamped;
+                }
+                true
+            }
+            None => {
+                issues.push(format!(
+                    "Auto mobbing extra bound {bound:?} is empty or outside of the minimap, removing it"
+                ));
+                false
+            }
+        });
+
+    issues
+}
+
+/// Clamps `bound` named `name` to be within a minimap of `width` and `height`.
+///
+/// If `bound` has zero size or lies outside of the minimap, it is replaced with the whole minimap.
+fn validate_bound(bound: &mut Bound, name: &str, width: i32, height: i32) -> Option<String> {
+    let Some(clamped) = clamped_bound(*bound, width, height) else {
+        let issue = format!(
+            "{name} bound {bound:?} is empty or outside of the minimap, using the whole minimap"
+        );
+        *bound = Bound {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        };
+        return Some(issue);
+    };
+    if clamped != *bound {
+        let issue = format!("{name} bound {bound:?} is clamped to within the minimap");
+        *bound = clamped;
+        return Some(issue);
+    }
+
+    None
+}
+
+/// Clamps `bound` to be within a minimap of `width` and `height`.
+///
+/// Returns [`None`] if the clamped bound has zero size.
+fn clamped_bound(bound: Bound, width: i32, height: i32) -> Option<Bound> {
+    let x = bound.x.clamp(0, width);
+    let y = bound.y.clamp(0, height);
+    let clamped = Bound {
+        x,
+        y,
+        width: (bound.x + bound.width).clamp(0, width) - x,
+        height: (bound.y + bound.height).clamp(0, height) - y,
+    };
+
+    (clamped.width > 0 && clamped.height > 0).then_some(clamped)
+}
+
 #[cfg(test)]
 mod tests {
     use std::assert_matches::assert_matches;
@@ -113,7 +416,7 @@ mod tests {
 
     use super::*;
     use crate::{
-        Platform as DatabasePlatform,
+        ActionCondition, ActionSnippet, KeyBinding, Platform as DatabasePlatform, RotationMode,
         minimap::{Minimap, MinimapIdle},
         pathing::Platform,
     };
@@ -133,6 +436,7 @@ mod tests {
             rune_platforms_pathing_up_jump_only: true,
             auto_mob_platforms_pathing: true,
             auto_mob_platforms_bound: true,
+            movement_scale_percent: Some(80),
             ..Default::default()
         }
     }
@@ -176,6 +480,214 @@ mod tests {
         assert_eq!(service.preset, preset);
     }
 
+    #[test]
+    fn undo_redo_actions_restores_edits() {
+        let first = vec![Action::Key(ActionKey::default())];
+        let second = vec![Action::Move(ActionMove::default())];
+        let preset = Some("preset".to_string());
+        let mut minimap = mock_minimap_data();
+        minimap.actions.insert("preset".to_string(), first.clone());
+        let mut service = DefaultMinimapService::default();
+        service.update_minimap_preset(Some(minimap.clone()), preset.clone());
+        assert!(service.undo_actions().is_none());
+
+        minimap.actions.insert("preset".to_string(), second.clone());
+        service.update_minimap_preset(Some(minimap), preset.clone());
+
+        let minimap = service.undo_actions().unwrap();
+        assert_eq!(minimap.actions.get("preset"), Some(&first));
+        assert_eq!(service.minimap, Some(minimap.clone()));
+        assert!(service.undo_actions().is_none());
+
+        // Updating with the undone minimap does not record history
+        service.update_minimap_preset(Some(minimap), preset);
+
+        let minimap = service.redo_actions().unwrap();
+        assert_eq!(minimap.actions.get("preset"), Some(&second));
+        assert_eq!(service.minimap, Some(minimap));
+        assert!(service.redo_actions().is_none());
+    }
+
+    #[test]
+    fn duplicate_preset_copies_actions() {
+        let mut minimap = mock_minimap_data();
+        minimap
+            .actions
+            .insert("src".to_string(), vec![Action::Key(ActionKey::default())]);
+        let service = DefaultMinimapService {
+            minimap: Some(minimap.clone()),
+            preset: Some("src".to_string()),
+            ..Default::default()
+        };
+
+        let duplicated = service
+            .duplicate_preset("src".to_string(), "dst".to_string())
+            .unwrap();
+
+        assert_eq!(duplicated.actions.get("src"), duplicated.actions.get("dst"));
+        // Only the returned copy is updated
+        assert_eq!(service.minimap, Some(minimap));
+        assert_eq!(service.preset, Some("src".to_string()));
+        // Existing destination
+        assert!(
+            service
+                .duplicate_preset("src".to_string(), "src".to_string())
+                .is_none()
+        );
+        // Missing source
+        assert!(
+            service
+                .duplicate_preset("missing".to_string(), "other".to_string())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn rename_preset_preserves_actions() {
+        let actions = vec![Action::Key(ActionKey::default())];
+        let mut minimap = mock_minimap_data();
+        minimap.actions.insert("old".to_string(), actions.clone());
+        minimap.preset_metadata.insert(
+            "old".to_string(),
+            PresetMetadata::new("night farming".to_string()),
+        );
+        let service = DefaultMinimapService {
+            minimap: Some(minimap.clone()),
+            preset: Some("old".to_string()),
+            ..Default::default()
+        };
+
+        let renamed = service
+            .rename_preset("old".to_string(), "new".to_string())
+            .unwrap();
+
+        assert!(!renamed.actions.contains_key("old"));
+        assert_eq!(renamed.actions.get("new"), Some(&actions));
+        assert_eq!(
+            renamed.preset_metadata.get("new").unwrap().description,
+            "night farming"
+        );
+        // Only the returned copy is updated
+        assert_eq!(service.minimap, Some(minimap));
+        assert_eq!(service.preset, Some("old".to_string()));
+        // Missing source
+        assert!(
+            service
+                .rename_preset("missing".to_string(), "other".to_string())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn import_actions_copies_and_scales_positions() {
+        let position = Position {
+            x: 50,
+            x_random_range: 10,
+            y: 20,
+            allow_adjusting: false,
+        };
+        let mut source = MinimapData {
+            width: 50,
+            height: 200,
+            ..Default::default()
+        };
+        source.actions.insert(
+            "source".to_string(),
+            vec![
+                Action::Move(ActionMove {
+                    position,
+                    ..Default::default()
+                }),
+                Action::Key(ActionKey::default()),
+            ],
+        );
+        let service = DefaultMinimapService {
+            minimap: Some(mock_minimap_data()),
+            preset: Some("preset".to_string()),
+            ..Default::default()
+        };
+
+        let minimap = service
+            .import_actions(&source, "source".to_string())
+            .unwrap();
+
+        assert_eq!(
+            minimap.actions.get("preset").unwrap().as_slice(),
+            &[
+                Action::Move(ActionMove {
+                    position: Position {
+                        x: 100,
+                        x_random_range: 20,
+                        y: 10,
+                        allow_adjusting: false,
+                    },
+                    ..Default::default()
+                }),
+                Action::Key(ActionKey::default()),
+            ]
+        );
+        assert_eq!(service.minimap, Some(mock_minimap_data()));
+        // Missing source preset
+        assert!(
+            service
+                .import_actions(&source, "missing".to_string())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn insert_actions_appends_snippet_to_current_preset() {
+        let existing = Action::Key(ActionKey {
+            key: KeyBinding::A,
+            ..Default::default()
+        });
+        let snippet = ActionSnippet {
+            name: "Buffs".to_string(),
+            actions: vec![
+                Action::Key(ActionKey {
+                    key: KeyBinding::B,
+                    ..Default::default()
+                }),
+                Action::Key(ActionKey {
+                    key: KeyBinding::C,
+                    condition: ActionCondition::Linked,
+                    ..Default::default()
+                }),
+            ],
+            ..Default::default()
+        };
+        let mut minimap = mock_minimap_data();
+        minimap.actions.insert("preset".to_string(), vec![existing]);
+        let mut service = DefaultMinimapService {
+            minimap: Some(minimap),
+            preset: Some("preset".to_string()),
+            ..Default::default()
+        };
+
+        let minimap = service.insert_actions(&snippet.actions).unwrap();
+
+        assert_eq!(
+            minimap.actions.get("preset").unwrap().as_slice(),
+            &[existing, snippet.actions[0], snippet.actions[1]]
+        );
+        // Not used until updated to record undo history
+        assert_eq!(service.preset_actions().unwrap().as_slice(), &[existing]);
+        service.update_minimap_preset(Some(minimap), Some("preset".to_string()));
+        assert_eq!(
+            service
+                .undo_actions()
+                .unwrap()
+                .actions
+                .get("preset")
+                .unwrap()
+                .as_slice(),
+            &[existing]
+        );
+        // No preset in use
+        service.preset = None;
+        assert!(service.insert_actions(&snippet.actions).is_none());
+    }
+
     #[test]
     fn redetect_sets_minimap_to_detecting() {
         let service = DefaultMinimapService::default();
@@ -230,6 +742,7 @@ mod tests {
         let service = DefaultMinimapService {
             minimap: Some(mock_minimap_data()),
             preset: Some("preset".to_string()),
+            ..Default::default()
         };
         let mut minimap_context = MinimapContext::default();
         let mut player_state = PlayerContext::default();
@@ -240,5 +753,153 @@ mod tests {
         assert!(player_state.config.rune_platforms_pathing_up_jump_only);
         assert!(player_state.config.auto_mob_platforms_pathing);
         assert!(player_state.config.auto_mob_platforms_bound);
+        assert_eq!(player_state.config.movement_scale_percent, Some(80));
+    }
+
+    #[test]
+    fn update_clamps_stored_movement_scale_percent() {
+        let service = DefaultMinimapService {
+            minimap: Some(MinimapData {
+                movement_scale_percent: Some(0),
+                ..mock_minimap_data()
+            }),
+            preset: Some("preset".to_string()),
+            ..Default::default()
+        };
+        let mut minimap_context = MinimapContext::default();
+        let mut player_state = PlayerContext::default();
+
+        service.apply(&mut minimap_context, &mut player_state);
+
+        assert_eq!(player_state.config.movement_scale_percent, Some(50));
+        assert_eq!(player_state.move_timeout(10), 20);
+    }
+
+    #[test]
+    fn validate_rotation_bounds_rejects_zero_size() {
+        let mut minimap = MinimapData {
+            rotation_mode: RotationMode::AutoMobbing,
+            rotation_auto_mob_bound: Bound {
+                x: 10,
+                y: 10,
+                width: 0,
+                height: 20,
+            },
+            ..mock_minimap_data()
+        };
+
+        assert!(!validate_rotation_bounds(&mut minimap).is_empty());
+        assert_eq!(
+            minimap.rotation_auto_mob_bound,
+            Bound {
+                x: 0,
+                y: 0,
+                width: 100,
+                height: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn validate_rotation_bounds_clamps_to_minimap() {
+        let mut minimap = MinimapData {
+            rotation_mode: RotationMode::PingPong,
+            rotation_ping_pong_bound: Bound {
+                x: -10,
+                y: 50,
+                width: 50,
+                height: 80,
+            },
+            ..mock_minimap_data()
+        };
+
+        assert!(!validate_rotation_bounds(&mut minimap).is_empty());
+        assert_eq!(
+            minimap.rotation_ping_pong_bound,
+            Bound {
+                x: 0,
+                y: 50,
+                width: 40,
+                height: 50,
+            }
+        );
+        assert!(validate_rotation_bounds(&mut minimap).is_empty());
+    }
+
+    #[test]
+    fn validate_rotation_bounds_clamps_and_removes_extra_bounds() {
+        let mut minimap = MinimapData {
+            rotation_mode: RotationMode::AutoMobbing,
+            rotation_ping_pong_bound: Bound {
+                x: 0,
+                y: 0,
+                width: 50,
+                height: 50,
+            },
+            rotation_auto_mob_bound: Bound {
+                x: 0,
+                y: 0,
+                width: 50,
+                height: 50,
+            },
+            rotation_auto_mob_extra_bounds: vec![
+                Bound {
+                    x: 60,
+                    y: 60,
+                    width: 60,
+                    height: 20,
+                },
+                Bound {
+                    x: 150,
+                    y: 0,
+                    width: 10,
+                    height: 10,
+                },
+            ],
+            ..mock_minimap_data()
+        };
+
+        assert_eq!(validate_rotation_bounds(&mut minimap).len(), 2);
+        assert_eq!(
+            minimap.rotation_auto_mob_extra_bounds,
+            vec![Bound {
+                x: 60,
+                y: 60,
+                width: 40,
+                height: 20,
+            }]
+        );
+        assert!(validate_rotation_bounds(&mut minimap).is_empty());
+    }
+
+    #[test]
+    fn validate_rotation_bounds_validates_bounds_of_other_modes() {
+        let mut minimap = MinimapData {
+            rotation_mode: RotationMode::AutoMobbing,
+            rotation_ping_pong_bound: Bound {
+                x: 90,
+                y: 0,
+                width: 20,
+                height: 50,
+            },
+            rotation_auto_mob_bound: Bound {
+                x: 0,
+                y: 0,
+                width: 50,
+                height: 50,
+            },
+            ..mock_minimap_data()
+        };
+
+        assert_eq!(validate_rotation_bounds(&mut minimap).len(), 1);
+        assert_eq!(
+            minimap.rotation_ping_pong_bound,
+            Bound {
+                x: 90,
+                y: 0,
+                width: 10,
+                height: 50,
+            }
+        );
     }
 }
@@ -1,45 +1,58 @@
 use std::{cell::RefCell, rc::Rc, sync::Arc, time::Duration};
 
 use dyn_clone::clone_box;
+use log::{debug, info};
 use opencv::{
-    core::{ToInputArray, Vector},
+    core::{MatTraitConst, ToInputArray, Vector},
     imgcodecs::{IMREAD_COLOR, IMREAD_GRAYSCALE, imdecode, imencode_def},
 };
 use platforms::{Window, input::InputKind};
 use serenity::all::{CreateAttachment, EditInteractionResponse};
 use strum::EnumMessage;
 use tokio::{
-    sync::broadcast::Receiver,
+    sync::{
+        broadcast::{Receiver, Sender},
+        oneshot,
+    },
     task::{JoinHandle, spawn, spawn_blocking},
     time::sleep,
 };
 
 use crate::{
-    ActionKeyDirection, ActionKeyWith, Character, GameState, GameTemplate, KeyBinding,
-    LinkKeyBinding, Localization, Minimap, NavigationPath, RequestHandler, RotateKind, Settings,
+    Action, ActionKeyDirection, ActionKeyWith, Character, GameState, GameTemplate, KeyBinding,
+    LinkKeyBinding, Localization, Minimap, NavigationPath, OtherPlayerBehavior, RequestHandler,
+    RotateKind, Settings,
     bridge::{Capture, DefaultInputReceiver, Input},
+    buff::BuffKind,
     control::{BotAction, BotCommandKind},
-    detect::to_base64_from_mat,
+    database::{query_action_snippets, query_minimaps, upsert_minimap},
+    detect::{Detector, to_base64_from_mat},
     ecs::{Resources, World, WorldEvent},
+    minimap::Minimap as MinimapState,
     navigator::Navigator,
     notification::NotificationKind,
     operation::Operation,
-    player::{Chat, ChattingContent, Key, Panic, PanicTo, Panicking, Player, PlayerAction},
+    player::{
+        Chat, ChattingContent, Key, Panic, PanicTo, Panicking, Player, PlayerAction, PlayerContext,
+    },
     poll_request,
     rotator::Rotator,
     services::{
-        character::{CharacterService, DefaultCharacterService},
-        control::ControlService,
+        character::{
+            CharacterService, DefaultCharacterService, character_key_bindings, find_key_collisions,
+        },
         game::{DefaultGameService, GameEvent, GameService},
         localization::{DefaultLocalizationService, LocalizationService},
-        minimap::{DefaultMinimapService, MinimapService},
+        minimap::{DefaultMinimapService, MinimapService, validate_rotation_bounds},
         navigator::{DefaultNavigatorService, NavigatorService},
         rotator::{DefaultRotatorService, RotatorService},
-        settings::{DefaultSettingsService, SettingsService},
+        settings::{DefaultSettingsService, SettingsService, settings_key_bindings},
     },
 };
 #[cfg(debug_assertions)]
-use crate::{DebugState, services::debug::DebugService};
+use crate::{
+    ColorThresholds, DebugState, TemplateSelfTest, bridge::KeyKind, services::debug::DebugService,
+};
 
 mod character;
 mod control;
@@ -52,6 +65,33 @@ mod navigator;
 mod rotator;
 mod settings;
 
+pub use {
+    character::{set_character_actions_enabled, validate_character_keys},
+    control::ControlService,
+};
+
+/// Instance-level states and requests that can only be fulfilled by the systems loop.
+///
+/// Only the selected instance has access to this struct when polling.
+#[derive(Debug, Default)]
+pub struct InstanceControl {
+    /// The number of currently running instances.
+    pub count: usize,
+    /// The index of the instance receiving UI requests and bot commands.
+    pub selected: usize,
+    /// Whether a new instance should be created.
+    pub pending_create: bool,
+    /// The index of the instance to be removed.
+    pub pending_remove: Option<usize>,
+    /// The index of the non-selected instance and the sender to reply its status to.
+    ///
+    /// Only the selected instance receives bot commands, so status requests for other instances
+    /// are forwarded to the systems loop through this field.
+    pub pending_status: Option<(usize, oneshot::Sender<EditInteractionResponse>)>,
+    /// The index of the non-selected instance and the sender to reply its location to.
+    pub pending_where: Option<(usize, oneshot::Sender<EditInteractionResponse>)>,
+}
+
 #[derive(Debug)]
 pub struct DefaultService {
     event_rx: Receiver<WorldEvent>,
@@ -63,7 +103,8 @@ pub struct DefaultService {
     navigator: Box<dyn NavigatorService>,
     settings: Box<dyn SettingsService>,
     localization: Box<dyn LocalizationService>,
-    bot: ControlService,
+    /// The bot shared between all instances.
+    bot: Rc<RefCell<ControlService>>,
     #[cfg(debug_assertions)]
     debug: DebugService,
 }
@@ -72,18 +113,19 @@ impl DefaultService {
     pub fn new(
         settings: Rc<RefCell<Settings>>,
         localization: Rc<RefCell<Arc<Localization>>>,
+        bot: Rc<RefCell<ControlService>>,
+        key_tx: Sender<KeyBinding>,
+        game_state_tx: Sender<GameState>,
         event_rx: Receiver<WorldEvent>,
     ) -> Self {
         let settings_service = DefaultSettingsService::new(settings.clone());
         let window = settings_service.selected_window();
-        let input_rx = DefaultInputReceiver::new(window, InputKind::Focused);
-        let mut bot = ControlService::default();
-        bot.update(&settings_service.settings());
+        let input_rx = DefaultInputReceiver::new(window, settings_service.selected_input_kind());
 
         Self {
             event_rx,
             pending_halt: None,
-            game: Box::new(DefaultGameService::new(input_rx)),
+            game: Box::new(DefaultGameService::new(input_rx, key_tx, game_state_tx)),
             minimap: Box::new(DefaultMinimapService::default()),
             character: Box::new(DefaultCharacterService::default()),
             rotator: Box::new(DefaultRotatorService::default()),
@@ -101,10 +143,51 @@ impl DefaultService {
             .apply_selected_window(input, self.game.input_receiver_mut(), capture);
     }
 
+    /// Applies [`Settings`] to the operation and input of `resources` and to `capture`.
+    pub fn apply_settings(&mut self, resources: &mut Resources, capture: &mut dyn Capture) {
+        self.settings.apply_settings(
+            &mut resources.operation,
+            resources.input.as_mut(),
+            self.game.input_receiver_mut(),
+            capture,
+        );
+    }
+
+    /// Switches `capture` to the next fallback capture mode, returning `true` if switched.
+    pub fn fallback_capture(&mut self, input: &mut dyn Input, capture: &mut dyn Capture) -> bool {
+        self.settings
+            .fallback_capture(input, self.game.input_receiver_mut(), capture)
+    }
+
+    /// Applies player-related settings to `player_context`.
+    pub fn apply_player_settings(&self, player_context: &mut PlayerContext) {
+        self.settings.apply_player_settings(player_context);
+    }
+
+    /// Applies settings that are only used in debug builds to `resources`.
+    #[cfg(debug_assertions)]
+    pub fn apply_debug_settings(&self, resources: &Resources) {
+        self.debug
+            .apply_settings(resources, &self.settings.settings());
+    }
+
     pub fn selected_window(&self) -> Window {
         self.settings.selected_window()
     }
 
+    pub fn selected_input_kind(&self) -> InputKind {
+        self.settings.selected_input_kind()
+    }
+
+    /// Returns the name of the currently used map data.
+    pub fn minimap_name(&self) -> Option<String> {
+        self.minimap.minimap().map(|minimap| minimap.name.clone())
+    }
+
+    /// Polls for requests and events.
+    ///
+    /// `control` is only provided for the selected instance, which is the only instance that
+    /// handles UI requests, bot commands and broadcasts its state.
     #[inline]
     pub fn poll(
         &mut self,
@@ -113,7 +196,9 @@ impl DefaultService {
         rotator: &mut dyn Rotator,
         navigator: &mut dyn Navigator,
         capture: &mut dyn Capture,
+        control: Option<&mut InstanceControl>,
     ) {
+        let selected = control.is_some();
         let mut handler = DefaultRequestHandler {
             service: self,
             resources,
@@ -121,13 +206,18 @@ impl DefaultService {
             rotator,
             navigator,
             capture,
+            control,
         };
         // TODO: Maybe handling 1 by 1 on each tick instead of all at once?
-        handler.poll_request();
+        if selected {
+            handler.poll_request();
+        }
         handler.poll_game_events();
         handler.poll_context_event();
-        handler.poll_bot();
-        handler.broadcast_state();
+        if selected {
+            handler.poll_bot();
+            handler.broadcast_state();
+        }
     }
 }
 
@@ -139,6 +229,7 @@ struct DefaultRequestHandler<'a> {
     rotator: &'a mut dyn Rotator,
     navigator: &'a mut dyn Navigator,
     capture: &'a mut dyn Capture,
+    control: Option<&'a mut InstanceControl>,
 }
 
 impl DefaultRequestHandler<'_> {
@@ -146,6 +237,50 @@ impl DefaultRequestHandler<'_> {
         poll_request(self);
     }
 
+    /// Clears the movement calibration of the player and the current minimap.
+    fn reset_movement_calibration(&mut self) {
+        self.world.player.context.clear_movement_calibration();
+
+        let Some(mut minimap) = self.service.minimap.minimap().cloned() else {
+            return;
+        };
+        if minimap.id.is_none() || minimap.movement_scale_percent.is_none() {
+            return;
+        }
+
+        // Reapplied through the database event
+        minimap.movement_scale_percent = None;
+        let _ = upsert_minimap(&mut minimap);
+    }
+
+    /// Updates the enabled states of buffs from the current character and settings.
+    ///
+    /// Buffs waited on by the actions of the currently in use preset are also enabled.
+    fn update_buffs_enabled_state(&mut self) {
+        let Some(character) = self.service.character.character() else {
+            return;
+        };
+        let waited = self
+            .service
+            .minimap
+            .minimap()
+            .zip(self.service.minimap.preset())
+            .and_then(|(minimap, preset)| minimap.actions.get(&preset))
+            .into_iter()
+            .flatten()
+            .filter_map(|action| match action {
+                Action::WaitForBuff(action) => Some(BuffKind::from(action.buff)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let settings = self.service.settings.settings();
+
+        self.world.buffs.iter_mut().for_each(|buff| {
+            buff.context
+                .update_enabled_state(character, &settings, &waited);
+        });
+    }
+
     fn poll_game_events(&mut self) {
         let events = self.service.game.poll_events(
             self.service
@@ -169,18 +304,23 @@ impl DefaultRequestHandler<'_> {
                     self.update_halting(kind);
                 }
                 GameEvent::MinimapUpdated(minimap) => {
-                    self.on_update_minimap(self.service.minimap.preset(), minimap)
+                    let _ = self.on_update_minimap(self.service.minimap.preset(), minimap);
                 }
                 GameEvent::CharacterUpdated(character) => self.on_update_character(character),
                 GameEvent::SettingsUpdated(settings) => {
                     self.service.settings.update_settings(settings);
-                    self.service.settings.apply_settings(
-                        &mut self.resources.operation,
-                        self.resources.input.as_mut(),
-                        self.service.game.input_receiver_mut(),
-                        self.capture,
-                    );
-                    self.service.bot.update(&self.service.settings.settings());
+                    self.service
+                        .apply_player_settings(&mut self.world.player.context);
+                    #[cfg(debug_assertions)]
+                    self.service.apply_debug_settings(self.resources);
+                    self.service.apply_settings(self.resources, self.capture);
+                    // Bot is shared so only the selected instance restarts it
+                    if self.control.is_some() {
+                        self.service
+                            .bot
+                            .borrow_mut()
+                            .update(&self.service.settings.settings());
+                    }
                     self.service.rotator.apply(
                         self.rotator,
                         self.service.minimap.minimap(),
@@ -258,11 +398,126 @@ impl DefaultRequestHandler<'_> {
                     .notification
                     .schedule_notification(NotificationKind::FailOrMapChange);
             }
+            WorldEvent::CaptureWindowNotFound => {
+                if self.service.settings.update_windows_and_reselect() {
+                    self.service.settings.apply_selected_window(
+                        self.resources.input.as_mut(),
+                        self.service.game.input_receiver_mut(),
+                        self.capture,
+                    );
+                }
+            }
+            WorldEvent::GameFrozen => {
+                if self.resources.operation.halting() {
+                    return;
+                }
+
+                if self.service.settings.settings().stop_on_game_frozen {
+                    self.update_halt_or_panic(true, false);
+                }
+                let _ = self
+                    .resources
+                    .notification
+                    .schedule_notification(NotificationKind::GameFrozen);
+            }
+            WorldEvent::PlayerStrangerAppeared => {
+                let settings = self.service.settings.settings();
+                let behavior = settings.stranger_player_behavior;
+                let whitelisted = behavior != OtherPlayerBehavior::None
+                    && self.resources.detector.as_deref().is_some_and(|detector| {
+                        player_names_whitelisted(detector, &settings.player_name_whitelist)
+                    });
+                drop(settings);
+
+                if !whitelisted {
+                    self.update_from_other_player_behavior(behavior);
+                }
+            }
+            WorldEvent::PlayerGuildieAppeared => {
+                let behavior = self.service.settings.settings().guildie_player_behavior;
+                self.update_from_other_player_behavior(behavior);
+            }
+            WorldEvent::NavigationFailed => {
+                if self.resources.operation.halting() {
+                    return;
+                }
+
+                self.update_halt_or_panic(true, false);
+                let _ = self
+                    .resources
+                    .notification
+                    .schedule_notification(NotificationKind::FailOrMapChange);
+            }
+            WorldEvent::MovementCalibrated(percent) => {
+                let Some(mut minimap) = self.service.minimap.minimap().cloned() else {
+                    return;
+                };
+                if minimap.id.is_none() {
+                    return;
+                }
+
+                // Reapplied through the database event
+                minimap.movement_scale_percent = Some(percent);
+                let _ = upsert_minimap(&mut minimap);
+            }
         }
     }
 
+    fn on_bot_status(
+        &mut self,
+        instance: Option<usize>,
+        sender: oneshot::Sender<EditInteractionResponse>,
+    ) {
+        let control = self
+            .control
+            .as_deref_mut()
+            .expect("only selected instance can handle bot commands");
+        let Some((index, sender)) = bot_instance_index(control, instance, sender) else {
+            return;
+        };
+        if index != control.selected {
+            control.pending_status = Some((index, sender));
+            return;
+        }
+
+        send_status(
+            self.resources,
+            self.world,
+            self.navigator.navigation_progress(),
+            self.rotator.action_schedules(),
+            index,
+            sender,
+        );
+    }
+
+    fn on_bot_where(
+        &mut self,
+        instance: Option<usize>,
+        sender: oneshot::Sender<EditInteractionResponse>,
+    ) {
+        let control = self
+            .control
+            .as_deref_mut()
+            .expect("only selected instance can handle bot commands");
+        let Some((index, sender)) = bot_instance_index(control, instance, sender) else {
+            return;
+        };
+        if index != control.selected {
+            control.pending_where = Some((index, sender));
+            return;
+        }
+
+        send_where(
+            self.resources,
+            self.world,
+            self.service.minimap_name(),
+            sender,
+        );
+    }
+
     fn poll_bot(&mut self) {
-        if let Some(command) = self.service.bot.poll() {
+        let command = self.service.bot.borrow_mut().poll();
+        if let Some(command) = command {
             match command.kind {
                 BotCommandKind::Start => {
                     if !self.resources.operation.halting() {
@@ -296,21 +551,11 @@ impl DefaultRequestHandler<'_> {
                         .send(EditInteractionResponse::new().content("Bot attempted to suspend."));
                     self.update_halting(RotateKind::TemporaryHalt);
                 }
-                BotCommandKind::Status => {
-                    let provider = state_and_frame_provider(self.resources, self.world);
-
-                    spawn_blocking(move || {
-                        let (status, frame) = provider();
-                        let attachment =
-                            frame.map(|bytes| CreateAttachment::bytes(bytes, "image.webp"));
-
-                        let mut builder = EditInteractionResponse::new().content(status);
-                        if let Some(attachment) = attachment {
-                            builder = builder.new_attachment(attachment);
-                        }
-
-                        let _ = command.sender.send(builder);
-                    });
+                BotCommandKind::Status { instance } => {
+                    self.on_bot_status(instance, command.sender);
+                }
+                BotCommandKind::Where { instance } => {
+                    self.on_bot_where(instance, command.sender);
                 }
                 BotCommandKind::Chat { content } => {
                     if content.chars().count() >= ChattingContent::MAX_LENGTH {
@@ -342,6 +587,7 @@ impl DefaultRequestHandler<'_> {
                             wait_before_use_ticks_random_range: 5,
                             wait_after_use_ticks: 15,
                             wait_after_use_ticks_random_range: 0,
+                            force: false,
                         }),
                         BotAction::DoubleJump => {
                             PlayerAction::Key(Key {
@@ -357,6 +603,7 @@ impl DefaultRequestHandler<'_> {
                                 wait_before_use_ticks_random_range: 0,
                                 wait_after_use_ticks: 0,
                                 wait_after_use_ticks_random_range: 55,
+                                force: false,
                             })
                         }
                         BotAction::Crouch => {
@@ -371,6 +618,7 @@ impl DefaultRequestHandler<'_> {
                                 wait_before_use_ticks_random_range: 0,
                                 wait_after_use_ticks: 10,
                                 wait_after_use_ticks_random_range: 0,
+                                force: false,
                             })
                         }
                     };
@@ -391,6 +639,7 @@ impl DefaultRequestHandler<'_> {
             self.resources,
             self.world,
             self.service.minimap.minimap(),
+            self.navigator.navigation_progress(),
         );
     }
 
@@ -413,6 +662,26 @@ impl DefaultRequestHandler<'_> {
         }
     }
 
+    fn update_from_other_player_behavior(&mut self, behavior: OtherPlayerBehavior) {
+        if self.resources.operation.halting() {
+            return;
+        }
+
+        match behavior {
+            OtherPlayerBehavior::None => (),
+            OtherPlayerBehavior::Halt => self.update_halt_or_panic(true, false),
+            OtherPlayerBehavior::CycleChannel => {
+                if matches!(self.world.player.state, Player::Panicking(_)) {
+                    return;
+                }
+                self.rotator.inject_action(PlayerAction::Panic(Panic {
+                    to: PanicTo::Channel,
+                }));
+            }
+            OtherPlayerBehavior::GoToTown => self.update_halt_or_panic(true, true),
+        }
+    }
+
     fn update_halt_or_panic(&mut self, should_halt: bool, should_panic: bool) {
         self.rotator.reset_queue();
         self.world
@@ -445,7 +714,19 @@ impl RequestHandler for DefaultRequestHandler<'_> {
         self.service.minimap.create(self.world.minimap.state, name)
     }
 
-    fn on_update_minimap(&mut self, preset: Option<String>, minimap: Option<Minimap>) {
+    fn on_update_minimap(
+        &mut self,
+        preset: Option<String>,
+        mut minimap: Option<Minimap>,
+    ) -> (Option<Minimap>, Vec<String>) {
+        let issues = minimap
+            .as_mut()
+            .map(validate_rotation_bounds)
+            .unwrap_or_default()
+            .into_iter()
+            .inspect(|issue| info!(target: "handler", "{issue}"))
+            .collect::<Vec<_>>();
+        let corrected = minimap.clone().filter(|_| !issues.is_empty());
         self.service.minimap.update_minimap_preset(minimap, preset);
         self.service.minimap.apply(
             &mut self.world.minimap.context,
@@ -467,6 +748,76 @@ impl RequestHandler for DefaultRequestHandler<'_> {
             character,
             &self.service.settings.settings(),
         );
+        self.update_buffs_enabled_state();
+
+        (corrected, issues)
+    }
+
+    fn on_duplicate_preset(&mut self, src: String, dst: String) -> Option<Minimap> {
+        let minimap = self.service.minimap.duplicate_preset(src, dst)?;
+        let _ = self.on_update_minimap(self.service.minimap.preset(), Some(minimap.clone()));
+        Some(minimap)
+    }
+
+    fn on_rename_preset(&mut self, old: String, new: String) -> Option<Minimap> {
+        let minimap = self
+            .service
+            .minimap
+            .rename_preset(old.clone(), new.clone())?;
+        let preset = self
+            .service
+            .minimap
+            .preset()
+            .map(|preset| if preset == old { new } else { preset });
+        let _ = self.on_update_minimap(preset, Some(minimap.clone()));
+        Some(minimap)
+    }
+
+    fn on_import_actions_from(&mut self, minimap_id: i64, preset: String) -> Option<Minimap> {
+        let source = query_minimaps()
+            .ok()?
+            .into_iter()
+            .find(|minimap| minimap.id == Some(minimap_id))?;
+        let minimap = self.service.minimap.import_actions(&source, preset)?;
+        let _ = self.on_update_minimap(self.service.minimap.preset(), Some(minimap.clone()));
+
+        Some(minimap)
+    }
+
+    fn on_insert_action_snippet(&mut self, snippet_id: i64) -> Option<Minimap> {
+        let snippet = query_action_snippets()
+            .ok()?
+            .into_iter()
+            .find(|snippet| snippet.id == Some(snippet_id))?;
+        let minimap = self.service.minimap.insert_actions(&snippet.actions)?;
+        let _ = self.on_update_minimap(self.service.minimap.preset(), Some(minimap.clone()));
+
+        Some(minimap)
+    }
+
+    fn on_insert_character_action_snippet(&mut self, snippet_id: i64) -> Option<Character> {
+        let snippet = query_action_snippets()
+            .ok()?
+            .into_iter()
+            .find(|snippet| snippet.id == Some(snippet_id))?;
+        let character = self.service.character.insert_actions(&snippet.actions)?;
+        self.on_update_character(Some(character.clone()));
+
+        Some(character)
+    }
+
+    fn on_undo_minimap_actions(&mut self) -> Option<Minimap> {
+        let minimap = self.service.minimap.undo_actions()?;
+        let _ = self.on_update_minimap(self.service.minimap.preset(), Some(minimap.clone()));
+
+        Some(minimap)
+    }
+
+    fn on_redo_minimap_actions(&mut self) -> Option<Minimap> {
+        let minimap = self.service.minimap.redo_actions()?;
+        let _ = self.on_update_minimap(self.service.minimap.preset(), Some(minimap.clone()));
+
+        Some(minimap)
     }
 
     fn on_create_navigation_path(&self) -> Option<NavigationPath> {
@@ -481,6 +832,14 @@ impl RequestHandler for DefaultRequestHandler<'_> {
             .recapture_path(self.resources, self.world.minimap.state, path)
     }
 
+    fn on_reorder_navigation_path(
+        &self,
+        path: NavigationPath,
+        new_order: Vec<usize>,
+    ) -> NavigationPath {
+        self.service.navigator.reorder_path_points(path, new_order)
+    }
+
     fn on_navigation_snapshot_as_grayscale(&self, base64: String) -> String {
         self.service
             .navigator
@@ -488,6 +847,14 @@ impl RequestHandler for DefaultRequestHandler<'_> {
     }
 
     fn on_update_character(&mut self, character: Option<Character>) {
+        let previous_id = self
+            .service
+            .character
+            .character()
+            .and_then(|character| character.id);
+        // Walking speed depends on the character so calibration is redone on switching character
+        let character_changed = previous_id.is_some()
+            && character.as_ref().and_then(|character| character.id) != previous_id;
         self.service.character.update_character(character);
         self.service
             .character
@@ -502,14 +869,15 @@ impl RequestHandler for DefaultRequestHandler<'_> {
             .rotator
             .update_actions(minimap, preset, character);
         self.service.rotator.update_buffs(character);
-        if let Some(character) = character {
-            self.world.buffs.iter_mut().for_each(|buff| {
-                buff.context.update_enabled_state(character, &settings);
-            });
-        }
         self.service
             .rotator
             .apply(self.rotator, minimap, character, &settings);
+        drop(settings);
+        self.update_buffs_enabled_state();
+
+        if character_changed {
+            self.reset_movement_calibration();
+        }
     }
 
     fn on_redetect_minimap(&mut self) {
@@ -562,16 +930,59 @@ impl RequestHandler for DefaultRequestHandler<'_> {
         to_base64_from_mat(&mat).ok()
     }
 
+    fn on_query_key_conflicts(&self) -> Vec<String> {
+        let mut keys = settings_key_bindings(&self.service.settings.settings());
+        if let Some(character) = self.service.character.character() {
+            keys.extend(character_key_bindings(character));
+        }
+
+        find_key_collisions(&keys)
+    }
+
+    fn on_query_instances(&self) -> (usize, usize) {
+        self.control
+            .as_ref()
+            .map(|control| (control.count, control.selected))
+            .unwrap_or((1, 0))
+    }
+
+    fn on_create_instance(&mut self) {
+        let Some(control) = self.control.as_mut() else {
+            debug!(target: "instance", "ignored create request from non-selected instance");
+            return;
+        };
+        control.pending_create = true;
+    }
+
+    fn on_remove_instance(&mut self, index: usize) {
+        let Some(control) = self.control.as_mut() else {
+            debug!(target: "instance", "ignored remove request from non-selected instance");
+            return;
+        };
+        if index != 0 && index < control.count {
+            control.pending_remove = Some(index);
+        }
+    }
+
+    fn on_select_instance(&mut self, index: usize) {
+        let Some(control) = self.control.as_mut() else {
+            debug!(target: "instance", "ignored select request from non-selected instance");
+            return;
+        };
+        if index < control.count {
+            control.selected = index;
+        }
+    }
+
     #[cfg(debug_assertions)]
     fn on_debug_state_receiver(&self) -> Receiver<DebugState> {
         self.service.debug.subscribe_state()
     }
 
     #[cfg(debug_assertions)]
-    fn on_auto_save_rune(&self, auto_save: bool) {
-        self.service
-            .debug
-            .set_auto_save_rune(self.resources, auto_save);
+    fn on_auto_save_rune(&mut self, auto_save: bool) {
+        self.service.settings.set_auto_save_rune(auto_save);
+        self.service.apply_debug_settings(self.resources);
     }
 
     #[cfg(debug_assertions)]
@@ -586,6 +997,11 @@ impl RequestHandler for DefaultRequestHandler<'_> {
         self.service.debug.infer_rune();
     }
 
+    #[cfg(debug_assertions)]
+    fn on_manual_rune_keys(&self, keys: [KeyKind; 4]) {
+        self.service.debug.manual_rune_keys(self.resources, keys);
+    }
+
     #[cfg(debug_assertions)]
     fn on_infer_minimap(&self) {
         self.service.debug.infer_minimap(self.resources);
@@ -596,41 +1012,800 @@ impl RequestHandler for DefaultRequestHandler<'_> {
         self.service.debug.record_images(start);
     }
 
+    #[cfg(debug_assertions)]
+    fn on_dump_recent_frames(&self) {
+        self.service.debug.dump_recent_frames();
+    }
+
     #[cfg(debug_assertions)]
     fn on_test_spin_rune(&self) {
         self.service.debug.test_spin_rune();
     }
+
+    #[cfg(debug_assertions)]
+    fn on_replay_actions(&mut self) {
+        let actions = self.rotator.action_log();
+        self.rotator.replay_actions(actions);
+    }
+
+    #[cfg(debug_assertions)]
+    fn on_detector_self_test(&self) -> Vec<TemplateSelfTest> {
+        self.service.debug.detector_self_test(self.resources)
+    }
+
+    #[cfg(debug_assertions)]
+    fn on_query_minimap_color_mask(&self, thresholds: ColorThresholds) -> Option<String> {
+        self.service
+            .debug
+            .minimap_color_mask(self.resources, self.world.minimap.state, thresholds)
+    }
+}
+
+/// Returns whether all of the other player names visible on the screen are in `whitelist`.
+///
+/// Names are trimmed and compared case-insensitively.
+fn player_names_whitelisted(detector: &dyn Detector, whitelist: &[String]) -> bool {
+    let whitelist = whitelist
+        .iter()
+        .map(|name| normalized_player_name(name))
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    if whitelist.is_empty() {
+        return false;
+    }
+
+    let names = detector.detect_player_names();
+    !names.is_empty()
+        && names
+            .iter()
+            .all(|name| whitelist.contains(&normalized_player_name(name)))
+}
+
+#[inline]
+fn normalized_player_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Resolves the index of the instance targeted by a bot command.
+///
+/// Defaults to the selected instance. Replies through `sender` and returns `None` if the instance
+/// does not exist.
+fn bot_instance_index(
+    control: &InstanceControl,
+    instance: Option<usize>,
+    sender: oneshot::Sender<EditInteractionResponse>,
+) -> Option<(usize, oneshot::Sender<EditInteractionResponse>)> {
+    let index = instance.unwrap_or(control.selected);
+    if index >= control.count {
+        let _ = sender.send(
+            EditInteractionResponse::new()
+                .content(format!("Instance {} does not exist.", index + 1)),
+        );
+        return None;
+    }
+
+    Some((index, sender))
+}
+
+/// Returns the detected map name in `minimap` and the name of the selected `map_data`.
+fn where_content(
+    detector: Option<&dyn Detector>,
+    minimap: MinimapState,
+    map_data: Option<&str>,
+) -> String {
+    let name = match (detector, minimap) {
+        (Some(detector), MinimapState::Idle(idle)) => detector
+            .detect_minimap_name_text(idle.bbox)
+            .unwrap_or_else(|_| "Unknown".to_string()),
+        _ => "Not detected".to_string(),
+    };
+
+    [
+        format!("- Map: ``{name}``"),
+        format!("- Map data: ``{}``", map_data.unwrap_or("None")),
+    ]
+    .join("\n")
+}
+
+/// Replies the map name and the cropped minimap image of an instance through `sender`.
+pub fn send_where(
+    resources: &Resources,
+    world: &World,
+    map_data: Option<String>,
+    sender: oneshot::Sender<EditInteractionResponse>,
+) {
+    let detector = resources
+        .detector
+        .as_ref()
+        .map(|detector| clone_box(detector.as_ref()));
+    let minimap = world.minimap.state;
+
+    spawn_blocking(move || {
+        let content = where_content(detector.as_deref(), minimap, map_data.as_deref());
+        let frame = match (detector, minimap) {
+            (Some(detector), MinimapState::Idle(idle)) => detector
+                .mat()
+                .roi(idle.bbox)
+                .ok()
+                .and_then(|minimap| frame_from(&minimap)),
+            _ => None,
+        };
+        let attachment = frame.map(|bytes| CreateAttachment::bytes(bytes, "minimap.webp"));
+
+        let mut builder = EditInteractionResponse::new().content(content);
+        if let Some(attachment) = attachment {
+            builder = builder.new_attachment(attachment);
+        }
+
+        let _ = sender.send(builder);
+    });
+}
+
+/// Replies the status of the instance at `index` through `sender`.
+pub fn send_status(
+    resources: &Resources,
+    world: &World,
+    navigation_progress: Option<(usize, usize)>,
+    action_schedules: Vec<(String, u64)>,
+    index: usize,
+    sender: oneshot::Sender<EditInteractionResponse>,
+) {
+    let provider = state_and_frame_provider(
+        resources,
+        world,
+        navigation_progress,
+        action_schedules,
+        index,
+    );
+
+    spawn_blocking(move || {
+        let (status, frame) = provider();
+        let attachment = frame.map(|bytes| CreateAttachment::bytes(bytes, "image.webp"));
+
+        let mut builder = EditInteractionResponse::new().content(status);
+        if let Some(attachment) = attachment {
+            builder = builder.new_attachment(attachment);
+        }
+
+        let _ = sender.send(builder);
+    });
 }
 
 fn state_and_frame_provider(
     resources: &Resources,
     world: &World,
+    navigation_progress: Option<(usize, usize)>,
+    action_schedules: Vec<(String, u64)>,
+    index: usize,
 ) -> impl FnOnce() -> (String, Option<Vec<u8>>) + Send + 'static {
-    #[inline]
-    fn frame_from(mat: &impl ToInputArray) -> Option<Vec<u8>> {
-        let mut vector = Vector::new();
-        imencode_def(".webp", mat, &mut vector).ok()?;
-        Some(Vec::from_iter(vector))
-    }
-
     let detector = resources
         .detector
         .as_ref()
         .map(|detector| clone_box(detector.as_ref()));
     let state = world.player.state.to_string();
     let operation = resources.operation.to_string();
+    let navigation = navigation_progress
+        .map(|(current, total)| format!("{current}/{total}"))
+        .unwrap_or_else(|| "None".to_string());
+    let schedules = if action_schedules.is_empty() {
+        "None".to_string()
+    } else {
+        action_schedules
+            .into_iter()
+            .map(|(name, millis)| format!("{name} in {:.1}s", millis as f32 / 1000.0))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
 
     move || {
         let frame = detector.and_then(|detector| frame_from(detector.mat()));
         let info = [
+            format!("- Instance: ``{}``", index + 1),
             format!("- State: ``{state}``"),
             format!("- Operation: ``{operation}``"),
+            format!("- Navigation: ``{navigation}``"),
+            format!("- Next ready: ``{schedules}``"),
         ]
         .join("\n");
         (info, frame)
     }
 }
 
+#[inline]
+fn frame_from(mat: &impl ToInputArray) -> Option<Vec<u8>> {
+    let mut vector = Vector::new();
+    imencode_def(".webp", mat, &mut vector).ok()?;
+    Some(Vec::from_iter(vector))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{assert_matches::assert_matches, cell::RefCell, rc::Rc, sync::Arc};
+
+    use strum::IntoEnumIterator;
+    use tokio::sync::broadcast::channel;
+
+    use super::*;
+    use crate::{
+        ActionConfiguration, KeyBindingConfiguration,
+        bridge::MockCapture,
+        buff::{Buff, BuffContext, BuffEntity, BuffKind},
+        detect::MockDetector,
+        minimap::{MinimapContext, MinimapEntity, MinimapIdle},
+        navigator::MockNavigator,
+        player::{PlayerContext, PlayerEntity},
+        rotator::MockRotator,
+        services::{
+            character::MockCharacterService, game::MockGameService, minimap::MockMinimapService,
+            rotator::MockRotatorService, settings::MockSettingsService,
+        },
+        skill::{Skill, SkillContext, SkillEntity, SkillKind},
+    };
+
+    fn mock_world() -> World {
+        World {
+            minimap: MinimapEntity {
+                state: MinimapState::Detecting,
+                context: MinimapContext::default(),
+            },
+            player: PlayerEntity {
+                state: Player::Idle,
+                context: PlayerContext::default(),
+            },
+            skills: SkillKind::iter()
+                .map(|kind| SkillEntity {
+                    state: Skill::Detecting,
+                    context: SkillContext::new(kind),
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+            buffs: BuffKind::iter()
+                .map(|kind| BuffEntity {
+                    state: Buff::No,
+                    context: BuffContext::new(kind),
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+        }
+    }
+
+    fn mock_service(settings: Settings, event_rx: Receiver<WorldEvent>) -> DefaultService {
+        let settings = Box::leak(Box::new(RefCell::new(settings)));
+        let mut settings_service = MockSettingsService::default();
+        settings_service
+            .expect_settings()
+            .returning_st(|| settings.borrow());
+
+        DefaultService {
+            event_rx,
+            pending_halt: None,
+            game: Box::new(MockGameService::default()),
+            minimap: Box::new(MockMinimapService::default()),
+            character: Box::new(MockCharacterService::default()),
+            rotator: Box::new(MockRotatorService::default()),
+            navigator: Box::new(DefaultNavigatorService),
+            settings: Box::new(settings_service),
+            localization: Box::new(DefaultLocalizationService::new(Rc::new(RefCell::new(
+                Arc::new(Localization::default()),
+            )))),
+            bot: Rc::new(RefCell::new(ControlService::default())),
+            #[cfg(debug_assertions)]
+            debug: DebugService::default(),
+        }
+    }
+
+    #[test]
+    fn instances_maintain_independent_operations() {
+        let minimap = Box::leak(Box::new(Minimap::default()));
+        let character = Box::leak(Box::new(Character::default()));
+        let operations = [
+            Operation::Halting,
+            Operation::TemporaryHalting {
+                resume: Duration::from_secs(5),
+                run_duration_millis: 0,
+                stop_duration_millis: 0,
+                once: false,
+            },
+        ];
+        let mut instances = operations
+            .into_iter()
+            .map(|operation| {
+                let (_tx, rx) = channel(1);
+                let mut service = mock_service(Settings::default(), rx);
+                let mut minimap_service = MockMinimapService::default();
+                minimap_service
+                    .expect_minimap()
+                    .return_const(Some(&*minimap));
+                let mut character_service = MockCharacterService::default();
+                character_service
+                    .expect_character()
+                    .return_const(Some(&*character));
+                service.minimap = Box::new(minimap_service);
+                service.character = Box::new(character_service);
+
+                let mut resources = Resources::new(None, None);
+                resources.operation = operation;
+                (service, resources, mock_world())
+            })
+            .collect::<Vec<_>>();
+        let mut control = InstanceControl {
+            count: 2,
+            ..InstanceControl::default()
+        };
+        let mut rotator = MockRotator::default();
+        rotator.expect_reset_queue().return_const(());
+        let mut navigator = MockNavigator::default();
+        let mut capture = MockCapture::default();
+
+        let (service, resources, world) = &mut instances[0];
+        let mut handler = DefaultRequestHandler {
+            service,
+            resources,
+            world,
+            rotator: &mut rotator,
+            navigator: &mut navigator,
+            capture: &mut capture,
+            control: Some(&mut control),
+        };
+        handler.on_rotate_actions(RotateKind::Run);
+        handler.on_select_instance(1);
+        handler.on_remove_instance(0);
+
+        assert_matches!(instances[0].1.operation, Operation::Running);
+        assert_matches!(
+            instances[1].1.operation,
+            Operation::TemporaryHalting { resume, .. } if resume == Duration::from_secs(5)
+        );
+        assert_eq!(control.selected, 1);
+        assert_eq!(control.pending_remove, None);
+
+        let (service, resources, world) = &mut instances[1];
+        let mut handler = DefaultRequestHandler {
+            service,
+            resources,
+            world,
+            rotator: &mut rotator,
+            navigator: &mut navigator,
+            capture: &mut capture,
+            control: Some(&mut control),
+        };
+        handler.on_rotate_actions(RotateKind::Halt);
+
+        assert_matches!(instances[0].1.operation, Operation::Running);
+        assert_matches!(instances[1].1.operation, Operation::Halting);
+    }
+
+    #[test]
+    fn bot_status_forwards_non_selected_instance() {
+        let (_tx, rx) = channel(1);
+        let mut service = mock_service(Settings::default(), rx);
+        let mut resources = Resources::new(None, None);
+        let mut world = mock_world();
+        let mut rotator = MockRotator::default();
+        let mut navigator = MockNavigator::default();
+        let mut capture = MockCapture::default();
+        let mut control = InstanceControl {
+            count: 2,
+            ..InstanceControl::default()
+        };
+        let (sender, mut receiver) = oneshot::channel();
+
+        let mut handler = DefaultRequestHandler {
+            service: &mut service,
+            resources: &mut resources,
+            world: &mut world,
+            rotator: &mut rotator,
+            navigator: &mut navigator,
+            capture: &mut capture,
+            control: Some(&mut control),
+        };
+        handler.on_bot_status(Some(1), sender);
+
+        assert_matches!(control.pending_status, Some((1, _)));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn bot_where_forwards_non_selected_instance() {
+        let (_tx, rx) = channel(1);
+        let mut service = mock_service(Settings::default(), rx);
+        let mut resources = Resources::new(None, None);
+        let mut world = mock_world();
+        let mut rotator = MockRotator::default();
+        let mut navigator = MockNavigator::default();
+        let mut capture = MockCapture::default();
+        let mut control = InstanceControl {
+            count: 2,
+            ..InstanceControl::default()
+        };
+        let (sender, mut receiver) = oneshot::channel();
+
+        let mut handler = DefaultRequestHandler {
+            service: &mut service,
+            resources: &mut resources,
+            world: &mut world,
+            rotator: &mut rotator,
+            navigator: &mut navigator,
+            capture: &mut capture,
+            control: Some(&mut control),
+        };
+        handler.on_bot_where(Some(1), sender);
+
+        assert_matches!(control.pending_where, Some((1, _)));
+        assert_matches!(control.pending_status, None);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn non_selected_instance_ignores_instance_requests() {
+        let (_tx, rx) = channel(1);
+        let mut service = mock_service(Settings::default(), rx);
+        let mut resources = Resources::new(None, None);
+        let mut world = mock_world();
+        let mut rotator = MockRotator::default();
+        let mut navigator = MockNavigator::default();
+        let mut capture = MockCapture::default();
+
+        let mut handler = DefaultRequestHandler {
+            service: &mut service,
+            resources: &mut resources,
+            world: &mut world,
+            rotator: &mut rotator,
+            navigator: &mut navigator,
+            capture: &mut capture,
+            control: None,
+        };
+        handler.on_create_instance();
+        handler.on_remove_instance(1);
+        handler.on_select_instance(1);
+
+        assert_eq!(handler.on_query_instances(), (1, 0));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn apply_debug_settings_restores_auto_save_rune() {
+        let (_tx, rx) = channel(1);
+        let service = mock_service(
+            Settings {
+                auto_save_rune: true,
+                ..Settings::default()
+            },
+            rx,
+        );
+        let resources = Resources::new(None, None);
+        assert!(!resources.debug.auto_save_rune());
+
+        service.apply_debug_settings(&resources);
+
+        assert!(resources.debug.auto_save_rune());
+    }
+
+    #[test]
+    fn on_query_key_conflicts_toggle_key_equal_action_key() {
+        let (_tx, rx) = channel(1);
+        let mut service = mock_service(
+            Settings {
+                toggle_actions_key: KeyBindingConfiguration {
+                    key: KeyBinding::F1,
+                    enabled: true,
+                },
+                ..Settings::default()
+            },
+            rx,
+        );
+        let mut character_service = DefaultCharacterService::default();
+        character_service.update_character(Some(Character {
+            actions: vec![ActionConfiguration {
+                key: KeyBinding::F1,
+                enabled: true,
+                ..ActionConfiguration::default()
+            }],
+            ..Character::default()
+        }));
+        service.character = Box::new(character_service);
+        let mut resources = Resources::new(None, None);
+        let mut world = mock_world();
+        let mut rotator = MockRotator::default();
+        let mut navigator = MockNavigator::default();
+        let mut capture = MockCapture::default();
+        let handler = DefaultRequestHandler {
+            service: &mut service,
+            resources: &mut resources,
+            world: &mut world,
+            rotator: &mut rotator,
+            navigator: &mut navigator,
+            capture: &mut capture,
+            control: None,
+        };
+
+        assert_eq!(
+            handler.on_query_key_conflicts(),
+            vec!["Key F1 is used by Toggle actions, Action #1".to_string()]
+        );
+    }
+
+    #[test]
+    fn state_and_frame_provider_labels_instance() {
+        let resources = Resources::new(None, None);
+        let mut world = mock_world();
+        world.player.state = Player::Detecting;
+
+        let (status, frame) = state_and_frame_provider(
+            &resources,
+            &world,
+            Some((3, 7)),
+            vec![("Key A".to_string(), 2500)],
+            1,
+        )();
+
+        assert!(status.contains("- Instance: ``2``"));
+        assert!(status.contains(&format!("- State: ``{}``", Player::Detecting)));
+        assert!(status.contains("- Navigation: ``3/7``"));
+        assert!(status.contains("- Next ready: ``Key A in 2.5s``"));
+        assert!(frame.is_none());
+    }
+
+    #[test]
+    fn poll_context_event_stranger_appeared_cycle_channel() {
+        let (tx, rx) = channel(1);
+        let mut service = mock_service(
+            Settings {
+                stranger_player_behavior: OtherPlayerBehavior::CycleChannel,
+                ..Settings::default()
+            },
+            rx,
+        );
+        let mut resources = Resources::new(None, None);
+        let mut world = mock_world();
+        let mut rotator = MockRotator::default();
+        let mut navigator = MockNavigator::default();
+        let mut capture = MockCapture::default();
+        rotator
+            .expect_inject_action()
+            .withf(|action| {
+                matches!(
+                    action,
+                    PlayerAction::Panic(Panic {
+                        to: PanicTo::Channel
+                    })
+                )
+            })
+            .once()
+            .return_const(());
+        rotator.expect_reset_queue().never();
+
+        tx.send(WorldEvent::PlayerStrangerAppeared).unwrap();
+        let mut handler = DefaultRequestHandler {
+            service: &mut service,
+            resources: &mut resources,
+            world: &mut world,
+            rotator: &mut rotator,
+            navigator: &mut navigator,
+            capture: &mut capture,
+            control: None,
+        };
+        handler.poll_context_event();
+
+        assert!(!handler.resources.operation.halting());
+    }
+
+    #[test]
+    fn poll_context_event_stranger_appeared_whitelisted_names() {
+        let (tx, rx) = channel(1);
+        let mut service = mock_service(
+            Settings {
+                stranger_player_behavior: OtherPlayerBehavior::CycleChannel,
+                player_name_whitelist: vec!["Friend".to_string(), "Me".to_string()],
+                ..Settings::default()
+            },
+            rx,
+        );
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_player_names()
+            .once()
+            .returning(|| vec!["friend".to_string(), "Me".to_string()]);
+        let mut resources = Resources::new(None, Some(detector));
+        let mut world = mock_world();
+        let mut rotator = MockRotator::default();
+        let mut navigator = MockNavigator::default();
+        let mut capture = MockCapture::default();
+        rotator.expect_inject_action().never();
+
+        tx.send(WorldEvent::PlayerStrangerAppeared).unwrap();
+        let mut handler = DefaultRequestHandler {
+            service: &mut service,
+            resources: &mut resources,
+            world: &mut world,
+            rotator: &mut rotator,
+            navigator: &mut navigator,
+            capture: &mut capture,
+            control: None,
+        };
+        handler.poll_context_event();
+    }
+
+    #[test]
+    fn where_content_reports_detected_map_name() {
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_minimap_name_text()
+            .once()
+            .returning(|_| Ok("Henesys".to_string()));
+        let minimap = MinimapState::Idle(MinimapIdle::default());
+
+        let content = where_content(Some(&detector), minimap, Some("Henesys Hunting Ground"));
+
+        assert_eq!(
+            content,
+            "- Map: ``Henesys``\n- Map data: ``Henesys Hunting Ground``"
+        );
+        assert_eq!(
+            where_content(Some(&detector), MinimapState::Detecting, None),
+            "- Map: ``Not detected``\n- Map data: ``None``"
+        );
+    }
+
+    #[test]
+    fn player_names_whitelisted_requires_all_names() {
+        let whitelist = vec!["Friend".to_string()];
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_player_names()
+            .returning(|| vec!["Friend".to_string(), "Stranger".to_string()]);
+
+        assert!(!player_names_whitelisted(&detector, &whitelist));
+        assert!(!player_names_whitelisted(&detector, &[]));
+    }
+
+    #[test]
+    fn player_names_whitelisted_trims_and_ignores_case() {
+        let whitelist = vec![
+            " friend ".to_string(),
+            "ÉLODIE".to_string(),
+            " ".to_string(),
+        ];
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_player_names()
+            .returning(|| vec!["Friend ".to_string(), "élodie".to_string()]);
+
+        assert!(player_names_whitelisted(&detector, &whitelist));
+        assert!(!player_names_whitelisted(&detector, &[" ".to_string()]));
+    }
+
+    #[test]
+    fn poll_context_event_guildie_appeared_go_to_town() {
+        let (tx, rx) = channel(1);
+        let mut service = mock_service(
+            Settings {
+                guildie_player_behavior: OtherPlayerBehavior::GoToTown,
+                ..Settings::default()
+            },
+            rx,
+        );
+        let mut resources = Resources::new(None, None);
+        let mut world = mock_world();
+        let mut rotator = MockRotator::default();
+        let mut navigator = MockNavigator::default();
+        let mut capture = MockCapture::default();
+        rotator.expect_reset_queue().once().return_const(());
+        rotator
+            .expect_inject_action()
+            .withf(|action| matches!(action, PlayerAction::Panic(Panic { to: PanicTo::Town })))
+            .once()
+            .return_const(());
+
+        tx.send(WorldEvent::PlayerGuildieAppeared).unwrap();
+        let mut handler = DefaultRequestHandler {
+            service: &mut service,
+            resources: &mut resources,
+            world: &mut world,
+            rotator: &mut rotator,
+            navigator: &mut navigator,
+            capture: &mut capture,
+            control: None,
+        };
+        handler.poll_context_event();
+
+        assert!(handler.resources.operation.halting());
+    }
+
+    #[test]
+    fn poll_context_event_capture_failed_halts() {
+        let (tx, rx) = channel(1);
+        let mut service = mock_service(
+            Settings {
+                stop_on_fail_or_change_map: true,
+                ..Settings::default()
+            },
+            rx,
+        );
+        let mut resources = Resources::new(None, None);
+        let mut world = mock_world();
+        let mut rotator = MockRotator::default();
+        let mut navigator = MockNavigator::default();
+        let mut capture = MockCapture::default();
+        rotator.expect_reset_queue().once().return_const(());
+
+        tx.send(WorldEvent::CaptureFailed).unwrap();
+        let mut handler = DefaultRequestHandler {
+            service: &mut service,
+            resources: &mut resources,
+            world: &mut world,
+            rotator: &mut rotator,
+            navigator: &mut navigator,
+            capture: &mut capture,
+            control: None,
+        };
+        handler.poll_context_event();
+
+        assert!(handler.resources.operation.halting());
+    }
+
+    #[test]
+    fn poll_context_event_game_frozen_halts_when_enabled() {
+        let (tx, rx) = channel(1);
+        let mut service = mock_service(
+            Settings {
+                stop_on_game_frozen: true,
+                ..Settings::default()
+            },
+            rx,
+        );
+        let mut resources = Resources::new(None, None);
+        let mut world = mock_world();
+        let mut rotator = MockRotator::default();
+        let mut navigator = MockNavigator::default();
+        let mut capture = MockCapture::default();
+        rotator.expect_reset_queue().once().return_const(());
+
+        tx.send(WorldEvent::GameFrozen).unwrap();
+        let mut handler = DefaultRequestHandler {
+            service: &mut service,
+            resources: &mut resources,
+            world: &mut world,
+            rotator: &mut rotator,
+            navigator: &mut navigator,
+            capture: &mut capture,
+            control: None,
+        };
+        handler.poll_context_event();
+
+        assert!(handler.resources.operation.halting());
+    }
+
+    #[test]
+    fn poll_context_event_navigation_failed_halts() {
+        let (tx, rx) = channel(1);
+        let mut service = mock_service(Settings::default(), rx);
+        let mut resources = Resources::new(None, None);
+        let mut world = mock_world();
+        let mut rotator = MockRotator::default();
+        let mut navigator = MockNavigator::default();
+        let mut capture = MockCapture::default();
+        rotator.expect_reset_queue().once().return_const(());
+        rotator.expect_inject_action().never();
+
+        tx.send(WorldEvent::NavigationFailed).unwrap();
+        let mut handler = DefaultRequestHandler {
+            service: &mut service,
+            resources: &mut resources,
+            world: &mut world,
+            rotator: &mut rotator,
+            navigator: &mut navigator,
+            capture: &mut capture,
+            control: None,
+        };
+        handler.poll_context_event();
+
+        assert!(handler.resources.operation.halting());
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use std::cell::RefCell;
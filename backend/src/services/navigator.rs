@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, mem};
 
 use base64::{Engine, prelude::BASE64_STANDARD};
 use opencv::{
@@ -23,6 +23,12 @@ pub trait NavigatorService: Debug {
         path: NavigationPath,
     ) -> NavigationPath;
 
+    /// Reorders `path` points so that the point at `new_order[i]` becomes the `i`-th point.
+    ///
+    /// Returns the reordered [`NavigationPath`] or the original if `new_order` is not a
+    /// permutation of `path` point indices.
+    fn reorder_path_points(&self, path: NavigationPath, new_order: Vec<usize>) -> NavigationPath;
+
     /// Converts image `base64` to grayscale.
     fn navigation_snapshot_as_grayscale(&self, base64: String) -> String;
 }
@@ -66,6 +72,28 @@ impl NavigatorService for DefaultNavigatorService {
         path
     }
 
+    fn reorder_path_points(
+        &self,
+        mut path: NavigationPath,
+        new_order: Vec<usize>,
+    ) -> NavigationPath {
+        let len = path.points.len();
+        let mut seen = vec![false; len];
+        let is_permutation = new_order.len() == len
+            && new_order
+                .iter()
+                .all(|&index| index < len && !mem::replace(&mut seen[index], true));
+        if !is_permutation {
+            return path;
+        }
+
+        path.points = new_order
+            .into_iter()
+            .map(|index| path.points[index])
+            .collect();
+        path
+    }
+
     fn navigation_snapshot_as_grayscale(&self, base64: String) -> String {
         convert_color_base64_to_grayscale_base64(base64.clone()).unwrap_or(base64)
     }
@@ -108,4 +136,51 @@ fn extract_minimap_and_name_base64(
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::{NavigationPoint, NavigationTransition};
+
+    fn path_with_points_x(xs: &[i32]) -> NavigationPath {
+        NavigationPath {
+            points: xs
+                .iter()
+                .map(|&x| NavigationPoint {
+                    next_paths_id_index: None,
+                    x,
+                    y: 0,
+                    transition: NavigationTransition::Portal,
+                })
+                .collect(),
+            ..NavigationPath::default()
+        }
+    }
+
+    fn points_x(path: &NavigationPath) -> Vec<i32> {
+        path.points.iter().map(|point| point.x).collect()
+    }
+
+    #[test]
+    fn reorder_path_points_produces_new_order() {
+        let service = DefaultNavigatorService;
+        let path = path_with_points_x(&[10, 20, 30, 40]);
+
+        let path = service.reorder_path_points(path, vec![2, 0, 3, 1]);
+
+        assert_eq!(points_x(&path), vec![30, 10, 40, 20]);
+    }
+
+    #[test]
+    fn reorder_path_points_keeps_original_on_invalid_order() {
+        let service = DefaultNavigatorService;
+        let path = path_with_points_x(&[10, 20, 30]);
+
+        let path = service.reorder_path_points(path, vec![0, 0, 1]);
+        assert_eq!(points_x(&path), vec![10, 20, 30]);
+
+        let path = service.reorder_path_points(path, vec![0, 1]);
+        assert_eq!(points_x(&path), vec![10, 20, 30]);
+
+        let path = service.reorder_path_points(path, vec![0, 1, 3]);
+        assert_eq!(points_x(&path), vec![10, 20, 30]);
+    }
+}
@@ -1,5 +1,6 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, iter};
 
+use log::warn;
 #[cfg(test)]
 use mockall::{automock, concretize};
 use strum::IntoEnumIterator;
@@ -17,6 +18,9 @@ use crate::{
 #[cfg_attr(test, automock)]
 pub trait RotatorService: Debug {
     /// Builds a new actions list to be used.
+    ///
+    /// If `preset` no longer exists in `minimap`, the first preset in alphabetical order is used
+    /// instead.
     fn update_actions<'a>(
         &mut self,
         minimap: Option<&'a Minimap>,
@@ -56,7 +60,22 @@ impl RotatorService for DefaultRotatorService {
         let character_actions = character.map(actions_from).unwrap_or_default();
         let minimap_actions = minimap
             .zip(preset)
-            .and_then(|(minimap, preset)| minimap.actions.get(&preset).cloned())
+            .and_then(|(minimap, preset)| {
+                if let Some(actions) = minimap.actions.get(&preset) {
+                    return Some(actions.clone());
+                }
+
+                let (fallback, actions) = minimap
+                    .actions
+                    .iter()
+                    .min_by_key(|(preset, _)| preset.as_str())?;
+                warn!(
+                    target: "rotator",
+                    "preset {preset} is missing from minimap {}, falling back to preset {fallback}",
+                    minimap.name
+                );
+                Some(actions.clone())
+            })
             .unwrap_or_default();
 
         self.actions = [character_actions, minimap_actions].concat();
@@ -78,6 +97,10 @@ impl RotatorService for DefaultRotatorService {
         let reset_normal_actions_on_erda = minimap
             .map(|minimap| minimap.actions_any_reset_on_erda_condition)
             .unwrap_or_default();
+        let enable_rune_solving = settings.enable_rune_solving
+            && !minimap
+                .map(|minimap| minimap.disable_rune_solving)
+                .unwrap_or_default();
         let familiar_essence_key = character
             .map(|character| character.familiar_essence_key.key)
             .unwrap_or_default();
@@ -90,21 +113,43 @@ impl RotatorService for DefaultRotatorService {
         let enable_using_vip_booster = character
             .map(|character| character.vip_booster_key.enabled)
             .unwrap_or_default();
+        let halting_buffs = character
+            .map(|character| {
+                character
+                    .buffs_while_halting
+                    .iter()
+                    .copied()
+                    .map(BuffKind::from)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
         let args = RotatorBuildArgs {
             mode,
             actions: &self.actions,
             buffs: &self.buffs,
+            halting_buffs: &halting_buffs,
             familiar_essence_key,
             familiar_swappable_slots: settings.familiars.swappable_familiars,
             familiar_swappable_rarities: &settings.familiars.swappable_rarities,
             familiar_swap_check_millis: settings.familiars.swap_check_millis,
+            familiar_sort_by_level: settings.familiars.sort_by_level,
+            familiar_max_swaps_per_hour: settings.familiars.max_swaps_per_hour,
             elite_boss_behavior,
             elite_boss_behavior_key,
             enable_panic_mode: settings.enable_panic_mode,
-            enable_rune_solving: settings.enable_rune_solving,
+            enable_rune_solving,
             enable_familiars_swapping: settings.familiars.enable_familiars_swapping,
             enable_reset_normal_actions_on_erda: reset_normal_actions_on_erda,
             enable_using_vip_booster,
+            startup_delay_ticks: settings.startup_delay_ticks,
+            enable_buffs_burst_on_start: settings.enable_buffs_burst_on_start,
+            rotation_idle_ticks: (
+                settings.rotation_idle_min_ticks,
+                settings.rotation_idle_max_ticks,
+            ),
+            minimap_size: minimap
+                .map(|minimap| (minimap.width, minimap.height))
+                .unwrap_or_default(),
         };
 
         rotator.build_actions(args);
@@ -117,11 +162,25 @@ fn rotator_mode_from(minimap: Option<&Minimap>) -> RotatorMode {
         .map(|minimap| match minimap.rotation_mode {
             RotationMode::StartToEnd => RotatorMode::StartToEnd,
             RotationMode::StartToEndThenReverse => RotatorMode::StartToEndThenReverse,
-            RotationMode::AutoMobbing => RotatorMode::AutoMobbing(
+            RotationMode::AutoMobbing => {
+                // Platforms bound replaces every bound so there is nothing to cycle through
+                let extra_bounds = if minimap.auto_mob_platforms_bound {
+                    [].as_slice()
+                } else {
+                    minimap.rotation_auto_mob_extra_bounds.as_slice()
+                };
+                RotatorMode::AutoMobbing(
+                    minimap.rotation_mobbing_key,
+                    iter::once(minimap.rotation_auto_mob_bound)
+                        .chain(extra_bounds.iter().copied())
+                        .collect(),
+                )
+            }
+            RotationMode::PingPong => RotatorMode::PingPong(
                 minimap.rotation_mobbing_key,
-                minimap.rotation_auto_mob_bound,
+                minimap.rotation_ping_pong_bound,
             ),
-            RotationMode::PingPong => RotatorMode::PingPong(
+            RotationMode::Patrol => RotatorMode::Patrol(
                 minimap.rotation_mobbing_key,
                 minimap.rotation_ping_pong_bound,
             ),
@@ -130,13 +189,22 @@ fn rotator_mode_from(minimap: Option<&Minimap>) -> RotatorMode {
 }
 
 fn actions_from(character: &Character) -> Vec<Action> {
-    fn make_key_action(key: KeyBinding, millis: u64, count: u32) -> Action {
+    fn make_key_action(
+        key: KeyBinding,
+        millis: u64,
+        count: u32,
+        wait_before_millis: u64,
+        wait_after_millis: u64,
+        wait_millis_random_range: u64,
+    ) -> Action {
         Action::Key(ActionKey {
             key,
             count,
             condition: ActionCondition::EveryMillis(millis),
-            wait_before_use_millis: 350,
-            wait_after_use_millis: 350,
+            wait_before_use_millis: wait_before_millis,
+            wait_before_use_millis_random_range: wait_millis_random_range,
+            wait_after_use_millis: wait_after_millis,
+            wait_after_use_millis_random_range: wait_millis_random_range,
             ..ActionKey::default()
         })
     }
@@ -148,13 +216,41 @@ fn actions_from(character: &Character) -> Vec<Action> {
             key,
             character.feed_pet_millis,
             character.feed_pet_count,
+            character.feed_pet_wait_before_millis,
+            character.feed_pet_wait_after_millis,
+            character.feed_pet_wait_millis_random_range,
         ));
     }
 
     if let KeyBindingConfiguration { key, enabled: true } = character.potion_key
         && let PotionMode::EveryMillis(millis) = character.potion_mode
     {
-        vec.push(make_key_action(key, millis, 1));
+        vec.push(make_key_action(
+            key,
+            millis,
+            1,
+            character.potion_wait_before_millis,
+            character.potion_wait_after_millis,
+            character.potion_wait_millis_random_range,
+        ));
+    }
+
+    for potion in character
+        .extra_potions
+        .iter()
+        .filter(|potion| potion.key.enabled)
+    {
+        let PotionMode::EveryMillis(millis) = potion.mode else {
+            continue;
+        };
+        vec.push(make_key_action(
+            potion.key.key,
+            millis,
+            1,
+            character.potion_wait_before_millis,
+            character.potion_wait_after_millis,
+            character.potion_wait_millis_random_range,
+        ));
     }
 
     let mut iter = character.actions.clone().into_iter().peekable();
@@ -178,7 +274,7 @@ fn actions_from(character: &Character) -> Vec<Action> {
 }
 
 fn buffs_from(character: &Character) -> Vec<(BuffKind, KeyBinding)> {
-    BuffKind::iter()
+    let mut buffs = BuffKind::iter()
         .filter_map(|kind| {
             let enabled_key = match kind {
                 BuffKind::Rune => None, // Internal buff
@@ -261,7 +357,17 @@ fn buffs_from(character: &Character) -> Vec<(BuffKind, KeyBinding)> {
             };
             Some(kind).zip(enabled_key)
         })
-        .collect()
+        .collect::<Vec<_>>();
+
+    // Stable sort so that non-prioritized buffs keep their default order
+    buffs.sort_by_key(|(kind, _)| {
+        character
+            .buffs_priority
+            .iter()
+            .position(|priority| BuffKind::from(*priority) == *kind)
+            .unwrap_or(usize::MAX)
+    });
+    buffs
 }
 
 #[cfg(test)]
@@ -274,8 +380,9 @@ mod tests {
     use super::*;
     use crate::{ActionCondition, ActionConfiguration, ActionConfigurationCondition, ActionKey};
     use crate::{
-        Bound, EliteBossBehavior, FamiliarRarity, KeyBindingConfiguration, SwappableFamiliars,
-        rotator::MockRotator,
+        Bound, CharacterBuffKind, EliteBossBehavior, FamiliarRarity, KeyBindingConfiguration,
+        PotionConfiguration, SwappableFamiliars, rotator::MockRotator,
+        services::set_character_actions_enabled,
     };
 
     #[test]
@@ -308,14 +415,18 @@ mod tests {
                     let original_mode = match args.mode {
                         RotatorMode::StartToEnd => RotationMode::StartToEnd,
                         RotatorMode::StartToEndThenReverse => RotationMode::StartToEndThenReverse,
-                        RotatorMode::AutoMobbing(key, bound) => {
-                            key_bound = Some((key, bound));
+                        RotatorMode::AutoMobbing(key, ref bounds) => {
+                            key_bound = Some((key, bounds[0]));
                             RotationMode::AutoMobbing
                         }
                         RotatorMode::PingPong(key, bound) => {
                             key_bound = Some((key, bound));
                             RotationMode::PingPong
                         }
+                        RotatorMode::Patrol(key, bound) => {
+                            key_bound = Some((key, bound));
+                            RotationMode::Patrol
+                        }
                     };
                     let key_bound_match = match key_bound {
                         Some((key, bound)) => {
@@ -343,6 +454,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn rotator_mode_from_skips_extra_bounds_with_platforms_bound() {
+        let bound = Bound {
+            x: 1,
+            y: 1,
+            width: 1,
+            height: 1,
+        };
+        let mut minimap = Minimap {
+            rotation_mode: RotationMode::AutoMobbing,
+            rotation_auto_mob_bound: bound,
+            rotation_auto_mob_extra_bounds: vec![Bound { x: 5, ..bound }],
+            ..Default::default()
+        };
+
+        assert_matches!(
+            rotator_mode_from(Some(&minimap)),
+            RotatorMode::AutoMobbing(_, bounds) if bounds.len() == 2
+        );
+
+        minimap.auto_mob_platforms_bound = true;
+        assert_matches!(
+            rotator_mode_from(Some(&minimap)),
+            RotatorMode::AutoMobbing(_, bounds) if bounds == vec![bound]
+        );
+    }
+
+    #[test]
+    fn update_actions_missing_preset_falls_back_to_first_preset() {
+        let mut minimap = Minimap::default();
+        minimap.actions.insert(
+            "b".to_string(),
+            vec![Action::Key(ActionKey {
+                key: KeyBinding::B,
+                ..Default::default()
+            })],
+        );
+        minimap.actions.insert(
+            "a".to_string(),
+            vec![Action::Key(ActionKey {
+                key: KeyBinding::A,
+                ..Default::default()
+            })],
+        );
+        let mut service = DefaultRotatorService::default();
+
+        service.update_actions(Some(&minimap), Some("deleted".to_string()), None);
+
+        assert_matches!(
+            service.actions.as_slice(),
+            [Action::Key(ActionKey {
+                key: KeyBinding::A,
+                ..
+            })]
+        );
+    }
+
     #[test]
     fn update_with_buffs() {
         let buffs = vec![(BuffKind::SayramElixir, KeyBinding::F1)];
@@ -362,6 +530,23 @@ mod tests {
         service.apply(&mut rotator, None, None, &Settings::default());
     }
 
+    #[test]
+    fn update_with_halting_buffs() {
+        let character = Character {
+            buffs_while_halting: vec![CharacterBuffKind::ExpCouponX2],
+            ..Default::default()
+        };
+        let mut rotator = MockRotator::new();
+        rotator
+            .expect_build_actions()
+            .withf(|args| args.halting_buffs == [BuffKind::ExpCouponX2])
+            .once()
+            .return_const(());
+
+        let service = DefaultRotatorService::default();
+        service.apply(&mut rotator, None, Some(&character), &Settings::default());
+    }
+
     #[test]
     fn update_with_familiar_essence_key() {
         let character = Character {
@@ -390,6 +575,8 @@ mod tests {
         settings.familiars.swappable_rarities =
             HashSet::from_iter([FamiliarRarity::Epic, FamiliarRarity::Rare]);
         settings.familiars.swap_check_millis = 5000;
+        settings.familiars.sort_by_level = false;
+        settings.familiars.max_swaps_per_hour = 4;
         settings.familiars.enable_familiars_swapping = true;
 
         let settings_clone = settings.clone();
@@ -400,6 +587,8 @@ mod tests {
                 args.familiar_swappable_slots == SwappableFamiliars::SecondAndLast
                     && args.familiar_swappable_rarities == &settings.familiars.swappable_rarities
                     && args.familiar_swap_check_millis == 5000
+                    && !args.familiar_sort_by_level
+                    && args.familiar_max_swaps_per_hour == 4
                     && args.enable_familiars_swapping
             })
             .once()
@@ -468,6 +657,28 @@ mod tests {
         service.apply(&mut rotator, None, None, &settings);
     }
 
+    #[test]
+    fn update_with_rune_solving_disabled_for_minimap() {
+        let settings = Settings {
+            enable_rune_solving: true,
+            ..Default::default()
+        };
+        let minimap = Minimap {
+            disable_rune_solving: true,
+            ..Default::default()
+        };
+
+        let mut rotator = MockRotator::new();
+        rotator
+            .expect_build_actions()
+            .withf(|args| !args.enable_rune_solving)
+            .once()
+            .return_const(());
+
+        let service = DefaultRotatorService::default();
+        service.apply(&mut rotator, Some(&minimap), None, &settings);
+    }
+
     #[test]
     fn update_combine_actions_and_fixed_actions() {
         let actions = vec![
@@ -655,4 +866,251 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn update_character_actions_bulk_disabled() {
+        let mut character = Character {
+            actions: vec![
+                ActionConfiguration {
+                    key: KeyBinding::C,
+                    enabled: true,
+                    ..Default::default()
+                },
+                ActionConfiguration {
+                    key: KeyBinding::D,
+                    condition: ActionConfigurationCondition::Linked,
+                    ..Default::default()
+                },
+                ActionConfiguration {
+                    key: KeyBinding::F,
+                    enabled: true,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let mut service = DefaultRotatorService::default();
+
+        set_character_actions_enabled(&mut character, Some(2..10), false);
+        service.update_actions(None, None, Some(&character));
+        assert_matches!(
+            service.actions.as_slice(),
+            [
+                Action::Key(ActionKey {
+                    key: KeyBinding::C,
+                    ..
+                }),
+                Action::Key(ActionKey {
+                    key: KeyBinding::D,
+                    condition: ActionCondition::Linked,
+                    ..
+                }),
+            ]
+        );
+
+        // Inverted range does nothing
+        #[allow(clippy::reversed_empty_ranges)]
+        set_character_actions_enabled(&mut character, Some(3..1), true);
+        service.update_actions(None, None, Some(&character));
+        assert_eq!(service.actions.len(), 2);
+
+        set_character_actions_enabled(&mut character, None, false);
+        service.update_actions(None, None, Some(&character));
+        assert!(service.actions.is_empty());
+    }
+
+    #[test]
+    fn update_character_actions_with_wait_random_range() {
+        let character = Character {
+            feed_pet_key: KeyBindingConfiguration {
+                key: KeyBinding::A,
+                enabled: true,
+            },
+            feed_pet_wait_millis_random_range: 100,
+            potion_key: KeyBindingConfiguration {
+                key: KeyBinding::B,
+                enabled: true,
+            },
+            potion_mode: PotionMode::EveryMillis(1000),
+            potion_wait_millis_random_range: 200,
+            actions: vec![ActionConfiguration {
+                key: KeyBinding::C,
+                enabled: true,
+                wait_before_millis_random_range: 300,
+                wait_after_millis_random_range: 400,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut service = DefaultRotatorService::default();
+
+        service.update_actions(None, None, Some(&character));
+
+        assert_matches!(
+            service.actions.as_slice(),
+            [
+                Action::Key(ActionKey {
+                    key: KeyBinding::A,
+                    wait_before_use_millis_random_range: 100,
+                    wait_after_use_millis_random_range: 100,
+                    ..
+                }),
+                Action::Key(ActionKey {
+                    key: KeyBinding::B,
+                    wait_before_use_millis_random_range: 200,
+                    wait_after_use_millis_random_range: 200,
+                    ..
+                }),
+                Action::Key(ActionKey {
+                    key: KeyBinding::C,
+                    wait_before_use_millis_random_range: 300,
+                    wait_after_use_millis_random_range: 400,
+                    ..
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn update_character_actions_with_feed_pet_and_potion_waits() {
+        let character = Character {
+            feed_pet_key: KeyBindingConfiguration {
+                key: KeyBinding::A,
+                enabled: true,
+            },
+            feed_pet_wait_before_millis: 100,
+            feed_pet_wait_after_millis: 200,
+            potion_key: KeyBindingConfiguration {
+                key: KeyBinding::B,
+                enabled: true,
+            },
+            potion_mode: PotionMode::EveryMillis(1000),
+            potion_wait_before_millis: 300,
+            potion_wait_after_millis: 400,
+            ..Default::default()
+        };
+        let mut service = DefaultRotatorService::default();
+
+        service.update_actions(None, None, Some(&character));
+
+        assert_matches!(
+            service.actions.as_slice(),
+            [
+                Action::Key(ActionKey {
+                    key: KeyBinding::A,
+                    wait_before_use_millis: 100,
+                    wait_after_use_millis: 200,
+                    ..
+                }),
+                Action::Key(ActionKey {
+                    key: KeyBinding::B,
+                    condition: ActionCondition::EveryMillis(1000),
+                    wait_before_use_millis: 300,
+                    wait_after_use_millis: 400,
+                    ..
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn update_character_actions_with_extra_potions() {
+        let character = Character {
+            potion_key: KeyBindingConfiguration {
+                key: KeyBinding::A,
+                enabled: true,
+            },
+            potion_mode: PotionMode::EveryMillis(1000),
+            extra_potions: vec![
+                PotionConfiguration {
+                    key: KeyBindingConfiguration {
+                        key: KeyBinding::B,
+                        enabled: true,
+                    },
+                    mode: PotionMode::EveryMillis(2000),
+                },
+                PotionConfiguration {
+                    key: KeyBindingConfiguration {
+                        key: KeyBinding::C,
+                        enabled: false,
+                    },
+                    mode: PotionMode::EveryMillis(3000),
+                },
+                PotionConfiguration {
+                    key: KeyBindingConfiguration {
+                        key: KeyBinding::D,
+                        enabled: true,
+                    },
+                    mode: PotionMode::EveryMillis(4000),
+                },
+                PotionConfiguration {
+                    key: KeyBindingConfiguration {
+                        key: KeyBinding::E,
+                        enabled: true,
+                    },
+                    mode: PotionMode::Percentage(50.0),
+                },
+            ],
+            ..Default::default()
+        };
+        let mut service = DefaultRotatorService::default();
+
+        service.update_actions(None, None, Some(&character));
+
+        assert_matches!(
+            service.actions.as_slice(),
+            [
+                Action::Key(ActionKey {
+                    key: KeyBinding::A,
+                    condition: ActionCondition::EveryMillis(1000),
+                    ..
+                }),
+                Action::Key(ActionKey {
+                    key: KeyBinding::B,
+                    condition: ActionCondition::EveryMillis(2000),
+                    ..
+                }),
+                Action::Key(ActionKey {
+                    key: KeyBinding::D,
+                    condition: ActionCondition::EveryMillis(4000),
+                    ..
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn update_buffs_with_priority_first() {
+        let character = Character {
+            sayram_elixir_key: KeyBindingConfiguration {
+                key: KeyBinding::A,
+                enabled: true,
+            },
+            legion_luck_key: KeyBindingConfiguration {
+                key: KeyBinding::B,
+                enabled: true,
+            },
+            extreme_red_potion_key: KeyBindingConfiguration {
+                key: KeyBinding::C,
+                enabled: true,
+            },
+            buffs_priority: vec![
+                CharacterBuffKind::ExtremeRedPotion,
+                CharacterBuffKind::LegionLuck,
+            ],
+            ..Default::default()
+        };
+        let mut service = DefaultRotatorService::default();
+
+        service.update_buffs(Some(&character));
+
+        assert_eq!(
+            service.buffs,
+            vec![
+                (BuffKind::ExtremeRedPotion, KeyBinding::C),
+                (BuffKind::LegionLuck, KeyBinding::B),
+                (BuffKind::SayramElixir, KeyBinding::A),
+            ]
+        );
+    }
 }
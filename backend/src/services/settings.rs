@@ -1,5 +1,5 @@
 use std::{
-    cell::{Ref, RefCell},
+    cell::{Cell, Ref, RefCell},
     fmt::Debug,
     rc::Rc,
 };
@@ -8,10 +8,14 @@ use std::{
 use mockall::automock;
 use platforms::{Window, capture::query_capture_name_window_pairs, input::InputKind};
 
+#[cfg(debug_assertions)]
+use crate::database::upsert_settings;
 use crate::{
-    CaptureMode, InputMethod as DatabaseInputMethod, Settings,
+    CaptureMode, InputMethod as DatabaseInputMethod, KeyBinding, KeyBindingConfiguration, Settings,
     bridge::{Capture, Input, InputMethod, InputReceiver},
+    detect::SpinArrowsConfig,
     operation::Operation,
+    player::PlayerContext,
 };
 
 /// A service to handle [`Settings`]-related incoming requests.
@@ -33,6 +37,15 @@ pub trait SettingsService: Debug {
         capture: &mut dyn Capture,
     );
 
+    /// Sets and persists [`Settings::auto_save_rune`].
+    ///
+    /// The persisted [`Settings`] are broadcasted back to all instances.
+    #[cfg(debug_assertions)]
+    fn set_auto_save_rune(&mut self, auto_save: bool);
+
+    /// Updates `player_context` to use the current [`Settings`].
+    fn apply_player_settings(&self, player_context: &mut PlayerContext);
+
     /// Gets a list of [`Window`] names to be used for selection.
     ///
     /// The index of a name corresponds to a [`Window`].
@@ -49,9 +62,19 @@ pub trait SettingsService: Debug {
     /// If none is selected, the default [`Window`] is returned.
     fn selected_window(&self) -> Window;
 
+    /// Gets the [`InputKind`] to use for the configured [`Settings::capture_mode`].
+    fn selected_input_kind(&self) -> InputKind;
+
     /// Updates the selected [`Window`] specified by `index`.
     fn update_selected_window(&mut self, index: Option<usize>);
 
+    /// Updates the list of available [`Window`]s and reselects the previously selected [`Window`]
+    /// by its name, or the first available [`Window`] if none is selected.
+    ///
+    /// This is used to recover when the selected [`Window`] becomes invalid (e.g. the game client
+    /// restarted). Returns `true` if the selected [`Window`] has changed.
+    fn update_windows_and_reselect(&mut self) -> bool;
+
     /// Updates `input`, `input_receiver` and `capture` to use the currently selected [`Window`].
     fn apply_selected_window(
         &self,
@@ -59,6 +82,19 @@ pub trait SettingsService: Debug {
         input_rx: &mut dyn InputReceiver,
         capture: &mut dyn Capture,
     );
+
+    /// Switches `capture` to the next mode in the capture mode fallback chain and updates
+    /// `input` and `input_rx` accordingly.
+    ///
+    /// The chain starts with [`Settings::capture_mode`] followed by
+    /// [`Settings::capture_mode_fallbacks`] and wraps around. Returns `false` if there is no
+    /// other mode to fall back to.
+    fn fallback_capture(
+        &self,
+        input: &mut dyn Input,
+        input_rx: &mut dyn InputReceiver,
+        capture: &mut dyn Capture,
+    ) -> bool;
 }
 
 #[derive(Debug)]
@@ -67,6 +103,15 @@ pub struct DefaultSettingsService {
     capture_default_window: Window,
     capture_name_window_pairs: Vec<(String, Window)>,
     capture_selected_window_index: Option<usize>,
+    /// The [`Settings::capture_mode`] last applied to the capture.
+    ///
+    /// Used to detect when the configured capture mode changes while the capture is using one of
+    /// the fallback modes.
+    capture_applied_mode: Cell<CaptureMode>,
+    /// The [`Settings::capture_window_pattern`] last applied to the capture.
+    ///
+    /// Used to only update the capture [`Window`] when the pattern changes.
+    capture_applied_window_pattern: RefCell<String>,
 }
 
 impl DefaultSettingsService {
@@ -76,6 +121,9 @@ impl DefaultSettingsService {
         // MapleStoryClassTW <- TMS
         if cfg!(windows) {
             let window = Window::new("MapleStoryClass");
+            let capture_applied_mode = Cell::new(settings.borrow().capture_mode);
+            let capture_applied_window_pattern =
+                RefCell::new(settings.borrow().capture_window_pattern.clone());
 
             return Self {
                 settings,
@@ -83,15 +131,68 @@ impl DefaultSettingsService {
                 capture_name_window_pairs: query_capture_name_window_pairs()
                     .expect("supported platform"),
                 capture_selected_window_index: None,
+                capture_applied_mode,
+                capture_applied_window_pattern,
             };
         }
 
         panic!("unsupported platform")
     }
 
+    /// Finds the first [`Window`] whose name contains [`Settings::capture_window_pattern`].
+    fn pattern_matched_window(&self) -> Option<Window> {
+        let settings = self.settings();
+        let pattern = settings.capture_window_pattern.trim();
+        if pattern.is_empty() {
+            return None;
+        }
+
+        self.capture_name_window_pairs
+            .iter()
+            .find(|(name, _)| name.contains(pattern))
+            .map(|(_, window)| *window)
+    }
+
+    fn reselect_window_from(&mut self, pairs: Vec<(String, Window)>) -> bool {
+        let previous_window = self.selected_window();
+        let index = match self
+            .capture_selected_window_index
+            .and_then(|index| self.capture_name_window_pairs.get(index))
+        {
+            Some((name, _)) => pairs.iter().position(|(pair_name, _)| pair_name == name),
+            // Falls back to the first available window when none is selected
+            None => (!pairs.is_empty()).then_some(0),
+        };
+        // Keeps the old list when the window is not yet available so that it can be retried later
+        let Some(index) = index else {
+            return false;
+        };
+
+        self.capture_name_window_pairs = pairs;
+        self.capture_selected_window_index = Some(index);
+        self.selected_window() != previous_window
+    }
+
+    /// Gets the capture mode fallback chain starting with [`Settings::capture_mode`].
+    fn capture_mode_chain(&self) -> Vec<CaptureMode> {
+        let settings = self.settings();
+        let mut chain = vec![settings.capture_mode];
+        for mode in settings.capture_mode_fallbacks.iter().copied() {
+            if !chain.contains(&mode) {
+                chain.push(mode);
+            }
+        }
+        chain
+    }
+
     fn update_capture(&self, capture: &mut dyn Capture, forced: bool) {
+        let chain = self.capture_mode_chain();
         let settings = self.settings();
-        if forced || capture.mode() != settings.capture_mode {
+        let mode = capture.mode();
+        let mode_changed =
+            self.capture_applied_mode.replace(settings.capture_mode) != settings.capture_mode;
+        // Keeps the current mode if it is a fallback of the unchanged configured mode
+        if forced || (mode_changed && mode != settings.capture_mode) || !chain.contains(&mode) {
             capture.set_mode(settings.capture_mode);
             capture.set_window(self.selected_window());
         }
@@ -104,13 +205,15 @@ impl DefaultSettingsService {
         capture: &dyn Capture,
     ) {
         let settings = self.settings();
-        let (window, kind) = if matches!(capture.mode(), CaptureMode::BitBltArea) {
-            (capture.window(), InputKind::Foreground)
+        let kind = input_kind_from(capture.mode());
+        let window = if matches!(kind, InputKind::Foreground) {
+            capture.window()
         } else {
-            (self.selected_window(), InputKind::Focused)
+            self.selected_window()
         };
 
         input_rx.set_window_and_input_kind(window, kind);
+        input.set_delay_compensation_millis(settings.input_delay_compensation_millis);
         match settings.input_method {
             DatabaseInputMethod::Default => {
                 input.set_method(InputMethod::Default(window, kind));
@@ -147,10 +250,39 @@ impl SettingsService for DefaultSettingsService {
             settings.cycle_run_duration_millis,
             settings.cycle_stop_duration_millis,
         );
-        self.update_capture(capture, false);
+        // Forces updating in case the changed pattern now matches a different window
+        let pattern_changed = self
+            .capture_applied_window_pattern
+            .replace(settings.capture_window_pattern.clone())
+            != settings.capture_window_pattern;
+        self.update_capture(capture, pattern_changed);
         self.update_inputs(input, input_receiver, capture);
     }
 
+    #[cfg(debug_assertions)]
+    fn set_auto_save_rune(&mut self, auto_save: bool) {
+        let mut settings = self.settings().clone();
+        settings.auto_save_rune = auto_save;
+        let _ = upsert_settings(&mut settings);
+        self.update_settings(settings);
+    }
+
+    fn apply_player_settings(&self, player_context: &mut PlayerContext) {
+        let settings = self.settings();
+        player_context.config.rune_spin_arrows = SpinArrowsConfig {
+            lag_samples: settings.rune_spin_lag_samples,
+            lag_threshold: settings.rune_spin_lag_threshold as i32,
+        };
+        player_context.config.rune_cooldown_ticks = settings.rune_cooldown_ticks;
+        player_context.config.rune_press_key_interval_ticks =
+            settings.rune_press_key_interval_ticks;
+        player_context.config.solve_rune_only_when_safe = settings.solve_rune_only_when_safe;
+        player_context.config.portal_avoidance_margin = settings.portal_avoidance_margin;
+        player_context.config.move_timeout_scale_percent = settings.move_timeout_scale_percent;
+        player_context.config.change_direction_timeout_ticks =
+            settings.change_direction_timeout_ticks;
+    }
+
     fn window_names(&self) -> Vec<String> {
         self.capture_name_window_pairs
             .iter()
@@ -176,13 +308,26 @@ impl SettingsService for DefaultSettingsService {
                     .map(|(_, handle)| handle)
                     .copied()
             })
+            .or_else(|| self.pattern_matched_window())
             .unwrap_or(self.capture_default_window)
     }
 
+    fn selected_input_kind(&self) -> InputKind {
+        input_kind_from(self.settings().capture_mode)
+    }
+
     fn update_selected_window(&mut self, index: Option<usize>) {
         self.capture_selected_window_index = index;
     }
 
+    fn update_windows_and_reselect(&mut self) -> bool {
+        let Ok(pairs) = query_capture_name_window_pairs() else {
+            return false;
+        };
+
+        self.reselect_window_from(pairs)
+    }
+
     fn apply_selected_window(
         &self,
         input: &mut dyn Input,
@@ -192,6 +337,56 @@ impl SettingsService for DefaultSettingsService {
         self.update_capture(capture, true);
         self.update_inputs(input, input_rx, capture);
     }
+
+    fn fallback_capture(
+        &self,
+        input: &mut dyn Input,
+        input_rx: &mut dyn InputReceiver,
+        capture: &mut dyn Capture,
+    ) -> bool {
+        let chain = self.capture_mode_chain();
+        if chain.len() <= 1 {
+            return false;
+        }
+        let next = chain
+            .iter()
+            .position(|mode| *mode == capture.mode())
+            .map(|index| (index + 1) % chain.len())
+            .unwrap_or_default();
+
+        capture.set_mode(chain[next]);
+        capture.set_window(self.selected_window());
+        self.update_inputs(input, input_rx, capture);
+        true
+    }
+}
+
+/// Gets the [`InputKind`] for capturing with `mode`.
+///
+/// [`CaptureMode::BitBltArea`] captures an area on top of the game window so inputs are sent
+/// while that area is in the foreground instead.
+fn input_kind_from(mode: CaptureMode) -> InputKind {
+    if matches!(mode, CaptureMode::BitBltArea) {
+        InputKind::Foreground
+    } else {
+        InputKind::Focused
+    }
+}
+
+/// Collects enabled `settings` hotkeys along with their names.
+pub fn settings_key_bindings(settings: &Settings) -> Vec<(KeyBinding, String)> {
+    [
+        (settings.toggle_actions_key, "Toggle actions"),
+        (settings.platform_start_key, "Platform start"),
+        (settings.platform_end_key, "Platform end"),
+        (settings.platform_add_key, "Platform add"),
+    ]
+    .into_iter()
+    .filter_map(|(key, name)| match key {
+        KeyBindingConfiguration { key, enabled: true } => Some((key, name.to_string())),
+        KeyBindingConfiguration { enabled: false, .. } => None,
+    })
+    .collect()
 }
 
 #[cfg(test)]
@@ -215,6 +410,44 @@ mod tests {
         assert_eq!(service.settings().input_method, InputMethod::Default);
     }
 
+    #[test]
+    fn apply_player_settings_updates_rune_spin_arrows() {
+        let settings = Rc::new(RefCell::new(Settings {
+            rune_spin_lag_samples: 3,
+            rune_spin_lag_threshold: 45,
+            rune_cooldown_ticks: 200,
+            portal_avoidance_margin: 5,
+            move_timeout_scale_percent: 150,
+            change_direction_timeout_ticks: 6,
+            ..Default::default()
+        }));
+        let service = DefaultSettingsService::new(settings);
+        let mut player_context = PlayerContext::default();
+
+        service.apply_player_settings(&mut player_context);
+
+        assert_eq!(player_context.config.rune_spin_arrows.lag_samples, 3);
+        assert_eq!(player_context.config.rune_spin_arrows.lag_threshold, 45);
+        assert_eq!(player_context.config.rune_cooldown_ticks, 200);
+        assert_eq!(player_context.config.portal_avoidance_margin, 5);
+        assert_eq!(player_context.config.move_timeout_scale_percent, 150);
+        assert_eq!(player_context.config.change_direction_timeout_ticks, 6);
+    }
+
+    #[test]
+    fn selected_input_kind_follows_capture_mode() {
+        let settings = Rc::new(RefCell::new(Settings {
+            capture_mode: CaptureMode::BitBltArea,
+            ..Default::default()
+        }));
+        let service = DefaultSettingsService::new(settings.clone());
+
+        assert_matches!(service.selected_input_kind(), InputKind::Foreground);
+
+        settings.borrow_mut().capture_mode = CaptureMode::WindowsGraphicsCapture;
+        assert_matches!(service.selected_input_kind(), InputKind::Focused);
+    }
+
     #[test]
     fn current_handle_fallbacks_to_default() {
         let settings = Rc::new(RefCell::new(Settings::default()));
@@ -239,6 +472,9 @@ mod tests {
         ];
 
         let mut mock_keys = MockInput::default();
+        mock_keys
+            .expect_set_delay_compensation_millis()
+            .return_const(());
         mock_keys.expect_set_method().withf(|method| match method {
             BridgeInputMethod::Rpc(_, _) => false,
             BridgeInputMethod::Default(window, kind) => {
@@ -273,6 +509,69 @@ mod tests {
         assert_eq!(service.selected_window(), Window::new("Bar"));
     }
 
+    #[test]
+    fn selected_window_matches_custom_pattern() {
+        let settings = Rc::new(RefCell::new(Settings {
+            capture_window_pattern: "Private".to_string(),
+            ..Default::default()
+        }));
+        let mut service = DefaultSettingsService::new(settings.clone());
+        service.capture_name_window_pairs = vec![
+            ("Foo".to_string(), Window::new("Foo")),
+            ("MapleStory Private".to_string(), Window::new("Bar")),
+        ];
+
+        assert_eq!(service.selected_window(), Window::new("Bar"));
+
+        // Explicitly selected window takes precedence
+        service.update_selected_window(Some(0));
+        assert_eq!(service.selected_window(), Window::new("Foo"));
+
+        // Fallbacks to default when nothing matches
+        service.update_selected_window(None);
+        settings.borrow_mut().capture_window_pattern = "Baz".to_string();
+        assert_eq!(service.selected_window(), service.capture_default_window);
+    }
+
+    #[test]
+    fn reselect_window_by_name_when_invalidated() {
+        let settings = Rc::new(RefCell::new(Settings::default()));
+        let mut service = DefaultSettingsService::new(settings.clone());
+        service.capture_name_window_pairs = vec![
+            ("Foo".to_string(), Window::new("Foo")),
+            ("Bar".to_string(), Window::new("Bar")),
+        ];
+        service.update_selected_window(Some(1));
+
+        // Window not yet available
+        assert!(!service.reselect_window_from(vec![("Foo".to_string(), Window::new("Foo"))]));
+        assert_eq!(service.selected_window_index(), Some(1));
+        assert_eq!(service.selected_window(), Window::new("Bar"));
+
+        assert!(service.reselect_window_from(vec![
+            ("Bar".to_string(), Window::new("Baz")),
+            ("Foo".to_string(), Window::new("Foo")),
+        ]));
+        assert_eq!(service.selected_window_index(), Some(0));
+        assert_eq!(service.selected_window(), Window::new("Baz"));
+    }
+
+    #[test]
+    fn reselect_first_window_when_none_selected() {
+        let settings = Rc::new(RefCell::new(Settings::default()));
+        let mut service = DefaultSettingsService::new(settings.clone());
+
+        assert!(!service.reselect_window_from(vec![]));
+        assert_eq!(service.selected_window_index(), None);
+
+        assert!(service.reselect_window_from(vec![
+            ("Foo".to_string(), Window::new("Foo")),
+            ("Bar".to_string(), Window::new("Bar")),
+        ]));
+        assert_eq!(service.selected_window_index(), Some(0));
+        assert_eq!(service.selected_window(), Window::new("Foo"));
+    }
+
     #[test]
     fn update_settings_replaces_state_and_updates_components() {
         let settings = Rc::new(RefCell::new(Settings::default()));
@@ -283,9 +582,15 @@ mod tests {
             cycle_run_stop: CycleRunStopMode::Once,
             cycle_run_duration_millis: 1000,
             capture_mode: CaptureMode::WindowsGraphicsCapture,
+            input_delay_compensation_millis: 60,
             ..Default::default()
         };
         let mut mock_keys = MockInput::default();
+        mock_keys
+            .expect_set_delay_compensation_millis()
+            .withf(|millis| *millis == 60)
+            .once()
+            .return_const(());
         mock_keys.expect_set_method().withf(|method| match method {
             BridgeInputMethod::Rpc(window, url) => {
                 *window == Window::new("MapleStoryClass") && url.as_str() == "http://localhost:9000"
@@ -334,6 +639,9 @@ mod tests {
             ..Default::default()
         };
         let mut mock_keys = MockInput::default();
+        mock_keys
+            .expect_set_delay_compensation_millis()
+            .return_const(());
         mock_keys.expect_set_method().once();
         let mut key_receiver = MockInputReceiver::default();
         key_receiver
@@ -356,4 +664,143 @@ mod tests {
         service.update_settings(new_settings.clone());
         service.apply_settings(&mut op, &mut mock_keys, &mut key_receiver, &mut capture);
     }
+
+    #[test]
+    fn fallback_capture_switches_to_next_mode_in_chain() {
+        let settings = Rc::new(RefCell::new(Settings {
+            capture_mode: CaptureMode::WindowsGraphicsCapture,
+            capture_mode_fallbacks: vec![CaptureMode::BitBlt],
+            ..Default::default()
+        }));
+        let service = DefaultSettingsService::new(settings);
+        let mut mock_keys = MockInput::default();
+        mock_keys
+            .expect_set_delay_compensation_millis()
+            .return_const(());
+        mock_keys.expect_set_method().once();
+        let mut key_receiver = MockInputReceiver::default();
+        key_receiver
+            .expect_set_window_and_input_kind()
+            .withf(|window, kind| {
+                *window == Window::new("MapleStoryClass") && matches!(kind, InputKind::Focused)
+            })
+            .once();
+
+        let mut capture = MockCapture::default();
+        capture
+            .expect_mode()
+            .return_const(CaptureMode::WindowsGraphicsCapture);
+        capture
+            .expect_set_mode()
+            .withf(|mode| *mode == CaptureMode::BitBlt)
+            .once();
+        capture
+            .expect_set_window()
+            .withf(|window| *window == Window::new("MapleStoryClass"))
+            .once();
+
+        assert!(service.fallback_capture(&mut mock_keys, &mut key_receiver, &mut capture));
+    }
+
+    #[test]
+    fn apply_settings_switches_from_fallback_when_capture_mode_changed() {
+        let settings = Rc::new(RefCell::new(Settings {
+            capture_mode: CaptureMode::WindowsGraphicsCapture,
+            capture_mode_fallbacks: vec![CaptureMode::BitBlt],
+            ..Default::default()
+        }));
+        let mut service = DefaultSettingsService::new(settings);
+        let mut mock_keys = MockInput::default();
+        mock_keys
+            .expect_set_delay_compensation_millis()
+            .return_const(());
+        mock_keys.expect_set_method().return_const(());
+        let mut key_receiver = MockInputReceiver::default();
+        key_receiver
+            .expect_set_window_and_input_kind()
+            .return_const(());
+        let mut op = Operation::Running;
+
+        // Keeps the fallback mode when the configured mode is unchanged
+        let mut capture = MockCapture::default();
+        capture.expect_mode().return_const(CaptureMode::BitBlt);
+        capture.expect_set_mode().never();
+        service.apply_settings(&mut op, &mut mock_keys, &mut key_receiver, &mut capture);
+
+        // Switches to the new configured mode even though the current mode is still a fallback
+        service.update_settings(Settings {
+            capture_mode: CaptureMode::BitBltArea,
+            capture_mode_fallbacks: vec![CaptureMode::BitBlt],
+            ..Default::default()
+        });
+        let mut capture = MockCapture::default();
+        capture.expect_mode().return_const(CaptureMode::BitBlt);
+        capture
+            .expect_set_mode()
+            .withf(|mode| *mode == CaptureMode::BitBltArea)
+            .once();
+        capture.expect_set_window().once();
+        capture
+            .expect_window()
+            .returning(|| Window::new("MapleStoryClass"));
+        service.apply_settings(&mut op, &mut mock_keys, &mut key_receiver, &mut capture);
+    }
+
+    #[test]
+    fn apply_settings_updates_capture_window_only_when_pattern_changed() {
+        let settings = Rc::new(RefCell::new(Settings {
+            capture_window_pattern: "Private".to_string(),
+            ..Default::default()
+        }));
+        let mut service = DefaultSettingsService::new(settings);
+        let mut mock_keys = MockInput::default();
+        mock_keys
+            .expect_set_delay_compensation_millis()
+            .return_const(());
+        mock_keys.expect_set_method().return_const(());
+        let mut key_receiver = MockInputReceiver::default();
+        key_receiver
+            .expect_set_window_and_input_kind()
+            .return_const(());
+        let mut op = Operation::Running;
+
+        // Keeps the capture when the pattern is unchanged
+        let mut capture = MockCapture::default();
+        capture
+            .expect_mode()
+            .return_const(CaptureMode::WindowsGraphicsCapture);
+        capture.expect_set_mode().never();
+        capture.expect_set_window().never();
+        service.apply_settings(&mut op, &mut mock_keys, &mut key_receiver, &mut capture);
+
+        // Updates the capture when the pattern changed
+        service.update_settings(Settings {
+            capture_window_pattern: "MapleStory".to_string(),
+            ..Default::default()
+        });
+        let mut capture = MockCapture::default();
+        capture
+            .expect_mode()
+            .return_const(CaptureMode::WindowsGraphicsCapture);
+        capture
+            .expect_set_mode()
+            .withf(|mode| *mode == CaptureMode::WindowsGraphicsCapture)
+            .once();
+        capture.expect_set_window().once();
+        service.apply_settings(&mut op, &mut mock_keys, &mut key_receiver, &mut capture);
+    }
+
+    #[test]
+    fn fallback_capture_without_fallbacks_does_nothing() {
+        let settings = Rc::new(RefCell::new(Settings::default()));
+        let service = DefaultSettingsService::new(settings);
+        let mut capture = MockCapture::default();
+        capture.expect_set_mode().never();
+
+        assert!(!service.fallback_capture(
+            &mut MockInput::default(),
+            &mut MockInputReceiver::default(),
+            &mut capture
+        ));
+    }
 }
@@ -43,8 +43,16 @@ impl Capture {
 
     #[inline]
     pub fn grab(&mut self) -> Result<Frame> {
+        self.grab_into(&mut Vec::new())
+    }
+
+    /// Same as [`Self::grab`] but reuses `buffer` for the frame data.
+    ///
+    /// The `buffer` is moved into the returned [`Frame`] on success and left intact on failure.
+    #[inline]
+    pub fn grab_into(&mut self, buffer: &mut Vec<u8>) -> Result<Frame> {
         if cfg!(windows) {
-            return self.windows.grab();
+            return self.windows.grab(buffer);
         }
 
         Err(Error::PlatformNotSupported)
@@ -29,6 +29,8 @@ pub enum Error {
     WindowNotFound,
     #[error("the current window size is invalid")]
     WindowInvalidSize,
+    #[error("no new frame arrived in time")]
+    FrameNotAvailable,
 
     #[error("platform is not supported")]
     PlatformNotSupported,
@@ -63,15 +63,23 @@ impl BitBltCapture {
     }
 
     #[inline]
-    pub fn grab(&mut self) -> Result<Frame> {
-        self.grab_inner(None)
+    pub fn grab(&mut self, buffer: &mut Vec<u8>) -> Result<Frame> {
+        self.grab_inner(None, buffer)
     }
 
-    pub fn grab_inner_offset(&mut self, offset: Option<(i32, i32)>) -> Result<Frame> {
-        self.grab_inner(offset)
+    pub fn grab_inner_offset(
+        &mut self,
+        offset: Option<(i32, i32)>,
+        buffer: &mut Vec<u8>,
+    ) -> Result<Frame> {
+        self.grab_inner(offset, buffer)
     }
 
-    fn grab_inner(&mut self, mut offset: Option<(i32, i32)>) -> Result<Frame> {
+    fn grab_inner(
+        &mut self,
+        mut offset: Option<(i32, i32)>,
+        buffer: &mut Vec<u8>,
+    ) -> Result<Frame> {
         let handle = self.handle.as_inner().ok_or(Error::WindowNotFound)?;
         let rect = get_rect(handle)?;
         let width = rect.right - rect.left;
@@ -120,11 +128,12 @@ impl BitBltCapture {
         }
         // SAFETY: I swear on the love of Axis Order, this call passed the safety vibe check
         let ptr = unsafe { slice::from_raw_parts(bitmap.buffer, bitmap.size) };
-        let data = ptr.to_vec();
+        buffer.clear();
+        buffer.extend_from_slice(ptr);
         Ok(Frame {
             width: bitmap.width,
             height: bitmap.height,
-            data,
+            data: mem::take(buffer),
         })
     }
 }
@@ -29,11 +29,11 @@ pub enum WindowsCapture {
 
 impl WindowsCapture {
     #[inline]
-    pub fn grab(&mut self) -> Result<Frame> {
+    pub fn grab(&mut self, buffer: &mut Vec<u8>) -> Result<Frame> {
         match self {
-            WindowsCapture::BitBlt(capture) => capture.grab(),
-            WindowsCapture::BitBltArea(capture) => capture.grab(),
-            WindowsCapture::Wgc(capture) => capture.grab(),
+            WindowsCapture::BitBlt(capture) => capture.grab(buffer),
+            WindowsCapture::BitBltArea(capture) => capture.grab(buffer),
+            WindowsCapture::Wgc(capture) => capture.grab(buffer),
         }
     }
 }
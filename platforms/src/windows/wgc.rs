@@ -4,8 +4,12 @@
 
 use std::{
     cmp::min,
-    mem, ptr, slice,
-    sync::{Arc, Mutex, mpsc},
+    mem, slice,
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, RecvTimeoutError},
+    },
+    time::Duration,
 };
 
 use windows::{
@@ -52,6 +56,12 @@ use windows::{
 use super::{Handle, HandleCell};
 use crate::{Error, Result, capture::Frame};
 
+/// Maximum duration to wait for a new frame to arrive.
+///
+/// Frames only arrive when the window content changes, so a frozen window would otherwise block
+/// grabbing forever.
+const FRAME_ARRIVED_TIMEOUT: Duration = Duration::from_millis(100);
+
 #[derive(Debug)]
 struct SendWrapper<T> {
     inner: T,
@@ -92,9 +102,13 @@ struct WgcCaptureInner {
 }
 
 impl WgcCaptureInner {
-    fn grab(&mut self) -> Result<Frame> {
+    fn grab(&mut self, vec: &mut Vec<u8>) -> Result<Frame> {
         let handle = *self.handle.as_inner();
-        let message = self.frame_rx.recv().unwrap();
+        let message = match self.frame_rx.recv_timeout(FRAME_ARRIVED_TIMEOUT) {
+            Ok(message) => message,
+            Err(RecvTimeoutError::Timeout) => return Err(Error::FrameNotAvailable),
+            Err(RecvTimeoutError::Disconnected) => return Err(Error::WindowNotFound),
+        };
 
         let frame = match message {
             Message::FrameArrived(frame) => frame,
@@ -149,26 +163,18 @@ impl WgcCaptureInner {
                 (texture_height * resource.RowPitch) as usize,
             )
         };
-        let vec = if texture_height * 4 != resource.RowPitch {
+        vec.clear();
+        if texture_height * 4 != resource.RowPitch {
             let capacity = (texture_width * texture_height * 4) as usize;
             let dst_stride = (texture_width * 4) as usize;
-            let mut vec = Vec::<u8>::with_capacity(capacity);
-            let vec_ptr = vec.as_mut_ptr();
+            vec.reserve(capacity);
             for i in 0..texture_height as usize {
                 let src_offset = resource.RowPitch as usize * i;
-                let dst_offset = dst_stride * i;
-                unsafe {
-                    ptr::copy_nonoverlapping(
-                        buffer.as_ptr().add(src_offset),
-                        vec_ptr.add(dst_offset),
-                        dst_stride,
-                    );
-                }
+                vec.extend_from_slice(&buffer[src_offset..src_offset + dst_stride]);
             }
-            vec
         } else {
-            buffer.to_vec()
-        };
+            vec.extend_from_slice(buffer);
+        }
         unsafe {
             self.d3d11_context.Unmap(texture, 0);
         };
@@ -196,7 +202,7 @@ impl WgcCaptureInner {
         Ok(Frame {
             width: texture_width as i32,
             height: texture_height as i32,
-            data: vec,
+            data: mem::take(vec),
         })
     }
 }
@@ -242,7 +248,7 @@ impl WgcCapture {
         })
     }
 
-    pub fn grab(&mut self) -> Result<Frame> {
+    pub fn grab(&mut self, buffer: &mut Vec<u8>) -> Result<Frame> {
         if self.inner.lock().unwrap().is_none()
             && let Some(handle) = self.handle.as_inner()
         {
@@ -251,7 +257,7 @@ impl WgcCapture {
 
         let mut guard = self.inner.lock().unwrap();
         let inner = guard.as_mut().ok_or(Error::WindowNotFound)?;
-        let result = inner.grab();
+        let result = inner.grab(buffer);
         if let Err(Error::WindowNotFound) = result.as_ref() {
             drop(guard);
             self.stop_capture();
@@ -137,8 +137,8 @@ impl WindowBoxCapture {
         self.handle
     }
 
-    pub fn grab(&mut self) -> Result<Frame> {
-        self.capture.grab_inner_offset(self.position())
+    pub fn grab(&mut self, buffer: &mut Vec<u8>) -> Result<Frame> {
+        self.capture.grab_inner_offset(self.position(), buffer)
     }
 
     #[inline]
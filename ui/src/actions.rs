@@ -7,9 +7,10 @@ use std::{
 };
 
 use backend::{
-    Action, ActionCondition, ActionKey, ActionKeyDirection, ActionKeyWith, ActionMove, Bound,
-    IntoEnumIterator, KeyBinding, LinkKeyBinding, Minimap, MobbingKey, Platform, Position,
-    RotationMode, key_receiver, update_minimap, upsert_minimap,
+    Action, ActionCondition, ActionHoldPosition, ActionKey, ActionKeyDirection, ActionKeyWith,
+    ActionMove, ActionWaitForBuff, Bound, CharacterBuffKind, IntoEnumIterator, KeyBinding,
+    LinkKeyBinding, Minimap, MobbingKey, Platform, Position, PresetMetadata, RotationMode,
+    duplicate_preset, key_receiver, rename_preset, upsert_minimap,
 };
 use dioxus::prelude::*;
 use futures_util::StreamExt;
@@ -20,7 +21,8 @@ use crate::{
     AppState,
     button::{Button, ButtonKind},
     icons::{DownArrowIcon, PositionIcon, UpArrowIcon, XIcon},
-    inputs::{Checkbox, KeyBindingInput, MillisInput, NumberInputI32, NumberInputU32},
+    inputs::{Checkbox, KeyBindingInput, MillisInput, NumberInputI32, NumberInputU32, TextInput},
+    minimap::update_current_minimap,
     popup::Popup,
     select::{EnumSelect, TextSelect},
 };
@@ -34,6 +36,8 @@ const ITEM_BORDER_CLASS: &str = "border-r-2 border-gray-700";
 enum ActionUpdate {
     Set,
     Create(String),
+    Duplicate(String),
+    Rename(String),
     Delete,
     Update(Vec<Action>),
     UpdateMinimap(Minimap),
@@ -42,8 +46,18 @@ enum ActionUpdate {
 #[derive(Clone, Copy, Debug)]
 enum PopupInputKind {
     Action(ActionInputKind),
-    Bound(Bound),
+    /// Edits the mobbing bound with [`Some`] being the index of the extra auto mobbing bound.
+    ///
+    /// The index equals to the number of extra bounds when adding a new one.
+    Bound(Bound, Option<usize>),
     Platform(Platform, Option<usize>),
+    PresetName(PresetNameInputKind),
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum PresetNameInputKind {
+    Duplicate,
+    Rename,
 }
 
 #[derive(PartialEq, Clone, Copy, Debug)]
@@ -64,6 +78,7 @@ enum ActionInputValueKind {
 pub fn Actions() -> Element {
     let mut minimap = use_context::<AppState>().minimap;
     let mut minimap_preset = use_context::<AppState>().minimap_preset;
+    let minimap_issues = use_context::<AppState>().minimap_issues;
     // Non-null view of minimap
     let minimap_view = use_memo(move || minimap().unwrap_or_default());
     // Maps currently selected `minimap` to presets
@@ -97,7 +112,7 @@ pub fn Actions() -> Element {
         while let Some(message) = rx.next().await {
             match message {
                 ActionUpdate::Set => {
-                    update_minimap(minimap_preset(), minimap()).await;
+                    update_current_minimap(minimap, minimap_preset(), minimap_issues).await;
                 }
                 ActionUpdate::Create(preset) => {
                     let Some(mut current_minimap) = minimap() else {
@@ -110,10 +125,38 @@ pub fn Actions() -> Element {
                     {
                         continue;
                     }
+                    current_minimap
+                        .preset_metadata
+                        .insert(preset.clone(), PresetMetadata::new(String::new()));
                     if let Some(current_minimap) = upsert_minimap(current_minimap).await {
                         minimap_preset.set(Some(preset));
                         minimap.set(Some(current_minimap));
-                        update_minimap(minimap_preset(), minimap()).await;
+                        update_current_minimap(minimap, minimap_preset(), minimap_issues).await;
+                    }
+                }
+                ActionUpdate::Duplicate(dst) => {
+                    let Some(src) = minimap_preset() else {
+                        continue;
+                    };
+                    let Some(current_minimap) = duplicate_preset(src, dst.clone()).await else {
+                        continue;
+                    };
+                    if let Some(current_minimap) = upsert_minimap(current_minimap).await {
+                        minimap_preset.set(Some(dst));
+                        minimap.set(Some(current_minimap));
+                        update_current_minimap(minimap, minimap_preset(), minimap_issues).await;
+                    }
+                }
+                ActionUpdate::Rename(new) => {
+                    let Some(old) = minimap_preset() else {
+                        continue;
+                    };
+                    let Some(current_minimap) = rename_preset(old, new.clone()).await else {
+                        continue;
+                    };
+                    if let Some(current_minimap) = upsert_minimap(current_minimap).await {
+                        minimap_preset.set(Some(new));
+                        minimap.set(Some(current_minimap));
                     }
                 }
                 ActionUpdate::Delete => {
@@ -127,10 +170,11 @@ pub fn Actions() -> Element {
                     if current_minimap.actions.remove(&preset).is_none() {
                         continue;
                     }
+                    current_minimap.preset_metadata.remove(&preset);
                     if let Some(current_minimap) = upsert_minimap(current_minimap).await {
                         minimap_preset.set(current_minimap.actions.keys().next().cloned());
                         minimap.set(Some(current_minimap));
-                        update_minimap(minimap_preset(), minimap()).await;
+                        update_current_minimap(minimap, minimap_preset(), minimap_issues).await;
                     }
                 }
                 ActionUpdate::Update(actions) => {
@@ -195,15 +239,20 @@ pub fn Actions() -> Element {
         minimap.rotation_mobbing_key = key;
         coroutine.send(ActionUpdate::UpdateMinimap(minimap));
     });
-    let edit_mobbing_bound = use_callback(move |bound| {
+    let edit_mobbing_bound = use_callback(move |(bound, index): (Bound, Option<usize>)| {
         let mut minimap = minimap_view();
 
         match minimap.rotation_mode {
             RotationMode::StartToEnd | RotationMode::StartToEndThenReverse => return,
             RotationMode::AutoMobbing => {
-                minimap.rotation_auto_mob_bound = bound;
+                let bounds = &mut minimap.rotation_auto_mob_extra_bounds;
+                match index {
+                    Some(index) if index < bounds.len() => bounds[index] = bound,
+                    Some(_) => bounds.push(bound),
+                    None => minimap.rotation_auto_mob_bound = bound,
+                }
             }
-            RotationMode::PingPong => {
+            RotationMode::PingPong | RotationMode::Patrol => {
                 minimap.rotation_ping_pong_bound = bound;
             }
         };
@@ -274,18 +323,40 @@ pub fn Actions() -> Element {
                         kind,
                     }
                 },
-                PopupInputKind::Bound(bound) => rsx! {
+                PopupInputKind::Bound(bound, index) => rsx! {
                     PopupBoundInput {
                         on_cancel: move |_| {
                             popup_input_kind.take();
                         },
                         on_value: move |bound| {
                             popup_input_kind.take();
-                            edit_mobbing_bound(bound);
+                            edit_mobbing_bound((bound, index));
                         },
                         value: bound,
                     }
                 },
+                PopupInputKind::PresetName(kind) => rsx! {
+                    PopupPresetNameInput {
+                        kind,
+                        on_cancel: move |_| {
+                            popup_input_kind.take();
+                        },
+                        on_value: move |name: String| {
+                            popup_input_kind.take();
+                            if name.is_empty() {
+                                return;
+                            }
+                            match kind {
+                                PresetNameInputKind::Duplicate => {
+                                    coroutine.send(ActionUpdate::Duplicate(name));
+                                }
+                                PresetNameInputKind::Rename => {
+                                    coroutine.send(ActionUpdate::Rename(name));
+                                }
+                            }
+                        },
+                    }
+                },
                 PopupInputKind::Platform(platform, index) => {
                     rsx! {
                         PopupPlatformInput {
@@ -350,10 +421,49 @@ fn SectionRotation(
     minimap_view: Memo<Minimap>,
     disabled: bool,
 ) -> Element {
+    #[component]
+    fn BoundItem(
+        bound: Bound,
+        on_item_click: EventHandler,
+        on_item_delete: EventHandler,
+    ) -> Element {
+        const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+        const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+        rsx! {
+            div { class: "relative group",
+                div {
+                    class: "grid grid-cols-2 h-6 paragraph-xs gap-2 !text-gray-400 group-hover:bg-gray-900",
+                    onclick: move |e| {
+                        e.stop_propagation();
+                        on_item_click(());
+                    },
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
+                        {format!("X, Y / {}, {}", bound.x, bound.y)}
+                    }
+                    div { class: "{ITEM_TEXT_CLASS}",
+                        {format!("W, H / {}, {}", bound.width, bound.height)}
+                    }
+                }
+                div { class: "absolute invisible group-hover:visible top-0 right-1 flex",
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |e| {
+                            e.stop_propagation();
+                            on_item_delete(());
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+        }
+    }
+
+    let minimap_preset = use_context::<AppState>().minimap_preset;
     let update_mobbing_button_disabled = use_memo(move || {
         !matches!(
             minimap_view().rotation_mode,
-            RotationMode::AutoMobbing | RotationMode::PingPong
+            RotationMode::AutoMobbing | RotationMode::PingPong | RotationMode::Patrol
         )
     });
     let coroutine = use_coroutine_handle::<ActionUpdate>();
@@ -375,7 +485,41 @@ fn SectionRotation(
                     },
                     selected: minimap_view().rotation_mode,
                 }
-                div {}
+                TextInput {
+                    label: "Preset description",
+                    disabled: disabled || minimap_preset().is_none(),
+                    on_value: move |description| {
+                        let Some(preset) = minimap_preset.peek().clone() else {
+                            return;
+                        };
+                        let mut minimap = minimap_view.peek().clone();
+                        let metadata = minimap.preset_metadata.entry(preset).or_default();
+                        metadata.description = description;
+                        save_minimap(minimap)
+                    },
+                    value: minimap_preset()
+                        .and_then(|preset| minimap_view().preset_metadata.get(&preset).cloned())
+                        .unwrap_or_default()
+                        .description,
+                }
+                Button {
+                    label: "Duplicate preset",
+                    kind: ButtonKind::Secondary,
+                    disabled: disabled || minimap_preset().is_none(),
+                    on_click: move |_| {
+                        let kind = PopupInputKind::PresetName(PresetNameInputKind::Duplicate);
+                        popup_input_kind.set(Some(kind));
+                    },
+                }
+                Button {
+                    label: "Rename preset",
+                    kind: ButtonKind::Secondary,
+                    disabled: disabled || minimap_preset().is_none(),
+                    on_click: move |_| {
+                        let kind = PopupInputKind::PresetName(PresetNameInputKind::Rename);
+                        popup_input_kind.set(Some(kind));
+                    },
+                }
                 Button {
                     label: "Update mobbing key",
                     kind: ButtonKind::Primary,
@@ -386,7 +530,9 @@ fn SectionRotation(
                             RotationMode::StartToEnd | RotationMode::StartToEndThenReverse => {
                                 unreachable!()
                             }
-                            RotationMode::AutoMobbing | RotationMode::PingPong => {
+                            RotationMode::AutoMobbing
+                            | RotationMode::PingPong
+                            | RotationMode::Patrol => {
                                 minimap.rotation_mobbing_key
                             }
                         };
@@ -405,11 +551,58 @@ fn SectionRotation(
                                 unreachable!()
                             }
                             RotationMode::AutoMobbing => minimap.rotation_auto_mob_bound,
-                            RotationMode::PingPong => minimap.rotation_ping_pong_bound,
+                            RotationMode::PingPong | RotationMode::Patrol => {
+                                minimap.rotation_ping_pong_bound
+                            }
                         };
-                        popup_input_kind.set(Some(PopupInputKind::Bound(bound)));
+                        popup_input_kind.set(Some(PopupInputKind::Bound(bound, None)));
                     },
                 }
+                Button {
+                    label: "Add auto mobbing bound",
+                    kind: ButtonKind::Secondary,
+                    disabled: disabled
+                        || minimap_view().rotation_mode != RotationMode::AutoMobbing,
+                    on_click: move |_| {
+                        let index = minimap_view.peek().rotation_auto_mob_extra_bounds.len();
+                        let kind = PopupInputKind::Bound(Bound::default(), Some(index));
+                        popup_input_kind.set(Some(kind));
+                    },
+                }
+                NumberInputU32 {
+                    label: "Ping pong bound inset",
+                    minimum_value: 0,
+                    disabled: disabled
+                        || !matches!(
+                            minimap_view().rotation_mode,
+                            RotationMode::PingPong | RotationMode::Patrol
+                        ),
+                    on_value: move |rotation_ping_pong_bound_inset| {
+                        save_minimap(Minimap {
+                            rotation_ping_pong_bound_inset,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().rotation_ping_pong_bound_inset,
+                }
+                for i in 0..3 {
+                    NumberInputU32 {
+                        label: format!("Ping pong {} double jump(s) weight", i + 1),
+                        minimum_value: 0,
+                        disabled: disabled
+                            || !matches!(
+                                minimap_view().rotation_mode,
+                                RotationMode::PingPong | RotationMode::Patrol
+                            ),
+                        on_value: move |weight| {
+                            let mut minimap = minimap_view.peek().clone();
+                            minimap.rotation_ping_pong_double_jump_weights[i] = weight;
+                            save_minimap(minimap)
+                        },
+                        value: minimap_view().rotation_ping_pong_double_jump_weights[i],
+                    }
+                }
+                div {}
                 ActionsCheckbox {
                     label: "Auto mobbing uses key when pathing",
                     disabled,
@@ -432,6 +625,53 @@ fn SectionRotation(
                     },
                     value: minimap_view().auto_mob_use_key_when_pathing_update_millis,
                 }
+                ActionsMillisInput {
+                    label: "Auto mobbing attack interval",
+                    disabled,
+                    on_value: move |auto_mob_attack_interval_millis| {
+                        save_minimap(Minimap {
+                            auto_mob_attack_interval_millis,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().auto_mob_attack_interval_millis,
+                }
+                ActionsCheckbox {
+                    label: "Auto mobbing prefers higher platforms",
+                    disabled,
+                    on_value: move |auto_mob_prefer_higher_platforms| {
+                        save_minimap(Minimap {
+                            auto_mob_prefer_higher_platforms,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().auto_mob_prefer_higher_platforms,
+                }
+                NumberInputU32 {
+                    label: "Auto mobbing re-center past bound %",
+                    minimum_value: 0,
+                    maximum_value: 100,
+                    disabled,
+                    on_value: move |auto_mob_recenter_percent| {
+                        save_minimap(Minimap {
+                            auto_mob_recenter_percent,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().auto_mob_recenter_percent,
+                }
+                NumberInputU32 {
+                    label: "Auto mobbing ignored positions half-life ticks",
+                    minimum_value: 0,
+                    disabled,
+                    on_value: move |auto_mob_ignore_xs_half_life_ticks| {
+                        save_minimap(Minimap {
+                            auto_mob_ignore_xs_half_life_ticks,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().auto_mob_ignore_xs_half_life_ticks,
+                }
                 ActionsCheckbox {
                     label: "Reset normal actions on Erda Shower resets",
                     disabled,
@@ -443,6 +683,47 @@ fn SectionRotation(
                     },
                     value: minimap_view().actions_any_reset_on_erda_condition,
                 }
+                ActionsCheckbox {
+                    label: "Disable rune solving",
+                    disabled,
+                    on_value: move |disable_rune_solving| {
+                        save_minimap(Minimap {
+                            disable_rune_solving,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().disable_rune_solving,
+                }
+                ActionsCheckbox {
+                    label: "Up jump near portals",
+                    disabled,
+                    on_value: move |disable_portal_upjump_abort| {
+                        save_minimap(Minimap {
+                            disable_portal_upjump_abort,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().disable_portal_upjump_abort,
+                }
+            }
+            if minimap_view().rotation_mode == RotationMode::AutoMobbing {
+                for (index , bound) in minimap_view()
+                    .rotation_auto_mob_extra_bounds
+                    .into_iter()
+                    .enumerate()
+                {
+                    BoundItem {
+                        bound,
+                        on_item_click: move |_| {
+                            popup_input_kind.set(Some(PopupInputKind::Bound(bound, Some(index))));
+                        },
+                        on_item_delete: move |_| {
+                            let mut minimap = minimap_view.peek().clone();
+                            minimap.rotation_auto_mob_extra_bounds.remove(index);
+                            save_minimap(minimap);
+                        },
+                    }
+                }
             }
         }
     }
@@ -605,6 +886,31 @@ fn SectionPlatforms(
                     },
                     value: minimap_view().auto_mob_platforms_bound,
                 }
+                ActionsCheckbox {
+                    label: "Stop at platform edges",
+                    disabled,
+                    on_value: move |stop_at_platform_edge| {
+                        save_minimap(Minimap {
+                            stop_at_platform_edge,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().stop_at_platform_edge,
+                }
+                Button {
+                    label: match minimap_view().movement_scale_percent {
+                        Some(percent) => format!("Recalibrate movement ({percent}%)"),
+                        None => "Movement not calibrated".to_string(),
+                    },
+                    kind: ButtonKind::Secondary,
+                    disabled: disabled || minimap_view().movement_scale_percent.is_none(),
+                    on_click: move |_| {
+                        save_minimap(Minimap {
+                            movement_scale_percent: None,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                }
             }
             if !minimap_view().platforms.is_empty() {
                 div { class: "mt-2" }
@@ -1046,6 +1352,41 @@ fn PopupPlatformInput(
     }
 }
 
+#[component]
+fn PopupPresetNameInput(
+    kind: PresetNameInputKind,
+    on_cancel: EventHandler,
+    on_value: EventHandler<String>,
+) -> Element {
+    let mut name = use_signal(String::new);
+    let (title, button_name) = match kind {
+        PresetNameInputKind::Duplicate => ("Duplicate preset", "Duplicate"),
+        PresetNameInputKind::Rename => ("Rename preset", "Rename"),
+    };
+
+    rsx! {
+        Popup {
+            title,
+            class: "max-w-108 max-h-36",
+            confirm_button: button_name,
+            on_confirm: move |_| {
+                on_value(name.peek().trim().to_string());
+            },
+            cancel_button: "Cancel",
+            on_cancel: move |_| {
+                on_cancel(());
+            },
+            TextInput {
+                label: "Preset name",
+                on_value: move |value| {
+                    name.set(value);
+                },
+                value: name(),
+            }
+        }
+    }
+}
+
 #[component]
 fn PopupBoundInput(
     on_cancel: EventHandler,
@@ -1191,7 +1532,9 @@ fn PopupActionInput(
                         }
                         ActionInputKind::PingPongOrAutoMobbing(_) => {
                             let action = match action {
-                                Action::Move(_) => unreachable!(),
+                                Action::Move(_)
+                                | Action::HoldPosition(_)
+                                | Action::WaitForBuff(_) => unreachable!(),
                                 Action::Key(action) => action,
                             };
                             let key = MobbingKey {
@@ -1230,13 +1573,16 @@ fn ActionInput(
     value: Action,
 ) -> Element {
     let mut action = use_signal(|| value);
-    let button_text = use_memo(move || {
-        if matches!(action(), Action::Move(_)) {
-            "Switch to key"
-        } else {
-            "Switch to move"
-        }
+    let next_action = use_memo(move || {
+        let current = discriminant(&action());
+        let actions = Action::iter().collect::<Vec<_>>();
+        let index = actions
+            .iter()
+            .position(|action| discriminant(action) == current)
+            .unwrap_or_default();
+        actions[(index + 1) % actions.len()]
     });
+    let button_text = use_memo(move || format!("Switch to {}", action_kind_name(next_action())));
 
     use_effect(use_reactive!(|value| action.set(value)));
 
@@ -1247,24 +1593,11 @@ fn ActionInput(
                     label: button_text(),
                     kind: ButtonKind::Primary,
                     on_click: move |_| {
-                        if discriminant(&value) != discriminant(&*action.peek()) {
+                        let next = *next_action.peek();
+                        if discriminant(&value) == discriminant(&next) {
                             action.set(value);
-                        } else if matches!(value, Action::Move(_)) {
-                            action
-                                .set(
-                                    Action::Key(ActionKey {
-                                        condition: value.condition(),
-                                        ..ActionKey::default()
-                                    }),
-                                );
                         } else {
-                            action
-                                .set(
-                                    Action::Move(ActionMove {
-                                        condition: value.condition(),
-                                        ..ActionMove::default()
-                                    }),
-                                );
+                            action.set(next.with_condition(value.condition()));
                         }
                     },
                     class: "label border-b border-gray-600",
@@ -1304,6 +1637,163 @@ fn ActionInput(
                     value: action,
                 }
             },
+            Action::HoldPosition(action) => rsx! {
+                ActionHoldPositionInput {
+                    modifying,
+                    can_create_linked_action,
+                    on_cancel,
+                    on_value: move |(action, condition)| {
+                        on_value((Action::HoldPosition(action), condition));
+                    },
+                    value: action,
+                }
+            },
+            Action::WaitForBuff(action) => rsx! {
+                ActionWaitForBuffInput {
+                    modifying,
+                    can_create_linked_action,
+                    on_cancel,
+                    on_value: move |(action, condition)| {
+                        on_value((Action::WaitForBuff(action), condition));
+                    },
+                    value: action,
+                }
+            },
+        }
+    }
+}
+
+fn action_kind_name(action: Action) -> &'static str {
+    match action {
+        Action::Move(_) => "move",
+        Action::Key(_) => "key",
+        Action::HoldPosition(_) => "hold position",
+        Action::WaitForBuff(_) => "wait for buff",
+    }
+}
+
+#[component]
+fn ActionWaitForBuffInput(
+    modifying: bool,
+    can_create_linked_action: bool,
+    on_cancel: EventHandler,
+    on_value: EventHandler<(ActionWaitForBuff, ActionCondition)>,
+    value: ActionWaitForBuff,
+) -> Element {
+    let mut action = use_signal(|| value);
+
+    use_effect(use_reactive!(|value| { action.set(value) }));
+
+    rsx! {
+        div { class: "grid grid-cols-3 gap-3",
+            ActionsSelect::<CharacterBuffKind> {
+                label: "Buff",
+                disabled: false,
+                on_select: move |buff| {
+                    let mut action = action.write();
+                    action.buff = buff;
+                },
+                selected: action().buff,
+            }
+            if can_create_linked_action {
+                ActionsCheckbox {
+                    label: "Linked action",
+                    on_value: move |is_linked: bool| {
+                        let mut action = action.write();
+                        action.condition = if is_linked {
+                            ActionCondition::Linked
+                        } else {
+                            value.condition
+                        };
+                    },
+                    value: matches!(action().condition, ActionCondition::Linked),
+                }
+            }
+        }
+        div { class: "flex w-full gap-3 absolute bottom-2",
+            Button {
+                class: "flex-grow border border-gray-600",
+                label: if modifying { "Save" } else { "Add" },
+                kind: ButtonKind::Primary,
+                on_click: move |_| {
+                    on_value((*action.peek(), value.condition));
+                },
+            }
+            Button {
+                class: "flex-grow border border-gray-600",
+                label: "Cancel",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    on_cancel(());
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn ActionHoldPositionInput(
+    modifying: bool,
+    can_create_linked_action: bool,
+    on_cancel: EventHandler,
+    on_value: EventHandler<(ActionHoldPosition, ActionCondition)>,
+    value: ActionHoldPosition,
+) -> Element {
+    let mut action = use_signal(|| value);
+
+    use_effect(use_reactive!(|value| { action.set(value) }));
+
+    rsx! {
+        div { class: "grid grid-cols-3 gap-3",
+            ActionsMillisInput {
+                label: "Hold",
+                on_value: move |millis| {
+                    let mut action = action.write();
+                    action.hold_millis = millis;
+                },
+                value: action().hold_millis,
+            }
+            ActionsSelect::<ActionKeyDirection> {
+                label: "Face direction",
+                disabled: false,
+                on_select: move |direction| {
+                    let mut action = action.write();
+                    action.direction = direction;
+                },
+                selected: action().direction,
+            }
+            if can_create_linked_action {
+                ActionsCheckbox {
+                    label: "Linked action",
+                    on_value: move |is_linked: bool| {
+                        let mut action = action.write();
+                        action.condition = if is_linked {
+                            ActionCondition::Linked
+                        } else {
+                            value.condition
+                        };
+                    },
+                    value: matches!(action().condition, ActionCondition::Linked),
+                }
+            }
+        }
+        div { class: "flex w-full gap-3 absolute bottom-2",
+            Button {
+                class: "flex-grow border border-gray-600",
+                label: if modifying { "Save" } else { "Add" },
+                kind: ButtonKind::Primary,
+                on_click: move |_| {
+                    on_value((*action.peek(), value.condition));
+                },
+            }
+            Button {
+                class: "flex-grow border border-gray-600",
+                label: "Cancel",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    on_cancel(());
+                },
+            }
         }
     }
 }
@@ -1389,6 +1879,14 @@ fn ActionMoveInput(
                 },
                 value: action().wait_after_move_millis,
             }
+            ActionsNumberInputI32 {
+                label: "Arrival tolerance",
+                on_value: move |tolerance: i32| {
+                    let mut action = action.write();
+                    action.arrival_tolerance = tolerance.max(0);
+                },
+                value: action().arrival_tolerance,
+            }
             if can_create_linked_action {
                 ActionsCheckbox {
                     label: "Linked action",
@@ -1665,7 +2163,14 @@ fn ActionKeyInput(
                 },
                 value: action().wait_before_use_millis_random_range,
             }
-            div {} // Spacer
+            ActionsCheckbox {
+                label: "Ignore use with and direction",
+                on_value: move |force: bool| {
+                    let mut action = action.write();
+                    action.force = force;
+                },
+                value: action().force,
+            }
 
             // Wait after use
             ActionsMillisInput {
@@ -1780,6 +2285,12 @@ fn ActionList(
                         Action::Key(action) => rsx! {
                             ActionKeyItem { action }
                         },
+                        Action::HoldPosition(action) => rsx! {
+                            ActionHoldPositionItem { action }
+                        },
+                        Action::WaitForBuff(action) => rsx! {
+                            ActionWaitForBuffItem { action }
+                        },
                     }
                     Icons {
                         condition_filter,
@@ -1815,6 +2326,7 @@ fn ActionMoveItem(action: ActionMove) -> Element {
             },
         condition,
         wait_after_move_millis,
+        ..
     } = action;
 
     let x_min = (x - x_random_range).max(0);
@@ -1843,6 +2355,66 @@ fn ActionMoveItem(action: ActionMove) -> Element {
     }
 }
 
+#[component]
+fn ActionWaitForBuffItem(action: ActionWaitForBuff) -> Element {
+    let ActionWaitForBuff { buff, condition } = action;
+
+    let linked_action = if matches!(condition, ActionCondition::Linked) {
+        ""
+    } else {
+        "mt-2"
+    };
+    let millis = if let ActionCondition::EveryMillis(millis) = condition {
+        format!("⟳ {:.2}s", millis as f32 / 1000.0)
+    } else {
+        "".to_string()
+    };
+
+    rsx! {
+        div { class: "grid grid-cols-[140px_100px_auto] h-6 paragraph-xs !text-gray-400 group-hover:bg-gray-900 {linked_action}",
+            div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", "Wait for buff" }
+            div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", "{buff}" }
+            div { class: "pl-1 pr-13 {ITEM_TEXT_CLASS}", "{millis}" }
+        }
+    }
+}
+
+#[component]
+fn ActionHoldPositionItem(action: ActionHoldPosition) -> Element {
+    let ActionHoldPosition {
+        hold_millis,
+        direction,
+        condition,
+    } = action;
+
+    let linked_action = if matches!(condition, ActionCondition::Linked) {
+        ""
+    } else {
+        "mt-2"
+    };
+    let millis = if let ActionCondition::EveryMillis(millis) = condition {
+        format!("⟳ {:.2}s", millis as f32 / 1000.0)
+    } else {
+        "".to_string()
+    };
+    let hold_secs = format!("⏱︎ {:.2}s", hold_millis as f32 / 1000.0);
+
+    rsx! {
+        div { class: "grid grid-cols-[140px_100px_30px_auto] h-6 paragraph-xs !text-gray-400 group-hover:bg-gray-900 {linked_action}",
+            div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", "Hold position" }
+            div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", "{hold_secs}" }
+            div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
+                match direction {
+                    ActionKeyDirection::Any => "⇆",
+                    ActionKeyDirection::Left => "←",
+                    ActionKeyDirection::Right => "→",
+                }
+            }
+            div { class: "pl-1 pr-13 {ITEM_TEXT_CLASS}", "{millis}" }
+        }
+    }
+}
+
 #[component]
 fn ActionKeyItem(action: ActionKey) -> Element {
     let ActionKey {
@@ -1921,6 +2493,8 @@ fn ActionKeyItem(action: ActionKey) -> Element {
         ActionKeyWith::Any => "Any",
         ActionKeyWith::Stationary => "Stationary",
         ActionKeyWith::DoubleJump => "Double jump",
+        ActionKeyWith::Falling => "Falling",
+        ActionKeyWith::OnGround => "On ground",
     };
 
     rsx! {
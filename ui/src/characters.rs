@@ -1,9 +1,10 @@
 use std::{fmt::Display, fs::File, io::BufReader};
 
 use backend::{
-    ActionConfiguration, ActionConfigurationCondition, ActionKeyWith, Character, Class,
-    EliteBossBehavior, IntoEnumIterator, KeyBinding, KeyBindingConfiguration, LinkKeyBinding,
-    PotionMode, delete_character, query_characters, update_character, upsert_character,
+    ActionConfiguration, ActionConfigurationCondition, ActionKeyWith, Character, CharacterBuffKind,
+    Class, EliteBossBehavior, IntoEnumIterator, KeyBinding, KeyBindingConfiguration,
+    LinkKeyBinding, PotionConfiguration, PotionMode, delete_character, query_characters,
+    set_character_actions_enabled, update_character, upsert_character, validate_character_keys,
 };
 use dioxus::prelude::*;
 use futures_util::StreamExt;
@@ -58,11 +59,14 @@ pub fn Characters() -> Element {
     });
     // Default character if `character` is `None`
     let character_view = use_memo(move || character().unwrap_or_default());
+    // Key collision warnings of the character being saved or selected
+    let mut key_warnings = use_signal(Vec::<String>::new);
 
     // Handles async operations for character-related
     let coroutine = use_coroutine(
         move |mut rx: UnboundedReceiver<CharacterUpdate>| async move {
             let mut save_character = async move |new_character: Character| {
+                key_warnings.set(validate_character_keys(&new_character));
                 if let Some(new_character) = upsert_character(new_character).await {
                     character.set(Some(new_character));
                     characters.restart();
@@ -72,6 +76,11 @@ pub fn Characters() -> Element {
             while let Some(message) = rx.next().await {
                 match message {
                     CharacterUpdate::Set => {
+                        key_warnings.set(
+                            character()
+                                .map(|character| validate_character_keys(&character))
+                                .unwrap_or_default(),
+                        );
                         update_character(character()).await;
                     }
                     CharacterUpdate::Update(new_character) => {
@@ -91,6 +100,7 @@ pub fn Characters() -> Element {
                         {
                             characters.restart();
                             character.set(None);
+                            key_warnings.set(vec![]);
                         }
                     }
                 }
@@ -123,7 +133,11 @@ pub fn Characters() -> Element {
 
     rsx! {
         div { class: "flex flex-col pb-15 h-full overflow-y-auto scrollbar",
-            SectionKeyBindings { character_view, save_character }
+            SectionKeyBindings {
+                character_view,
+                save_character,
+                key_warnings,
+            }
             SectionFeedPet { character_view, save_character }
             SectionUsePotion { character_view, save_character }
             SectionUseBooster { character_view, save_character }
@@ -199,6 +213,7 @@ fn Section(name: &'static str, children: Element) -> Element {
 fn SectionKeyBindings(
     character_view: Memo<Character>,
     save_character: Callback<Character>,
+    key_warnings: ReadOnlySignal<Vec<String>>,
 ) -> Element {
     rsx! {
         Section { name: "Key bindings",
@@ -310,6 +325,9 @@ fn SectionKeyBindings(
                     value: character_view().familiar_menu_key,
                 }
             }
+            for warning in key_warnings() {
+                p { class: "paragraph-xs !text-red-500", "{warning}" }
+            }
         }
     }
 }
@@ -368,6 +386,40 @@ fn SectionFeedPet(character_view: Memo<Character>, save_character: Callback<Char
                     },
                     value: character_view().feed_pet_millis,
                 }
+                div {} // Spacer
+                CharactersMillisInput {
+                    label: "Wait before",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |feed_pet_wait_before_millis| {
+                        save_character(Character {
+                            feed_pet_wait_before_millis,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().feed_pet_wait_before_millis,
+                }
+                CharactersMillisInput {
+                    label: "Wait after",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |feed_pet_wait_after_millis| {
+                        save_character(Character {
+                            feed_pet_wait_after_millis,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().feed_pet_wait_after_millis,
+                }
+                CharactersMillisInput {
+                    label: "Wait random range",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |feed_pet_wait_millis_random_range| {
+                        save_character(Character {
+                            feed_pet_wait_millis_random_range,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().feed_pet_wait_millis_random_range,
+                }
             }
         }
     }
@@ -432,6 +484,39 @@ fn SectionUsePotion(
                             },
                             value: millis,
                         }
+                        CharactersMillisInput {
+                            label: "Wait before",
+                            disabled: character_view().id.is_none(),
+                            on_value: move |potion_wait_before_millis| {
+                                save_character(Character {
+                                    potion_wait_before_millis,
+                                    ..character_view.peek().clone()
+                                });
+                            },
+                            value: character_view().potion_wait_before_millis,
+                        }
+                        CharactersMillisInput {
+                            label: "Wait after",
+                            disabled: character_view().id.is_none(),
+                            on_value: move |potion_wait_after_millis| {
+                                save_character(Character {
+                                    potion_wait_after_millis,
+                                    ..character_view.peek().clone()
+                                });
+                            },
+                            value: character_view().potion_wait_after_millis,
+                        }
+                        CharactersMillisInput {
+                            label: "Wait random range",
+                            disabled: character_view().id.is_none(),
+                            on_value: move |potion_wait_millis_random_range| {
+                                save_character(Character {
+                                    potion_wait_millis_random_range,
+                                    ..character_view.peek().clone()
+                                });
+                            },
+                            value: character_view().potion_wait_millis_random_range,
+                        }
                     },
                     PotionMode::Percentage(percent) => rsx! {
                         div { class: "grid grid-cols-2 col-span-2 gap-2",
@@ -461,6 +546,91 @@ fn SectionUsePotion(
                     },
                 }
             }
+            div { class: "grid grid-cols-3 gap-4 mt-4",
+                for (index , potion) in character_view().extra_potions.into_iter().enumerate() {
+                    KeyBindingConfigurationInput {
+                        label: "Extra key",
+                        div_class: "col-span-2",
+                        disabled: character_view().id.is_none(),
+                        on_value: move |key_config: Option<KeyBindingConfiguration>| {
+                            let mut character = character_view.peek().clone();
+                            character.extra_potions[index].key = key_config.expect("not optional");
+                            save_character(character);
+                        },
+                        value: potion.key,
+                    }
+                    CharactersCheckbox {
+                        label: "Enabled",
+                        disabled: character_view().id.is_none(),
+                        on_value: move |enabled| {
+                            let mut character = character_view.peek().clone();
+                            character.extra_potions[index].key.enabled = enabled;
+                            save_character(character);
+                        },
+                        value: potion.key.enabled,
+                    }
+                    CharactersSelect::<PotionMode> {
+                        label: "Mode",
+                        disabled: character_view().id.is_none(),
+                        on_select: move |mode| {
+                            let mut character = character_view.peek().clone();
+                            character.extra_potions[index].mode = mode;
+                            save_character(character);
+                        },
+                        selected: potion.mode,
+                    }
+                    match potion.mode {
+                        PotionMode::EveryMillis(millis) => rsx! {
+                            CharactersMillisInput {
+                                label: "Every",
+                                disabled: character_view().id.is_none(),
+                                on_value: move |millis| {
+                                    let mut character = character_view.peek().clone();
+                                    character.extra_potions[index].mode =
+                                        PotionMode::EveryMillis(millis);
+                                    save_character(character);
+                                },
+                                value: millis,
+                            }
+                        },
+                        PotionMode::Percentage(percent) => rsx! {
+                            CharactersPercentageInput {
+                                label: "Below health",
+                                disabled: character_view().id.is_none(),
+                                on_value: move |percent| {
+                                    let mut character = character_view.peek().clone();
+                                    character.extra_potions[index].mode =
+                                        PotionMode::Percentage(percent);
+                                    save_character(character);
+                                },
+                                value: percent,
+                            }
+                        },
+                    }
+                    Button {
+                        class: "label mt-5",
+                        label: "Remove extra potion",
+                        kind: ButtonKind::Danger,
+                        disabled: character_view().id.is_none(),
+                        on_click: move |_| {
+                            let mut character = character_view.peek().clone();
+                            character.extra_potions.remove(index);
+                            save_character(character);
+                        },
+                    }
+                }
+                Button {
+                    class: "col-span-3 label",
+                    label: "Add extra potion",
+                    kind: ButtonKind::Secondary,
+                    disabled: character_view().id.is_none(),
+                    on_click: move |_| {
+                        let mut character = character_view.peek().clone();
+                        character.extra_potions.push(PotionConfiguration::default());
+                        save_character(character);
+                    },
+                }
+            }
         }
     }
 }
@@ -535,6 +705,17 @@ fn SectionMovement(
                     },
                     value: character_view().up_jump_specific_key_should_jump,
                 }
+                CharactersCheckbox {
+                    label: "Use flash jump",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |use_flash_jump| {
+                        save_character(Character {
+                            use_flash_jump,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().use_flash_jump,
+                }
                 CharactersCheckbox {
                     label: "Disable teleport on fall",
                     disabled: character_view().id.is_none(),
@@ -557,6 +738,75 @@ fn SectionMovement(
                     },
                     value: character_view().disable_double_jumping,
                 }
+                CharactersNumberU32Input {
+                    label: "Double jump minimum distance",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |double_jump_threshold| {
+                        save_character(Character {
+                            double_jump_threshold,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().double_jump_threshold,
+                }
+                NumberInputU32 {
+                    label: "Teleport hop distance",
+                    minimum_value: 0,
+                    disabled: character_view().id.is_none(),
+                    on_value: move |teleport_range| {
+                        save_character(Character {
+                            teleport_range,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().teleport_range,
+                }
+                CharactersNumberU32Input {
+                    label: "Stationary ticks",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |stationary_ticks| {
+                        save_character(Character {
+                            stationary_ticks,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().stationary_ticks,
+                }
+                CharactersNumberU32Input {
+                    label: "Up jump spam delay ticks",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |up_jump_spam_delay| {
+                        save_character(Character {
+                            up_jump_spam_delay,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().up_jump_spam_delay,
+                }
+                NumberInputU32 {
+                    label: "Climb up interval ticks",
+                    minimum_value: 0,
+                    disabled: character_view().id.is_none(),
+                    on_value: move |climb_up_interval_ticks| {
+                        save_character(Character {
+                            climb_up_interval_ticks,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().climb_up_interval_ticks,
+                }
+                NumberInputU32 {
+                    label: "Climb down interval ticks",
+                    minimum_value: 0,
+                    disabled: character_view().id.is_none(),
+                    on_value: move |climb_down_interval_ticks| {
+                        save_character(Character {
+                            climb_down_interval_ticks,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().climb_down_interval_ticks,
+                }
                 CharactersCheckbox {
                     label: "Disable walking",
                     disabled: character_view().id.is_none(),
@@ -568,6 +818,17 @@ fn SectionMovement(
                     },
                     value: character_view().disable_adjusting,
                 }
+                CharactersCheckbox {
+                    label: "Verify direction by movement",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |verify_direction_by_detection| {
+                        save_character(Character {
+                            verify_direction_by_detection,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().verify_direction_by_detection,
+                }
             }
         }
     }
@@ -575,6 +836,61 @@ fn SectionMovement(
 
 #[component]
 fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Character>) -> Element {
+    #[component]
+    fn BuffKinds(
+        label: &'static str,
+        disabled: bool,
+        on_value: EventHandler<Vec<CharacterBuffKind>>,
+        value: Vec<CharacterBuffKind>,
+    ) -> Element {
+        let mut selected = use_signal(|| CharacterBuffKind::Familiar);
+        let value_clone = value.clone();
+
+        rsx! {
+            div { class: "col-span-full flex gap-2 items-end",
+                CharactersSelect::<CharacterBuffKind> {
+                    label,
+                    disabled,
+                    on_select: move |kind| {
+                        selected.set(kind);
+                    },
+                    selected: selected(),
+                }
+                Button {
+                    class: "w-20",
+                    label: "Add",
+                    kind: ButtonKind::Secondary,
+                    disabled,
+                    on_click: move |_| {
+                        let kind = *selected.peek();
+                        if !value_clone.contains(&kind) {
+                            let mut value = value_clone.clone();
+                            value.push(kind);
+                            on_value(value);
+                        }
+                    },
+                }
+            }
+            div { class: "col-span-full flex flex-wrap gap-2",
+                for (index , kind) in value.clone().into_iter().enumerate() {
+                    Button {
+                        label: format!("{}. {kind} ✕", index + 1),
+                        kind: ButtonKind::Secondary,
+                        disabled,
+                        on_click: {
+                            let value = value.clone();
+                            move |_| {
+                                let mut value = value.clone();
+                                value.remove(index);
+                                on_value(value);
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+
     #[component]
     fn Buff(
         label: &'static str,
@@ -850,6 +1166,28 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                     },
                     value: character_view().extreme_gold_potion_key,
                 }
+                BuffKinds {
+                    label: "Priority buff",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |buffs_priority| {
+                        save_character(Character {
+                            buffs_priority,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().buffs_priority,
+                }
+                BuffKinds {
+                    label: "Keep while halting",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |buffs_while_halting| {
+                        save_character(Character {
+                            buffs_while_halting,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().buffs_while_halting,
+                }
             }
         }
     }
@@ -872,9 +1210,34 @@ fn SectionFixedActions(
         action.enabled = enabled;
         save_character(character);
     });
+    let toggle_all_actions = use_callback(move |enabled| {
+        let mut character = character_view.peek().clone();
+        set_character_actions_enabled(&mut character, None, enabled);
+        save_character(character);
+    });
 
     rsx! {
         Section { name: "Fixed actions",
+            div { class: "grid grid-cols-2 gap-2 mb-2",
+                Button {
+                    label: "Enable all",
+                    kind: ButtonKind::Secondary,
+                    disabled: character_view().id.is_none(),
+                    on_click: move |_| {
+                        toggle_all_actions(true);
+                    },
+                    class: "label",
+                }
+                Button {
+                    label: "Disable all",
+                    kind: ButtonKind::Secondary,
+                    disabled: character_view().id.is_none(),
+                    on_click: move |_| {
+                        toggle_all_actions(false);
+                    },
+                    class: "label",
+                }
+            }
             ActionConfigurationList {
                 disabled: character_view().id.is_none(),
                 on_add_click: move |_| {
@@ -1493,6 +1856,8 @@ fn ActionConfigurationItem(action: ActionConfiguration) -> Element {
         ActionKeyWith::Any => "Any",
         ActionKeyWith::Stationary => "Stationary",
         ActionKeyWith::DoubleJump => "Double jump",
+        ActionKeyWith::Falling => "Falling",
+        ActionKeyWith::OnGround => "On ground",
     };
 
     rsx! {
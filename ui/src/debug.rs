@@ -1,15 +1,36 @@
 use backend::{
-    DebugState, auto_save_rune, capture_image, debug_state_receiver, infer_minimap, infer_rune,
-    record_images, test_spin_rune,
+    ColorThresholds, DebugState, KeyBinding, Settings as SettingsData, TemplateSelfTest,
+    auto_save_rune, capture_image, debug_state_receiver, detector_self_test, dump_recent_frames,
+    infer_minimap, infer_rune, manual_rune_keys, query_minimap_color_mask, query_settings,
+    record_images, test_spin_rune, upsert_settings,
 };
 use dioxus::prelude::*;
 use tokio::sync::broadcast::error::RecvError;
 
-use crate::button::{Button, ButtonKind};
+use crate::{
+    AppState,
+    button::{Button, ButtonKind},
+    inputs::NumberInputU32,
+    select::Select,
+};
+
+const COLOR_CHANNELS: [&str; 3] = ["H", "S", "V"];
+
+const RUNE_KEYS: [KeyBinding; 4] = [
+    KeyBinding::Up,
+    KeyBinding::Down,
+    KeyBinding::Left,
+    KeyBinding::Right,
+];
 
 #[component]
 pub fn Debug() -> Element {
     let mut state = use_signal(DebugState::default);
+    let mut rune_keys = use_signal(|| [KeyBinding::Up; 4]);
+    let mut self_test = use_signal(Vec::<TemplateSelfTest>::new);
+    let mut settings = use_context::<AppState>().settings;
+    let mut color_thresholds = use_signal(ColorThresholds::default);
+    let mut color_mask = use_signal(|| None::<String>);
 
     use_future(move || async move {
         let mut rx = debug_state_receiver().await;
@@ -25,6 +46,15 @@ pub fn Debug() -> Element {
         }
     });
 
+    use_future(move || async move {
+        if settings.peek().is_none() {
+            settings.set(Some(query_settings().await));
+        }
+        if let Some(settings) = settings.peek().as_ref() {
+            color_thresholds.set(settings.player_dot_color_thresholds);
+        }
+    });
+
     rsx! {
         div { class: "flex flex-col h-full overflow-y-auto scrollbar",
             Section { name: "Debug",
@@ -78,6 +108,111 @@ pub fn Debug() -> Element {
                             auto_save_rune(!state.peek().is_rune_auto_saving).await;
                         },
                     }
+                    Button {
+                        label: "Dump recent frames",
+                        kind: ButtonKind::Secondary,
+                        on_click: move |_| async {
+                            dump_recent_frames().await;
+                        },
+                    }
+                }
+            }
+            Section { name: "Manual rune keys",
+                div { class: "grid grid-cols-4 gap-3",
+                    for i in 0..4 {
+                        Select {
+                            label: format!("Key {}", i + 1),
+                            options: RUNE_KEYS.to_vec(),
+                            on_select: move |(_, key): (usize, KeyBinding)| {
+                                rune_keys.write()[i] = key;
+                            },
+                            selected: RUNE_KEYS
+                                .iter()
+                                .position(|key| *key == rune_keys()[i])
+                                .unwrap_or_default(),
+                        }
+                    }
+                }
+                Button {
+                    class: "mt-3",
+                    label: "Press manual rune keys",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| async move {
+                        manual_rune_keys(*rune_keys.peek()).await;
+                    },
+                }
+            }
+            Section { name: "Minimap color mask",
+                div { class: "grid grid-cols-3 gap-3",
+                    for i in 0..3 {
+                        NumberInputU32 {
+                            label: format!("Lower {}", COLOR_CHANNELS[i]),
+                            minimum_value: 0,
+                            maximum_value: Some(255),
+                            on_value: move |value: u32| {
+                                color_thresholds.write().lower[i] = value as u8;
+                            },
+                            value: color_thresholds().lower[i] as u32,
+                        }
+                    }
+                    for i in 0..3 {
+                        NumberInputU32 {
+                            label: format!("Upper {}", COLOR_CHANNELS[i]),
+                            minimum_value: 0,
+                            maximum_value: Some(255),
+                            on_value: move |value: u32| {
+                                color_thresholds.write().upper[i] = value as u8;
+                            },
+                            value: color_thresholds().upper[i] as u32,
+                        }
+                    }
+                }
+                div { class: "grid grid-cols-2 gap-3 mt-3",
+                    Button {
+                        label: "Preview mask",
+                        kind: ButtonKind::Secondary,
+                        on_click: move |_| async move {
+                            let thresholds = *color_thresholds.peek();
+                            color_mask.set(query_minimap_color_mask(thresholds).await);
+                        },
+                    }
+                    Button {
+                        label: "Save as player dot color",
+                        kind: ButtonKind::Secondary,
+                        on_click: move |_| async move {
+                            let Some(current) = settings.peek().clone() else {
+                                return;
+                            };
+                            let new_settings = SettingsData {
+                                player_dot_color_thresholds: *color_thresholds.peek(),
+                                ..current
+                            };
+                            settings.set(Some(upsert_settings(new_settings).await));
+                        },
+                    }
+                }
+                if let Some(mask) = color_mask() {
+                    img {
+                        src: format!("data:image/png;base64,{mask}"),
+                        class: "w-full mt-3 p-1 border border-gray-600",
+                    }
+                }
+            }
+            Section { name: "Detector self test",
+                Button {
+                    label: "Run self test",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| async move {
+                        self_test.set(detector_self_test().await);
+                    },
+                }
+                div { class: "flex flex-col mt-3",
+                    for result in self_test() {
+                        div { class: "flex justify-between paragraph-xs",
+                            p { {format!("{:?}", result.template)} }
+                            p { {self_test_result_text(result)} }
+                        }
+                    }
                 }
             }
         }
@@ -93,3 +228,11 @@ fn Section(name: &'static str, children: Element) -> Element {
         }
     }
 }
+
+fn self_test_result_text(result: TemplateSelfTest) -> String {
+    match result.confidence {
+        Some(confidence) if result.detected => format!("Detected ({confidence:.2})"),
+        Some(confidence) => format!("Not detected ({confidence:.2})"),
+        None => "Failed".to_string(),
+    }
+}
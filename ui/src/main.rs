@@ -3,7 +3,7 @@
 #![feature(map_try_insert)]
 #![feature(iter_intersperse)]
 
-use std::{env::current_exe, io::stdout, string::ToString, sync::LazyLock};
+use std::{string::ToString, sync::LazyLock};
 
 use actions::Actions;
 use backend::{
@@ -20,8 +20,6 @@ use dioxus::{
     },
     prelude::*,
 };
-use fern::Dispatch;
-use log::LevelFilter;
 use minimap::Minimap;
 use navigation::Navigation;
 use rand::distr::{Alphanumeric, SampleString};
@@ -66,29 +64,9 @@ static TABS: LazyLock<Vec<String>> = LazyLock::new(|| {
 });
 
 fn main() {
-    let level = if cfg!(debug_assertions) {
-        LevelFilter::Debug
-    } else {
-        LevelFilter::Info
-    };
-    Dispatch::new()
-        .format(|out, message, record| {
-            out.finish(format_args!(
-                "[{} {} {}] {}",
-                humantime::format_rfc3339(std::time::SystemTime::now()),
-                record.level(),
-                record.target(),
-                message
-            ))
-        })
-        .level(level)
-        .chain(stdout())
-        .chain(fern::log_file(current_exe().unwrap().parent().unwrap().join("log.txt")).unwrap())
-        .apply()
-        .unwrap();
+    backend::init();
     log_panics::init();
 
-    backend::init();
     let window = WindowBuilder::new()
         .with_drag_and_drop(false)
         .with_inner_size(Size::new(PhysicalSize::new(1024, 483)))
@@ -104,6 +82,8 @@ fn main() {
 pub struct AppState {
     minimap: Signal<Option<MinimapData>>,
     minimap_preset: Signal<Option<String>>,
+    /// Issues found by the backend with the currently used minimap.
+    minimap_issues: Signal<Vec<String>>,
     character: Signal<Option<Character>>,
     settings: Signal<Option<SettingsData>>,
     localization: Signal<Option<LocalizationData>>,
@@ -118,6 +98,7 @@ fn App() -> Element {
     use_context_provider(|| AppState {
         minimap: Signal::new(None),
         minimap_preset: Signal::new(None),
+        minimap_issues: Signal::new(vec![]),
         character: Signal::new(None),
         settings: Signal::new(None),
         localization: Signal::new(None),
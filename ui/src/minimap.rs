@@ -294,6 +294,7 @@ struct MinimapState {
     erda_shower_state: String,
     operation: GameOperation,
     detected_size: Option<(usize, usize)>,
+    navigation_progress: Option<(usize, usize)>,
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -309,6 +310,7 @@ enum MinimapUpdate {
 pub fn Minimap() -> Element {
     let mut minimap = use_context::<AppState>().minimap;
     let mut minimap_preset = use_context::<AppState>().minimap_preset;
+    let minimap_issues = use_context::<AppState>().minimap_issues;
     let mut minimaps = use_resource(async || query_minimaps().await.unwrap_or_default());
     let position = use_context::<AppState>().position;
     // Maps queried `minimaps` to names
@@ -337,7 +339,7 @@ pub fn Minimap() -> Element {
         while let Some(message) = rx.next().await {
             match message {
                 MinimapUpdate::Set => {
-                    update_minimap(minimap_preset(), minimap()).await;
+                    update_current_minimap(minimap, minimap_preset(), minimap_issues).await;
                 }
                 MinimapUpdate::Create(name) => {
                     let Some(new_minimap) = create_minimap(name).await else {
@@ -349,7 +351,7 @@ pub fn Minimap() -> Element {
 
                     minimap.set(Some(new_minimap));
                     minimap_preset.set(None);
-                    update_minimap(None, minimap()).await;
+                    update_current_minimap(minimap, None, minimap_issues).await;
                 }
                 MinimapUpdate::Import(minimap) => {
                     upsert_minimap(minimap).await;
@@ -395,6 +397,16 @@ pub fn Minimap() -> Element {
                 Err(RecvError::Closed) => break,
                 Err(RecvError::Lagged(_)) => continue,
             };
+            // Keeps the current minimap in sync with backend-side updates (e.g. movement
+            // calibration) so later saves from the UI do not overwrite them
+            if let DatabaseEvent::MinimapUpdated(updated) = &event
+                && minimap
+                    .peek()
+                    .as_ref()
+                    .is_some_and(|current| current.id == updated.id)
+            {
+                minimap.set(Some(updated.clone()));
+            }
             if matches!(
                 event,
                 DatabaseEvent::MinimapUpdated(_) | DatabaseEvent::MinimapDeleted(_)
@@ -418,6 +430,9 @@ pub fn Minimap() -> Element {
             }
             Buttons { state, minimap }
             Info { state, minimap }
+            for issue in minimap_issues() {
+                p { class: "paragraph-xs text-yellow-500 px-2", "{issue}" }
+            }
             div { class: "flex-grow flex items-end px-2",
                 div { class: "flex flex-col items-end w-full",
                     ImportExport { minimap }
@@ -454,6 +469,24 @@ pub fn Minimap() -> Element {
     }
 }
 
+/// Updates the minimap used by the main game loop to the current `minimap` with `preset`.
+///
+/// If the backend corrected invalid bounds of `minimap`, the corrected minimap is persisted and
+/// `issues` is set to the issues found.
+pub async fn update_current_minimap(
+    mut minimap: Signal<Option<MinimapData>>,
+    preset: Option<String>,
+    mut issues: Signal<Vec<String>>,
+) {
+    let (corrected, found) = update_minimap(preset, minimap.peek().clone()).await;
+    issues.set(found);
+    if let Some(corrected) = corrected
+        && let Some(corrected) = upsert_minimap(corrected).await
+    {
+        minimap.set(Some(corrected));
+    }
+}
+
 #[component]
 fn Canvas(
     state: Signal<Option<MinimapState>>,
@@ -461,18 +494,21 @@ fn Canvas(
     minimap_preset: ReadOnlySignal<Option<String>>,
     position: Signal<(i32, i32)>,
 ) -> Element {
-    let mut platforms_bound = use_signal(|| None);
+    let mut rotation_bound = use_signal(|| None);
     let rotation_bound_and_type = use_memo(move || {
-        let platforms_bound = platforms_bound();
+        let rotation_bound = rotation_bound();
         let minimap = minimap()?;
 
         match minimap.rotation_mode {
             RotationMode::StartToEnd | RotationMode::StartToEndThenReverse => None,
             RotationMode::AutoMobbing => Some((
-                platforms_bound.unwrap_or(minimap.rotation_auto_mob_bound),
+                rotation_bound.unwrap_or(minimap.rotation_auto_mob_bound),
                 "AutoMobbing",
             )),
-            RotationMode::PingPong => Some((minimap.rotation_ping_pong_bound, "PingPong")),
+            RotationMode::PingPong | RotationMode::Patrol => Some((
+                rotation_bound.unwrap_or(minimap.rotation_ping_pong_bound),
+                "PingPong",
+            )),
         }
     });
 
@@ -525,7 +561,7 @@ fn Canvas(
                 continue;
             };
             let destinations = current_state.destinations;
-            let bound = current_state.platforms_bound;
+            let bound = current_state.rotation_bound;
             let quadrant = current_state
                 .auto_mob_quadrant
                 .map(|quadrant| quadrant.to_string());
@@ -540,10 +576,11 @@ fn Canvas(
                 erda_shower_state: current_state.erda_shower_state,
                 operation: current_state.operation,
                 detected_size: frame.as_ref().map(|(_, width, height)| (*width, *height)),
+                navigation_progress: current_state.navigation_progress,
             };
 
-            if *platforms_bound.peek() != bound {
-                platforms_bound.set(bound);
+            if *rotation_bound.peek() != bound {
+                rotation_bound.set(bound);
             }
             if *position.peek() != current_state.position.unwrap_or_default() {
                 position.set(current_state.position.unwrap_or_default());
@@ -600,6 +637,7 @@ fn Info(
         detected_minimap_size: String,
         selected_minimap_size: String,
         cycle_duration: String,
+        navigation: String,
     }
 
     let info = use_memo(move || {
@@ -613,6 +651,7 @@ fn Info(
             detected_minimap_size: "Unknown".to_string(),
             selected_minimap_size: "Unknown".to_string(),
             cycle_duration: "None".to_string(),
+            navigation: "None".to_string(),
         };
 
         if let Some(minimap) = minimap() {
@@ -644,6 +683,9 @@ fn Info(
             if let Some((width, height)) = state.detected_size {
                 info.detected_minimap_size = format!("{width}px x {height}px")
             }
+            if let Some((current, total)) = state.navigation_progress {
+                info.navigation = format!("{current} / {total}");
+            }
         }
 
         info
@@ -660,6 +702,7 @@ fn Info(
             InfoItem { name: "Detected size", value: info().detected_minimap_size }
             InfoItem { name: "Selected size", value: info().selected_minimap_size }
             InfoItem { name: "Run/stop cycle", value: info().cycle_duration }
+            InfoItem { name: "Navigation", value: info().navigation }
         }
     }
 }
@@ -14,7 +14,7 @@ use crate::{
     icons::{DetailsIcon, PositionIcon, XIcon},
     inputs::{Checkbox, NumberInputI32},
     popup::Popup,
-    select::{Select, TextSelect},
+    select::{EnumSelect, Select, TextSelect},
 };
 
 type PathsIdIndex = Option<(i64, usize)>;
@@ -138,13 +138,29 @@ fn PopupPoint(
         PopupPointValue::Add(point) => (point.x, point.y),
         PopupPointValue::Edit(point, _) => (point.x, point.y),
     });
+    let mut transition = use_signal(|| match value() {
+        PopupPointValue::Add(point) => point.transition,
+        PopupPointValue::Edit(point, _) => point.transition,
+    });
     let on_save_click = use_callback(move |_| {
         let (x, y) = *xy.peek();
+        let transition = *transition.peek();
         let value = match value.peek().clone() {
-            PopupPointValue::Add(point) => PopupPointValue::Add(NavigationPoint { x, y, ..point }),
-            PopupPointValue::Edit(point, index) => {
-                PopupPointValue::Edit(NavigationPoint { x, y, ..point }, index)
-            }
+            PopupPointValue::Add(point) => PopupPointValue::Add(NavigationPoint {
+                x,
+                y,
+                transition,
+                ..point
+            }),
+            PopupPointValue::Edit(point, index) => PopupPointValue::Edit(
+                NavigationPoint {
+                    x,
+                    y,
+                    transition,
+                    ..point
+                },
+                index,
+            ),
         };
         on_save(value);
     });
@@ -152,7 +168,7 @@ fn PopupPoint(
     rsx! {
         Popup {
             title: "Point",
-            class: "max-w-80 min-h-35 max-h-35",
+            class: "max-w-80 min-h-50 max-h-50",
             confirm_button: "Save",
             on_confirm: move |_| {
                 on_save_click(());
@@ -194,6 +210,15 @@ fn PopupPoint(
                         PositionIcon { class: ICON_CLASS }
                     }
                 }
+                div { class: "col-span-2",
+                    EnumSelect {
+                        label: "Transition",
+                        on_select: move |value| {
+                            transition.set(value);
+                        },
+                        selected: transition(),
+                    }
+                }
             }
         }
     }
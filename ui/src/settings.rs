@@ -1,10 +1,12 @@
 use std::{fmt::Display, fs::File, io::BufReader};
 
 use backend::{
-    CaptureMode, CycleRunStopMode, FamiliarRarity, Familiars, InputMethod, IntoEnumIterator,
-    KeyBinding, KeyBindingConfiguration, Notifications, Settings as SettingsData,
-    SwappableFamiliars, query_capture_handles, query_settings, refresh_capture_handles,
-    select_capture_handle, upsert_settings,
+    CaptureMode, CycleRunStopMode, DetectionRegion, FamiliarRarity, Familiars, InputMethod,
+    IntoEnumIterator, KeyBinding, KeyBindingConfiguration, LogLevel, Notifications,
+    OtherPlayerBehavior, Settings as SettingsData, SwappableFamiliars, create_instance,
+    query_capture_handles, query_instances, query_key_conflicts, query_settings,
+    refresh_capture_handles, remove_instance, select_capture_handle, select_instance,
+    upsert_settings,
 };
 use dioxus::prelude::*;
 use futures_util::StreamExt;
@@ -14,7 +16,7 @@ use crate::{
     AppState,
     button::{Button, ButtonKind},
     icons::{EyePasswordHideIcon, EyePasswordShowIcon},
-    inputs::{Checkbox, KeyBindingInput, MillisInput, TextInput},
+    inputs::{Checkbox, KeyBindingInput, MillisInput, NumberInputU32, TextInput},
     select::{EnumSelect, Select},
 };
 
@@ -52,6 +54,7 @@ pub fn Settings() -> Element {
 
     rsx! {
         div { class: "flex flex-col h-full overflow-y-auto scrollbar",
+            SectionInstances {}
             SectionCapture { settings_view, save_settings }
             SectionInput { settings_view, save_settings }
             SectionFamiliars { settings_view, save_settings }
@@ -73,6 +76,54 @@ fn Section(name: &'static str, children: Element) -> Element {
     }
 }
 
+#[component]
+fn SectionInstances() -> Element {
+    let mut instances = use_resource(query_instances);
+    let instance_names = use_memo(move || {
+        let (count, _) = instances().unwrap_or((1, 0));
+
+        (1..=count)
+            .map(|index| format!("Instance {index}"))
+            .collect::<Vec<_>>()
+    });
+    let selected_index = use_memo(move || instances().map(|(_, selected)| selected));
+
+    rsx! {
+        Section { name: "Instances",
+            SettingsSelect {
+                label: "Selected",
+                options: instance_names(),
+                on_select: move |(index, _)| async move {
+                    select_instance(index).await;
+                    instances.restart();
+                },
+                selected: selected_index().unwrap_or_default(),
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                Button {
+                    label: "Create",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| async move {
+                        create_instance().await;
+                        instances.restart();
+                    },
+                }
+                Button {
+                    label: "Remove selected",
+                    kind: ButtonKind::Danger,
+                    disabled: selected_index().unwrap_or_default() == 0,
+                    on_click: move |_| async move {
+                        if let Some(index) = selected_index() {
+                            remove_instance(index).await;
+                            instances.restart();
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
 #[component]
 fn SectionCapture(
     settings_view: Memo<SettingsData>,
@@ -118,6 +169,87 @@ fn SectionCapture(
                     },
                     selected: settings_view().capture_mode,
                 }
+                for mode in CaptureMode::iter()
+                    .filter(|mode| *mode != settings_view().capture_mode)
+                {
+                    Checkbox {
+                        label: format!("Fall back to {mode}"),
+                        input_class: "w-6",
+                        on_value: move |enabled| {
+                            let mut settings = settings_view.peek().clone();
+                            settings.capture_mode_fallbacks.retain(|fallback| *fallback != mode);
+                            if enabled {
+                                settings.capture_mode_fallbacks.push(mode);
+                            }
+                            save_settings(settings);
+                        },
+                        value: settings_view().capture_mode_fallbacks.contains(&mode),
+                    }
+                }
+                SettingsTextInput {
+                    text_label: "Default window name pattern",
+                    button_label: "Update",
+                    on_value: move |capture_window_pattern| {
+                        save_settings(SettingsData {
+                            capture_window_pattern,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().capture_window_pattern,
+                }
+                SettingsCheckbox {
+                    label: "Enable parallel detection",
+                    on_value: move |enable_parallel_detection| {
+                        save_settings(SettingsData {
+                            enable_parallel_detection,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_parallel_detection,
+                }
+                NumberInputU32 {
+                    label: "Buff detection scale (%)",
+                    minimum_value: 25,
+                    maximum_value: Some(100),
+                    on_value: move |buff_detection_scale_percent| {
+                        save_settings(SettingsData {
+                            buff_detection_scale_percent,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().buff_detection_scale_percent,
+                }
+                SettingsCheckbox {
+                    label: "Detect on worker thread",
+                    on_value: move |enable_detection_pipeline| {
+                        save_settings(SettingsData {
+                            enable_detection_pipeline,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_detection_pipeline,
+                }
+                SettingsDetectionRegion {
+                    label: "Buffs region",
+                    on_value: move |buffs_detection_region| {
+                        save_settings(SettingsData {
+                            buffs_detection_region,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().buffs_detection_region,
+                }
+                SettingsDetectionRegion {
+                    label: "Popups region",
+                    on_value: move |popups_detection_region| {
+                        save_settings(SettingsData {
+                            popups_detection_region,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().popups_detection_region,
+                }
+                div {}
             }
             Button {
                 label: "Refresh handles",
@@ -161,6 +293,16 @@ fn SectionInput(
                     },
                     value: settings_view().input_method_rpc_server_url,
                 }
+                MillisInput {
+                    label: "Delay compensation",
+                    on_value: move |input_delay_compensation_millis| {
+                        save_settings(SettingsData {
+                            input_delay_compensation_millis,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().input_delay_compensation_millis,
+                }
             }
         }
     }
@@ -258,6 +400,35 @@ fn SectionFamiliars(
                     },
                     value: familiars_view().swappable_rarities.contains(&FamiliarRarity::Epic),
                 }
+                SettingsCheckbox {
+                    label: "Sort cards by level",
+                    disabled: !familiars_view().enable_familiars_swapping,
+                    on_value: move |sort_by_level| {
+                        save_settings(SettingsData {
+                            familiars: Familiars {
+                                sort_by_level,
+                                ..familiars_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: familiars_view().sort_by_level,
+                }
+                NumberInputU32 {
+                    label: "Max swaps per hour",
+                    minimum_value: 0,
+                    disabled: !familiars_view().enable_familiars_swapping,
+                    on_value: move |max_swaps_per_hour| {
+                        save_settings(SettingsData {
+                            familiars: Familiars {
+                                max_swaps_per_hour,
+                                ..familiars_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: familiars_view().max_swaps_per_hour,
+                }
             }
         }
     }
@@ -407,6 +578,19 @@ fn SectionControlAndNotifications(
                     },
                     value: notifications_view().notify_on_fail_or_change_map,
                 }
+                SettingsCheckbox {
+                    label: "Game frozen",
+                    on_value: move |notify_on_game_frozen| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_game_frozen,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_game_frozen,
+                }
             }
         }
     }
@@ -417,6 +601,14 @@ fn SectionHotkeys(
     settings_view: Memo<SettingsData>,
     save_settings: EventHandler<SettingsData>,
 ) -> Element {
+    let character = use_context::<AppState>().character;
+    // Re-queries whenever settings or character changes
+    let key_conflicts = use_resource(move || async move {
+        let _ = settings_view();
+        let _ = character();
+        query_key_conflicts().await
+    });
+
     #[component]
     fn Hotkey(
         label: &'static str,
@@ -494,6 +686,9 @@ fn SectionHotkeys(
                     value: settings_view().platform_end_key,
                 }
             }
+            for conflict in key_conflicts().unwrap_or_default() {
+                p { class: "paragraph-xs !text-red-500", "{conflict}" }
+            }
         }
     }
 }
@@ -611,7 +806,136 @@ fn SectionOthers(
                     },
                     value: settings_view().enable_rune_solving,
                 }
-                div {}
+                NumberInputU32 {
+                    label: "Startup delay ticks",
+                    minimum_value: 0,
+                    on_value: move |startup_delay_ticks| {
+                        save_settings(SettingsData {
+                            startup_delay_ticks,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().startup_delay_ticks,
+                }
+                NumberInputU32 {
+                    label: "Min idle ticks between rotations",
+                    minimum_value: 0,
+                    on_value: move |rotation_idle_min_ticks| {
+                        save_settings(SettingsData {
+                            rotation_idle_min_ticks,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().rotation_idle_min_ticks,
+                }
+                NumberInputU32 {
+                    label: "Max idle ticks between rotations",
+                    minimum_value: 0,
+                    on_value: move |rotation_idle_max_ticks| {
+                        save_settings(SettingsData {
+                            rotation_idle_max_ticks,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().rotation_idle_max_ticks,
+                }
+                NumberInputU32 {
+                    label: "Rune spinning arrow lag samples",
+                    minimum_value: 1,
+                    on_value: move |rune_spin_lag_samples| {
+                        save_settings(SettingsData {
+                            rune_spin_lag_samples,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().rune_spin_lag_samples,
+                }
+                NumberInputU32 {
+                    label: "Rune spinning arrow lag threshold",
+                    minimum_value: 0,
+                    on_value: move |rune_spin_lag_threshold| {
+                        save_settings(SettingsData {
+                            rune_spin_lag_threshold,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().rune_spin_lag_threshold,
+                }
+                NumberInputU32 {
+                    label: "Rune cooldown ticks",
+                    minimum_value: 1,
+                    on_value: move |rune_cooldown_ticks| {
+                        save_settings(SettingsData {
+                            rune_cooldown_ticks,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().rune_cooldown_ticks,
+                }
+                SettingsCheckbox {
+                    label: "Solve rune only when safe",
+                    on_value: move |solve_rune_only_when_safe| {
+                        save_settings(SettingsData {
+                            solve_rune_only_when_safe,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().solve_rune_only_when_safe,
+                }
+                NumberInputU32 {
+                    label: "Rune key press interval ticks",
+                    minimum_value: 1,
+                    on_value: move |rune_press_key_interval_ticks| {
+                        save_settings(SettingsData {
+                            rune_press_key_interval_ticks,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().rune_press_key_interval_ticks,
+                }
+                SettingsCheckbox {
+                    label: "Cast all buffs on start",
+                    on_value: move |enable_buffs_burst_on_start| {
+                        save_settings(SettingsData {
+                            enable_buffs_burst_on_start,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_buffs_burst_on_start,
+                }
+                NumberInputU32 {
+                    label: "Portal avoidance margin",
+                    minimum_value: 0,
+                    on_value: move |portal_avoidance_margin| {
+                        save_settings(SettingsData {
+                            portal_avoidance_margin,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().portal_avoidance_margin,
+                }
+                NumberInputU32 {
+                    label: "Movement timeout scale (%)",
+                    minimum_value: 100,
+                    on_value: move |move_timeout_scale_percent| {
+                        save_settings(SettingsData {
+                            move_timeout_scale_percent,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().move_timeout_scale_percent,
+                }
+                NumberInputU32 {
+                    label: "Change direction timeout ticks",
+                    minimum_value: 1,
+                    on_value: move |change_direction_timeout_ticks| {
+                        save_settings(SettingsData {
+                            change_direction_timeout_ticks,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().change_direction_timeout_ticks,
+                }
                 SettingsCheckbox {
                     label: "Stop actions on fail or map changed",
                     on_value: move |stop_on_fail_or_change_map| {
@@ -622,6 +946,36 @@ fn SectionOthers(
                     },
                     value: settings_view().stop_on_fail_or_change_map,
                 }
+                MillisInput {
+                    label: "Capture failed after",
+                    on_value: move |capture_failed_threshold_millis| {
+                        save_settings(SettingsData {
+                            capture_failed_threshold_millis,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().capture_failed_threshold_millis,
+                }
+                SettingsCheckbox {
+                    label: "Stop actions on game frozen",
+                    on_value: move |stop_on_game_frozen| {
+                        save_settings(SettingsData {
+                            stop_on_game_frozen,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().stop_on_game_frozen,
+                }
+                MillisInput {
+                    label: "Game frozen after (0 to disable)",
+                    on_value: move |game_frozen_threshold_millis| {
+                        save_settings(SettingsData {
+                            game_frozen_threshold_millis,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().game_frozen_threshold_millis,
+                }
                 SettingsCheckbox {
                     label: "Enable panic mode",
                     on_value: move |enable_panic_mode| {
@@ -632,6 +986,64 @@ fn SectionOthers(
                     },
                     value: settings_view().enable_panic_mode,
                 }
+                SettingsEnumSelect::<OtherPlayerBehavior> {
+                    label: "When stranger appears",
+                    on_select: move |stranger_player_behavior| {
+                        save_settings(SettingsData {
+                            stranger_player_behavior,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    selected: settings_view().stranger_player_behavior,
+                }
+                SettingsEnumSelect::<OtherPlayerBehavior> {
+                    label: "When guildie appears",
+                    on_select: move |guildie_player_behavior| {
+                        save_settings(SettingsData {
+                            guildie_player_behavior,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    selected: settings_view().guildie_player_behavior,
+                }
+                SettingsTextInput {
+                    text_label: "Whitelisted player names (comma-separated)",
+                    button_label: "Update",
+                    on_value: move |names: String| {
+                        let player_name_whitelist = names
+                            .split(',')
+                            .map(|name| name.trim().to_string())
+                            .filter(|name| !name.is_empty())
+                            .collect();
+                        save_settings(SettingsData {
+                            player_name_whitelist,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().player_name_whitelist.join(", "),
+                }
+                SettingsEnumSelect::<LogLevel> {
+                    label: "Log level (requires restart)",
+                    on_select: move |log_level| {
+                        save_settings(SettingsData {
+                            log_level,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    selected: settings_view().log_level,
+                }
+                div {}
+                SettingsTextInput {
+                    text_label: "Log file path (requires restart, empty for default)",
+                    button_label: "Update",
+                    on_value: move |log_file_path| {
+                        save_settings(SettingsData {
+                            log_file_path,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().log_file_path,
+                }
                 div {
                     a { id: export_element_id(), class: "w-0 h-0 invisible" }
                     Button {
@@ -708,6 +1120,58 @@ fn SettingsEnumSelect<T: 'static + Clone + PartialEq + Display + IntoEnumIterato
     }
 }
 
+#[component]
+fn SettingsDetectionRegion(
+    label: &'static str,
+    on_value: EventHandler<DetectionRegion>,
+    value: DetectionRegion,
+) -> Element {
+    rsx! {
+        NumberInputU32 {
+            label: "{label} X",
+            minimum_value: 0,
+            maximum_value: Some(100),
+            on_value: move |x_percent| {
+                on_value(DetectionRegion { x_percent, ..value });
+            },
+            value: value.x_percent,
+        }
+        NumberInputU32 {
+            label: "{label} Y",
+            minimum_value: 0,
+            maximum_value: Some(100),
+            on_value: move |y_percent| {
+                on_value(DetectionRegion { y_percent, ..value });
+            },
+            value: value.y_percent,
+        }
+        NumberInputU32 {
+            label: "{label} width",
+            minimum_value: 1,
+            maximum_value: Some(100),
+            on_value: move |width_percent| {
+                on_value(DetectionRegion {
+                    width_percent,
+                    ..value
+                });
+            },
+            value: value.width_percent,
+        }
+        NumberInputU32 {
+            label: "{label} height",
+            minimum_value: 1,
+            maximum_value: Some(100),
+            on_value: move |height_percent| {
+                on_value(DetectionRegion {
+                    height_percent,
+                    ..value
+                });
+            },
+            value: value.height_percent,
+        }
+    }
+}
+
 #[component]
 fn SettingsCheckbox(
     label: &'static str,